@@ -2,7 +2,7 @@ use std::env;
 
 fn main() {
   if env::var("TARGET").unwrap() == "wasm32-unknown-emscripten" {
-    let mut linker_flags = "-s ALLOW_MEMORY_GROWTH=1 -s MAX_WEBGL_VERSION=2 -s TOTAL_MEMORY=512MB --preload-file shaders/gles/basic.vert --preload-file shaders/gles/basic.frag --preload-file image.jpg ".to_string();
+    let mut linker_flags = "-s ALLOW_MEMORY_GROWTH=1 -s MAX_WEBGL_VERSION=2 -s TOTAL_MEMORY=512MB --preload-file shaders/gl/basic.vert --preload-file shaders/gl/basic.frag --preload-file image.jpg ".to_string();
     let mut emcc_flags = "".to_string();
 
     if env::var("PROFILE").unwrap() == "debug" {
@@ -27,6 +27,7 @@ fn main() {
     "directx" => gpu::renderer_types::RendererType::DirectX,
     "vulkan" => gpu::renderer_types::RendererType::Vulkan,
     "metal" => gpu::renderer_types::RendererType::Metal,
+    "software" => gpu::renderer_types::RendererType::Software,
     _ => gpu::renderer_types::RendererType::OpenGL
   };
 
@@ -47,8 +47,9 @@ impl Renderer {
     };
 
     let shader_path = match window.renderer_type {
-      renderer_types::RendererType::OpenGL => "shaders/gl/",
-      renderer_types::RendererType::OpenGLES => "shaders/gles/",
+      // gles contexts translate the gl shaders
+      renderer_types::RendererType::OpenGL | renderer_types::RendererType::OpenGLES => "shaders/gl/",
+      renderer_types::RendererType::Vulkan => "shaders/vulkan/",
       _ => "shaders/"
     };
 
@@ -113,7 +114,7 @@ impl Renderer {
     let geometry = renderer.gen_geometry(&vert_buffer);
 
     let material = Box::new(material::MaterialBasic::new(shader_program, 
-      renderer.gen_sampler(texture.into())));
+      renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default())));
 
     // let uniform_mvp = self.renderer.get_uniform(&mut shader_program, "u_mvp");
 
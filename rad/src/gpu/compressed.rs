@@ -0,0 +1,275 @@
+use crate::gpu::renderer_types::*;
+use crate::gpu::decompress;
+
+const KTX2_IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const DDS_MAGIC: [u8; 4] = *b"DDS ";
+
+// dds header flags
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDPF_FOURCC: u32 = 0x4;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+const D3D11_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+// a 2d texture with its mip chain still in blocks, level 0 is the largest. rows start at v = 0 the same as images
+#[derive(Clone)]
+pub struct CompressedImage {
+  pub format: CompressedFormat,
+  // rgb is converted from srgb to linear when sampled
+  pub srgb: bool,
+  pub width: u32,
+  pub height: u32,
+  pub levels: Vec<Vec<u8>>,
+}
+
+#[allow(dead_code)]
+impl CompressedImage {
+  // picks the container from the first bytes
+  pub fn from_bytes(a_data: &[u8]) -> Result<CompressedImage, RendererError>{
+    if a_data.starts_with(&KTX2_IDENTIFIER) {
+      CompressedImage::from_ktx2(a_data)
+    }
+    else if a_data.starts_with(&DDS_MAGIC) {
+      CompressedImage::from_dds(a_data)
+    }
+    else {
+      Err(RendererError::Error)
+    }
+  }
+
+  pub fn from_file(a_path: &str) -> Result<CompressedImage, RendererError>{
+    match crate::core::filesystem::filesystem::read_file_immediate::<u8>(a_path) {
+      Ok(res) => CompressedImage::from_bytes(&res),
+      Err(_res) => Err(RendererError::Error)
+    }
+  }
+
+  // supercompressed files and cubemaps, arrays or 3d textures are unsupported
+  pub fn from_ktx2(a_data: &[u8]) -> Result<CompressedImage, RendererError>{
+    if a_data.len() < 80 || !a_data.starts_with(&KTX2_IDENTIFIER) {
+      return Err(RendererError::Error)
+    }
+
+    let vk_format = read_u32(a_data, 12);
+    let width = read_u32(a_data, 20);
+    let height = read_u32(a_data, 24);
+    let depth = read_u32(a_data, 28);
+    let layers = read_u32(a_data, 32);
+    let faces = read_u32(a_data, 36);
+    // 0 asks for mipmaps to be generated, only the first level is in the file
+    let num_levels = std::cmp::max(read_u32(a_data, 40), 1);
+    let supercompression = read_u32(a_data, 44);
+
+    if width == 0 || height == 0 {
+      return Err(RendererError::Error)
+    }
+
+    if num_levels > get_max_levels(width, height) {
+      return Err(RendererError::Error)
+    }
+
+    if depth > 1 || layers > 1 || faces != 1 || supercompression != 0 {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    let (format, srgb) = match get_ktx2_format(vk_format) {
+      Some(res) => res,
+      None => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let mut image = CompressedImage{format: format, srgb: srgb, width: width, height: height, levels: Vec::new()};
+
+    // level index of byte offset, byte length and uncompressed length
+    for level in 0..num_levels {
+      let index = 80 + level as usize * 24;
+      if a_data.len() < index + 24 {
+        return Err(RendererError::Error)
+      }
+
+      let offset = read_u64(a_data, index) as usize;
+      let length = read_u64(a_data, index + 8) as usize;
+      let (level_width, level_height) = image.get_level_size(level as usize);
+
+      if length != format.get_level_bytes(level_width, level_height) || a_data.len() < offset.saturating_add(length) {
+        return Err(RendererError::Error)
+      }
+
+      image.levels.push(a_data[offset..offset + length].to_vec());
+    }
+
+    Ok(image)
+  }
+
+  // bc formats from four character codes or the dx10 header
+  pub fn from_dds(a_data: &[u8]) -> Result<CompressedImage, RendererError>{
+    if a_data.len() < 128 || !a_data.starts_with(&DDS_MAGIC) || read_u32(a_data, 4) != 124 {
+      return Err(RendererError::Error)
+    }
+
+    let flags = read_u32(a_data, 8);
+    let height = read_u32(a_data, 12);
+    let width = read_u32(a_data, 16);
+    let num_levels = match flags & DDSD_MIPMAPCOUNT != 0 {
+      true => std::cmp::max(read_u32(a_data, 28), 1),
+      false => 1
+    };
+    let pixel_flags = read_u32(a_data, 80);
+    let four_cc = &a_data[84..88];
+    let caps2 = read_u32(a_data, 112);
+
+    if width == 0 || height == 0 {
+      return Err(RendererError::Error)
+    }
+
+    if num_levels > get_max_levels(width, height) {
+      return Err(RendererError::Error)
+    }
+
+    if pixel_flags & DDPF_FOURCC == 0 || caps2 & (DDSCAPS2_CUBEMAP | DDSCAPS2_VOLUME) != 0 {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    let (format, srgb, mut offset) = match four_cc {
+      b"DXT1" => (CompressedFormat::BC1, false, 128),
+      b"DXT2" | b"DXT3" => (CompressedFormat::BC2, false, 128),
+      b"DXT4" | b"DXT5" => (CompressedFormat::BC3, false, 128),
+      b"ATI1" | b"BC4U" => (CompressedFormat::BC4, false, 128),
+      b"ATI2" | b"BC5U" => (CompressedFormat::BC5, false, 128),
+      b"DX10" => {
+        if a_data.len() < 148 {
+          return Err(RendererError::Error)
+        }
+
+        // 3 is a 2d texture
+        if read_u32(a_data, 132) != 3 || read_u32(a_data, 136) & D3D11_RESOURCE_MISC_TEXTURECUBE != 0 || read_u32(a_data, 140) > 1 {
+          return Err(RendererError::UnsupportedAPI)
+        }
+
+        match get_dxgi_format(read_u32(a_data, 128)) {
+          Some((format, srgb)) => (format, srgb, 148),
+          None => return Err(RendererError::UnsupportedAPI)
+        }
+      },
+      _ => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let mut image = CompressedImage{format: format, srgb: srgb, width: width, height: height, levels: Vec::new()};
+
+    // levels are tightly packed after the header
+    for level in 0..num_levels as usize {
+      let (level_width, level_height) = image.get_level_size(level);
+      let length = format.get_level_bytes(level_width, level_height);
+
+      if a_data.len() < offset + length {
+        return Err(RendererError::Error)
+      }
+
+      image.levels.push(a_data[offset..offset + length].to_vec());
+      offset += length;
+    }
+
+    Ok(image)
+  }
+
+  // levels past the last are 1x1
+  pub fn get_level_size(&self, a_level: usize) -> (u32, u32){
+    let level = std::cmp::min(a_level, 31) as u32;
+    (std::cmp::max(self.width >> level, 1), std::cmp::max(self.height >> level, 1))
+  }
+
+  // decodes a level to rgba8 for renderers without the format. bc7 isn't decoded
+  pub fn decompress(&self, a_level: usize) -> Result<image::DynamicImage, RendererError>{
+    let data = match self.levels.get(a_level) {
+      Some(res) => res,
+      None => return Err(RendererError::Error)
+    };
+
+    let (width, height) = self.get_level_size(a_level);
+    let (block_width, block_height) = self.format.get_block_size();
+    let block_bytes = self.format.get_block_bytes() as usize;
+    let blocks_x = (width + block_width - 1) / block_width;
+
+    let mut image = image::RgbaImage::new(width, height);
+
+    for (index, block) in data.chunks_exact(block_bytes).enumerate() {
+      let texels = match decompress::decode_block(self.format, block) {
+        Some(res) => res,
+        None => return Err(RendererError::UnsupportedAPI)
+      };
+
+      let block_x = (index as u32 % blocks_x) * block_width;
+      let block_y = (index as u32 / blocks_x) * block_height;
+
+      // edge blocks are clipped to the image
+      for (texel, color) in texels.iter().enumerate() {
+        let x = block_x + texel as u32 % block_width;
+        let y = block_y + texel as u32 / block_width;
+        if x < width && y < height {
+          image.put_pixel(x, y, image::Rgba(*color));
+        }
+      }
+    }
+
+    Ok(image::DynamicImage::ImageRgba8(image))
+  }
+
+  // the uncompressed format decompressed levels are uploaded as
+  pub fn get_decompressed_format(&self) -> TextureFormat{
+    match self.srgb {
+      true => TextureFormat::SRGB8A8,
+      false => TextureFormat::RGBA8
+    }
+  }
+}
+
+// levels down to 1x1, more would repeat the last level
+fn get_max_levels(a_width: u32, a_height: u32) -> u32 {
+  32 - std::cmp::max(a_width, a_height).leading_zeros()
+}
+
+fn read_u32(a_data: &[u8], a_offset: usize) -> u32 {
+  u32::from_le_bytes([a_data[a_offset], a_data[a_offset + 1], a_data[a_offset + 2], a_data[a_offset + 3]])
+}
+
+fn read_u64(a_data: &[u8], a_offset: usize) -> u64 {
+  read_u32(a_data, a_offset) as u64 | ((read_u32(a_data, a_offset + 4) as u64) << 32)
+}
+
+// VkFormat values, the unorm format is followed by the srgb one
+fn get_ktx2_format(a_vk_format: u32) -> Option<(CompressedFormat, bool)> {
+  let format = match a_vk_format {
+    131..=134 => CompressedFormat::BC1,
+    135 | 136 => CompressedFormat::BC2,
+    137 | 138 => CompressedFormat::BC3,
+    139 => CompressedFormat::BC4,
+    141 => CompressedFormat::BC5,
+    145 | 146 => CompressedFormat::BC7,
+    147 | 148 => CompressedFormat::ETC2RGB8,
+    151 | 152 => CompressedFormat::ETC2RGBA8,
+    157..=184 => {
+      let (width, height) = ASTC_BLOCK_SIZES[(a_vk_format as usize - 157) / 2];
+      CompressedFormat::ASTC(width, height)
+    },
+    _ => return None
+  };
+
+  // bc4 and bc5 have no srgb formats, their odd values are snorm
+  Some((format, a_vk_format % 2 == 0 && !matches!(format, CompressedFormat::BC4 | CompressedFormat::BC5)))
+}
+
+// DXGI_FORMAT values
+fn get_dxgi_format(a_dxgi_format: u32) -> Option<(CompressedFormat, bool)> {
+  match a_dxgi_format {
+    71 => Some((CompressedFormat::BC1, false)),
+    72 => Some((CompressedFormat::BC1, true)),
+    74 => Some((CompressedFormat::BC2, false)),
+    75 => Some((CompressedFormat::BC2, true)),
+    77 => Some((CompressedFormat::BC3, false)),
+    78 => Some((CompressedFormat::BC3, true)),
+    80 => Some((CompressedFormat::BC4, false)),
+    83 => Some((CompressedFormat::BC5, false)),
+    98 => Some((CompressedFormat::BC7, false)),
+    99 => Some((CompressedFormat::BC7, true)),
+    _ => None
+  }
+}
@@ -0,0 +1,818 @@
+use crate::gpu::renderer_types::*;
+
+// decodes a block to rgba8 texels in rows of the block width. bc7 returns None
+pub fn decode_block(a_format: CompressedFormat, a_block: &[u8]) -> Option<Vec<[u8; 4]>> {
+  match a_format {
+    CompressedFormat::BC1 => Some(decode_bc1(a_block, false)),
+    CompressedFormat::BC2 => {
+      let mut texels = decode_bc1(&a_block[8..16], true);
+      for (i, texel) in texels.iter_mut().enumerate() {
+        let alpha = (a_block[i / 2] >> (4 * (i % 2))) & 0xF;
+        texel[3] = alpha * 17;
+      }
+      Some(texels)
+    },
+    CompressedFormat::BC3 => {
+      let mut texels = decode_bc1(&a_block[8..16], true);
+      for (texel, alpha) in texels.iter_mut().zip(decode_bc4(&a_block[0..8])) {
+        texel[3] = alpha;
+      }
+      Some(texels)
+    },
+    CompressedFormat::BC4 => Some(decode_bc4(a_block).iter().map(|red| [*red, 0, 0, 255]).collect()),
+    CompressedFormat::BC5 => Some(decode_bc4(&a_block[0..8]).iter().zip(decode_bc4(&a_block[8..16]))
+      .map(|(red, green)| [*red, green, 0, 255]).collect()),
+    CompressedFormat::BC7 => None,
+    CompressedFormat::ETC2RGB8 => Some(decode_etc2(a_block)),
+    CompressedFormat::ETC2RGBA8 => {
+      let mut texels = decode_etc2(&a_block[8..16]);
+      for (texel, alpha) in texels.iter_mut().zip(decode_eac(&a_block[0..8])) {
+        texel[3] = alpha;
+      }
+      Some(texels)
+    },
+    CompressedFormat::ASTC(width, height) => Some(decode_astc(a_block, width, height))
+  }
+}
+
+fn expand_565(a_color: u16) -> [u32; 3] {
+  let r = (a_color >> 11) as u32 & 0x1F;
+  let g = (a_color >> 5) as u32 & 0x3F;
+  let b = a_color as u32 & 0x1F;
+  [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+// bc2 and bc3 always use four colors, bc1 has three and transparent black when the first color isn't greater
+fn decode_bc1(a_block: &[u8], a_four_colors: bool) -> Vec<[u8; 4]> {
+  let color0 = u16::from_le_bytes([a_block[0], a_block[1]]);
+  let color1 = u16::from_le_bytes([a_block[2], a_block[3]]);
+  let indices = u32::from_le_bytes([a_block[4], a_block[5], a_block[6], a_block[7]]);
+
+  let c0 = expand_565(color0);
+  let c1 = expand_565(color1);
+
+  let mut palette = [[0u8; 4]; 4];
+  for i in 0..3 {
+    palette[0][i] = c0[i] as u8;
+    palette[1][i] = c1[i] as u8;
+
+    if a_four_colors || color0 > color1 {
+      palette[2][i] = ((2 * c0[i] + c1[i]) / 3) as u8;
+      palette[3][i] = ((c0[i] + 2 * c1[i]) / 3) as u8;
+    }
+    else {
+      palette[2][i] = ((c0[i] + c1[i]) / 2) as u8;
+    }
+  }
+
+  palette[0][3] = 255;
+  palette[1][3] = 255;
+  palette[2][3] = 255;
+  palette[3][3] = match a_four_colors || color0 > color1 {
+    true => 255,
+    false => 0
+  };
+
+  (0..16).map(|i| palette[((indices >> (2 * i)) & 3) as usize]).collect()
+}
+
+// 3 bit indices into 8 values, or 6 values with 0 and 255 when the first endpoint isn't greater
+fn decode_bc4(a_block: &[u8]) -> Vec<u8> {
+  let e0 = a_block[0] as u32;
+  let e1 = a_block[1] as u32;
+  let indices = a_block[2..8].iter().rev().fold(0u64, |bits, byte| (bits << 8) | *byte as u64);
+
+  let mut palette = [0u8; 8];
+  palette[0] = e0 as u8;
+  palette[1] = e1 as u8;
+
+  if e0 > e1 {
+    for i in 1..7 {
+      palette[i + 1] = (((7 - i as u32) * e0 + i as u32 * e1) / 7) as u8;
+    }
+  }
+  else {
+    for i in 1..5 {
+      palette[i + 1] = (((5 - i as u32) * e0 + i as u32 * e1) / 5) as u8;
+    }
+    palette[6] = 0;
+    palette[7] = 255;
+  }
+
+  (0..16).map(|i| palette[((indices >> (3 * i)) & 7) as usize]).collect()
+}
+
+const ETC_MODIFIERS: [[i32; 2]; 8] = [[2, 8], [5, 17], [9, 29], [13, 42], [18, 60], [24, 80], [33, 106], [47, 183]];
+const ETC_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+  [-3, -6, -9, -15, 2, 5, 8, 14],
+  [-3, -7, -10, -13, 2, 6, 9, 12],
+  [-2, -5, -8, -13, 1, 4, 7, 12],
+  [-2, -4, -6, -13, 1, 3, 5, 12],
+  [-3, -6, -8, -12, 2, 5, 7, 11],
+  [-3, -7, -9, -11, 2, 6, 8, 10],
+  [-4, -7, -8, -11, 3, 6, 7, 10],
+  [-3, -5, -8, -11, 2, 4, 7, 10],
+  [-2, -6, -8, -10, 1, 5, 7, 9],
+  [-2, -5, -8, -10, 1, 4, 7, 9],
+  [-2, -4, -8, -10, 1, 3, 7, 9],
+  [-2, -5, -7, -10, 1, 4, 6, 9],
+  [-3, -4, -7, -10, 2, 3, 6, 9],
+  [-1, -2, -3, -10, 0, 1, 2, 9],
+  [-4, -6, -8, -9, 3, 5, 7, 8],
+  [-3, -5, -7, -9, 2, 4, 6, 8]
+];
+
+fn get_bits(a_value: u64, a_start: u32, a_count: u32) -> u32 {
+  ((a_value >> a_start) & ((1 << a_count) - 1)) as u32
+}
+
+fn extend_bits(a_value: u32, a_count: u32) -> i32 {
+  ((a_value << (8 - a_count)) | (a_value >> (2 * a_count - 8))) as i32
+}
+
+fn clamp_color(a_color: [i32; 3]) -> [u8; 4] {
+  [a_color[0].clamp(0, 255) as u8, a_color[1].clamp(0, 255) as u8, a_color[2].clamp(0, 255) as u8, 255]
+}
+
+fn offset_color(a_color: [i32; 3], a_offset: i32) -> [u8; 4] {
+  clamp_color([a_color[0] + a_offset, a_color[1] + a_offset, a_color[2] + a_offset])
+}
+
+// etc1 individual and differential modes with the t, h and planar modes of etc2 in the invalid differential colors
+fn decode_etc2(a_block: &[u8]) -> Vec<[u8; 4]> {
+  let bits = u64::from_be_bytes([a_block[0], a_block[1], a_block[2], a_block[3], a_block[4], a_block[5], a_block[6], a_block[7]]);
+
+  // indices are in columns
+  let get_index = |a_texel: usize| -> usize {
+    let i = (a_texel % 4) * 4 + a_texel / 4;
+    ((get_bits(bits, 16 + i as u32, 1) << 1) | get_bits(bits, i as u32, 1)) as usize
+  };
+
+  let differential = get_bits(bits, 33, 1) == 1;
+  let flip = get_bits(bits, 32, 1) == 1;
+
+  let delta = |a_start: u32| -> i32 {
+    ((get_bits(bits, a_start, 3) as i32) << 29) >> 29
+  };
+  let r = get_bits(bits, 59, 5) as i32 + delta(56);
+  let g = get_bits(bits, 51, 5) as i32 + delta(48);
+  let b = get_bits(bits, 43, 5) as i32 + delta(40);
+
+  if differential && !(0..32).contains(&r) {
+    // t mode
+    let color0 = [
+      extend_bits((get_bits(bits, 59, 2) << 2) | get_bits(bits, 56, 2), 4),
+      extend_bits(get_bits(bits, 52, 4), 4),
+      extend_bits(get_bits(bits, 48, 4), 4)
+    ];
+    let color1 = [extend_bits(get_bits(bits, 44, 4), 4), extend_bits(get_bits(bits, 40, 4), 4), extend_bits(get_bits(bits, 36, 4), 4)];
+    let distance = ETC_DISTANCES[((get_bits(bits, 34, 2) << 1) | get_bits(bits, 32, 1)) as usize];
+
+    let paint = [clamp_color(color0), offset_color(color1, distance), clamp_color(color1), offset_color(color1, -distance)];
+    (0..16).map(|i| paint[get_index(i)]).collect()
+  }
+  else if differential && !(0..32).contains(&g) {
+    // h mode
+    let r0 = get_bits(bits, 59, 4);
+    let g0 = (get_bits(bits, 56, 3) << 1) | get_bits(bits, 52, 1);
+    let b0 = (get_bits(bits, 51, 1) << 3) | (get_bits(bits, 47, 3));
+    let r1 = get_bits(bits, 43, 4);
+    let g1 = get_bits(bits, 39, 4);
+    let b1 = get_bits(bits, 35, 4);
+
+    // the order of the colors is the lowest bit of the distance
+    let order = (((r0 << 8) | (g0 << 4) | b0) >= ((r1 << 8) | (g1 << 4) | b1)) as u32;
+    let distance = ETC_DISTANCES[((get_bits(bits, 34, 1) << 2) | (get_bits(bits, 32, 1) << 1) | order) as usize];
+
+    let color0 = [extend_bits(r0, 4), extend_bits(g0, 4), extend_bits(b0, 4)];
+    let color1 = [extend_bits(r1, 4), extend_bits(g1, 4), extend_bits(b1, 4)];
+
+    let paint = [offset_color(color0, distance), offset_color(color0, -distance), offset_color(color1, distance), offset_color(color1, -distance)];
+    (0..16).map(|i| paint[get_index(i)]).collect()
+  }
+  else if differential && !(0..32).contains(&b) {
+    // planar mode with origin, horizontal and vertical colors
+    let origin = [
+      extend_bits(get_bits(bits, 57, 6), 6),
+      extend_bits((get_bits(bits, 56, 1) << 6) | get_bits(bits, 49, 6), 7),
+      extend_bits((get_bits(bits, 48, 1) << 5) | (get_bits(bits, 43, 2) << 3) | get_bits(bits, 39, 3), 6)
+    ];
+    let horizontal = [
+      extend_bits((get_bits(bits, 34, 5) << 1) | get_bits(bits, 32, 1), 6),
+      extend_bits(get_bits(bits, 25, 7), 7),
+      extend_bits(get_bits(bits, 19, 6), 6)
+    ];
+    let vertical = [extend_bits(get_bits(bits, 13, 6), 6), extend_bits(get_bits(bits, 6, 7), 7), extend_bits(get_bits(bits, 0, 6), 6)];
+
+    (0..16).map(|i| {
+      let (x, y) = ((i % 4) as i32, (i / 4) as i32);
+      let mut color = [0; 3];
+      for c in 0..3 {
+        color[c] = (x * (horizontal[c] - origin[c]) + y * (vertical[c] - origin[c]) + 4 * origin[c] + 2) >> 2;
+      }
+      clamp_color(color)
+    }).collect()
+  }
+  else {
+    let (color0, color1) = match differential {
+      true => {
+        let base = [get_bits(bits, 59, 5), get_bits(bits, 51, 5), get_bits(bits, 43, 5)];
+        (
+          [extend_bits(base[0], 5), extend_bits(base[1], 5), extend_bits(base[2], 5)],
+          [extend_bits(r as u32, 5), extend_bits(g as u32, 5), extend_bits(b as u32, 5)]
+        )
+      },
+      false => (
+        [extend_bits(get_bits(bits, 60, 4), 4), extend_bits(get_bits(bits, 52, 4), 4), extend_bits(get_bits(bits, 44, 4), 4)],
+        [extend_bits(get_bits(bits, 56, 4), 4), extend_bits(get_bits(bits, 48, 4), 4), extend_bits(get_bits(bits, 40, 4), 4)]
+      )
+    };
+
+    let tables = [ETC_MODIFIERS[get_bits(bits, 37, 3) as usize], ETC_MODIFIERS[get_bits(bits, 34, 3) as usize]];
+
+    (0..16).map(|i| {
+      let (x, y) = (i % 4, i / 4);
+      // 2x4 subblocks side by side, or 4x2 above each other when flipped
+      let second = match flip {
+        true => y >= 2,
+        false => x >= 2
+      };
+
+      let (color, table) = match second {
+        true => (color1, tables[1]),
+        false => (color0, tables[0])
+      };
+
+      let modifier = match get_index(i) {
+        0 => table[0],
+        1 => table[1],
+        2 => -table[0],
+        _ => -table[1]
+      };
+
+      offset_color(color, modifier)
+    }).collect()
+  }
+}
+
+// alpha of etc2 rgba8
+fn decode_eac(a_block: &[u8]) -> Vec<u8> {
+  let bits = u64::from_be_bytes([a_block[0], a_block[1], a_block[2], a_block[3], a_block[4], a_block[5], a_block[6], a_block[7]]);
+
+  let base = get_bits(bits, 56, 8) as i32;
+  let multiplier = get_bits(bits, 52, 4) as i32;
+  let table = EAC_MODIFIERS[get_bits(bits, 48, 4) as usize];
+
+  (0..16).map(|i| {
+    let column = (i % 4) * 4 + i / 4;
+    let index = get_bits(bits, 45 - 3 * column as u32, 3) as usize;
+    (base + table[index] * multiplier).clamp(0, 255) as u8
+  }).collect()
+}
+
+// astc levels of integer sequence encoding as the number of levels, 3 for trits or 5 for quints and the bits
+const ISE_RANGES: [(u32, u32, u32); 21] = [
+  (2, 0, 1), (3, 3, 0), (4, 0, 2), (5, 5, 0), (6, 3, 1), (8, 0, 3), (10, 5, 1), (12, 3, 2), (16, 0, 4), (20, 5, 2),
+  (24, 3, 3), (32, 0, 5), (40, 5, 3), (48, 3, 4), (64, 0, 6), (80, 5, 4), (96, 3, 5), (128, 0, 7), (160, 5, 5),
+  (192, 3, 6), (256, 0, 8)
+];
+
+const ASTC_ERROR_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+fn get_ise_range(a_levels: u32) -> (u32, u32) {
+  match ISE_RANGES.iter().find(|range| range.0 == a_levels) {
+    Some(res) => (res.1, res.2),
+    None => (0, 8)
+  }
+}
+
+fn get_ise_bit_count(a_count: u32, a_levels: u32) -> u32 {
+  let (packed, bits) = get_ise_range(a_levels);
+  match packed {
+    3 => a_count * bits + (8 * a_count + 4) / 5,
+    5 => a_count * bits + (7 * a_count + 2) / 3,
+    _ => a_count * bits
+  }
+}
+
+// a_count values from a_start, bits past the end of the sequence read as 0
+fn decode_ise(a_bits: u128, a_start: u32, a_count: u32, a_levels: u32) -> Vec<u32> {
+  let (packed, bits) = get_ise_range(a_levels);
+  let end = a_start + get_ise_bit_count(a_count, a_levels);
+  let mut position = a_start;
+
+  let mut read = |a_count: u32| -> u32 {
+    let mut value = 0;
+    for i in 0..a_count {
+      if position + i < end && position + i < 128 {
+        value |= (((a_bits >> (position + i)) & 1) as u32) << i;
+      }
+    }
+    position += a_count;
+    value
+  };
+
+  let mut values = Vec::new();
+
+  while (values.len() as u32) < a_count {
+    match packed {
+      3 => {
+        // five trits with their bits in between
+        let mut low = [0; 5];
+        low[0] = read(bits);
+        let mut t = read(2);
+        low[1] = read(bits);
+        t |= read(2) << 2;
+        low[2] = read(bits);
+        t |= read(1) << 4;
+        low[3] = read(bits);
+        t |= read(2) << 5;
+        low[4] = read(bits);
+        t |= read(1) << 7;
+
+        for (trit, low) in decode_trits(t).iter().zip(low) {
+          values.push((trit << bits) | low);
+        }
+      },
+      5 => {
+        // three quints
+        let mut low = [0; 3];
+        low[0] = read(bits);
+        let mut q = read(3);
+        low[1] = read(bits);
+        q |= read(2) << 3;
+        low[2] = read(bits);
+        q |= read(2) << 5;
+
+        for (quint, low) in decode_quints(q).iter().zip(low) {
+          values.push((quint << bits) | low);
+        }
+      },
+      _ => values.push(read(bits))
+    }
+  }
+
+  values.truncate(a_count as usize);
+  values
+}
+
+fn decode_trits(a_t: u32) -> [u32; 5] {
+  let bit = |a_value: u32, a_index: u32| (a_value >> a_index) & 1;
+
+  let (c, t4, t3) = match (a_t >> 2) & 7 == 7 {
+    true => ((((a_t >> 5) & 7) << 2) | (a_t & 3), 2, 2),
+    false => match (a_t >> 5) & 3 == 3 {
+      true => (a_t & 0x1F, 2, bit(a_t, 7)),
+      false => (a_t & 0x1F, bit(a_t, 7), (a_t >> 5) & 3)
+    }
+  };
+
+  let (t2, t1, t0) = match (c & 3 == 3, (c >> 2) & 3 == 3) {
+    (true, _) => (2, bit(c, 4), (bit(c, 3) << 1) | (bit(c, 2) & !bit(c, 3) & 1)),
+    (false, true) => (2, 2, c & 3),
+    (false, false) => (bit(c, 4), (c >> 2) & 3, (bit(c, 1) << 1) | (bit(c, 0) & !bit(c, 1) & 1))
+  };
+
+  [t0, t1, t2, t3, t4]
+}
+
+fn decode_quints(a_q: u32) -> [u32; 3] {
+  let bit = |a_value: u32, a_index: u32| (a_value >> a_index) & 1;
+
+  if (a_q >> 1) & 3 == 3 && (a_q >> 5) & 3 == 0 {
+    let q2 = (bit(a_q, 0) << 2) | ((bit(a_q, 4) & !bit(a_q, 0) & 1) << 1) | (bit(a_q, 3) & !bit(a_q, 0) & 1);
+    return [4, 4, q2]
+  }
+
+  let (q2, c) = match (a_q >> 1) & 3 == 3 {
+    true => (4, (((a_q >> 3) & 3) << 3) | ((!(a_q >> 5) & 3) << 1) | (a_q & 1)),
+    false => ((a_q >> 5) & 3, a_q & 0x1F)
+  };
+
+  match c & 7 == 5 {
+    true => [(c >> 3) & 3, 4, q2],
+    false => [c & 7, (c >> 3) & 3, q2]
+  }
+}
+
+// repeats the bits of a value to fill a_to bits
+fn replicate_bits(a_value: u32, a_from: u32, a_to: u32) -> u32 {
+  let mut result = 0;
+  let mut filled = 0;
+  while filled < a_to {
+    result = (result << a_from) | a_value;
+    filled += a_from;
+  }
+  result >> (filled - a_to)
+}
+
+// endpoint values to 0 to 255
+fn unquantize_color(a_value: u32, a_levels: u32) -> i32 {
+  let (packed, bits) = get_ise_range(a_levels);
+  if packed == 0 {
+    return replicate_bits(a_value, bits, 8) as i32
+  }
+
+  let low = a_value & ((1 << bits) - 1);
+  let d = a_value >> bits;
+  let a = match low & 1 {
+    1 => 0x1FF,
+    _ => 0
+  };
+  let m = low >> 1;
+
+  let (b, c) = match (packed, bits) {
+    (3, 1) => (0, 204),
+    (3, 2) => (m * 0x116, 93),
+    (3, 3) => ((m << 7) | (m << 2) | m, 44),
+    (3, 4) => ((m << 6) | m, 22),
+    (3, 5) => ((m << 5) | (m >> 2), 11),
+    (3, _) => ((m << 4) | (m >> 4), 5),
+    (_, 1) => (0, 113),
+    (_, 2) => (m * 0x10C, 54),
+    (_, 3) => ((m << 7) | (m << 1) | (m >> 1), 26),
+    (_, 4) => ((m << 6) | (m >> 1), 13),
+    (_, _) => ((m << 5) | (m >> 3), 6)
+  };
+
+  let t = (d * c + b) ^ a;
+  ((a & 0x80) | (t >> 2)) as i32
+}
+
+// weights to 0 to 64
+fn unquantize_weight(a_value: u32, a_levels: u32) -> u32 {
+  let (packed, bits) = get_ise_range(a_levels);
+
+  let weight = match (packed, bits) {
+    (0, _) => replicate_bits(a_value, bits, 6),
+    (3, 0) => [0, 32, 63][a_value as usize],
+    (5, 0) => [0, 16, 32, 47, 63][a_value as usize],
+    _ => {
+      let low = a_value & ((1 << bits) - 1);
+      let d = a_value >> bits;
+      let a = match low & 1 {
+        1 => 0x7F,
+        _ => 0
+      };
+      let m = low >> 1;
+
+      let (b, c) = match (packed, bits) {
+        (3, 1) => (0, 50),
+        (3, 2) => (m * 0x45, 23),
+        (3, _) => ((m << 5) | m, 11),
+        (_, 1) => (0, 28),
+        (_, _) => (m * 0x42, 13)
+      };
+
+      let t = (d * c + b) ^ a;
+      (a & 0x20) | (t >> 2)
+    }
+  };
+
+  match weight > 32 {
+    true => weight + 1,
+    false => weight
+  }
+}
+
+// weight grid width, height, dual plane and weight levels of the 11 bit block mode
+fn decode_block_mode(a_mode: u32) -> Option<(u32, u32, bool, u32)> {
+  let bit = |a_index: u32| (a_mode >> a_index) & 1;
+  let a = (a_mode >> 5) & 3;
+
+  let (width, height, range, high, dual) = match a_mode & 3 != 0 {
+    true => {
+      let b = (a_mode >> 7) & 3;
+      let (width, height) = match (a_mode >> 2) & 3 {
+        0 => (b + 4, a + 2),
+        1 => (b + 8, a + 2),
+        2 => (a + 2, b + 8),
+        _ => match bit(8) {
+          0 => (a + 2, (b & 1) + 6),
+          _ => ((b & 1) + 2, a + 2)
+        }
+      };
+      (width, height, bit(4) | ((a_mode & 3) << 1), bit(9), bit(10))
+    },
+    false => {
+      if a_mode & 0xF == 0 {
+        return None
+      }
+
+      let range = bit(4) | (((a_mode >> 2) & 3) << 1);
+      match (a_mode >> 7) & 3 {
+        0 => (12, a + 2, range, bit(9), bit(10)),
+        1 => (a + 2, 12, range, bit(9), bit(10)),
+        2 => (a + 6, ((a_mode >> 9) & 3) + 6, range, 0, 0),
+        _ => match a {
+          0 => (6, 10, range, bit(9), bit(10)),
+          1 => (10, 6, range, bit(9), bit(10)),
+          _ => return None
+        }
+      }
+    }
+  };
+
+  if range < 2 {
+    return None
+  }
+
+  let levels = match high {
+    1 => [10, 12, 16, 20, 24, 32][range as usize - 2],
+    _ => [2, 3, 4, 5, 6, 8][range as usize - 2]
+  };
+
+  Some((width, height, dual == 1, levels))
+}
+
+fn hash52(a_value: u32) -> u32 {
+  let mut p = a_value;
+  p ^= p >> 15;
+  p = p.wrapping_sub(p << 17);
+  p = p.wrapping_add(p << 7);
+  p = p.wrapping_add(p << 4);
+  p ^= p >> 5;
+  p = p.wrapping_add(p << 16);
+  p ^= p >> 7;
+  p ^= p >> 3;
+  p ^= p << 6;
+  p ^= p >> 17;
+  p
+}
+
+// the partition of a texel from the partition pattern seed
+fn select_partition(a_seed: u32, a_x: u32, a_y: u32, a_partitions: u32, a_small_block: bool) -> usize {
+  let (x, y) = match a_small_block {
+    true => (a_x << 1, a_y << 1),
+    false => (a_x, a_y)
+  };
+
+  let seed = a_seed + (a_partitions - 1) * 1024;
+  let rnum = hash52(seed);
+
+  let mut seeds = [
+    rnum & 0xF, (rnum >> 4) & 0xF, (rnum >> 8) & 0xF, (rnum >> 12) & 0xF, (rnum >> 16) & 0xF, (rnum >> 20) & 0xF,
+    (rnum >> 24) & 0xF, (rnum >> 28) & 0xF, (rnum >> 18) & 0xF, (rnum >> 22) & 0xF, (rnum >> 26) & 0xF,
+    ((rnum >> 30) | (rnum << 2)) & 0xF
+  ];
+
+  let (shift1, shift2) = match seed & 1 {
+    1 => (if seed & 2 != 0 { 4 } else { 5 }, if a_partitions == 3 { 6 } else { 5 }),
+    _ => (if a_partitions == 3 { 6 } else { 5 }, if seed & 2 != 0 { 4 } else { 5 })
+  };
+  let shift3 = match seed & 0x10 {
+    0 => shift2,
+    _ => shift1
+  };
+
+  for (i, value) in seeds.iter_mut().enumerate() {
+    let shift = match i {
+      0..=7 if i % 2 == 0 => shift1,
+      0..=7 => shift2,
+      _ => shift3
+    };
+    *value = (*value * *value) >> shift;
+  }
+
+  // 2d blocks have a z of 0
+  let a = (seeds[0] * x + seeds[1] * y + (rnum >> 14)) & 0x3F;
+  let b = (seeds[2] * x + seeds[3] * y + (rnum >> 10)) & 0x3F;
+  let c = match a_partitions >= 3 {
+    true => (seeds[4] * x + seeds[5] * y + (rnum >> 6)) & 0x3F,
+    false => 0
+  };
+  let d = match a_partitions >= 4 {
+    true => (seeds[6] * x + seeds[7] * y + (rnum >> 2)) & 0x3F,
+    false => 0
+  };
+
+  if a >= b && a >= c && a >= d {
+    0
+  }
+  else if b >= c && b >= d {
+    1
+  }
+  else if c >= d {
+    2
+  }
+  else {
+    3
+  }
+}
+
+// moves precision from the offset to the base of the endpoint modes with offsets
+fn bit_transfer_signed(a_offset: i32, a_base: i32) -> (i32, i32) {
+  let base = (a_base >> 1) | (a_offset & 0x80);
+  let mut offset = (a_offset >> 1) & 0x3F;
+  if offset & 0x20 != 0 {
+    offset -= 0x40;
+  }
+  (offset, base)
+}
+
+fn blue_contract(a_color: [i32; 4]) -> [i32; 4] {
+  [(a_color[0] + a_color[2]) >> 1, (a_color[1] + a_color[2]) >> 1, a_color[2], a_color[3]]
+}
+
+// both endpoints of an ldr color endpoint mode, None for hdr modes
+fn decode_endpoints(a_mode: u32, a_values: &[i32]) -> Option<([i32; 4], [i32; 4])> {
+  let v = a_values;
+
+  let (e0, e1) = match a_mode {
+    0 => ([v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]),
+    1 => {
+      let l0 = (v[0] >> 2) | (v[1] & 0xC0);
+      let l1 = std::cmp::min(l0 + (v[1] & 0x3F), 255);
+      ([l0, l0, l0, 255], [l1, l1, l1, 255])
+    },
+    4 => ([v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]),
+    5 => {
+      let (offset_l, base_l) = bit_transfer_signed(v[1], v[0]);
+      let (offset_a, base_a) = bit_transfer_signed(v[3], v[2]);
+      let l1 = base_l + offset_l;
+      ([base_l, base_l, base_l, base_a], [l1, l1, l1, base_a + offset_a])
+    },
+    6 => ([(v[0] * v[3]) >> 8, (v[1] * v[3]) >> 8, (v[2] * v[3]) >> 8, 255], [v[0], v[1], v[2], 255]),
+    8 | 12 => {
+      let (a0, a1) = match a_mode {
+        12 => (v[6], v[7]),
+        _ => (255, 255)
+      };
+      match v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+        true => ([v[0], v[2], v[4], a0], [v[1], v[3], v[5], a1]),
+        false => (blue_contract([v[1], v[3], v[5], a1]), blue_contract([v[0], v[2], v[4], a0]))
+      }
+    },
+    9 | 13 => {
+      let (offset_r, base_r) = bit_transfer_signed(v[1], v[0]);
+      let (offset_g, base_g) = bit_transfer_signed(v[3], v[2]);
+      let (offset_b, base_b) = bit_transfer_signed(v[5], v[4]);
+      let (offset_a, base_a) = match a_mode {
+        13 => bit_transfer_signed(v[7], v[6]),
+        _ => (0, 255)
+      };
+
+      let base = [base_r, base_g, base_b, base_a];
+      let sum = [base_r + offset_r, base_g + offset_g, base_b + offset_b, base_a + offset_a];
+      match offset_r + offset_g + offset_b >= 0 {
+        true => (base, sum),
+        false => (blue_contract(sum), blue_contract(base))
+      }
+    },
+    10 => ([(v[0] * v[3]) >> 8, (v[1] * v[3]) >> 8, (v[2] * v[3]) >> 8, v[4]], [v[0], v[1], v[2], v[5]]),
+    _ => return None
+  };
+
+  Some((e0.map(|value| value.clamp(0, 255)), e1.map(|value| value.clamp(0, 255))))
+}
+
+// ldr 2d blocks, errors decode to magenta
+fn decode_astc(a_block: &[u8], a_width: u32, a_height: u32) -> Vec<[u8; 4]> {
+  let texel_count = (a_width * a_height) as usize;
+  let error = vec![ASTC_ERROR_COLOR; texel_count];
+
+  let bits = u128::from_le_bytes(match a_block.try_into() {
+    Ok(res) => res,
+    Err(_res) => return error
+  });
+  let read = |a_start: u32, a_count: u32| ((bits >> a_start) & ((1 << a_count) - 1)) as u32;
+
+  // void extent blocks are a single color of 16 bit channels, hdr isn't supported
+  if read(0, 9) == 0x1FC {
+    if read(9, 1) == 1 {
+      return error
+    }
+    let color = [(read(64, 16) >> 8) as u8, (read(80, 16) >> 8) as u8, (read(96, 16) >> 8) as u8, (read(112, 16) >> 8) as u8];
+    return vec![color; texel_count]
+  }
+
+  let (grid_width, grid_height, dual, weight_levels) = match decode_block_mode(read(0, 11)) {
+    Some(res) => res,
+    None => return error
+  };
+
+  let planes = 1 + dual as u32;
+  let weight_count = grid_width * grid_height * planes;
+  let weight_bits = get_ise_bit_count(weight_count, weight_levels);
+
+  if grid_width > a_width || grid_height > a_height || weight_count > 64 || !(24..=96).contains(&weight_bits) {
+    return error
+  }
+
+  let partitions = read(11, 2) + 1;
+  if dual && partitions == 4 {
+    return error
+  }
+
+  // color endpoint modes of each partition, more than one partition may put some of the bits below the weights
+  let mut modes = vec![read(13, 4); partitions as usize];
+  let mut extra_bits = 0;
+  let color_start = match partitions {
+    1 => 17,
+    _ => {
+      let selector = read(23, 2);
+      if selector != 0 {
+        extra_bits = 3 * partitions - 4;
+        let mode_bits = read(25, 4) | (read(128 - weight_bits - extra_bits, extra_bits) << 4);
+        for (i, mode) in modes.iter_mut().enumerate() {
+          let class = selector - 1 + ((mode_bits >> i) & 1);
+          *mode = (class << 2) | ((mode_bits >> (partitions + 2 * i as u32)) & 3);
+        }
+      }
+      else {
+        modes = vec![read(25, 4); partitions as usize];
+      }
+      29
+    }
+  };
+
+  let plane_bits = match dual {
+    true => 2,
+    false => 0
+  };
+  let color_end = 128 - weight_bits - extra_bits - plane_bits;
+  let plane_component = read(color_end, plane_bits) as usize;
+
+  let value_count: u32 = modes.iter().map(|mode| 2 * ((mode >> 2) + 1)).sum();
+  if value_count > 18 || color_end < color_start || color_end - color_start < (13 * value_count + 4) / 5 {
+    return error
+  }
+
+  // the most levels that fit in the bits left for colors
+  let color_levels = match ISE_RANGES.iter().rev().find(|range| {
+    range.0 >= 6 && get_ise_bit_count(value_count, range.0) <= color_end - color_start
+  }) {
+    Some(res) => res.0,
+    None => return error
+  };
+
+  let values: Vec<i32> = decode_ise(bits, color_start, value_count, color_levels).iter()
+    .map(|value| unquantize_color(*value, color_levels)).collect();
+
+  let mut endpoints = Vec::new();
+  let mut offset = 0;
+  for mode in &modes {
+    let count = 2 * ((mode >> 2) as usize + 1);
+    match decode_endpoints(*mode, &values[offset..offset + count]) {
+      Some(res) => endpoints.push(res),
+      None => return error
+    };
+    offset += count;
+  }
+
+  // weights are read from the top of the block backwards
+  let weights: Vec<u32> = decode_ise(bits.reverse_bits(), 0, weight_count, weight_levels).iter()
+    .map(|weight| unquantize_weight(*weight, weight_levels)).collect();
+
+  let seed = read(13, 10);
+  let small_block = texel_count < 31;
+
+  let scale_x = (1024 + a_width / 2) / (a_width - 1);
+  let scale_y = (1024 + a_height / 2) / (a_height - 1);
+
+  (0..texel_count).map(|texel| {
+    let x = texel as u32 % a_width;
+    let y = texel as u32 / a_width;
+
+    // bilinear infill of the weight grid
+    let gs = (scale_x * x * (grid_width - 1) + 32) >> 6;
+    let gt = (scale_y * y * (grid_height - 1) + 32) >> 6;
+    let (js, fs) = (gs >> 4, gs & 0xF);
+    let (jt, ft) = (gt >> 4, gt & 0xF);
+
+    let w11 = (fs * ft + 8) >> 4;
+    let w10 = ft - w11;
+    let w01 = fs - w11;
+    let w00 = 16 + w11 - fs - ft;
+
+    let get_weight = |a_plane: u32| -> u32 {
+      let weight = |a_x: u32, a_y: u32| -> u32 {
+        let index = ((a_y * grid_width + a_x) * planes + a_plane) as usize;
+        weights.get(index).copied().unwrap_or(0)
+      };
+      (weight(js, jt) * w00 + weight(js + 1, jt) * w01 + weight(js, jt + 1) * w10 + weight(js + 1, jt + 1) * w11 + 8) >> 4
+    };
+
+    let partition = match partitions {
+      1 => 0,
+      _ => select_partition(seed, x, y, partitions, small_block)
+    };
+    let (e0, e1) = endpoints[partition];
+
+    let mut color = [0u8; 4];
+    for c in 0..4 {
+      let weight = match dual && c == plane_component {
+        true => get_weight(1),
+        false => get_weight(0)
+      };
+
+      // interpolated in 16 bits
+      let c0 = (e0[c] as u32) * 257;
+      let c1 = (e1[c] as u32) * 257;
+      color[c] = (((c0 * (64 - weight) + c1 * weight + 32) >> 6) >> 8) as u8;
+    }
+    color
+  }).collect()
+}
@@ -7,6 +7,8 @@ use crate::gpu::camera::*;
 use crate::gpu::uniforms::*;
 use crate::gpu::directx::renderer_common::*;
 use crate::gpu::image::*;
+use crate::gpu::compressed::*;
+use crate::gpu::shader_compiler::*;
 
 use std::result::Result;
 use std::rc::Rc;
@@ -60,11 +62,21 @@ impl Uniform for UniformDirectX12 {
 
   fn set_f32(&mut self, a: f32){
     self.data.set::<f32>(a);
+    self.modified = true;
   }
 
   fn get_f32(&self) -> f32{
     self.data.get::<f32>()
   }
+
+  fn get_data(&self) -> &UniformData{
+    &self.data
+  }
+
+  fn get_data_mut(&mut self) -> &mut UniformData{
+    self.modified = true;
+    &mut self.data
+  }
   
   fn get_name(&self) -> &str{
     &self.name.get_name()
@@ -87,13 +99,48 @@ impl UniformShader for UniformShaderDirectX12 {
   }
 }
 
+pub struct InstancesDirectX12 {
+}
+
+impl Instances for InstancesDirectX12 {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_num_instances(&self) -> u32{
+    0
+  }
+}
+
+pub struct IndicesDirectX12 {
+}
+
+impl Indices for IndicesDirectX12 {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+}
+
 pub struct GeometryDirectX12 {
+  topology: PrimitiveTopology,
 }
 
 impl Geometry for GeometryDirectX12 {
   fn any(&self) -> &dyn std::any::Any{
     self
   }
+
+  fn get_num_elements(&self) -> u32{
+    0
+  }
+
+  fn get_topology(&self) -> PrimitiveTopology{
+    self.topology
+  }
+
+  fn set_topology(&mut self, a_topology: PrimitiveTopology){
+    self.topology = a_topology;
+  }
 }
 
 #[allow(dead_code)]
@@ -121,6 +168,10 @@ impl Sampler for SamplerDirectX12 {
   fn set_name(&mut self, a_name: &str){
     self.name = String::from(a_name);
   }
+
+  fn get_name(&self) -> &str{
+    &self.name
+  }
 }
 
 pub struct RendererDirectX12 {
@@ -135,6 +186,8 @@ pub struct RendererDirectX12 {
 
   viewport_pos: IVec2,
   viewport_size: IVec2,
+
+  render_state: RenderState,
 }
 
 #[allow(dead_code)]
@@ -193,27 +246,131 @@ impl Renderer for RendererDirectX12 {
   fn get_viewport_size(&self) -> IVec2{
     return self.viewport_size
   }
+
+  fn set_render_state(&mut self, a_state: &RenderState){
+    self.render_state = *a_state;
+  }
+
+  fn get_render_state(&self) -> &RenderState{
+    &self.render_state
+  }
   
   fn load_shader(&mut self, _a_shader_type: ShaderType, _a_source: &str) -> Result<Box<dyn Shader>, RendererError>{
     return Err(RendererError::Unimplemented)
   }
 
+  fn load_shader_neutral(&mut self, a_shader_type: ShaderType, a_source: &str) -> Result<Box<dyn Shader>, RendererError>{
+    let source = match ParsedShader::parse(a_shader_type, a_source) {
+      Ok(res) => res.to_hlsl(),
+      Err(res) => Err(res)
+    };
+
+    match source {
+      Ok(res) => self.load_shader(a_shader_type, &res),
+      Err(res) => Err(res)
+    }
+  }
+
   fn load_program_vert_frag(&mut self, _a_shader_vert: Box<dyn Shader>, _a_shader_frag: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
     return Err(RendererError::Unimplemented)
   }
 
+  fn load_program(&mut self, _a_builder: ProgramBuilder) -> Result<Box<dyn Program>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn set_patch_vertices(&mut self, _a_count: u32){
+
+  }
+
   fn get_uniform(&mut self, a_shader: &mut Box<dyn Program>, a_name: &str) -> Box<dyn UniformShader>{
     Box::new(UniformShaderDirectX12{
       name: UniformName::new(a_name)
     })
   }
 
+  fn load_program_compute(&mut self, _a_shader: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn dispatch(&mut self, _a_x: u32, _a_y: u32, _a_z: u32){
+
+  }
+
+  fn memory_barrier(&mut self, _a_barrier: BarrierType){
+
+  }
+
+  fn gen_buffer_storage(&mut self, _a_data: &[u8]) -> Result<Box<dyn StorageBuffer>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn update_buffer_storage(&mut self, _a_buffer: &mut Box<dyn StorageBuffer>, _a_data: &[u8]){
+
+  }
+
+  fn read_buffer_storage(&mut self, _a_buffer: &Box<dyn StorageBuffer>) -> Vec<u8>{
+    Vec::new()
+  }
+
+  fn bind_buffer_storage(&mut self, _a_binding: u32, _a_buffer: &Box<dyn StorageBuffer>){
+
+  }
+
+  fn gen_texture_storage(&mut self, _a_width: u32, _a_height: u32, _a_format: TextureFormat) -> Result<Box<dyn Texture>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn bind_image_storage(&mut self, _a_unit: u32, _a_texture: &dyn Texture, _a_access: StorageAccess, _a_format: TextureFormat){
+
+  }
+
+  fn gen_buffer_uniform(&mut self, _a_layout: &UniformBlockLayout, _a_block: &dyn UniformBlock) -> Result<Box<dyn UniformBuffer>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn update_buffer_uniform(&mut self, _a_buffer: &mut Box<dyn UniformBuffer>, _a_block: &dyn UniformBlock) -> Result<(), RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn bind_buffer_uniform(&mut self, _a_binding: u32, _a_buffer: &Box<dyn UniformBuffer>){
+
+  }
+
+  fn bind_uniform_block(&mut self, _a_program: &Box<dyn Program>, _a_block_name: &str, _a_binding: u32, _a_layout: &UniformBlockLayout) -> Result<(), RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
   fn gen_buffer_vertex(&mut self, _a_verts: &std::vec::Vec<f32>) -> Box<dyn Vertices>{
     Box::new(VerticesDirectX12{})
   }
 
+  fn gen_buffer_vertex_bytes(&mut self, _a_data: &[u8]) -> Box<dyn Vertices>{
+    Box::new(VerticesDirectX12{})
+  }
+
   fn gen_geometry(&mut self, _a_buffer: &Box<dyn Vertices>) -> Box<dyn Geometry>{
-    Box::new(GeometryDirectX12{})
+    Box::new(GeometryDirectX12{topology: PrimitiveTopology::Triangles})
+  }
+
+  fn gen_geometry_layout(&mut self, _a_buffers: &[&Box<dyn Vertices>], _a_layout: &VertexLayout) -> Result<Box<dyn Geometry>, RendererError>{
+    Ok(Box::new(GeometryDirectX12{topology: PrimitiveTopology::Triangles}))
+  }
+
+  fn gen_buffer_index(&mut self, _a_indices: IndexData) -> Box<dyn Indices>{
+    Box::new(IndicesDirectX12{})
+  }
+
+  fn gen_geometry_indexed(&mut self, _a_buffers: &[&Box<dyn Vertices>], _a_layout: &VertexLayout, _a_indices: &Box<dyn Indices>) -> Result<Box<dyn Geometry>, RendererError>{
+    Ok(Box::new(GeometryDirectX12{topology: PrimitiveTopology::Triangles}))
+  }
+
+  fn gen_instances(&mut self, _a_layout: &VertexBufferLayout, _a_data: &[u8]) -> Result<Box<dyn Instances>, RendererError>{
+    Ok(Box::new(InstancesDirectX12{}))
+  }
+
+  fn update_instances(&mut self, _a_instances: &mut Box<dyn Instances>, _a_data: &[u8]){
+
   }
 
   fn gen_mesh(&mut self, a_geometry: Box<dyn Geometry>, a_material: Box<dyn Material>) -> Box<Mesh>{
@@ -229,7 +386,7 @@ impl Renderer for RendererDirectX12 {
       height: 0})
   }
 
-  fn gen_sampler(&mut self, a_texture: Rc<dyn Texture>) -> Box<dyn Sampler>{
+  fn gen_sampler(&mut self, a_texture: Rc<dyn Texture>, _a_desc: &SamplerDesc) -> Box<dyn Sampler>{
     Box::new(SamplerDirectX12{name: String::from(""), texture: a_texture})
   }
 
@@ -237,6 +394,22 @@ impl Renderer for RendererDirectX12 {
 
   }
 
+  fn load_texture_format(&mut self, _a_image: &image::DynamicImage, _a_format: TextureFormat, _a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn load_texture_layers(&mut self, _a_images: &[image::DynamicImage], _a_dimension: TextureDimension, _a_format: TextureFormat, _a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn load_texture_compressed(&mut self, _a_image: &CompressedImage, _a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn is_compressed_format_supported(&self, _a_format: CompressedFormat, _a_srgb: bool) -> bool{
+    false
+  }
+
   fn use_program(&mut self, _a_program: &Box<dyn Program>){
 
   }
@@ -245,13 +418,33 @@ impl Renderer for RendererDirectX12 {
 
   }
 
+  fn draw_geometry_range(&mut self, _a_geometry: &Box<dyn Geometry>, _a_range: DrawRange){
+
+  }
+
   fn draw_mesh(&mut self, _a_camera: &Camera, _a_mesh: &mut Box<Mesh>){
 
   }
 
+  fn draw_mesh_instanced(&mut self, _a_camera: &Camera, _a_mesh: &mut Box<Mesh>, _a_instances: &Box<dyn Instances>){
+
+  }
+
   fn read_render_buffer(&mut self) -> Image{
     return Image{width: 0, height: 0, pitch: 0, pixels: std::vec::Vec::<u8>::new()}
   }
+
+  fn gen_render_target(&mut self, _a_desc: &RenderTargetDesc) -> Result<Box<dyn RenderTarget>, RendererError>{
+    return Err(RendererError::Unimplemented)
+  }
+
+  fn set_render_target(&mut self, _a_target: Option<&Box<dyn RenderTarget>>){
+
+  }
+
+  fn clear_render_target(&mut self, _a_target: &Box<dyn RenderTarget>, _a_clear: RendererClearType){
+
+  }
 }
 
 fn print_type_of<T>(_: &T) {
@@ -453,6 +646,7 @@ impl RendererDirectX12 {
       clear_stencil: 0,
       viewport_pos: IVec2::new(0,0),
       viewport_size: IVec2::new(0,0),
+      render_state: RenderState::default(),
     })
   }
 
@@ -7,3 +7,57 @@ pub struct Image {
   pub pixels: std::vec::Vec<u8>,
 }
 
+// the direction through texel x, y of a face of a cubemap with the face order and orientation of gl
+pub fn get_cube_direction(a_face: usize, a_x: u32, a_y: u32, a_size: u32) -> glam::Vec3 {
+  let s = 2.0 * (a_x as f32 + 0.5) / a_size as f32 - 1.0;
+  let t = 2.0 * (a_y as f32 + 0.5) / a_size as f32 - 1.0;
+
+  let direction = match a_face {
+    0 => glam::Vec3::new(1.0, -t, -s),
+    1 => glam::Vec3::new(-1.0, -t, s),
+    2 => glam::Vec3::new(s, 1.0, t),
+    3 => glam::Vec3::new(s, -1.0, -t),
+    4 => glam::Vec3::new(s, -t, 1.0),
+    _ => glam::Vec3::new(-s, -t, -1.0)
+  };
+
+  direction.normalize()
+}
+
+// six faces of a_size for TextureDimension::Cube from a panorama with +y at the top row and -z at the center.
+// faces are float so hdr panoramas keep their range
+pub fn equirectangular_to_cube(a_image: &image::DynamicImage, a_size: u32) -> Vec<image::DynamicImage> {
+  let panorama = a_image.to_rgba32f();
+  let (width, height) = panorama.dimensions();
+
+  // bilinear, wrapping around horizontally and clamped at the poles
+  let fetch = |a_x: i64, a_y: i64| -> glam::Vec4 {
+    let x = a_x.rem_euclid(width as i64) as u32;
+    let y = a_y.clamp(0, height as i64 - 1) as u32;
+    glam::Vec4::from_array(panorama.get_pixel(x, y).0)
+  };
+
+  (0..6).map(|face| {
+    let mut image = image::Rgba32FImage::new(a_size, a_size);
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+      let direction = get_cube_direction(face, x, y, a_size);
+
+      let u = 0.5 + direction.x.atan2(-direction.z) / (2.0 * std::f32::consts::PI);
+      let v = 0.5 - direction.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI;
+
+      let px = u * width as f32 - 0.5;
+      let py = v * height as f32 - 0.5;
+      let x0 = px.floor();
+      let y0 = py.floor();
+      let fx = px - x0;
+      let fy = py - y0;
+
+      let top = fetch(x0 as i64, y0 as i64).lerp(fetch(x0 as i64 + 1, y0 as i64), fx);
+      let bottom = fetch(x0 as i64, y0 as i64 + 1).lerp(fetch(x0 as i64 + 1, y0 as i64 + 1), fx);
+      pixel.0 = top.lerp(bottom, fy).to_array();
+    }
+
+    image::DynamicImage::ImageRgba32F(image)
+  }).collect()
+}
@@ -1,6 +1,7 @@
 
 use crate::gpu::renderer::*;
 use crate::gpu::uniforms::*;
+use crate::gpu::renderer_types::*;
 
 use glam::*;
 
@@ -12,13 +13,25 @@ pub trait Material{
 
   fn get_uniform(&mut self, a_index: usize) -> &mut Box<dyn Uniform>;
   //fn set_uniform(&self, a_index: usize, a_uniform: dyn Uniform);
-  //fn add_uniform(&self, a_uniform: dyn Uniform);
+  // gets the uniform from the material's program, an existing uniform with the same name is replaced
+  fn add_uniform(&mut self, a_name: &str, a_data: UniformData) -> &mut Box<dyn Uniform>;
+  fn find_uniform(&mut self, a_name: &str) -> Option<&mut Box<dyn Uniform>>;
 
   fn get_sampler(&mut self, a_index: usize) -> &mut Box<dyn Sampler>;
-  //fn set_texture(&self, a_index: usize, a_uniform: dyn Sampler);
-  //fn add_texture(&self, a_uniform: dyn Sampler);
+  // names the sampler after the program's sampler uniform it is bound to, an existing sampler with the same name is replaced
+  fn add_sampler(&mut self, a_name: &str, a_sampler: Box<dyn Sampler>) -> &mut Box<dyn Sampler>;
+  fn find_sampler(&mut self, a_name: &str) -> Option<&mut Box<dyn Sampler>>;
 
   fn get_program(&self) -> &Box<dyn Program>;
+  // uniforms are got from the new program keeping their values, the old program is returned
+  fn set_program(&mut self, a_program: Box<dyn Program>) -> Box<dyn Program>;
+
+  // applied by draw_mesh before drawing
+  fn get_render_state(&self) -> &RenderState;
+  fn set_render_state(&mut self, a_state: RenderState);
+
+  // every uniform and sampler has to be active in the program, uniforms with the type they were added with
+  fn validate(&self) -> Result<(), RendererError>;
 }
 
 impl Material for MaterialBasic {
@@ -37,18 +50,99 @@ impl Material for MaterialBasic {
     &mut self.uniforms[a_index]
   }
   //fn set_uniform(&self, a_index: usize, a_uniform: dyn Uniform){}
-  //fn add_uniform(&self, a_uniform: dyn Uniform){}
+
+  fn add_uniform(&mut self, a_name: &str, a_data: UniformData) -> &mut Box<dyn Uniform>{
+    if let Err(res) = self.program.get_reflection().validate_uniform(a_name, &a_data.info) {
+      eprintln!("{}", res);
+    }
+
+    let uniform = self.program.get_uniform(a_name, a_data);
+
+    let index = match self.uniforms.iter().position(|res| res.get_name() == a_name) {
+      Some(res) => {
+        self.uniforms[res] = uniform;
+        res
+      },
+      None => {
+        self.uniforms.push(uniform);
+        self.uniforms.len() - 1
+      }
+    };
+
+    &mut self.uniforms[index]
+  }
+
+  fn find_uniform(&mut self, a_name: &str) -> Option<&mut Box<dyn Uniform>>{
+    self.uniforms.iter_mut().find(|res| res.get_name() == a_name)
+  }
 
   fn get_sampler(&mut self, a_index: usize) -> &mut Box<dyn Sampler>{
     &mut self.samplers[a_index]
   }
-  //fn set_texture(&self, a_index: usize, a_uniform: dyn Sampler){}
-  //fn add_texture(&self, a_uniform: dyn Sampler){}
+
+  fn add_sampler(&mut self, a_name: &str, a_sampler: Box<dyn Sampler>) -> &mut Box<dyn Sampler>{
+    let mut sampler = a_sampler;
+    sampler.set_name(a_name);
+
+    let index = match self.samplers.iter().position(|res| res.get_name() == a_name) {
+      Some(res) => {
+        self.samplers[res] = sampler;
+        res
+      },
+      None => {
+        self.samplers.push(sampler);
+        self.samplers.len() - 1
+      }
+    };
+
+    &mut self.samplers[index]
+  }
+
+  fn find_sampler(&mut self, a_name: &str) -> Option<&mut Box<dyn Sampler>>{
+    self.samplers.iter_mut().find(|res| res.get_name() == a_name)
+  }
 
   fn get_program(&self) -> &Box<dyn Program>{
     &self.program
   }
 
+  fn set_program(&mut self, a_program: Box<dyn Program>) -> Box<dyn Program>{
+    for uniform in self.uniforms.iter_mut() {
+      let name = String::from(uniform.get_name());
+      let data = std::mem::replace(uniform.get_data_mut(), UniformData::new(0.0f32));
+      *uniform = a_program.get_uniform(&name, data);
+    }
+
+    std::mem::replace(&mut self.program, a_program)
+  }
+
+  fn get_render_state(&self) -> &RenderState{
+    &self.render_state
+  }
+
+  fn set_render_state(&mut self, a_state: RenderState){
+    self.render_state = a_state;
+  }
+
+  fn validate(&self) -> Result<(), RendererError>{
+    let reflection = self.program.get_reflection();
+
+    for uniform in &self.uniforms {
+      match reflection.validate_uniform(uniform.get_name(), &uniform.get_data().info) {
+        Ok(res) => res,
+        Err(res) => return Err(res)
+      };
+    }
+
+    for sampler in &self.samplers {
+      if reflection.find_sampler(sampler.get_name()).is_none() {
+        return Err(RendererError::InvalidUniform(String::from(sampler.get_name())))
+      }
+    }
+
+    Ok(())
+  }
+
 }
 
 #[allow(dead_code)]
@@ -56,6 +150,7 @@ pub struct MaterialBasic{
   program: Box<dyn Program>,
   uniforms: std::vec::Vec<Box<dyn Uniform>>,
   samplers: std::vec::Vec<Box<dyn Sampler>>,
+  render_state: RenderState,
 
   mvp: Mat4,
 }
@@ -72,12 +167,38 @@ impl MaterialBasic{
 
     uniforms.push(uniform_mvp);
 
-    let mut material = MaterialBasic{program: a_program, uniforms: uniforms, samplers: samplers, mvp: Mat4::IDENTITY};
-    material.samplers[0].set_name("u_color");
+    let mut material = MaterialBasic{program: a_program, uniforms: uniforms, samplers: samplers, render_state: RenderState::default(), mvp: Mat4::IDENTITY};
+    material.samplers[0].set_name("u_texture");
 
     material
   }
 
+  // a uniform with the default value for every active uniform of the program, samplers are added afterwards.
+  // arrays are left out, uniform data only holds arrays with a size known when compiling
+  pub fn from_program(a_program: Box<dyn Program>) -> Self{
+    let mut uniforms: Vec<Box<dyn Uniform>> = std::vec::Vec::new();
+
+    for uniform in &a_program.get_reflection().uniforms {
+      if let Some(data) = UniformData::from_type(&uniform.info) {
+        uniforms.push(a_program.get_uniform(&uniform.name, data));
+      }
+    }
+
+    MaterialBasic{program: a_program, uniforms: uniforms, samplers: std::vec::Vec::new(), render_state: RenderState::default(), mvp: Mat4::IDENTITY}
+  }
+
+  pub fn set_mvp(&mut self, a_mvp: Mat4){
+    self.mvp = a_mvp;
+
+    if let Some(uniform) = self.find_uniform("u_mvp") {
+      uniform.set::<Mat4>(a_mvp);
+    }
+  }
+
+  pub fn get_mvp(&self) -> Mat4{
+    self.mvp
+  }
+
   pub fn set_color_texture(&self, _texture: &Box<dyn Texture>){
 
   }
@@ -4,6 +4,15 @@ pub mod renderer;
 pub mod uniforms;
 pub mod renderer_types;
 pub mod opengl;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod vulkan;
 pub mod directx;
+pub mod software;
 pub mod image;
+pub mod compressed;
+pub mod decompress;
+pub mod shader_log;
+pub mod shader_text;
+pub mod shader_preprocessor;
+pub mod shader_compiler;
+pub mod shader_reload;
@@ -0,0 +1,121 @@
+extern crate khronos_egl as egl;
+
+use crate::gpu::renderer_types::*;
+
+// EGL_MESA_platform_surfaceless, lets mesa create a display without x11 or wayland
+const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+// Offscreen gl context with no window or surface. Rendering goes to a framebuffer object created by the renderer
+pub struct ContextEGL {
+  egl: egl::DynamicInstance<egl::EGL1_4>,
+  display: egl::Display,
+  context: egl::Context,
+}
+
+#[allow(dead_code)]
+impl ContextEGL {
+  pub fn new(a_version_major: i32, a_version_minor: i32, a_is_gles: bool) -> Result<ContextEGL, RendererError> {
+    let egl = match unsafe { egl::DynamicInstance::<egl::EGL1_4>::load_required() } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let display = match ContextEGL::get_display(&egl) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    if egl.initialize(display).is_err() {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    let (api, renderable_type) = match a_is_gles {
+      true => (egl::OPENGL_ES_API, match a_version_major {
+        1 => egl::OPENGL_ES_BIT,
+        2 => egl::OPENGL_ES2_BIT,
+        _ => egl::OPENGL_ES3_BIT
+      }),
+      false => (egl::OPENGL_API, egl::OPENGL_BIT)
+    };
+
+    if egl.bind_api(api).is_err() {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    // default surface type is window which surfaceless displays do not have
+    let config_attributes = [
+      egl::SURFACE_TYPE, egl::PBUFFER_BIT,
+      egl::RENDERABLE_TYPE, renderable_type,
+      egl::NONE
+    ];
+
+    let config = match egl.choose_first_config(display, &config_attributes) {
+      Ok(Some(res)) => res,
+      _ => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let mut context_attributes = vec![
+      egl::CONTEXT_MAJOR_VERSION, a_version_major,
+      egl::CONTEXT_MINOR_VERSION, a_version_minor];
+
+    if !a_is_gles && (a_version_major > 3 || (a_version_major == 3 && a_version_minor >= 2)) {
+      context_attributes.push(egl::CONTEXT_OPENGL_PROFILE_MASK);
+      context_attributes.push(egl::CONTEXT_OPENGL_CORE_PROFILE_BIT);
+    }
+
+    context_attributes.push(egl::NONE);
+
+    let context = match egl.create_context(display, config, None, &context_attributes) {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::InvalidVersion)
+    };
+
+    // requires EGL_KHR_surfaceless_context
+    if egl.make_current(display, None, None, Some(context)).is_err() {
+      let _ = egl.destroy_context(display, context);
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    Ok(ContextEGL{
+      egl: egl,
+      display: display,
+      context: context
+    })
+  }
+
+  pub fn get_proc_address(&self, a_name: &str) -> *const std::os::raw::c_void {
+    match self.egl.get_proc_address(a_name) {
+      Some(res) => res as *const std::os::raw::c_void,
+      None => std::ptr::null()
+    }
+  }
+
+  pub fn make_current(&self) -> Result<(), RendererError> {
+    match self.egl.make_current(self.display, None, None, Some(self.context)) {
+      Ok(_res) => Ok(()),
+      Err(_res) => Err(RendererError::Error)
+    }
+  }
+
+  fn get_display(a_egl: &egl::DynamicInstance<egl::EGL1_4>) -> Result<egl::Display, RendererError> {
+    // prefer the surfaceless platform so no display server is needed
+    if let Some(egl15) = a_egl.upcast::<egl::EGL1_5>() {
+      if let Ok(res) = unsafe { egl15.get_platform_display(PLATFORM_SURFACELESS_MESA, egl::DEFAULT_DISPLAY, &[egl::ATTRIB_NONE]) } {
+        return Ok(res)
+      }
+    }
+
+    match unsafe { a_egl.get_display(egl::DEFAULT_DISPLAY) } {
+      Some(res) => Ok(res),
+      None => Err(RendererError::UnsupportedAPI)
+    }
+  }
+}
+
+impl Drop for ContextEGL {
+  fn drop(&mut self) {
+    // display is not terminated as it is shared with any other egl contexts in the process
+    let _ = self.egl.make_current(self.display, None, None, None);
+    let _ = self.egl.destroy_context(self.display, self.context);
+  }
+}
@@ -0,0 +1,117 @@
+use crate::gpu::shader_text::replace_word;
+
+// types glsl es has no default precision for, declaring one without a precision statement is an error.
+// float and int have defaults in some stages but are always given one so every stage matches desktop gl
+const ES_PRECISION_TYPES: [&str; 30] = [
+  "float", "int",
+  "sampler3D", "samplerCubeShadow", "sampler2DShadow", "sampler2DArray", "sampler2DArrayShadow", "sampler2DMS",
+  "samplerCubeArray", "samplerCubeArrayShadow", "samplerBuffer",
+  "isampler2D", "isampler3D", "isamplerCube", "isampler2DArray", "isampler2DMS",
+  "usampler2D", "usampler3D", "usamplerCube", "usampler2DArray", "usampler2DMS",
+  "image2D", "image3D", "imageCube", "image2DArray",
+  "iimage2D", "iimage3D", "uimage2D", "uimage3D", "imageBuffer"];
+
+// rewrites a shader written in the other dialect for the context, sources in the context's dialect are left as is.
+// the version becomes the context's, es gets the precision statements it needs and extensions and qualifiers
+// only one dialect has are removed. lines keep their numbers so diagnostics point at the source.
+// gles 2 contexts get version 100 but 3.00 style in and out are not rewritten
+pub fn translate_glsl(a_source: &str, a_gles: bool, a_version: (i32, i32)) -> String {
+  let lines: Vec<&str> = a_source.lines().collect();
+
+  // without a version the compiler picks 110 or 100, which can't be translated to
+  let version_index = match lines.iter().position(|line| line.trim_start().starts_with("#version")) {
+    Some(res) => res,
+    None => return String::from(a_source)
+  };
+
+  let mut version = lines[version_index].trim_start().trim_start_matches("#version").split_whitespace();
+  let source_gles = match (version.next(), version.next()) {
+    (Some("100"), _) | (_, Some("es")) => true,
+    _ => false
+  };
+
+  if source_gles == a_gles {
+    return String::from(a_source)
+  }
+
+  let mut translated = String::new();
+
+  for (index, line) in lines.iter().enumerate() {
+    if index == version_index {
+      translated.push_str(&format!("#version {}\n", get_version_directive(a_gles, a_version)));
+
+      if a_gles {
+        let precisions = get_missing_precisions(&lines);
+        for precision in &precisions {
+          translated.push_str(&format!("precision highp {};\n", precision));
+        }
+
+        // numbers the following lines as they are in the source. before glsl 3.30 and es 3.00 #line gives
+        // the number of the line after it
+        if !precisions.is_empty() {
+          let line = match a_version.0 >= 3 {
+            true => index + 2,
+            false => index + 1
+          };
+          translated.push_str(&format!("#line {}\n", line));
+        }
+      }
+      continue;
+    }
+
+    let directive = line.trim_start();
+    let extension = directive.starts_with("#extension");
+
+    if extension && a_gles && directive.contains("GL_ARB_") {
+      translated.push('\n');
+    }
+    else if extension && !a_gles && (directive.contains("GL_OES_") || directive.contains("GL_EXT_geometry_shader") ||
+      directive.contains("GL_EXT_tessellation_shader") || directive.contains("GL_EXT_shader_io_blocks")) {
+      translated.push('\n');
+    }
+    else if a_gles && line.contains("noperspective") {
+      translated.push_str(&replace_word(line, "noperspective", ""));
+      translated.push('\n');
+    }
+    else {
+      translated.push_str(line);
+      translated.push('\n');
+    }
+  }
+
+  translated
+}
+
+// the glsl version of the context
+fn get_version_directive(a_gles: bool, a_version: (i32, i32)) -> String {
+  match (a_gles, a_version) {
+    (true, (major, minor)) if major >= 3 => format!("{}{}0 es", major, minor),
+    (true, _) => String::from("100"),
+    (false, (major, minor)) if (major, minor) >= (3, 3) => format!("{}{}0 core", major, minor),
+    (false, (3, 2)) => String::from("150 core"),
+    (false, (3, 1)) => String::from("140"),
+    (false, (3, 0)) => String::from("130"),
+    (false, (2, 1)) => String::from("120"),
+    (false, _) => String::from("110")
+  }
+}
+
+// float and int, then the opaque types used without a precision statement for them
+fn get_missing_precisions(a_lines: &[&str]) -> Vec<&'static str> {
+  let mut used: Vec<&str> = Vec::new();
+  let mut declared: Vec<&str> = Vec::new();
+
+  for line in a_lines {
+    let words: Vec<&str> = line.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).filter(|word| !word.is_empty()).collect();
+
+    match words.first() {
+      Some(&"precision") if words.len() >= 3 => declared.push(words[2]),
+      _ => used.extend(words)
+    }
+  }
+
+  ES_PRECISION_TYPES.iter()
+    .filter(|name| (**name == "float" || **name == "int" || used.contains(name)) && !declared.contains(name))
+    .copied()
+    .collect()
+}
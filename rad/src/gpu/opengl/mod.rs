@@ -1 +1,4 @@
 pub mod renderer_opengl;
+pub mod glsl_translate;
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten"))))]
+pub mod context_egl;
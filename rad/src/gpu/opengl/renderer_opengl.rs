@@ -2,7 +2,8 @@
 
 extern crate gl;
 
-use std::ffi::{CString};
+use std::cell::{Cell, RefCell};
+use std::ffi::{CStr, CString};
 use std::rc::Rc;
 use std::sync::Arc;
 use glam::*;
@@ -14,11 +15,33 @@ use crate::gpu::camera::*;
 use crate::gpu::uniforms::*;
 use crate::gui::window::Window;
 use crate::gpu::image::*;
+use crate::gpu::compressed::*;
+use crate::gpu::shader_log::*;
+use crate::gpu::shader_compiler::*;
+use crate::gpu::opengl::glsl_translate::translate_glsl;
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten"))))]
+use crate::gpu::opengl::context_egl::ContextEGL;
+
+// core in gl 4.6, the bindings are for 4.5 so the values of EXT_texture_filter_anisotropic are used
+const TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FF;
+
+// EXT_texture_compression_s3tc, EXT_texture_sRGB and KHR_texture_compression_astc_ldr
+const COMPRESSED_RGBA_S3TC_DXT1: gl::types::GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: gl::types::GLenum = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: gl::types::GLenum = 0x83F3;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: gl::types::GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: gl::types::GLenum = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: gl::types::GLenum = 0x8C4F;
+const COMPRESSED_RGBA_ASTC_4X4: gl::types::GLenum = 0x93B0;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_4X4: gl::types::GLenum = 0x93D0;
 
 pub struct SamplerOpenGL{
   name: String,
   texture: Rc<dyn Texture>,
-  uniform: gl::types::GLint,
+  // 0 when sampler objects are unsupported, the desc is then set on the texture
+  object: gl::types::GLuint,
+  desc: SamplerDesc,
 }
 
 impl Sampler for SamplerOpenGL {
@@ -29,10 +52,19 @@ impl Sampler for SamplerOpenGL {
   fn set_name(&mut self, a_name: &str){
     self.name = String::from(a_name);
   }
+
+  fn get_name(&self) -> &str{
+    &self.name
+  }
 }
 
 pub struct ProgramOpenGL {
-  id: gl::types::GLuint
+  id: gl::types::GLuint,
+  // draws with patches instead of triangles
+  patches: bool,
+  // texture unit of every sampler uniform, assigned when linking
+  sampler_units: Rc<Vec<(String, u32)>>,
+  reflection: ProgramReflection,
 }
 
 impl Program for ProgramOpenGL {
@@ -55,10 +87,15 @@ impl Program for ProgramOpenGL {
       modified: true
     })
   }
+
+  fn get_reflection(&self) -> &ProgramReflection{
+    &self.reflection
+  }
 }
 
 pub struct ShaderOpenGL {
   id: gl::types::GLuint,
+  shader_type: ShaderType,
   //source: String
 }
 
@@ -66,11 +103,15 @@ impl Shader for ShaderOpenGL {
   fn any(&self) -> &dyn std::any::Any{
     self
   }
+
+  fn get_type(&self) -> ShaderType{
+    self.shader_type
+  }
 }
 
 pub struct VerticesOpenGL {
   id: gl::types::GLuint,
-  num: gl::types::GLsizei
+  size: usize
 }
 
 impl Vertices for VerticesOpenGL {
@@ -79,22 +120,101 @@ impl Vertices for VerticesOpenGL {
   }
 }
 
+pub struct IndicesOpenGL {
+  id: gl::types::GLuint,
+  index_type: gl::types::GLenum,
+  index_size: u32,
+  num: gl::types::GLsizei
+}
+
+impl Indices for IndicesOpenGL {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+}
+
+pub struct StorageBufferOpenGL {
+  id: gl::types::GLuint,
+  size: Cell<usize>
+}
+
+impl StorageBuffer for StorageBufferOpenGL {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_size(&self) -> usize{
+    self.size.get()
+  }
+}
+
+pub struct UniformBufferOpenGL {
+  id: gl::types::GLuint,
+  layout: UniformBlockLayout,
+  // written by updates before being copied to the buffer
+  staging: RefCell<Vec<u8>>
+}
+
+impl UniformBuffer for UniformBufferOpenGL {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_layout(&self) -> &UniformBlockLayout{
+    &self.layout
+  }
+}
+
+pub struct InstancesOpenGL {
+  id: gl::types::GLuint,
+  layout: VertexBufferLayout,
+  num: Cell<u32>
+}
+
+impl Instances for InstancesOpenGL {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_num_instances(&self) -> u32{
+    self.num.get()
+  }
+}
+
+// the index buffer is part of the vao state
 pub struct GeometryOpenGL {
   vao: gl::types::GLuint,
-  num: gl::types::GLsizei
+  num: gl::types::GLsizei,
+  // type and size of the indices
+  indices: Option<(gl::types::GLenum, u32)>,
+  topology: PrimitiveTopology,
 }
 
 impl Geometry for GeometryOpenGL {
   fn any(&self) -> &dyn std::any::Any{
     self
   }
+
+  fn get_num_elements(&self) -> u32{
+    self.num as u32
+  }
+
+  fn get_topology(&self) -> PrimitiveTopology{
+    self.topology
+  }
+
+  fn set_topology(&mut self, a_topology: PrimitiveTopology){
+    self.topology = a_topology;
+  }
 }
 
 #[allow(dead_code)]
 pub struct TextureOpenGL {
   id: gl::types::GLuint,
   width: u32,
-  height: u32
+  height: u32,
+  // set by the dimension the texture was loaded with
+  target: Cell<gl::types::GLenum>,
 }
 
 impl Texture for TextureOpenGL {
@@ -103,6 +223,44 @@ impl Texture for TextureOpenGL {
   }
 }
 
+pub struct RenderTargetOpenGL {
+  framebuffer: gl::types::GLuint,
+  width: u32,
+  height: u32,
+  colors: Vec<Rc<TextureOpenGL>>,
+  color_formats: Vec<TextureFormat>,
+  depth: Option<Rc<TextureOpenGL>>,
+}
+
+impl RenderTarget for RenderTargetOpenGL {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_width(&self) -> u32{
+    self.width
+  }
+
+  fn get_height(&self) -> u32{
+    self.height
+  }
+
+  fn num_color_attachments(&self) -> usize{
+    self.colors.len()
+  }
+
+  fn get_color_texture(&self, a_index: usize) -> Rc<dyn Texture>{
+    self.colors[a_index].clone()
+  }
+
+  fn get_depth_texture(&self) -> Option<Rc<dyn Texture>>{
+    match &self.depth {
+      Some(res) => Some(res.clone()),
+      None => None
+    }
+  }
+}
+
 #[allow(dead_code)]
 pub struct UniformOpenGL {
   name: UniformName,
@@ -131,11 +289,21 @@ impl Uniform for UniformOpenGL {
 
   fn set_f32(&mut self, a: f32){
     self.data.set::<f32>(a);
+    self.modified = true;
   }
 
   fn get_f32(&self) -> f32{
     self.data.get::<f32>()
   }
+
+  fn get_data(&self) -> &UniformData{
+    &self.data
+  }
+
+  fn get_data_mut(&mut self) -> &mut UniformData{
+    self.modified = true;
+    &mut self.data
+  }
   
   fn get_name(&self) -> &str{
     &self.name.get_name()
@@ -158,12 +326,30 @@ impl UniformShader for UniformShaderOpenGL {
   }
 }
 
+pub enum ContextOpenGL {
+  Window{gl_context: sdl2::video::GLContext, window: Arc<Window>},
+  #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten"))))]
+  Headless(ContextEGL)
+}
+
 pub struct RendererOpenGL {
-  pub gl_context: sdl2::video::GLContext,
+  pub context: ContextOpenGL,
   pub version_major: i32,
   pub version_minor: i32,
+  is_gles: bool,
 
-  window: Arc<Window>,
+  width: u32,
+  height: u32,
+
+  // framebuffer drawn to when no target is set, 0 for windows. headless renders into its own framebuffer object
+  framebuffer: gl::types::GLuint,
+  renderbuffers: [gl::types::GLuint; 2],
+
+  // currently bound framebuffer, its size and the format of its first color attachment, none for the default framebuffer
+  target_framebuffer: gl::types::GLuint,
+  target_width: u32,
+  target_height: u32,
+  target_format: Option<TextureFormat>,
 
   clear_color: Vec4,
   clear_depth: f32,
@@ -174,6 +360,13 @@ pub struct RendererOpenGL {
 
   vao: gl::types::GLint,
   program_id: gl::types::GLint,
+  patches: bool,
+  sampler_units: Rc<Vec<(String, u32)>>,
+  render_state: RenderState,
+  // queried when the context is created, 0 when unsupported
+  max_anisotropy: f32,
+  // 16 bit unorm textures, gles needs an extension
+  norm16: bool
 }
 
 #[allow(dead_code)]
@@ -183,7 +376,10 @@ impl Renderer for RendererOpenGL {
   }
 
   fn get_type(&self) -> RendererType{
-    RendererType::OpenGL
+    match self.is_gles {
+      true => RendererType::OpenGLES,
+      false => RendererType::OpenGL
+    }
   }
 
   fn begin_frame(&mut self, a_clear: RendererClearType){
@@ -191,7 +387,11 @@ impl Renderer for RendererOpenGL {
   }
 
   fn end_frame(&mut self){
-    self.window.window.lock().unwrap().inner.gl_swap_window();
+    match &self.context {
+      ContextOpenGL::Window{window, ..} => window.window.lock().unwrap().inner.gl_swap_window(),
+      #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten"))))]
+      ContextOpenGL::Headless(_context) => unsafe { gl::Flush() }
+    }
   }
 
   //clear immediatly
@@ -210,9 +410,7 @@ impl Renderer for RendererOpenGL {
       bits |= gl::STENCIL_BUFFER_BIT;
     }
 
-    unsafe {
-      gl::Clear(bits);
-    }
+    self.clear_unmasked(bits);
   }
 
   // Get and set clear values may be called before BeginFrame
@@ -264,6 +462,107 @@ impl Renderer for RendererOpenGL {
     self.viewport_size
   }
 
+  // only the differences from the current state are applied. disabled parts keep gl's defaults so the
+  // next enable compares against known values
+  fn set_render_state(&mut self, a_state: &RenderState){
+    let current = self.render_state;
+    if current == *a_state {
+      return
+    }
+
+    self.render_state = *a_state;
+
+    let blend = (current.blend.unwrap_or_default(), a_state.blend.unwrap_or_default());
+    let depth = (current.depth.unwrap_or_default(), a_state.depth.unwrap_or_default());
+    let stencil = (current.stencil.unwrap_or_default(), a_state.stencil.unwrap_or_default());
+
+    unsafe {
+      if current.blend.is_some() != a_state.blend.is_some() {
+        set_capability(gl::BLEND, a_state.blend.is_some());
+      }
+
+      if (blend.0.color_src, blend.0.color_dst, blend.0.alpha_src, blend.0.alpha_dst) != (blend.1.color_src, blend.1.color_dst, blend.1.alpha_src, blend.1.alpha_dst) {
+        gl::BlendFuncSeparate(
+          get_blend_factor(blend.1.color_src),
+          get_blend_factor(blend.1.color_dst),
+          get_blend_factor(blend.1.alpha_src),
+          get_blend_factor(blend.1.alpha_dst));
+      }
+
+      if (blend.0.color_op, blend.0.alpha_op) != (blend.1.color_op, blend.1.alpha_op) {
+        gl::BlendEquationSeparate(get_blend_op(blend.1.color_op), get_blend_op(blend.1.alpha_op));
+      }
+
+      if current.depth.is_some() != a_state.depth.is_some() {
+        set_capability(gl::DEPTH_TEST, a_state.depth.is_some());
+      }
+
+      if depth.0.compare != depth.1.compare {
+        gl::DepthFunc(get_compare_function(depth.1.compare));
+      }
+
+      if depth.0.write != depth.1.write {
+        gl::DepthMask(depth.1.write as gl::types::GLboolean);
+      }
+
+      if current.stencil.is_some() != a_state.stencil.is_some() {
+        set_capability(gl::STENCIL_TEST, a_state.stencil.is_some());
+      }
+
+      for (face, old, new) in [(gl::FRONT, stencil.0.front, stencil.1.front), (gl::BACK, stencil.0.back, stencil.1.back)] {
+        if old.compare != new.compare || stencil.0.reference != stencil.1.reference || stencil.0.read_mask != stencil.1.read_mask {
+          gl::StencilFuncSeparate(face, get_compare_function(new.compare), stencil.1.reference as gl::types::GLint, stencil.1.read_mask);
+        }
+
+        if (old.fail, old.depth_fail, old.pass) != (new.fail, new.depth_fail, new.pass) {
+          gl::StencilOpSeparate(face, get_stencil_op(new.fail), get_stencil_op(new.depth_fail), get_stencil_op(new.pass));
+        }
+      }
+
+      if stencil.0.write_mask != stencil.1.write_mask {
+        gl::StencilMask(stencil.1.write_mask);
+      }
+
+      if current.cull_mode != a_state.cull_mode {
+        match a_state.cull_mode {
+          CullMode::None => gl::Disable(gl::CULL_FACE),
+          CullMode::Front | CullMode::Back => {
+            if current.cull_mode == CullMode::None {
+              gl::Enable(gl::CULL_FACE);
+            }
+
+            gl::CullFace(match a_state.cull_mode {
+              CullMode::Front => gl::FRONT,
+              _ => gl::BACK
+            });
+          }
+        }
+      }
+
+      if current.color_write != a_state.color_write {
+        gl::ColorMask(
+          a_state.color_write.contains(ColorWriteMask::RED) as gl::types::GLboolean,
+          a_state.color_write.contains(ColorWriteMask::GREEN) as gl::types::GLboolean,
+          a_state.color_write.contains(ColorWriteMask::BLUE) as gl::types::GLboolean,
+          a_state.color_write.contains(ColorWriteMask::ALPHA) as gl::types::GLboolean);
+      }
+
+      if current.scissor.is_some() != a_state.scissor.is_some() {
+        set_capability(gl::SCISSOR_TEST, a_state.scissor.is_some());
+      }
+
+      if let Some((pos, size)) = a_state.scissor {
+        if current.scissor != a_state.scissor {
+          gl::Scissor(pos.x, pos.y, std::cmp::max(size.x, 0), std::cmp::max(size.y, 0));
+        }
+      }
+    }
+  }
+
+  fn get_render_state(&self) -> &RenderState{
+    &self.render_state
+  }
+
   fn load_shader(&mut self, a_shader_type: ShaderType, a_source: &str) -> Result<Box<dyn Shader>, RendererError>{
     /*
     let id = match a_shader_type {
@@ -284,11 +583,25 @@ impl Renderer for RendererOpenGL {
       ShaderType::Compute => gl::COMPUTE_SHADER
     };
 
+    let supported = match a_shader_type {
+      ShaderType::Compute => self.is_compute_supported(),
+      ShaderType::TesselationControl | ShaderType::TesselationEvaluation => self.is_tesselation_supported(),
+      ShaderType::Geometry => self.is_geometry_supported(),
+      _ => true
+    };
+
+    if !supported {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    // shaders can be written for either gl or gles
+    let source = translate_glsl(a_source, self.is_gles, (self.version_major, self.version_minor));
+
     let id = unsafe {gl::CreateShader(shader_type)};
 
-    let c_str = match CString::new(a_source){
+    let c_str = match CString::new(source){
       Ok(res) => res,
-      Err(_res) => return Err(RendererError::ShaderCompile)
+      Err(_res) => return Err(RendererError::ShaderCompile(ShaderLog::from_message(Some(a_shader_type), "source contains a nul character")))
     };
     //let c_world: *const c_char = c_str.as_ptr() as *const c_char;
 
@@ -325,18 +638,29 @@ impl Renderer for RendererOpenGL {
         );
       }
 
-      eprintln!("Error {}", error.to_string_lossy().into_owned());
-      //return Err(error.to_string_lossy().into_owned());
+      unsafe {
+        gl::DeleteShader(id);
+      }
 
-      return Err(RendererError::Error)
+      return Err(RendererError::ShaderCompile(ShaderLog::from_info_log(Some(a_shader_type), &error.to_string_lossy(), 0)))
     }
 
-    Ok(Box::new(ShaderOpenGL{id:id}))
+    Ok(Box::new(ShaderOpenGL{id:id, shader_type: a_shader_type}))
   }
 
-  fn load_program_vert_frag(&mut self, a_shader_vert: Box<dyn Shader>, a_shader_frag: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
-    let program_id = unsafe { gl::CreateProgram() };
+  fn load_shader_neutral(&mut self, a_shader_type: ShaderType, a_source: &str) -> Result<Box<dyn Shader>, RendererError>{
+    let source = match ParsedShader::parse(a_shader_type, a_source) {
+      Ok(res) => res.to_glsl(self.is_gles, (self.version_major, self.version_minor)),
+      Err(res) => Err(res)
+    };
 
+    match source {
+      Ok(res) => self.load_shader(a_shader_type, &res),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn load_program_vert_frag(&mut self, a_shader_vert: Box<dyn Shader>, a_shader_frag: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
     let shader_vert = match a_shader_vert.any().downcast_ref::<ShaderOpenGL>() {
       Some(res) => res,
       None => return Err(RendererError::InvalidCast)
@@ -347,480 +671,2237 @@ impl Renderer for RendererOpenGL {
       None => return Err(RendererError::InvalidCast)
     };
 
-    unsafe {
-      gl::AttachShader(program_id, shader_vert.id);
-      gl::AttachShader(program_id, shader_frag.id);
-      gl::LinkProgram(program_id);
-      gl::DetachShader(program_id, shader_vert.id);
-      gl::DetachShader(program_id, shader_frag.id);
-    }
-
-    let mut success: gl::types::GLint = 1;
-    unsafe {
-        gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut success);
-    }
+    self.link_program(&[shader_vert, shader_frag])
+  }
 
-    if success == 0 {
-      let mut len: gl::types::GLint = 0;
-      unsafe {
-        gl::GetProgramiv(program_id, gl::INFO_LOG_LENGTH, &mut len);
-      }
+  fn load_program(&mut self, a_builder: ProgramBuilder) -> Result<Box<dyn Program>, RendererError>{
+    match a_builder.validate() {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
 
-      // allocate buffer of correct size
-      let mut buffer: Vec<u8> = Vec::with_capacity(len as usize + 1);
-      // fill it with len spaces
-      buffer.extend([b' '].iter().cycle().take(len as usize));
-      // convert buffer to CString
-      let error: CString = unsafe { CString::from_vec_unchecked(buffer) };
+    // gles has no default tesselation control stage
+    if self.is_gles && a_builder.has_stage(ShaderType::TesselationEvaluation) && !a_builder.has_stage(ShaderType::TesselationControl) {
+      return Err(RendererError::Error)
+    }
 
-      unsafe {
-        gl::GetProgramInfoLog(
-          program_id,
-          len,
-          std::ptr::null_mut(),
-          error.as_ptr() as *mut gl::types::GLchar
-        );
-      }
+    let mut shaders = Vec::new();
+    for shader in a_builder.get_shaders() {
+      match shader.any().downcast_ref::<ShaderOpenGL>() {
+        Some(res) => shaders.push(res),
+        None => return Err(RendererError::InvalidCast)
+      };
+    }
 
-      eprintln!("Error {}", error.to_string_lossy().into_owned());
+    self.link_program(&shaders)
+  }
 
-      //return Err(error.to_string_lossy().into_owned());
-      return Err(RendererError::Error)
+  fn set_patch_vertices(&mut self, a_count: u32){
+    if !self.is_tesselation_supported() {
+      return
     }
 
-    Ok(Box::new(ProgramOpenGL{id: program_id}))
+    unsafe {
+      gl::PatchParameteri(gl::PATCH_VERTICES, a_count as gl::types::GLint);
+    }
   }
 
-  fn get_uniform(&mut self, a_shader: &mut Box<dyn Program>, a_name: &str) -> Box<dyn UniformShader>{
-    let shader = match a_shader.any().downcast_ref::<ProgramOpenGL>() {
-      Some(res) => res,
-      None => panic!("Invalid shader cast")
-    };
+  fn load_program_compute(&mut self, a_shader: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
+    if !self.is_compute_supported() {
+      return Err(RendererError::UnsupportedAPI)
+    }
 
-    let c_str = match CString::new(a_name){
-      Ok(res) => res,
-      Err(_res) => panic!("Invalid text cast")
+    let shader = match a_shader.any().downcast_ref::<ShaderOpenGL>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
     };
 
-    let location = unsafe{gl::GetUniformLocation(shader.id, c_str.as_ptr())};
+    if shader.shader_type != ShaderType::Compute {
+      return Err(RendererError::Error)
+    }
 
-    Box::new(UniformShaderOpenGL{
-      name: UniformName::new(a_name),
-      id: location
-    })
+    self.link_program(&[shader])
   }
 
-  /*
-  fn set_uniform(&mut self, a_uniform: &Box<dyn Uniform>){
-    let uniform = match a_uniform.any().downcast_ref::<UniformOpenGL>() {
-      Some(res) => res,
-      None => panic!("Invalid uniform cast")
-    };
+  fn dispatch(&mut self, a_x: u32, a_y: u32, a_z: u32){
+    if !self.is_compute_supported() {
+      return
+    }
 
-    unsafe{
-      gl::Uniform1i(uniform.id, 0);
+    unsafe {
+      gl::DispatchCompute(a_x, a_y, a_z);
     }
   }
 
-  fn set_texture(&mut self, a_texture: &Box<dyn Texture>){
-    let texture = match a_texture.any().downcast_ref::<TextureOpenGL>() {
-      Some(res) => res,
-      None => panic!("Invalid uniform cast")
-    };
+  fn memory_barrier(&mut self, a_barrier: BarrierType){
+    if !self.is_compute_supported() || a_barrier.is_empty() {
+      return
+    }
 
-    unsafe{
-      gl::ActiveTexture(gl::TEXTURE0 + 0);
-      gl::BindTexture(gl::TEXTURE_2D,  texture.id);
+    let barriers = [
+      (BarrierType::VERTEX_ATTRIBUTE, gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT),
+      (BarrierType::INDEX, gl::ELEMENT_ARRAY_BARRIER_BIT),
+      (BarrierType::UNIFORM, gl::UNIFORM_BARRIER_BIT),
+      (BarrierType::TEXTURE_FETCH, gl::TEXTURE_FETCH_BARRIER_BIT),
+      (BarrierType::STORAGE_IMAGE, gl::SHADER_IMAGE_ACCESS_BARRIER_BIT),
+      (BarrierType::STORAGE_BUFFER, gl::SHADER_STORAGE_BARRIER_BIT),
+      (BarrierType::BUFFER_UPDATE, gl::BUFFER_UPDATE_BARRIER_BIT),
+      (BarrierType::TEXTURE_UPDATE, gl::TEXTURE_UPDATE_BARRIER_BIT),
+      (BarrierType::FRAMEBUFFER, gl::FRAMEBUFFER_BARRIER_BIT)];
+
+    let bits = barriers.iter()
+      .filter(|(barrier, _bit)| a_barrier.contains(*barrier))
+      .fold(0, |acc, (_barrier, bit)| acc | bit);
+
+    unsafe {
+      gl::MemoryBarrier(bits);
     }
   }
-  */
 
-  fn gen_buffer_vertex(&mut self, a_verts: &std::vec::Vec<f32>) -> Box<dyn Vertices>{
-    let mut vbo: gl::types::GLuint = 0;
-    unsafe {
-      gl::GenBuffers(1, &mut vbo);
+  fn gen_buffer_storage(&mut self, a_data: &[u8]) -> Result<Box<dyn StorageBuffer>, RendererError>{
+    if !self.is_compute_supported() {
+      return Err(RendererError::UnsupportedAPI)
     }
 
+    let mut id: gl::types::GLuint = 0;
     unsafe {
-      gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-      gl::BufferData(
-          gl::ARRAY_BUFFER, // target
-          (a_verts.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr, // size of data in bytes
-          a_verts.as_ptr() as *const gl::types::GLvoid, // pointer to data
-          gl::STATIC_DRAW, // usage
-      );
-      gl::BindBuffer(gl::ARRAY_BUFFER, 0); // unbind the buffer
+      gl::GenBuffers(1, &mut id);
     }
 
-    Box::new(VerticesOpenGL{id: vbo, num: (a_verts.len()/4) as gl::types::GLsizei})
+    let mut buffer: Box<dyn StorageBuffer> = Box::new(StorageBufferOpenGL{id: id, size: Cell::new(0)});
+    self.update_buffer_storage(&mut buffer, a_data);
+
+    Ok(buffer)
   }
 
-  fn gen_geometry(&mut self, a_buffer: &Box<dyn Vertices>) -> Box<dyn Geometry>{
-    let buffer = match a_buffer.any().downcast_ref::<VerticesOpenGL>() {
+  fn update_buffer_storage(&mut self, a_buffer: &mut Box<dyn StorageBuffer>, a_data: &[u8]){
+    let buffer = match a_buffer.any().downcast_ref::<StorageBufferOpenGL>() {
       Some(res) => res,
-      None => panic!("Invalid vertex")
+      None => panic!("Invalid storage buffer")
     };
 
-    let mut vao: gl::types::GLuint = 0;
+    // copy targets leave the vao and indexed storage bindings alone
     unsafe {
-      gl::GenVertexArrays(1, &mut vao);
-      gl::BindVertexArray(vao);
-      gl::BindBuffer(gl::ARRAY_BUFFER, buffer.id);
-
-      gl::EnableVertexAttribArray(0); // todo this is "layout (location = 0)" in vertex shader
-      gl::VertexAttribPointer(
-        0, // index of the generic vertex attribute ("layout (location = 0)")
-        2, // the number of components per generic vertex attribute
-        gl::FLOAT, // data type
-        gl::FALSE, // normalized (int-to-float conversion)
-        (4 * std::mem::size_of::<f32>()) as gl::types::GLint, // stride (byte offset between consecutive attributes)
-        std::ptr::null() // offset of the first component in bytes
-      );
-      
-      gl::EnableVertexAttribArray(1); // todo this is "layout (location = 0)" in vertex shader
-      gl::VertexAttribPointer(
-        1, // index of the generic vertex attribute ("layout (location = 0)")
-        2, // the number of components per generic vertex attribute
-        gl::FLOAT, // data type
-        gl::FALSE, // normalized (int-to-float conversion)
-        (4 * std::mem::size_of::<f32>()) as gl::types::GLint, // stride (byte offset between consecutive attributes)
-        (2 * std::mem::size_of::<f32>()) as *const gl::types::GLvoid // offset of the first component in bytes
+      gl::BindBuffer(gl::COPY_WRITE_BUFFER, buffer.id);
+      gl::BufferData(
+          gl::COPY_WRITE_BUFFER,
+          a_data.len() as gl::types::GLsizeiptr,
+          a_data.as_ptr() as *const gl::types::GLvoid,
+          gl::DYNAMIC_COPY,
       );
-      
-
-      gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-      gl::BindVertexArray(0);
+      gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
     }
-    Box::new(GeometryOpenGL{vao:vao, num: buffer.num})
-  }
 
-  fn gen_mesh(&mut self, a_geometry: Box<dyn Geometry>, a_material: Box<dyn Material>) -> Box<Mesh>{
-    Box::new(Mesh{
-      geometry: a_geometry,
-      material: a_material
-      })
+    buffer.size.set(a_data.len());
   }
 
+  fn read_buffer_storage(&mut self, a_buffer: &Box<dyn StorageBuffer>) -> Vec<u8>{
+    let buffer = match a_buffer.any().downcast_ref::<StorageBufferOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid storage buffer")
+    };
 
-  fn gen_buffer_texture(&mut self) -> Box<dyn Texture>{
-    let mut id: gl::types::GLuint = 0;
-    unsafe {
-      gl::GenTextures(1, &mut id);
+    let size = buffer.size.get();
+    if size == 0 {
+      return Vec::new()
+    }
+
+    // gles has no glGetBufferSubData
+    let mut data = vec![0u8; size];
+    unsafe {
+      gl::BindBuffer(gl::COPY_READ_BUFFER, buffer.id);
+      let ptr = gl::MapBufferRange(gl::COPY_READ_BUFFER, 0, size as gl::types::GLsizeiptr, gl::MAP_READ_BIT);
+      if !ptr.is_null() {
+        std::ptr::copy_nonoverlapping(ptr as *const u8, data.as_mut_ptr(), size);
+        gl::UnmapBuffer(gl::COPY_READ_BUFFER);
+      }
+      gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+    }
+
+    data
+  }
+
+  fn bind_buffer_storage(&mut self, a_binding: u32, a_buffer: &Box<dyn StorageBuffer>){
+    let buffer = match a_buffer.any().downcast_ref::<StorageBufferOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid storage buffer")
+    };
+
+    if !self.is_compute_supported() {
+      return
+    }
+
+    unsafe {
+      gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, a_binding, buffer.id);
+    }
+  }
+
+  fn gen_texture_storage(&mut self, a_width: u32, a_height: u32, a_format: TextureFormat) -> Result<Box<dyn Texture>, RendererError>{
+    if !self.is_compute_supported() {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    if a_width == 0 || a_height == 0 || a_format.is_depth() {
+      return Err(RendererError::Error)
+    }
+
+    let (internal_format, _format, _data_type) = get_texture_format(a_format);
+
+    // gles only binds immutable textures to image units
+    let mut id: gl::types::GLuint = 0;
+    unsafe {
+      gl::GenTextures(1, &mut id);
+      gl::BindTexture(gl::TEXTURE_2D, id);
+      gl::TexStorage2D(gl::TEXTURE_2D, 1, internal_format, a_width as i32, a_height as i32);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+      gl::BindTexture(gl::TEXTURE_2D, 0);
+    }
+
+    Ok(Box::new(TextureOpenGL{id: id, width: a_width, height: a_height, target: Cell::new(gl::TEXTURE_2D)}))
+  }
+
+  fn bind_image_storage(&mut self, a_unit: u32, a_texture: &dyn Texture, a_access: StorageAccess, a_format: TextureFormat){
+    let texture = match a_texture.any().downcast_ref::<TextureOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid texture")
+    };
+
+    if !self.is_compute_supported() {
+      return
+    }
+
+    let access = match a_access {
+      StorageAccess::ReadOnly => gl::READ_ONLY,
+      StorageAccess::WriteOnly => gl::WRITE_ONLY,
+      StorageAccess::ReadWrite => gl::READ_WRITE
+    };
+
+    let (internal_format, _format, _data_type) = get_texture_format(a_format);
+
+    unsafe {
+      gl::BindImageTexture(a_unit, texture.id, 0, gl::FALSE, 0, access, internal_format);
+    }
+  }
+
+  fn gen_buffer_uniform(&mut self, a_layout: &UniformBlockLayout, a_block: &dyn UniformBlock) -> Result<Box<dyn UniformBuffer>, RendererError>{
+    if !self.is_uniform_buffer_supported() {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    // gl has no std430 uniform blocks
+    if a_layout.layout != BlockLayout::Std140 {
+      return Err(RendererError::Error)
+    }
+
+    let data = match a_layout.write(a_block) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let mut id: gl::types::GLuint = 0;
+    unsafe {
+      gl::GenBuffers(1, &mut id);
+      gl::BindBuffer(gl::UNIFORM_BUFFER, id);
+      gl::BufferData(
+          gl::UNIFORM_BUFFER,
+          data.len() as gl::types::GLsizeiptr,
+          data.as_ptr() as *const gl::types::GLvoid,
+          gl::DYNAMIC_DRAW,
+      );
+      gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+    }
+
+    Ok(Box::new(UniformBufferOpenGL{id: id, layout: a_layout.clone(), staging: RefCell::new(data)}))
+  }
+
+  fn update_buffer_uniform(&mut self, a_buffer: &mut Box<dyn UniformBuffer>, a_block: &dyn UniformBlock) -> Result<(), RendererError>{
+    let buffer = match a_buffer.any().downcast_ref::<UniformBufferOpenGL>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    // the staging data is partly written when the block doesn't match, the buffer isn't
+    let data = &mut *buffer.staging.borrow_mut();
+    if let Err(res) = buffer.layout.write_to(a_block, data) {
+      return Err(res)
+    }
+
+    unsafe {
+      gl::BindBuffer(gl::UNIFORM_BUFFER, buffer.id);
+      gl::BufferSubData(
+          gl::UNIFORM_BUFFER,
+          0,
+          data.len() as gl::types::GLsizeiptr,
+          data.as_ptr() as *const gl::types::GLvoid,
+      );
+      gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+    }
+
+    Ok(())
+  }
+
+  fn bind_buffer_uniform(&mut self, a_binding: u32, a_buffer: &Box<dyn UniformBuffer>){
+    let buffer = match a_buffer.any().downcast_ref::<UniformBufferOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid uniform buffer")
+    };
+
+    unsafe {
+      gl::BindBufferBase(gl::UNIFORM_BUFFER, a_binding, buffer.id);
+    }
+  }
+
+  fn bind_uniform_block(&mut self, a_program: &Box<dyn Program>, a_block_name: &str, a_binding: u32, a_layout: &UniformBlockLayout) -> Result<(), RendererError>{
+    let program = match a_program.any().downcast_ref::<ProgramOpenGL>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    if !self.is_uniform_buffer_supported() {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    let c_str = match CString::new(a_block_name){
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    let index = unsafe { gl::GetUniformBlockIndex(program.id, c_str.as_ptr()) };
+    if index == gl::INVALID_INDEX {
+      eprintln!("Error program has no uniform block {}", a_block_name);
+      return Err(RendererError::Error)
+    }
+
+    let mut size: gl::types::GLint = 0;
+    unsafe {
+      gl::GetActiveUniformBlockiv(program.id, index, gl::UNIFORM_BLOCK_DATA_SIZE, &mut size);
+    }
+
+    if (size as u32) < a_layout.size {
+      eprintln!("Error uniform block {} is {} bytes, the layout needs {}", a_block_name, size, a_layout.size);
+      return Err(RendererError::Error)
+    }
+
+    for member in &a_layout.members {
+      let offset = match get_block_member_offset(program.id, a_block_name, &member.name) {
+        Some(res) => res,
+        None => {
+          eprintln!("Error uniform block {} has no member {}", a_block_name, member.name);
+          return Err(RendererError::Error)
+        }
+      };
+
+      if offset != member.offset {
+        eprintln!("Error uniform block {} member {} is at offset {} instead of {}", a_block_name, member.name, offset, member.offset);
+        return Err(RendererError::Error)
+      }
+    }
+
+    unsafe {
+      gl::UniformBlockBinding(program.id, index, a_binding);
+    }
+
+    Ok(())
+  }
+
+  fn get_uniform(&mut self, a_shader: &mut Box<dyn Program>, a_name: &str) -> Box<dyn UniformShader>{
+    let shader = match a_shader.any().downcast_ref::<ProgramOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid shader cast")
+    };
+
+    let c_str = match CString::new(a_name){
+      Ok(res) => res,
+      Err(_res) => panic!("Invalid text cast")
+    };
+
+    let location = unsafe{gl::GetUniformLocation(shader.id, c_str.as_ptr())};
+
+    Box::new(UniformShaderOpenGL{
+      name: UniformName::new(a_name),
+      id: location
+    })
+  }
+
+  /*
+  fn set_uniform(&mut self, a_uniform: &Box<dyn Uniform>){
+    let uniform = match a_uniform.any().downcast_ref::<UniformOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid uniform cast")
+    };
+
+    unsafe{
+      gl::Uniform1i(uniform.id, 0);
+    }
+  }
+
+  fn set_texture(&mut self, a_texture: &Box<dyn Texture>){
+    let texture = match a_texture.any().downcast_ref::<TextureOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid uniform cast")
+    };
+
+    unsafe{
+      gl::ActiveTexture(gl::TEXTURE0 + 0);
+      gl::BindTexture(gl::TEXTURE_2D,  texture.id);
+    }
+  }
+  */
+
+  fn gen_buffer_vertex(&mut self, a_verts: &std::vec::Vec<f32>) -> Box<dyn Vertices>{
+    let data = unsafe { std::slice::from_raw_parts(a_verts.as_ptr() as *const u8, a_verts.len() * std::mem::size_of::<f32>()) };
+    self.gen_buffer_vertex_bytes(data)
+  }
+
+  fn gen_buffer_vertex_bytes(&mut self, a_data: &[u8]) -> Box<dyn Vertices>{
+    let mut vbo: gl::types::GLuint = 0;
+    unsafe {
+      gl::GenBuffers(1, &mut vbo);
+    }
+
+    unsafe {
+      gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+      gl::BufferData(
+          gl::ARRAY_BUFFER, // target
+          a_data.len() as gl::types::GLsizeiptr, // size of data in bytes
+          a_data.as_ptr() as *const gl::types::GLvoid, // pointer to data
+          gl::STATIC_DRAW, // usage
+      );
+      gl::BindBuffer(gl::ARRAY_BUFFER, 0); // unbind the buffer
+    }
+
+    Box::new(VerticesOpenGL{id: vbo, size: a_data.len()})
+  }
+
+  fn gen_geometry(&mut self, a_buffer: &Box<dyn Vertices>) -> Box<dyn Geometry>{
+    match self.gen_geometry_layout(&[a_buffer], &VertexLayout::position_uv()) {
+      Ok(res) => res,
+      Err(_res) => panic!("Invalid vertex")
+    }
+  }
+
+  fn gen_geometry_layout(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout) -> Result<Box<dyn Geometry>, RendererError>{
+    match self.gen_vertex_array(a_buffers, a_layout, None) {
+      Ok(res) => Ok(Box::new(res)),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn gen_buffer_index(&mut self, a_indices: IndexData) -> Box<dyn Indices>{
+    let (index_type, index_size) = match a_indices {
+      IndexData::Uint16(_res) => (gl::UNSIGNED_SHORT, 2),
+      IndexData::Uint32(_res) => (gl::UNSIGNED_INT, 4)
+    };
+    let data = a_indices.to_bytes();
+
+    let mut ibo: gl::types::GLuint = 0;
+    unsafe {
+      gl::GenBuffers(1, &mut ibo);
+
+      // uploaded through the array buffer binding as the element binding belongs to the bound vao
+      gl::BindBuffer(gl::ARRAY_BUFFER, ibo);
+      gl::BufferData(
+          gl::ARRAY_BUFFER,
+          data.len() as gl::types::GLsizeiptr,
+          data.as_ptr() as *const gl::types::GLvoid,
+          gl::STATIC_DRAW,
+      );
+      gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+    }
+
+    Box::new(IndicesOpenGL{id: ibo, index_type: index_type, index_size: index_size, num: a_indices.len() as gl::types::GLsizei})
+  }
+
+  fn gen_geometry_indexed(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout, a_indices: &Box<dyn Indices>) -> Result<Box<dyn Geometry>, RendererError>{
+    let indices = match a_indices.any().downcast_ref::<IndicesOpenGL>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    match self.gen_vertex_array(a_buffers, a_layout, Some(indices)) {
+      Ok(res) => Ok(Box::new(res)),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn gen_instances(&mut self, a_layout: &VertexBufferLayout, a_data: &[u8]) -> Result<Box<dyn Instances>, RendererError>{
+    if a_layout.step != VertexStep::PerInstance || a_layout.stride == 0 {
+      return Err(RendererError::Error)
+    }
+
+    if !self.is_layout_supported(a_layout) {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    let mut vbo: gl::types::GLuint = 0;
+    unsafe {
+      gl::GenBuffers(1, &mut vbo);
+    }
+
+    let mut instances: Box<dyn Instances> = Box::new(InstancesOpenGL{id: vbo, layout: a_layout.clone(), num: Cell::new(0)});
+    self.update_instances(&mut instances, a_data);
+
+    Ok(instances)
+  }
+
+  fn update_instances(&mut self, a_instances: &mut Box<dyn Instances>, a_data: &[u8]){
+    let instances = match a_instances.any().downcast_ref::<InstancesOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid instances")
+    };
+
+    // new storage each time so draws still using the previous data are not waited on
+    unsafe {
+      gl::BindBuffer(gl::ARRAY_BUFFER, instances.id);
+      gl::BufferData(
+          gl::ARRAY_BUFFER,
+          a_data.len() as gl::types::GLsizeiptr,
+          a_data.as_ptr() as *const gl::types::GLvoid,
+          gl::DYNAMIC_DRAW,
+      );
+      gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+    }
+
+    instances.num.set((a_data.len() / instances.layout.stride as usize) as u32);
+  }
+
+  fn gen_mesh(&mut self, a_geometry: Box<dyn Geometry>, a_material: Box<dyn Material>) -> Box<Mesh>{
+    Box::new(Mesh{
+      geometry: a_geometry,
+      material: a_material
+      })
+  }
+
+
+  fn gen_buffer_texture(&mut self) -> Box<dyn Texture>{
+    let mut id: gl::types::GLuint = 0;
+    unsafe {
+      gl::GenTextures(1, &mut id);
     }
 
     Box::new(TextureOpenGL{
       id: id,
       width: 0,
-      height: 0})
+      height: 0,
+      target: Cell::new(gl::TEXTURE_2D)})
+  }
+
+  fn gen_sampler(&mut self, a_texture: Rc<dyn Texture>, a_desc: &SamplerDesc) -> Box<dyn Sampler>{
+    let mut object: gl::types::GLuint = 0;
+
+    if self.is_sampler_object_supported() {
+      unsafe {
+        gl::GenSamplers(1, &mut object);
+      }
+
+      set_sampler_parameters(a_desc, self.is_gles, self.is_border_supported(), self.get_max_anisotropy(),
+        |a_name, a_value| unsafe { gl::SamplerParameteri(object, a_name, a_value) },
+        |a_name, a_value| unsafe { gl::SamplerParameterf(object, a_name, a_value) },
+        |a_name, a_value| unsafe { gl::SamplerParameterfv(object, a_name, a_value.as_ptr()) });
+    }
+
+    let sampler = SamplerOpenGL{name: String::from(""), texture: a_texture, object: object, desc: *a_desc};
+
+    Box::new(sampler)
+  }
+
+  fn load_texture(&mut self, a_image: &image::DynamicImage, a_texture: &mut Box<dyn Texture>){
+    // gles 3 needs an extension for 16 bit unorm and gles 2 only has 8 bit textures
+    let preferred = TextureFormat::from_image(a_image);
+    let format = [preferred, preferred.get_fallback()].into_iter().find(|format| self.is_texture_format_supported(*format)).unwrap_or(TextureFormat::RGBA8);
+
+    if let Err(res) = self.load_texture_format(a_image, format, a_texture) {
+      eprintln!("Error {}", res);
+    }
+  }
+
+  fn load_texture_format(&mut self, a_image: &image::DynamicImage, a_format: TextureFormat, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    self.load_texture_layers(std::slice::from_ref(a_image), TextureDimension::D2, a_format, a_texture)
+  }
+
+  fn load_texture_layers(&mut self, a_images: &[image::DynamicImage], a_dimension: TextureDimension, a_format: TextureFormat, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    let texture = match a_texture.any().downcast_ref::<TextureOpenGL>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    match a_dimension.validate_images(a_images) {
+      Ok(_res) => {},
+      Err(res) => return Err(res)
+    };
+
+    // 3d textures and arrays need gl or gles 3
+    let layered = a_dimension == TextureDimension::D3 || a_dimension == TextureDimension::D2Array;
+    if !self.is_texture_format_supported(a_format) || (layered && self.version_major < 3) {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    let (internal_format, format, data_type) = get_texture_format(a_format);
+    let target = get_texture_target(a_dimension);
+    let mipmaps = is_mipmap_supported(a_format, self.is_gles);
+    let (width, height) = (a_images[0].width() as i32, a_images[0].height() as i32);
+
+    // gles 2 only takes unsized formats
+    let internal_format = match self.is_gles && self.version_major < 3 {
+      true => format,
+      false => internal_format
+    };
+
+    texture.target.set(target);
+
+    unsafe{
+      gl::BindTexture(target, texture.id);
+
+      // rows of single channel textures aren't aligned to 4 bytes
+      gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+
+      match a_dimension {
+        TextureDimension::D2 => {
+          let bytes = a_format.get_bytes(&a_images[0]);
+          gl::TexImage2D(gl::TEXTURE_2D, 0, internal_format as i32, width, height, 0,
+            format, data_type, bytes.as_ptr() as *const std::os::raw::c_void);
+        },
+        TextureDimension::Cube => {
+          for (face, image) in a_images.iter().enumerate() {
+            let bytes = a_format.get_bytes(image);
+            gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as gl::types::GLenum, 0, internal_format as i32, width, height, 0,
+              format, data_type, bytes.as_ptr() as *const std::os::raw::c_void);
+          }
+        },
+        TextureDimension::D3 | TextureDimension::D2Array => {
+          let bytes: Vec<u8> = a_images.iter().flat_map(|image| a_format.get_bytes(image)).collect();
+          gl::TexImage3D(target, 0, internal_format as i32, width, height, a_images.len() as i32, 0,
+            format, data_type, bytes.as_ptr() as *const std::os::raw::c_void);
+        }
+      }
+
+      gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+
+      gl::TexParameteri(target, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+      gl::TexParameteri(target, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+      gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+
+      if mipmaps {
+        gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32);
+        gl::TexParameteri(target, gl::TEXTURE_MAX_LEVEL, 1000);
+        gl::GenerateMipmap(target);
+      }
+      else {
+        // a single level keeps the texture complete for samplers with mip filters
+        gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(target, gl::TEXTURE_MAX_LEVEL, 0);
+      }
+
+      // gles cubemaps are always filtered across faces, gl 3.2 has to enable it
+      if a_dimension == TextureDimension::Cube && !self.is_gles && (self.version_major, self.version_minor) >= (3, 2) {
+        gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+      }
+    }
+
+    Ok(())
+  }
+
+  fn load_texture_compressed(&mut self, a_image: &CompressedImage, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    if !self.is_compressed_format_supported(a_image.format, a_image.srgb) {
+      let image = match a_image.decompress(0) {
+        Ok(res) => res,
+        Err(res) => return Err(res)
+      };
+
+      // gles 2 has no srgb textures
+      let format = match self.is_texture_format_supported(a_image.get_decompressed_format()) {
+        true => a_image.get_decompressed_format(),
+        false => TextureFormat::RGBA8
+      };
+
+      return self.load_texture_format(&image, format, a_texture)
+    }
+
+    let texture = match a_texture.any().downcast_ref::<TextureOpenGL>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    let internal_format = get_compressed_format(a_image.format, a_image.srgb);
+    texture.target.set(gl::TEXTURE_2D);
+
+    unsafe{
+      gl::BindTexture(gl::TEXTURE_2D, texture.id);
+
+      for (level, data) in a_image.levels.iter().enumerate() {
+        let (width, height) = a_image.get_level_size(level);
+        gl::CompressedTexImage2D(gl::TEXTURE_2D, level as i32, internal_format, width as i32, height as i32, 0,
+          data.len() as i32, data.as_ptr() as *const std::os::raw::c_void);
+      }
+
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+
+      // compressed formats can't generate mipmaps, only the levels in the file are used
+      let min_filter = match a_image.levels.len() > 1 {
+        true => gl::LINEAR_MIPMAP_LINEAR,
+        false => gl::LINEAR
+      };
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as i32);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, a_image.levels.len() as i32 - 1);
+    }
+
+    Ok(())
+  }
+
+  fn is_compressed_format_supported(&self, a_format: CompressedFormat, a_srgb: bool) -> bool{
+    let version = (self.version_major, self.version_minor);
+
+    match a_format {
+      CompressedFormat::BC1 | CompressedFormat::BC2 | CompressedFormat::BC3 => {
+        self.has_extension("GL_EXT_texture_compression_s3tc") &&
+          (!a_srgb || self.has_extension("GL_EXT_texture_sRGB") || self.has_extension("GL_EXT_texture_compression_s3tc_srgb"))
+      },
+      CompressedFormat::BC4 | CompressedFormat::BC5 => {
+        (!self.is_gles && version >= (3, 0)) || self.has_extension("GL_EXT_texture_compression_rgtc")
+      },
+      CompressedFormat::BC7 => {
+        (!self.is_gles && version >= (4, 2)) || self.has_extension("GL_ARB_texture_compression_bptc") ||
+          self.has_extension("GL_EXT_texture_compression_bptc")
+      },
+      CompressedFormat::ETC2RGB8 | CompressedFormat::ETC2RGBA8 => {
+        (self.is_gles && version >= (3, 0)) || (!self.is_gles && version >= (4, 3)) || self.has_extension("GL_ARB_ES3_compatibility")
+      },
+      CompressedFormat::ASTC(_width, _height) => {
+        a_format.get_astc_index().is_some() && self.has_extension("GL_KHR_texture_compression_astc_ldr")
+      }
+    }
+  }
+
+  fn use_program(&mut self, a_program: &Box<dyn Program>){
+    let program = match a_program.any().downcast_ref::<ProgramOpenGL>() {
+      Some(res) => res,
+      None => return
+    };
+
+    self.patches = program.patches;
+    self.sampler_units = program.sampler_units.clone();
+
+    if self.program_id != program.id as gl::types::GLint{
+      self.program_id = program.id as gl::types::GLint;
+
+      unsafe {
+        gl::UseProgram(program.id);
+      }
+    }
+  }
+
+  fn draw_geometry(&mut self, a_geometry: &Box<dyn Geometry>){
+    let geometry = match a_geometry.any().downcast_ref::<GeometryOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.draw_elements(geometry, DrawRange{first: 0, count: geometry.num as u32, base_vertex: 0}, None);
+  }
+
+  fn draw_geometry_range(&mut self, a_geometry: &Box<dyn Geometry>, a_range: DrawRange){
+    let geometry = match a_geometry.any().downcast_ref::<GeometryOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.draw_elements(geometry, a_range, None);
+  }
+
+  fn draw_mesh(&mut self, _camera: &Camera, a_mesh: &mut Box<Mesh>){
+    let geometry = match a_mesh.geometry.any().downcast_ref::<GeometryOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.use_program(a_mesh.material.get_program());
+    self.set_render_state(a_mesh.material.get_render_state());
+
+    let num_uniforms = a_mesh.material.num_uniforms();
+    for i in 0..num_uniforms {
+      self.update_uniform(a_mesh.material.get_uniform(i));
+    }
+
+    let num_samplers = a_mesh.material.num_samplers();
+    for i in 0..num_samplers {
+      self.update_sampler(a_mesh.material.get_sampler(i));
+    }
+
+    self.draw_elements(geometry, DrawRange{first: 0, count: geometry.num as u32, base_vertex: 0}, None);
+  }
+
+  fn draw_mesh_instanced(&mut self, _camera: &Camera, a_mesh: &mut Box<Mesh>, a_instances: &Box<dyn Instances>){
+    let geometry = match a_mesh.geometry.any().downcast_ref::<GeometryOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    let instances = match a_instances.any().downcast_ref::<InstancesOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid instances")
+    };
+
+    self.use_program(a_mesh.material.get_program());
+    self.set_render_state(a_mesh.material.get_render_state());
+
+    let num_uniforms = a_mesh.material.num_uniforms();
+    for i in 0..num_uniforms {
+      self.update_uniform(a_mesh.material.get_uniform(i));
+    }
+
+    let num_samplers = a_mesh.material.num_samplers();
+    for i in 0..num_samplers {
+      self.update_sampler(a_mesh.material.get_sampler(i));
+    }
+
+    self.draw_elements(geometry, DrawRange{first: 0, count: geometry.num as u32, base_vertex: 0}, Some(instances));
+  }
+
+  // float attachments can only be read as floats in gles, they're converted the same as unorm attachments are
+  fn read_render_buffer(&mut self) -> Image {
+    let mut image = Image{
+      width: self.target_width, 
+      height: self.target_height, 
+      pitch: self.target_width * 4, 
+      pixels: vec![0u8; (self.target_width * self.target_height  * 4) as usize]};
+
+    if self.target_format.map_or(false, |format| format.is_float()) {
+      let mut pixels = vec![0f32; image.pixels.len()];
+
+      unsafe { gl::ReadPixels(
+        0, 0,
+        self.target_width as i32, self.target_height as i32,
+        gl::RGBA,
+        gl::FLOAT,
+        pixels.as_mut_ptr() as *mut _) };
+
+      image.pixels = pixels.iter().map(|value| (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8).collect();
+      return image
+    }
+
+    unsafe { gl::ReadPixels( 
+      0, 0, 
+      self.target_width as i32, self.target_height as i32, 
+      gl::RGBA,
+      gl::UNSIGNED_BYTE,
+      image.pixels.as_mut_ptr() as *mut _) };
+    
+    return image
+  }
+
+  fn gen_render_target(&mut self, a_desc: &RenderTargetDesc) -> Result<Box<dyn RenderTarget>, RendererError>{
+    if a_desc.width == 0 || a_desc.height == 0 {
+      return Err(RendererError::Error)
+    }
+
+    if a_desc.color_formats.iter().any(|format| format.is_depth()) {
+      return Err(RendererError::Error)
+    }
+
+    let mut framebuffer: gl::types::GLuint = 0;
+    unsafe {
+      gl::GenFramebuffers(1, &mut framebuffer);
+      gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+    }
+
+    // deletes the framebuffer and textures on error
+    let mut target = RenderTargetOpenGL{
+      framebuffer: framebuffer,
+      width: a_desc.width,
+      height: a_desc.height,
+      colors: Vec::new(),
+      color_formats: a_desc.color_formats.clone(),
+      depth: None
+    };
+
+    let mut draw_buffers: Vec<gl::types::GLenum> = Vec::new();
+
+    for (i, format) in a_desc.color_formats.iter().enumerate() {
+      let texture = gen_attachment_texture(*format, a_desc.width, a_desc.height);
+      let attachment = gl::COLOR_ATTACHMENT0 + i as gl::types::GLenum;
+
+      unsafe {
+        gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture.id, 0);
+      }
+
+      draw_buffers.push(attachment);
+      target.colors.push(Rc::new(texture));
+    }
+
+    if let Some(format) = a_desc.depth_stencil_format {
+      if !format.is_depth() {
+        self.bind_framebuffer(self.target_framebuffer);
+        return Err(RendererError::Error)
+      }
+
+      let texture = gen_attachment_texture(format, a_desc.width, a_desc.height);
+      let attachment = match format.has_stencil() {
+        true => gl::DEPTH_STENCIL_ATTACHMENT,
+        false => gl::DEPTH_ATTACHMENT
+      };
+
+      unsafe {
+        gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture.id, 0);
+      }
+
+      target.depth = Some(Rc::new(texture));
+    }
+
+    let status = unsafe {
+      match draw_buffers.is_empty() {
+        true => gl::DrawBuffers(1, &gl::NONE),
+        false => gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr())
+      };
+
+      gl::CheckFramebufferStatus(gl::FRAMEBUFFER)
+    };
+
+    self.bind_framebuffer(self.target_framebuffer);
+
+    // formats such as float colors are not renderable everywhere
+    if status != gl::FRAMEBUFFER_COMPLETE {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    Ok(Box::new(target))
+  }
+
+  fn set_render_target(&mut self, a_target: Option<&Box<dyn RenderTarget>>){
+    let (framebuffer, width, height, format) = match a_target {
+      Some(target) => match target.any().downcast_ref::<RenderTargetOpenGL>() {
+        Some(res) => (res.framebuffer, res.width, res.height, res.color_formats.first().copied()),
+        None => panic!("Invalid render target")
+      },
+      None => (self.framebuffer, self.width, self.height, None)
+    };
+
+    self.target_framebuffer = framebuffer;
+    self.target_width = width;
+    self.target_height = height;
+    self.target_format = format;
+    self.bind_framebuffer(framebuffer);
+  }
+
+  fn clear_render_target(&mut self, a_target: &Box<dyn RenderTarget>, a_clear: RendererClearType){
+    let target = match a_target.any().downcast_ref::<RenderTargetOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid render target")
+    };
+
+    self.bind_framebuffer(target.framebuffer);
+    self.clear(a_clear);
+    self.bind_framebuffer(self.target_framebuffer);
+  }
+
+}
+
+#[allow(dead_code)]
+impl RendererOpenGL {
+  const GL_MAX_VERSION_MINOR: [i32; 5] = [0, 5, 1, 3, 6];
+  const GLES_MAX_VERSION_MINOR: [i32; 4] = [0, 1, 0, 2];
+
+  pub fn new(
+    a_video_subsystem: &sdl2::VideoSubsystem, 
+    a_min_version: Version, 
+    a_max_version: Version, 
+    a_window: Arc<Window>, 
+    a_is_gles: bool) -> Result<Self, RendererError>
+  {
+    let gl_context = match a_is_gles {
+      true => match init_gles_context(&a_video_subsystem, a_min_version, a_max_version, &a_window.window.lock().unwrap().inner) {
+        Ok(res) => res,
+        Err(_res) => return Err(RendererError::Error)
+      },
+      false => match init_gl_context(&a_video_subsystem, a_min_version, a_max_version, &a_window.window.lock().unwrap().inner) {
+        Ok(res) => res,
+        Err(_res) => return Err(RendererError::Error)
+      }
+    };
+    
+    gl::load_with(|s| a_video_subsystem.gl_get_proc_address(s) as *const std::os::raw::c_void);
+
+    let (version_major, version_minor) = get_context_version();
+
+    // // swap interval requires emscripten main loop to be set first
+    // #[cfg(not(target_os = "emscripten"))]
+    // match a_video_subsystem.gl_set_swap_interval(sdl2::video::SwapInterval::Immediate){
+    //   Ok(_res) => _res,
+    //   Err(_res) => print!("Unable to set vsync\n")
+    // };
+
+    let width = a_window.width;
+    let height = a_window.height;
+
+    let mut renderer = Self {
+      context: ContextOpenGL::Window{gl_context: gl_context, window: a_window},
+      version_major: version_major,
+      version_minor: version_minor,
+      is_gles: a_is_gles,
+      width: width,
+      height: height,
+      framebuffer: 0,
+      renderbuffers: [0, 0],
+      target_framebuffer: 0,
+      target_format: None,
+      target_width: width,
+      target_height: height,
+      clear_color: Vec4::new(0.0, 0.0, 0.0, 0.0),
+      clear_depth: 1.0,
+      clear_stencil: 0,
+      viewport_pos: IVec2::new(0,0),
+      viewport_size: IVec2::new(0,0),
+      vao: -1,
+      program_id: -1,
+      patches: false,
+      sampler_units: Rc::new(Vec::new()),
+      render_state: RenderState::default(),
+      max_anisotropy: 0.0,
+      norm16: false
+    };
+
+    renderer.max_anisotropy = renderer.query_max_anisotropy();
+    renderer.norm16 = !renderer.is_gles || renderer.has_extension("GL_EXT_texture_norm16");
+
+    Ok(renderer)
+  }
+
+  // Creates a renderer without a window. Output is rendered to an offscreen framebuffer of the given size
+  #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten"))))]
+  pub fn new_headless(
+    a_min_version: Version, 
+    a_max_version: Version, 
+    a_width: u32,
+    a_height: u32,
+    a_is_gles: bool) -> Result<Self, RendererError>
+  {
+    let context = match init_egl_context(a_min_version, a_max_version, a_is_gles) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    gl::load_with(|s| context.get_proc_address(s));
+
+    let (version_major, version_minor) = get_context_version();
+
+    let mut framebuffer: gl::types::GLuint = 0;
+    let mut renderbuffers: [gl::types::GLuint; 2] = [0, 0];
+    let status = unsafe {
+      gl::GenFramebuffers(1, &mut framebuffer);
+      gl::GenRenderbuffers(2, renderbuffers.as_mut_ptr());
+
+      gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffers[0]);
+      gl::RenderbufferStorage(gl::RENDERBUFFER, gl::RGBA8, a_width as i32, a_height as i32);
+      gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffers[1]);
+      gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, a_width as i32, a_height as i32);
+      gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+      gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+      gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, renderbuffers[0]);
+      gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, renderbuffers[1]);
+
+      gl::CheckFramebufferStatus(gl::FRAMEBUFFER)
+    };
+
+    let mut renderer = Self {
+      context: ContextOpenGL::Headless(context),
+      version_major: version_major,
+      version_minor: version_minor,
+      is_gles: a_is_gles,
+      width: a_width,
+      height: a_height,
+      framebuffer: framebuffer,
+      renderbuffers: renderbuffers,
+      target_framebuffer: framebuffer,
+      target_format: None,
+      target_width: a_width,
+      target_height: a_height,
+      clear_color: Vec4::new(0.0, 0.0, 0.0, 0.0),
+      clear_depth: 1.0,
+      clear_stencil: 0,
+      viewport_pos: IVec2::new(0,0),
+      viewport_size: IVec2::new(0,0),
+      vao: -1,
+      program_id: -1,
+      patches: false,
+      sampler_units: Rc::new(Vec::new()),
+      render_state: RenderState::default(),
+      max_anisotropy: 0.0,
+      norm16: false
+    };
+
+    renderer.max_anisotropy = renderer.query_max_anisotropy();
+    renderer.norm16 = !renderer.is_gles || renderer.has_extension("GL_EXT_texture_norm16");
+
+    if status != gl::FRAMEBUFFER_COMPLETE {
+      return Err(RendererError::Error)
+    }
+
+    // unlike a window the default viewport of a context without a surface is empty
+    renderer.set_viewport(IVec2::new(0, 0), IVec2::new(a_width as i32, a_height as i32));
+
+    Ok(renderer)
+  }
+
+  pub fn update_uniform(&self, a_uniform: &mut Box<dyn Uniform>){
+    let mut uniform = match a_uniform.any().downcast_mut::<UniformOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid uniform cast")
+    };
+
+    if uniform.modified {
+      let id = uniform.id;
+      let count = uniform.data.info.num_components as gl::types::GLsizei;
+      let bytes = uniform.data.as_bytes();
+      let floats = bytes.as_ptr() as *const f32;
+      let doubles = bytes.as_ptr() as *const f64;
+      let ints = bytes.as_ptr() as *const i32;
+      let uints = bytes.as_ptr() as *const u32;
+      // bools are uploaded as ints
+      let bools: Vec<i32> = match uniform.data.info.element_type {
+        ElementType::Bool => bytes.iter().map(|res| *res as i32).collect(),
+        _ => Vec::new()
+      };
+
+      if uniform.data.info.element_type == ElementType::Float64 && !gl::Uniform1dv::is_loaded() {
+        eprintln!("Error double uniforms require gl 4.0");
+      }
+      else {
+        unsafe{
+          match (uniform.data.info.element_type, uniform.data.info.container_type) {
+            (ElementType::Float32, ContainerType::Single) => gl::Uniform1fv(id, count, floats),
+            (ElementType::Float32, ContainerType::Vec2) => gl::Uniform2fv(id, count, floats),
+            (ElementType::Float32, ContainerType::Vec3) => gl::Uniform3fv(id, count, floats),
+            (ElementType::Float32, ContainerType::Vec4) => gl::Uniform4fv(id, count, floats),
+            (ElementType::Float32, ContainerType::Mat2x2) => gl::UniformMatrix2fv(id, count, gl::FALSE, floats),
+            (ElementType::Float32, ContainerType::Mat3x3) => gl::UniformMatrix3fv(id, count, gl::FALSE, floats),
+            (ElementType::Float32, ContainerType::Mat4x4) => gl::UniformMatrix4fv(id, count, gl::FALSE, floats),
+            (ElementType::Float64, ContainerType::Single) => gl::Uniform1dv(id, count, doubles),
+            (ElementType::Float64, ContainerType::Vec2) => gl::Uniform2dv(id, count, doubles),
+            (ElementType::Float64, ContainerType::Vec3) => gl::Uniform3dv(id, count, doubles),
+            (ElementType::Float64, ContainerType::Vec4) => gl::Uniform4dv(id, count, doubles),
+            (ElementType::Int32, ContainerType::Single) => gl::Uniform1iv(id, count, ints),
+            (ElementType::Int32, ContainerType::Vec2) => gl::Uniform2iv(id, count, ints),
+            (ElementType::Int32, ContainerType::Vec3) => gl::Uniform3iv(id, count, ints),
+            (ElementType::Int32, ContainerType::Vec4) => gl::Uniform4iv(id, count, ints),
+            (ElementType::Uint32, ContainerType::Single) => gl::Uniform1uiv(id, count, uints),
+            (ElementType::Uint32, ContainerType::Vec2) => gl::Uniform2uiv(id, count, uints),
+            (ElementType::Uint32, ContainerType::Vec3) => gl::Uniform3uiv(id, count, uints),
+            (ElementType::Uint32, ContainerType::Vec4) => gl::Uniform4uiv(id, count, uints),
+            (ElementType::Bool, ContainerType::Single) => gl::Uniform1iv(id, count, bools.as_ptr()),
+            (ElementType::Bool, ContainerType::Vec2) => gl::Uniform2iv(id, count, bools.as_ptr()),
+            (ElementType::Bool, ContainerType::Vec3) => gl::Uniform3iv(id, count, bools.as_ptr()),
+            (ElementType::Bool, ContainerType::Vec4) => gl::Uniform4iv(id, count, bools.as_ptr()),
+            (element_type, container_type) => eprintln!("Error unsupported uniform type {:?} {:?}", element_type, container_type)
+          };
+        }
+      }
+
+      uniform.modified = false;
+    }
+  }
+
+  // binds the texture to the unit of the program's sampler with the same name, samplers the program doesn't use are skipped
+  pub fn update_sampler(&self, a_sampler: &Box<dyn Sampler>){
+    let sampler = match a_sampler.any().downcast_ref::<SamplerOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid sampler cast")
+    };
+
+    let unit = match self.sampler_units.iter().find(|(name, _unit)| *name == sampler.name) {
+      Some((_name, unit)) => *unit,
+      None => return
+    };
+  
+    let texture = match sampler.texture.any().downcast_ref::<TextureOpenGL>() {
+      Some(res) => res,
+      None => panic!("Invalid uniform cast")
+    };
+
+    unsafe{
+      gl::ActiveTexture(gl::TEXTURE0 + unit);
+      gl::BindTexture(texture.target.get(),  texture.id);
+    }
+
+    if sampler.object != 0 {
+      unsafe{
+        gl::BindSampler(unit, sampler.object);
+      }
+    }
+    else {
+      set_sampler_parameters(&sampler.desc, self.is_gles, self.is_border_supported(), self.get_max_anisotropy(),
+        |a_name, a_value| unsafe { gl::TexParameteri(texture.target.get(), a_name, a_value) },
+        |a_name, a_value| unsafe { gl::TexParameterf(texture.target.get(), a_name, a_value) },
+        |a_name, a_value| unsafe { gl::TexParameterfv(texture.target.get(), a_name, a_value.as_ptr()) });
+    }
+  }
+
+  fn link_program(&self, a_shaders: &[&ShaderOpenGL]) -> Result<Box<dyn Program>, RendererError>{
+    let program_id = unsafe { gl::CreateProgram() };
+
+    unsafe {
+      for shader in a_shaders {
+        gl::AttachShader(program_id, shader.id);
+      }
+      gl::LinkProgram(program_id);
+      for shader in a_shaders {
+        gl::DetachShader(program_id, shader.id);
+      }
+    }
+
+    let mut success: gl::types::GLint = 1;
+    unsafe {
+        gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut success);
+    }
+
+    if success == 0 {
+      let mut len: gl::types::GLint = 0;
+      unsafe {
+        gl::GetProgramiv(program_id, gl::INFO_LOG_LENGTH, &mut len);
+      }
+
+      // allocate buffer of correct size
+      let mut buffer: Vec<u8> = Vec::with_capacity(len as usize + 1);
+      // fill it with len spaces
+      buffer.extend([b' '].iter().cycle().take(len as usize));
+      // convert buffer to CString
+      let error: CString = unsafe { CString::from_vec_unchecked(buffer) };
+
+      unsafe {
+        gl::GetProgramInfoLog(
+          program_id,
+          len,
+          std::ptr::null_mut(),
+          error.as_ptr() as *mut gl::types::GLchar
+        );
+      }
+
+      unsafe {
+        gl::DeleteProgram(program_id);
+      }
+
+      return Err(RendererError::ShaderLink(ShaderLog::from_info_log(None, &error.to_string_lossy(), 0)))
+    }
+
+    let patches = a_shaders.iter().any(|shader| shader.shader_type == ShaderType::TesselationEvaluation);
+
+    // the units are uniform values so the program is bound while they are set
+    let sampler_units = unsafe {
+      gl::UseProgram(program_id);
+      let sampler_units = set_sampler_units(program_id);
+      gl::UseProgram(std::cmp::max(self.program_id, 0) as gl::types::GLuint);
+      sampler_units
+    };
+
+    let reflection = reflect_program(program_id, self.is_uniform_buffer_supported(), &sampler_units);
+
+    Ok(Box::new(ProgramOpenGL{id: program_id, patches: patches, sampler_units: Rc::new(sampler_units), reflection: reflection}))
+  }
+
+  // gles 2 and gl 2 are limited to 8 bit color and depth without stencil
+  fn is_texture_format_supported(&self, a_format: TextureFormat) -> bool{
+    if self.version_major >= 3 {
+      return !a_format.is_unorm16() || self.norm16
+    }
+
+    match self.is_gles {
+      true => a_format == TextureFormat::RGBA8,
+      false => matches!(a_format, TextureFormat::RGBA8 | TextureFormat::SRGB8A8 | TextureFormat::Depth16 | TextureFormat::Depth24)
+    }
+  }
+
+  // gl and gles 3 list extensions by index
+  fn has_extension(&self, a_name: &str) -> bool{
+    unsafe {
+      if self.version_major >= 3 {
+        let mut count: gl::types::GLint = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+
+        (0..count as u32).any(|i| {
+          let extension = gl::GetStringi(gl::EXTENSIONS, i);
+          !extension.is_null() && CStr::from_ptr(extension as *const std::os::raw::c_char).to_bytes() == a_name.as_bytes()
+        })
+      }
+      else {
+        let extensions = gl::GetString(gl::EXTENSIONS);
+        !extensions.is_null() && CStr::from_ptr(extensions as *const std::os::raw::c_char).to_bytes()
+          .split(|byte| *byte == b' ').any(|extension| extension == a_name.as_bytes())
+      }
+    }
+  }
+
+  // gl 3.3 or gles 3.0
+  fn is_sampler_object_supported(&self) -> bool{
+    match self.is_gles {
+      true => self.version_major >= 3,
+      false => (self.version_major, self.version_minor) >= (3, 3)
+    }
+  }
+
+  // gl or gles 3.2
+  fn is_border_supported(&self) -> bool{
+    !self.is_gles || (self.version_major, self.version_minor) >= (3, 2)
+  }
+
+  fn get_max_anisotropy(&self) -> f32{
+    self.max_anisotropy
+  }
+
+  // gl 4.6 or the anisotropic filtering extensions, 0 when unsupported
+  fn query_max_anisotropy(&self) -> f32{
+    let supported = (!self.is_gles && (self.version_major, self.version_minor) >= (4, 6)) ||
+      self.has_extension("GL_EXT_texture_filter_anisotropic") || self.has_extension("GL_ARB_texture_filter_anisotropic");
+
+    if !supported {
+      return 0.0
+    }
+
+    let mut max_anisotropy: gl::types::GLfloat = 0.0;
+    unsafe {
+      gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy);
+    }
+
+    max_anisotropy
+  }
+
+  // gl 3.3 or gles 3.0
+  fn is_instancing_supported(&self) -> bool{
+    match self.is_gles {
+      true => self.version_major >= 3,
+      false => (self.version_major, self.version_minor) >= (3, 3)
+    }
+  }
+
+  // per instance attributes need gl 3.3 or gles 3.0 and integer attributes gl or gles 3.0, older
+  // contexts have no VertexAttribDivisor or VertexAttribIPointer
+  fn is_layout_supported(&self, a_layout: &VertexBufferLayout) -> bool{
+    if a_layout.step == VertexStep::PerInstance && !self.is_instancing_supported() {
+      return false
+    }
+
+    a_layout.attributes.iter().all(|attribute| get_attribute_type(attribute.element_type).is_some() &&
+      (!attribute.element_type.is_integer() || attribute.normalized || self.version_major >= 3))
+  }
+
+  // gl 3.1 or gles 3.0
+  fn is_uniform_buffer_supported(&self) -> bool{
+    match self.is_gles {
+      true => self.version_major >= 3,
+      false => (self.version_major, self.version_minor) >= (3, 1)
+    }
+  }
+
+  // gl 4.0 or gles 3.2
+  fn is_tesselation_supported(&self) -> bool{
+    match self.is_gles {
+      true => (self.version_major, self.version_minor) >= (3, 2),
+      false => (self.version_major, self.version_minor) >= (4, 0)
+    }
+  }
+
+  // gl 3.2 or gles 3.2
+  fn is_geometry_supported(&self) -> bool{
+    (self.version_major, self.version_minor) >= (3, 2)
+  }
+
+  // gl 4.3 or gles 3.1
+  fn is_compute_supported(&self) -> bool{
+    match self.is_gles {
+      true => (self.version_major, self.version_minor) >= (3, 1),
+      false => (self.version_major, self.version_minor) >= (4, 3)
+    }
+  }
+
+  // the element buffer is left bound to the vao when there are indices
+  fn gen_vertex_array(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout, a_indices: Option<&IndicesOpenGL>) -> Result<GeometryOpenGL, RendererError>{
+    if a_buffers.len() != a_layout.buffers.len() {
+      return Err(RendererError::Error)
+    }
+
+    let mut buffers = Vec::new();
+    for buffer in a_buffers {
+      match buffer.any().downcast_ref::<VerticesOpenGL>() {
+        Some(res) => buffers.push(res),
+        None => return Err(RendererError::InvalidCast)
+      }
+    }
+
+    // check every attribute before creating anything
+    for layout in &a_layout.buffers {
+      if !self.is_layout_supported(layout) {
+        return Err(RendererError::UnsupportedAPI)
+      }
+    }
+
+    let mut vao: gl::types::GLuint = 0;
+    let mut num: Option<usize> = None;
+    unsafe {
+      gl::GenVertexArrays(1, &mut vao);
+      gl::BindVertexArray(vao);
+
+      for (buffer, layout) in buffers.iter().zip(a_layout.buffers.iter()) {
+        gl::BindBuffer(gl::ARRAY_BUFFER, buffer.id);
+        set_vertex_attributes(layout);
+
+        if layout.step == VertexStep::PerVertex && layout.stride > 0 {
+          let count = buffer.size / layout.stride as usize;
+          num = Some(match num {
+            Some(res) => res.min(count),
+            None => count
+          });
+        }
+      }
+
+      gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+      if let Some(indices) = a_indices {
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, indices.id);
+      }
+
+      gl::BindVertexArray(0);
+    }
+
+    // the bound vao was changed
+    self.vao = -1;
+
+    match a_indices {
+      Some(indices) => Ok(GeometryOpenGL{vao:vao, num: indices.num, indices: Some((indices.index_type, indices.index_size)), topology: PrimitiveTopology::Triangles}),
+      None => Ok(GeometryOpenGL{vao:vao, num: num.unwrap_or(0) as gl::types::GLsizei, indices: None, topology: PrimitiveTopology::Triangles})
+    }
+  }
+
+  // draws triangles, or patches for tesselation programs, with the bound program, the range is clamped to the geometry.
+  // instance attributes are added to the geometry's vao for the draw only
+  fn draw_elements(&mut self, a_geometry: &GeometryOpenGL, a_range: DrawRange, a_instances: Option<&InstancesOpenGL>){
+    if self.vao != a_geometry.vao as gl::types::GLint{
+      self.vao = a_geometry.vao as gl::types::GLint;
+
+      unsafe {
+        gl::BindVertexArray(a_geometry.vao);
+      }
+    }
+
+    let first = std::cmp::min(a_range.first, a_geometry.num as u32);
+    let count = std::cmp::min(a_range.count, a_geometry.num as u32 - first) as gl::types::GLsizei;
+    let num_instances = match a_instances {
+      Some(res) => res.num.get() as gl::types::GLsizei,
+      None => 1
+    };
+
+    if count == 0 || num_instances == 0 {
+      return
+    }
+
+    let mode = match self.patches {
+      true => gl::PATCHES,
+      false => get_primitive_mode(a_geometry.topology)
+    };
+
+    if let Some(instances) = a_instances {
+      unsafe {
+        gl::BindBuffer(gl::ARRAY_BUFFER, instances.id);
+        set_vertex_attributes(&instances.layout);
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+      }
+    }
+
+    unsafe {
+      match (a_geometry.indices, a_instances.is_some()) {
+        (Some((index_type, index_size)), instanced) => {
+          let offset = (first * index_size) as usize as *const gl::types::GLvoid;
+
+          if a_range.base_vertex != 0 && !gl::DrawElementsBaseVertex::is_loaded() {
+            eprintln!("Error base vertex requires gl 3.2 or gles 3.2");
+          }
+          else if a_range.base_vertex != 0 && instanced {
+            gl::DrawElementsInstancedBaseVertex(mode, count, index_type, offset, num_instances, a_range.base_vertex);
+          }
+          else if a_range.base_vertex != 0 {
+            gl::DrawElementsBaseVertex(mode, count, index_type, offset, a_range.base_vertex);
+          }
+          else if instanced {
+            gl::DrawElementsInstanced(mode, count, index_type, offset, num_instances);
+          }
+          else {
+            gl::DrawElements(mode, count, index_type, offset);
+          }
+        },
+        (None, true) => {
+          gl::DrawArraysInstanced(mode, first as gl::types::GLint, count, num_instances);
+        },
+        (None, false) => {
+          gl::DrawArrays(
+            mode, // mode
+            first as gl::types::GLint, // starting index in the enabled arrays
+            count // number of indices to be rendered
+          );
+        }
+      }
+    }
+
+    if let Some(instances) = a_instances {
+      unset_vertex_attributes(&instances.layout);
+    }
+  }
+
+  // clears write every pixel of the target the same as the other renderers, the scissor test and write masks
+  // are turned off around the clear
+  fn clear_unmasked(&self, a_bits: gl::types::GLenum){
+    let state = self.render_state;
+    let depth_write = state.depth.map_or(true, |depth| depth.write);
+    let stencil_write = state.stencil.map_or(0xff, |stencil| stencil.write_mask);
+
+    unsafe {
+      if state.scissor.is_some() {
+        gl::Disable(gl::SCISSOR_TEST);
+      }
+      if state.color_write != ColorWriteMask::ALL {
+        gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+      }
+      if !depth_write {
+        gl::DepthMask(gl::TRUE);
+      }
+      if stencil_write != 0xff {
+        gl::StencilMask(0xff);
+      }
+
+      gl::Clear(a_bits);
+
+      if state.scissor.is_some() {
+        gl::Enable(gl::SCISSOR_TEST);
+      }
+      if state.color_write != ColorWriteMask::ALL {
+        gl::ColorMask(
+          state.color_write.contains(ColorWriteMask::RED) as gl::types::GLboolean,
+          state.color_write.contains(ColorWriteMask::GREEN) as gl::types::GLboolean,
+          state.color_write.contains(ColorWriteMask::BLUE) as gl::types::GLboolean,
+          state.color_write.contains(ColorWriteMask::ALPHA) as gl::types::GLboolean);
+      }
+      if !depth_write {
+        gl::DepthMask(gl::FALSE);
+      }
+      if stencil_write != 0xff {
+        gl::StencilMask(stencil_write);
+      }
+    }
+  }
+
+  fn bind_framebuffer(&self, a_framebuffer: gl::types::GLuint){
+    unsafe {
+      gl::BindFramebuffer(gl::FRAMEBUFFER, a_framebuffer);
+    }
+  }
+
+  fn to_bgra8(&self, a_image: &mut image::DynamicImage){
+    let mut pixels_it = match a_image.as_mut_rgba8(){
+      Some(res) => res.pixels_mut(),
+      None => panic!("invalid image")
+    };
+
+    for pix in &mut pixels_it{
+      let val = pix.0[2];
+      pix.0[2] = pix.0[0];
+      pix.0[0] = val;
+    }
+
+
+  }
+}
+
+impl Drop for RendererOpenGL {
+  fn drop(&mut self) {
+    if self.framebuffer != 0 {
+      unsafe {
+        gl::DeleteFramebuffers(1, &mut self.framebuffer);
+        gl::DeleteRenderbuffers(2, self.renderbuffers.as_mut_ptr());
+      }
+    }
+  }
+}
+
+impl Drop for RenderTargetOpenGL {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteFramebuffers(1, &mut self.framebuffer);
+    }
+  }
+}
+
+impl Drop for ShaderOpenGL {
+  fn drop(&mut self) {
+      unsafe {
+          gl::DeleteShader(self.id);
+      }
+  }
+}
+
+
+impl Drop for ProgramOpenGL {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteProgram(self.id);
+    }
+  }
+}
+
+impl Drop for VerticesOpenGL {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteBuffers(1, &mut self.id);
+    }
+  }
+}
+
+impl Drop for StorageBufferOpenGL {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteBuffers(1, &mut self.id);
+    }
+  }
+}
+
+impl Drop for SamplerOpenGL {
+  fn drop(&mut self) {
+    if self.object != 0 {
+      unsafe {
+        gl::DeleteSamplers(1, &mut self.object);
+      }
+    }
+  }
+}
+
+impl Drop for UniformBufferOpenGL {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteBuffers(1, &mut self.id);
+    }
+  }
+}
+
+impl Drop for InstancesOpenGL {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteBuffers(1, &mut self.id);
+    }
+  }
+}
+
+impl Drop for IndicesOpenGL {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteBuffers(1, &mut self.id);
+    }
+  }
+}
+
+impl Drop for TextureOpenGL {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteTextures(1, &mut self.id);
+    }
+  }
+}
+
+// version of the current context. gl and gles before 3.0 are unable to report it and are left as 0
+// gl and gles before 3.0 have no version integers and are read from the version string
+fn get_context_version() -> (i32, i32) {
+  let mut major: gl::types::GLint = 0;
+  let mut minor: gl::types::GLint = 0;
+
+  unsafe {
+    gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+    gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+
+    // the invalid enum error of older contexts is cleared either way
+    if gl::GetError() == gl::NO_ERROR && major > 0 {
+      return (major, minor);
+    }
+
+    let version = gl::GetString(gl::VERSION);
+    if version.is_null() {
+      return (0, 0);
+    }
+
+    let version = CStr::from_ptr(version as *const std::os::raw::c_char).to_string_lossy();
+    parse_version_string(&version).unwrap_or((0, 0))
+  }
+}
+
+// the version at the start of GL_VERSION, "2.1 Mesa 20.0" or "OpenGL ES 2.0 Mesa 20.0" for gles
+pub fn parse_version_string(a_version: &str) -> Option<(i32, i32)> {
+  let number = match a_version.split_whitespace().find(|word| word.starts_with(|c: char| c.is_ascii_digit())) {
+    Some(res) => res,
+    None => return None
+  };
+  let mut parts = number.split('.');
+
+  let major = match parts.next().map(|part| part.parse::<i32>()) {
+    Some(Ok(res)) => res,
+    _ => return None
+  };
+
+  let minor: String = parts.next().unwrap_or("").chars().take_while(|c| c.is_ascii_digit()).collect();
+  match minor.parse::<i32>() {
+    Ok(res) => Some((major, res)),
+    Err(_res) => None
+  }
+}
+
+// points the attributes at the bound array buffer, the layout must be supported
+fn set_vertex_attributes(a_layout: &VertexBufferLayout) {
+  for attribute in &a_layout.attributes {
+    let data_type = get_attribute_type(attribute.element_type).unwrap();
+    let rows = attribute.container_type.get_num_rows();
+
+    // a matrix takes a location for each column
+    for column in 0..attribute.container_type.get_num_columns() {
+      let location = attribute.location + column;
+      let offset = attribute.offset + column * rows * attribute.element_type.get_size();
+
+      unsafe {
+        gl::EnableVertexAttribArray(location);
+        if attribute.element_type.is_integer() && !attribute.normalized {
+          gl::VertexAttribIPointer(
+            location,
+            rows as gl::types::GLint,
+            data_type,
+            a_layout.stride as gl::types::GLint,
+            offset as usize as *const gl::types::GLvoid
+          );
+        }
+        else {
+          gl::VertexAttribPointer(
+            location,
+            rows as gl::types::GLint,
+            data_type,
+            attribute.normalized as gl::types::GLboolean,
+            a_layout.stride as gl::types::GLint,
+            offset as usize as *const gl::types::GLvoid
+          );
+        }
+
+        if a_layout.step == VertexStep::PerInstance {
+          gl::VertexAttribDivisor(location, 1);
+        }
+      }
+    }
+  }
+}
+
+// disables the attributes and resets their divisors
+fn unset_vertex_attributes(a_layout: &VertexBufferLayout) {
+  for attribute in &a_layout.attributes {
+    for column in 0..attribute.container_type.get_num_columns() {
+      unsafe {
+        gl::DisableVertexAttribArray(attribute.location + column);
+        gl::VertexAttribDivisor(attribute.location + column, 0);
+      }
+    }
   }
+}
 
-  fn gen_sampler(&mut self, a_texture: Rc<dyn Texture>) -> Box<dyn Sampler>{
-    let sampler = SamplerOpenGL{name: String::from(""), texture: a_texture, uniform: -1};
+fn get_wrap_mode(a_wrap: WrapMode, a_border: bool) -> gl::types::GLenum {
+  match a_wrap {
+    WrapMode::Repeat => gl::REPEAT,
+    WrapMode::MirroredRepeat => gl::MIRRORED_REPEAT,
+    WrapMode::ClampToEdge => gl::CLAMP_TO_EDGE,
+    WrapMode::ClampToBorder if !a_border => gl::CLAMP_TO_EDGE,
+    WrapMode::ClampToBorder => gl::CLAMP_TO_BORDER
+  }
+}
 
-    Box::new(sampler)
+fn get_min_filter(a_filter: FilterMode, a_mip_filter: Option<FilterMode>) -> gl::types::GLenum {
+  match (a_filter, a_mip_filter) {
+    (FilterMode::Nearest, None) => gl::NEAREST,
+    (FilterMode::Linear, None) => gl::LINEAR,
+    (FilterMode::Nearest, Some(FilterMode::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+    (FilterMode::Linear, Some(FilterMode::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+    (FilterMode::Nearest, Some(FilterMode::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+    (FilterMode::Linear, Some(FilterMode::Linear)) => gl::LINEAR_MIPMAP_LINEAR
   }
+}
 
-  fn load_texture(&mut self, a_image: &image::DynamicImage, a_texture: &mut Box<dyn Texture>){
-    let texture = match a_texture.any().downcast_ref::<TextureOpenGL>() {
-      Some(res) => res,
-      None => panic!("Invalid texture")
-    };
+fn get_primitive_mode(a_topology: PrimitiveTopology) -> gl::types::GLenum {
+  match a_topology {
+    PrimitiveTopology::Points => gl::POINTS,
+    PrimitiveTopology::Lines => gl::LINES,
+    PrimitiveTopology::LineStrip => gl::LINE_STRIP,
+    PrimitiveTopology::LineLoop => gl::LINE_LOOP,
+    PrimitiveTopology::Triangles => gl::TRIANGLES,
+    PrimitiveTopology::TriangleStrip => gl::TRIANGLE_STRIP,
+    PrimitiveTopology::TriangleFan => gl::TRIANGLE_FAN,
+    PrimitiveTopology::Patches => gl::PATCHES
+  }
+}
+
+unsafe fn set_capability(a_capability: gl::types::GLenum, a_enabled: bool) {
+  match a_enabled {
+    true => gl::Enable(a_capability),
+    false => gl::Disable(a_capability)
+  }
+}
 
-    // let mut bgra =  image::DynamicImage::ImageRgba8(a_image.to_rgba8());
-    // self.to_bgra8(&mut bgra);
+fn get_blend_factor(a_factor: BlendFactor) -> gl::types::GLenum {
+  match a_factor {
+    BlendFactor::Zero => gl::ZERO,
+    BlendFactor::One => gl::ONE,
+    BlendFactor::SrcColor => gl::SRC_COLOR,
+    BlendFactor::OneMinusSrcColor => gl::ONE_MINUS_SRC_COLOR,
+    BlendFactor::DstColor => gl::DST_COLOR,
+    BlendFactor::OneMinusDstColor => gl::ONE_MINUS_DST_COLOR,
+    BlendFactor::SrcAlpha => gl::SRC_ALPHA,
+    BlendFactor::OneMinusSrcAlpha => gl::ONE_MINUS_SRC_ALPHA,
+    BlendFactor::DstAlpha => gl::DST_ALPHA,
+    BlendFactor::OneMinusDstAlpha => gl::ONE_MINUS_DST_ALPHA
+  }
+}
 
-    let rgba =  image::DynamicImage::ImageRgba8(a_image.to_rgba8());
-    // self.to_rgba8(&mut rgba);
+fn get_blend_op(a_op: BlendOp) -> gl::types::GLenum {
+  match a_op {
+    BlendOp::Add => gl::FUNC_ADD,
+    BlendOp::Subtract => gl::FUNC_SUBTRACT,
+    BlendOp::ReverseSubtract => gl::FUNC_REVERSE_SUBTRACT,
+    BlendOp::Min => gl::MIN,
+    BlendOp::Max => gl::MAX
+  }
+}
 
-    unsafe{
-      gl::BindTexture(gl::TEXTURE_2D, texture.id);
-      // gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as i32, bgra.width() as i32, bgra.height() as i32, 0, 
-      //   gl::BGRA, gl::UNSIGNED_BYTE, bgra.as_bytes().as_ptr() as *const std::os::raw::c_void);
+fn get_stencil_op(a_op: StencilOp) -> gl::types::GLenum {
+  match a_op {
+    StencilOp::Keep => gl::KEEP,
+    StencilOp::Zero => gl::ZERO,
+    StencilOp::Replace => gl::REPLACE,
+    StencilOp::IncrementClamp => gl::INCR,
+    StencilOp::DecrementClamp => gl::DECR,
+    StencilOp::Invert => gl::INVERT,
+    StencilOp::IncrementWrap => gl::INCR_WRAP,
+    StencilOp::DecrementWrap => gl::DECR_WRAP
+  }
+}
 
-      gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as i32, rgba.width() as i32, rgba.height() as i32, 0, 
-        gl::RGBA, gl::UNSIGNED_BYTE, rgba.as_bytes().as_ptr() as *const std::os::raw::c_void);
+fn get_compare_function(a_compare: CompareFunction) -> gl::types::GLenum {
+  match a_compare {
+    CompareFunction::Never => gl::NEVER,
+    CompareFunction::Less => gl::LESS,
+    CompareFunction::Equal => gl::EQUAL,
+    CompareFunction::LessEqual => gl::LEQUAL,
+    CompareFunction::Greater => gl::GREATER,
+    CompareFunction::NotEqual => gl::NOTEQUAL,
+    CompareFunction::GreaterEqual => gl::GEQUAL,
+    CompareFunction::Always => gl::ALWAYS
+  }
+}
 
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+// the same parameters apply to sampler objects and textures. gles has no lod bias and
+// border colors need gles 3.2
+fn set_sampler_parameters(
+  a_desc: &SamplerDesc, a_is_gles: bool, a_border: bool, a_max_anisotropy: f32,
+  a_set_int: impl Fn(gl::types::GLenum, gl::types::GLint),
+  a_set_float: impl Fn(gl::types::GLenum, gl::types::GLfloat),
+  a_set_floats: impl Fn(gl::types::GLenum, &[gl::types::GLfloat]))
+{
+  let mag_filter = match a_desc.mag_filter {
+    FilterMode::Nearest => gl::NEAREST,
+    FilterMode::Linear => gl::LINEAR
+  };
+
+  a_set_int(gl::TEXTURE_WRAP_S, get_wrap_mode(a_desc.wrap_u, a_border) as gl::types::GLint);
+  a_set_int(gl::TEXTURE_WRAP_T, get_wrap_mode(a_desc.wrap_v, a_border) as gl::types::GLint);
+  a_set_int(gl::TEXTURE_WRAP_R, get_wrap_mode(a_desc.wrap_w, a_border) as gl::types::GLint);
+  a_set_int(gl::TEXTURE_MIN_FILTER, get_min_filter(a_desc.min_filter, a_desc.mip_filter) as gl::types::GLint);
+  a_set_int(gl::TEXTURE_MAG_FILTER, mag_filter as gl::types::GLint);
+  a_set_float(gl::TEXTURE_MIN_LOD, a_desc.min_lod);
+  a_set_float(gl::TEXTURE_MAX_LOD, a_desc.max_lod);
 
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+  if !a_is_gles {
+    a_set_float(gl::TEXTURE_LOD_BIAS, a_desc.lod_bias);
+  }
+
+  if a_border && (a_desc.wrap_u == WrapMode::ClampToBorder || a_desc.wrap_v == WrapMode::ClampToBorder || a_desc.wrap_w == WrapMode::ClampToBorder) {
+    a_set_floats(gl::TEXTURE_BORDER_COLOR, &a_desc.border_color.to_array());
+  }
+
+  if a_max_anisotropy > 0.0 {
+    a_set_float(TEXTURE_MAX_ANISOTROPY, a_desc.max_anisotropy.clamp(1.0, a_max_anisotropy));
+  }
+
+  match a_desc.compare {
+    Some(res) => {
+      a_set_int(gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as gl::types::GLint);
+      a_set_int(gl::TEXTURE_COMPARE_FUNC, get_compare_function(res) as gl::types::GLint);
+    },
+    None => a_set_int(gl::TEXTURE_COMPARE_MODE, gl::NONE as gl::types::GLint)
+  };
+}
+
+fn is_sampler_type(a_type: gl::types::GLenum) -> bool {
+  get_sampler_dimension(a_type).is_some()
+}
+
+// 1d and multisample samplers are reported as 2d and cube arrays as cubes, there are no dimensions for them
+fn get_sampler_dimension(a_type: gl::types::GLenum) -> Option<TextureDimension> {
+  match a_type {
+    gl::SAMPLER_1D | gl::SAMPLER_2D | gl::SAMPLER_1D_SHADOW | gl::SAMPLER_2D_SHADOW | gl::SAMPLER_2D_MULTISAMPLE |
+    gl::INT_SAMPLER_2D | gl::UNSIGNED_INT_SAMPLER_2D => Some(TextureDimension::D2),
+    gl::SAMPLER_3D | gl::INT_SAMPLER_3D | gl::UNSIGNED_INT_SAMPLER_3D => Some(TextureDimension::D3),
+    gl::SAMPLER_CUBE | gl::SAMPLER_CUBE_SHADOW | gl::SAMPLER_CUBE_MAP_ARRAY | gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW |
+    gl::INT_SAMPLER_CUBE | gl::UNSIGNED_INT_SAMPLER_CUBE => Some(TextureDimension::Cube),
+    gl::SAMPLER_1D_ARRAY | gl::SAMPLER_2D_ARRAY | gl::SAMPLER_1D_ARRAY_SHADOW | gl::SAMPLER_2D_ARRAY_SHADOW |
+    gl::INT_SAMPLER_2D_ARRAY | gl::UNSIGNED_INT_SAMPLER_2D_ARRAY => Some(TextureDimension::D2Array),
+    _ => None
+  }
+}
+
+// non-square matrices have no container type
+fn get_uniform_type(a_type: gl::types::GLenum) -> Option<(ElementType, ContainerType)> {
+  match a_type {
+    gl::FLOAT => Some((ElementType::Float32, ContainerType::Single)),
+    gl::FLOAT_VEC2 => Some((ElementType::Float32, ContainerType::Vec2)),
+    gl::FLOAT_VEC3 => Some((ElementType::Float32, ContainerType::Vec3)),
+    gl::FLOAT_VEC4 => Some((ElementType::Float32, ContainerType::Vec4)),
+    gl::FLOAT_MAT2 => Some((ElementType::Float32, ContainerType::Mat2x2)),
+    gl::FLOAT_MAT3 => Some((ElementType::Float32, ContainerType::Mat3x3)),
+    gl::FLOAT_MAT4 => Some((ElementType::Float32, ContainerType::Mat4x4)),
+    gl::DOUBLE => Some((ElementType::Float64, ContainerType::Single)),
+    gl::DOUBLE_VEC2 => Some((ElementType::Float64, ContainerType::Vec2)),
+    gl::DOUBLE_VEC3 => Some((ElementType::Float64, ContainerType::Vec3)),
+    gl::DOUBLE_VEC4 => Some((ElementType::Float64, ContainerType::Vec4)),
+    gl::DOUBLE_MAT2 => Some((ElementType::Float64, ContainerType::Mat2x2)),
+    gl::DOUBLE_MAT3 => Some((ElementType::Float64, ContainerType::Mat3x3)),
+    gl::DOUBLE_MAT4 => Some((ElementType::Float64, ContainerType::Mat4x4)),
+    gl::INT => Some((ElementType::Int32, ContainerType::Single)),
+    gl::INT_VEC2 => Some((ElementType::Int32, ContainerType::Vec2)),
+    gl::INT_VEC3 => Some((ElementType::Int32, ContainerType::Vec3)),
+    gl::INT_VEC4 => Some((ElementType::Int32, ContainerType::Vec4)),
+    gl::UNSIGNED_INT => Some((ElementType::Uint32, ContainerType::Single)),
+    gl::UNSIGNED_INT_VEC2 => Some((ElementType::Uint32, ContainerType::Vec2)),
+    gl::UNSIGNED_INT_VEC3 => Some((ElementType::Uint32, ContainerType::Vec3)),
+    gl::UNSIGNED_INT_VEC4 => Some((ElementType::Uint32, ContainerType::Vec4)),
+    gl::BOOL => Some((ElementType::Bool, ContainerType::Single)),
+    gl::BOOL_VEC2 => Some((ElementType::Bool, ContainerType::Vec2)),
+    gl::BOOL_VEC3 => Some((ElementType::Bool, ContainerType::Vec3)),
+    gl::BOOL_VEC4 => Some((ElementType::Bool, ContainerType::Vec4)),
+    _ => None
+  }
+}
+
+// name, size and type of an active uniform or attribute, arrays are named without [0]
+fn get_active_variable(a_program: gl::types::GLuint, a_index: gl::types::GLuint, a_attribute: bool) -> (String, i32, gl::types::GLenum) {
+  let mut max_length: gl::types::GLint = 0;
+  unsafe {
+    gl::GetProgramiv(a_program, if a_attribute { gl::ACTIVE_ATTRIBUTE_MAX_LENGTH } else { gl::ACTIVE_UNIFORM_MAX_LENGTH }, &mut max_length);
+  }
+
+  let mut buffer: Vec<u8> = vec![0; max_length as usize + 1];
+  let mut length: gl::types::GLsizei = 0;
+  let mut size: gl::types::GLint = 0;
+  let mut variable_type: gl::types::GLenum = 0;
+  unsafe {
+    match a_attribute {
+      true => gl::GetActiveAttrib(a_program, a_index, buffer.len() as gl::types::GLsizei, &mut length, &mut size, &mut variable_type,
+        buffer.as_mut_ptr() as *mut gl::types::GLchar),
+      false => gl::GetActiveUniform(a_program, a_index, buffer.len() as gl::types::GLsizei, &mut length, &mut size, &mut variable_type,
+        buffer.as_mut_ptr() as *mut gl::types::GLchar)
     }
   }
 
-  fn use_program(&mut self, a_program: &Box<dyn Program>){
-    let program = match a_program.any().downcast_ref::<ProgramOpenGL>() {
-      Some(res) => res,
-      None => return
-    };
+  buffer.truncate(length as usize);
+  let name = String::from_utf8_lossy(&buffer).into_owned();
+  let name = match name.strip_suffix("[0]") {
+    Some(res) => res.to_owned(),
+    None => name
+  };
 
-    if self.program_id != program.id as gl::types::GLint{
-      self.program_id = program.id as gl::types::GLint;
+  (name, size, variable_type)
+}
 
-      unsafe {
-        gl::UseProgram(program.id);
-      }
+// blocks are only queried when uniform buffers are supported, without them every uniform is outside of a block
+fn reflect_program(a_program: gl::types::GLuint, a_blocks: bool, a_sampler_units: &[(String, u32)]) -> ProgramReflection {
+  let mut reflection = ProgramReflection::default();
+
+  let mut num_uniforms: gl::types::GLint = 0;
+  let mut num_attributes: gl::types::GLint = 0;
+  let mut num_blocks: gl::types::GLint = 0;
+  unsafe {
+    gl::GetProgramiv(a_program, gl::ACTIVE_UNIFORMS, &mut num_uniforms);
+    gl::GetProgramiv(a_program, gl::ACTIVE_ATTRIBUTES, &mut num_attributes);
+    if a_blocks {
+      gl::GetProgramiv(a_program, gl::ACTIVE_UNIFORM_BLOCKS, &mut num_blocks);
     }
   }
 
-  fn draw_geometry(&mut self, a_geometry: &Box<dyn Geometry>){
-    let geometry = match a_geometry.any().downcast_ref::<GeometryOpenGL>() {
-      Some(res) => res,
-      None => panic!("Invalid vertex")
-    };
+  let mut block_members: Vec<Vec<UniformBlockMember>> = vec![Vec::new(); num_blocks as usize];
 
-    if self.vao != geometry.vao as gl::types::GLint{
-      self.vao = geometry.vao as gl::types::GLint;
+  for i in 0..num_uniforms as gl::types::GLuint {
+    let (name, size, uniform_type) = get_active_variable(a_program, i, false);
 
+    let mut block: gl::types::GLint = -1;
+    let mut offset: gl::types::GLint = -1;
+    if a_blocks {
       unsafe {
-        gl::BindVertexArray(geometry.vao);
+        gl::GetActiveUniformsiv(a_program, 1, &i, gl::UNIFORM_BLOCK_INDEX, &mut block);
+        gl::GetActiveUniformsiv(a_program, 1, &i, gl::UNIFORM_OFFSET, &mut offset);
       }
     }
 
-    unsafe {
-      gl::DrawArrays(
-        gl::TRIANGLES, // mode
-        0, // starting index in the enabled arrays
-        geometry.num // number of indices to be rendered
-      );
+    if let Some(dimension) = get_sampler_dimension(uniform_type) {
+      if let Some((_name, unit)) = a_sampler_units.iter().find(|(sampler, _unit)| *sampler == name) {
+        reflection.samplers.push(SamplerReflection{name: name, dimension: dimension, count: size as u32, unit: *unit});
+      }
+      continue;
     }
-  }
 
-  fn draw_mesh(&mut self, _camera: &Camera, a_mesh: &mut Box<Mesh>){
-    let geometry = match a_mesh.geometry.any().downcast_ref::<GeometryOpenGL>() {
+    let (element_type, container_type) = match get_uniform_type(uniform_type) {
       Some(res) => res,
-      None => panic!("Invalid vertex")
+      None => continue
     };
 
-    self.use_program(a_mesh.material.get_program());
-
-    if self.vao != geometry.vao as gl::types::GLint{
-      self.vao = geometry.vao as gl::types::GLint;
+    let info = UniformType{element_type: element_type, container_type: container_type, num_components: size as u16};
 
-      unsafe {
-        gl::BindVertexArray(geometry.vao);
-      }
+    if block >= 0 && (block as usize) < block_members.len() {
+      block_members[block as usize].push(UniformBlockMember{name: name, info: info, offset: offset as u32});
+      continue;
     }
 
-    let num_uniforms = a_mesh.material.num_uniforms();
-    for i in 0..num_uniforms {
-      self.update_uniform(a_mesh.material.get_uniform(i));
-    }
+    let location = match CString::new(name.as_str()) {
+      Ok(res) => unsafe { gl::GetUniformLocation(a_program, res.as_ptr()) },
+      Err(_res) => continue
+    };
 
-    let num_samplers = a_mesh.material.num_samplers();
-    for i in 0..num_samplers {
-      self.update_sampler(a_mesh.material.get_sampler(i));
-    }
+    reflection.uniforms.push(UniformReflection{name: name, info: info, location: location});
+  }
 
+  // members are listed in any order, the layout has them by offset
+  for (i, mut members) in block_members.into_iter().enumerate() {
+    members.sort_by_key(|member| member.offset);
+
+    let mut max_length: gl::types::GLint = 0;
+    let mut size: gl::types::GLint = 0;
     unsafe {
-      gl::DrawArrays(
-        gl::TRIANGLES, // mode
-        0, // starting index in the enabled arrays
-        geometry.num // number of indices to be rendered
-      );
+      gl::GetActiveUniformBlockiv(a_program, i as gl::types::GLuint, gl::UNIFORM_BLOCK_NAME_LENGTH, &mut max_length);
+      gl::GetActiveUniformBlockiv(a_program, i as gl::types::GLuint, gl::UNIFORM_BLOCK_DATA_SIZE, &mut size);
     }
-  }
 
-  fn read_render_buffer(&mut self) -> Image {
-    let mut image = Image{
-      width: self.window.width, 
-      height: self.window.height, 
-      pitch: self.window.height * 4, 
-      pixels: vec![0u8; (self.window.width * self.window.height  * 4) as usize]};
+    let mut buffer: Vec<u8> = vec![0; max_length as usize + 1];
+    let mut length: gl::types::GLsizei = 0;
+    unsafe {
+      gl::GetActiveUniformBlockName(a_program, i as gl::types::GLuint, buffer.len() as gl::types::GLsizei, &mut length,
+        buffer.as_mut_ptr() as *mut gl::types::GLchar);
+    }
+    buffer.truncate(length as usize);
 
-    unsafe { gl::ReadPixels( 
-      0, 0, 
-      self.window.width as i32, self.window.height as i32, 
-      gl::RGBA,
-      gl::UNSIGNED_BYTE,
-      image.pixels.as_mut_ptr() as *mut _) };
-    
-    return image
+    reflection.blocks.push(UniformBlockReflection{name: String::from_utf8_lossy(&buffer).into_owned(), size: size as u32, members: members});
   }
 
-}
+  // built in attributes such as gl_VertexID have no location
+  for i in 0..num_attributes as gl::types::GLuint {
+    let (name, size, attribute_type) = get_active_variable(a_program, i, true);
 
-#[allow(dead_code)]
-impl RendererOpenGL {
-  const GL_MAX_VERSION_MINOR: [i32; 5] = [0, 5, 1, 3, 6];
-  const GLES_MAX_VERSION_MINOR: [i32; 4] = [0, 1, 0, 2];
+    let (element_type, container_type) = match get_uniform_type(attribute_type) {
+      Some(res) => res,
+      None => continue
+    };
 
-  pub fn new(
-    a_video_subsystem: &sdl2::VideoSubsystem, 
-    a_min_version: Version, 
-    a_max_version: Version, 
-    a_window: Arc<Window>, 
-    a_is_gles: bool) -> Result<Self, RendererError>
-  {
-    let gl_context = match a_is_gles {
-      true => match init_gles_context(&a_video_subsystem, a_min_version, a_max_version, &a_window.window.lock().unwrap().inner) {
-        Ok(res) => res,
-        Err(_res) => return Err(RendererError::Error)
-      },
-      false => match init_gl_context(&a_video_subsystem, a_min_version, a_max_version, &a_window.window.lock().unwrap().inner) {
-        Ok(res) => res,
-        Err(_res) => return Err(RendererError::Error)
-      }
+    let location = match CString::new(name.as_str()) {
+      Ok(res) => unsafe { gl::GetAttribLocation(a_program, res.as_ptr()) },
+      Err(_res) => continue
     };
-    
-    gl::load_with(|s| a_video_subsystem.gl_get_proc_address(s) as *const std::os::raw::c_void);
 
-    // // swap interval requires emscripten main loop to be set first
-    // #[cfg(not(target_os = "emscripten"))]
-    // match a_video_subsystem.gl_set_swap_interval(sdl2::video::SwapInterval::Immediate){
-    //   Ok(_res) => _res,
-    //   Err(_res) => print!("Unable to set vsync\n")
-    // };
+    if location >= 0 {
+      let info = UniformType{element_type: element_type, container_type: container_type, num_components: size as u16};
+      reflection.attributes.push(AttributeReflection{name: name, info: info, location: location as u32});
+    }
+  }
 
-    Ok(Self {
-      gl_context: gl_context,
-      window: a_window,
-      version_major: 0,
-      version_minor: 0,
-      clear_color: Vec4::new(0.0, 0.0, 0.0, 0.0),
-      clear_depth: 1.0,
-      clear_stencil: 0,
-      viewport_pos: IVec2::new(0,0),
-      viewport_size: IVec2::new(0,0),
-      vao: -1,
-      program_id: -1
-    })
+  reflection.attributes.sort_by_key(|attribute| attribute.location);
+
+  reflection
+}
+
+// gives every active sampler of the bound program its own texture unit in declaration order.
+// elements of sampler arrays are named name[i], the array name is the first element
+fn set_sampler_units(a_program: gl::types::GLuint) -> Vec<(String, u32)> {
+  let mut num_uniforms: gl::types::GLint = 0;
+  unsafe {
+    gl::GetProgramiv(a_program, gl::ACTIVE_UNIFORMS, &mut num_uniforms);
   }
 
-  pub fn update_uniform(&self, a_uniform: &mut Box<dyn Uniform>){
-    let mut uniform = match a_uniform.any().downcast_mut::<UniformOpenGL>() {
-      Some(res) => res,
-      None => panic!("Invalid uniform cast")
-    };
+  let mut sampler_units: Vec<(String, u32)> = Vec::new();
+  let mut next_unit: u32 = 0;
 
-    if uniform.modified {
-      match uniform.data.info.element_type {
-        ElementType::Float32 => {
-          match uniform.data.info.container_type{
-            ContainerType::Single => {
-              let data = uniform.data.get::<f32>();
-              unsafe{
-                gl::Uniform1fv(uniform.id, 1, &data as *const f32);
-              }
-            },
-            ContainerType::Vec2 => {
-              let data = uniform.data.get::<Vec2>();
-              unsafe{
-                gl::Uniform2fv(uniform.id, 1, &data[0] as *const f32);
-              }
-            },
-            ContainerType::Vec3 => {
-              let data = uniform.data.get::<Vec3>();
-              unsafe{
-                gl::Uniform3fv(uniform.id, 1, &data[0] as *const f32);
-              }
-            },
-            ContainerType::Vec4 => {
-              let data = uniform.data.get::<Vec4>();
-              unsafe{
-                gl::Uniform4fv(uniform.id, 1, &data[0] as *const f32);
-              }
-            },
-            
-            ContainerType::Mat2x2 => {
-              let data = uniform.data.get::<Mat2>();
-              unsafe{
-                gl::UniformMatrix2fv(uniform.id, 1, 0, &data.to_cols_array()[0] as *const f32);
-              }
-            },
-            
-            ContainerType::Mat3x3 => {
-              let data = uniform.data.get::<Mat3>();
-              unsafe{
-                gl::UniformMatrix3fv(uniform.id, 1, 0, &data.to_cols_array()[0] as *const f32);
-              }
-            },
-            ContainerType::Mat4x4 => {
-              let data = uniform.data.get::<Mat4>();
-              unsafe{
-                gl::UniformMatrix4fv(uniform.id, 1, 0, &data.to_cols_array()[0] as *const f32);
-              }
-            }
-            //_ => panic!("Invalid number of components")
-          };
-        },
-        _ => panic!("Unimplemented type")
-        
-      };
+  for i in 0..num_uniforms as gl::types::GLuint {
+    let (name, size, uniform_type) = get_active_variable(a_program, i, false);
 
-      uniform.modified = false;
+    if !is_sampler_type(uniform_type) {
+      continue;
     }
-  }
 
-  pub fn update_sampler(&self, a_sampler: &Box<dyn Sampler>){
-    let sampler = match a_sampler.any().downcast_ref::<SamplerOpenGL>() {
-      Some(res) => res,
-      None => panic!("Invalid sampler cast")
+    let location = match CString::new(name.as_str()) {
+      Ok(res) => unsafe { gl::GetUniformLocation(a_program, res.as_ptr()) },
+      Err(_res) => continue
     };
 
-    unsafe{
-      gl::Uniform1i(sampler.uniform, 0);
+    let units: Vec<gl::types::GLint> = (0..size).map(|element| (next_unit + element as u32) as gl::types::GLint).collect();
+    unsafe {
+      gl::Uniform1iv(location, size, units.as_ptr());
     }
-  
-    let texture = match sampler.texture.any().downcast_ref::<TextureOpenGL>() {
-      Some(res) => res,
-      None => panic!("Invalid uniform cast")
-    };
 
-    unsafe{
-      gl::ActiveTexture(gl::TEXTURE0 + 0);
-      gl::BindTexture(gl::TEXTURE_2D,  texture.id);
+    sampler_units.push((name.clone(), next_unit));
+    if size > 1 {
+      for element in 0..size as u32 {
+        sampler_units.push((format!("{}[{}]", name, element), next_unit + element));
+      }
     }
+
+    next_unit += size as u32;
   }
 
-  fn to_bgra8(&self, a_image: &mut image::DynamicImage){
-    let mut pixels_it = match a_image.as_mut_rgba8(){
-      Some(res) => res.pixels_mut(),
-      None => panic!("invalid image")
+  sampler_units
+}
+
+// members of blocks without an instance name are named by themselves, otherwise by the block name.
+// arrays are found by their first element
+fn get_block_member_offset(a_program: gl::types::GLuint, a_block_name: &str, a_name: &str) -> Option<u32> {
+  let names = [
+    a_name.to_owned(),
+    a_name.to_owned() + "[0]",
+    a_block_name.to_owned() + "." + a_name,
+    a_block_name.to_owned() + "." + a_name + "[0]"];
+
+  for name in names {
+    let c_str = match CString::new(name){
+      Ok(res) => res,
+      Err(_res) => return None
     };
 
-    for pix in &mut pixels_it{
-      let val = pix.0[2];
-      pix.0[2] = pix.0[0];
-      pix.0[0] = val;
+    let mut index: gl::types::GLuint = gl::INVALID_INDEX;
+    unsafe {
+      gl::GetUniformIndices(a_program, 1, &c_str.as_ptr(), &mut index);
     }
 
+    if index != gl::INVALID_INDEX {
+      let mut offset: gl::types::GLint = -1;
+      unsafe {
+        gl::GetActiveUniformsiv(a_program, 1, &index, gl::UNIFORM_OFFSET, &mut offset);
+      }
 
+      return match offset {
+        res if res >= 0 => Some(res as u32),
+        _ => None
+      }
+    }
   }
+
+  None
 }
 
-impl Drop for ShaderOpenGL {
-  fn drop(&mut self) {
-      unsafe {
-          gl::DeleteShader(self.id);
-      }
+// 64 bit integers and bools have no gl vertex format
+fn get_attribute_type(a_type: ElementType) -> Option<gl::types::GLenum> {
+  match a_type {
+    ElementType::Float16 => Some(gl::HALF_FLOAT),
+    ElementType::Float32 => Some(gl::FLOAT),
+    ElementType::Float64 => Some(gl::DOUBLE),
+    ElementType::Int8 => Some(gl::BYTE),
+    ElementType::Int16 => Some(gl::SHORT),
+    ElementType::Int32 => Some(gl::INT),
+    ElementType::Uint8 => Some(gl::UNSIGNED_BYTE),
+    ElementType::Uint16 => Some(gl::UNSIGNED_SHORT),
+    ElementType::Uint32 => Some(gl::UNSIGNED_INT),
+    ElementType::Int64 | ElementType::Uint64 | ElementType::Bool => None
   }
 }
 
+// internal format, format and type
+fn get_texture_format(a_format: TextureFormat) -> (gl::types::GLenum, gl::types::GLenum, gl::types::GLenum) {
+  match a_format {
+    TextureFormat::R8 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+    TextureFormat::RG8 => (gl::RG8, gl::RG, gl::UNSIGNED_BYTE),
+    TextureFormat::RGBA8 => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+    TextureFormat::SRGB8A8 => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
+    TextureFormat::R16 => (gl::R16, gl::RED, gl::UNSIGNED_SHORT),
+    TextureFormat::RG16 => (gl::RG16, gl::RG, gl::UNSIGNED_SHORT),
+    TextureFormat::RGBA16 => (gl::RGBA16, gl::RGBA, gl::UNSIGNED_SHORT),
+    TextureFormat::R16F => (gl::R16F, gl::RED, gl::HALF_FLOAT),
+    TextureFormat::RG16F => (gl::RG16F, gl::RG, gl::HALF_FLOAT),
+    TextureFormat::RGBA16F => (gl::RGBA16F, gl::RGBA, gl::HALF_FLOAT),
+    TextureFormat::RGBA32F => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+    TextureFormat::Depth16 => (gl::DEPTH_COMPONENT16, gl::DEPTH_COMPONENT, gl::UNSIGNED_SHORT),
+    TextureFormat::Depth24 => (gl::DEPTH_COMPONENT24, gl::DEPTH_COMPONENT, gl::UNSIGNED_INT),
+    TextureFormat::Depth24Stencil8 => (gl::DEPTH24_STENCIL8, gl::DEPTH_STENCIL, gl::UNSIGNED_INT_24_8),
+    TextureFormat::Depth32F => (gl::DEPTH_COMPONENT32F, gl::DEPTH_COMPONENT, gl::FLOAT)
+  }
+}
 
-impl Drop for ProgramOpenGL {
-  fn drop(&mut self) {
-    unsafe {
-      gl::DeleteProgram(self.id);
+fn get_compressed_format(a_format: CompressedFormat, a_srgb: bool) -> gl::types::GLenum {
+  match (a_format, a_srgb) {
+    (CompressedFormat::BC1, false) => COMPRESSED_RGBA_S3TC_DXT1,
+    (CompressedFormat::BC1, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+    (CompressedFormat::BC2, false) => COMPRESSED_RGBA_S3TC_DXT3,
+    (CompressedFormat::BC2, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT3,
+    (CompressedFormat::BC3, false) => COMPRESSED_RGBA_S3TC_DXT5,
+    (CompressedFormat::BC3, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+    (CompressedFormat::BC4, _) => gl::COMPRESSED_RED_RGTC1,
+    (CompressedFormat::BC5, _) => gl::COMPRESSED_RG_RGTC2,
+    (CompressedFormat::BC7, false) => gl::COMPRESSED_RGBA_BPTC_UNORM,
+    (CompressedFormat::BC7, true) => gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+    (CompressedFormat::ETC2RGB8, false) => gl::COMPRESSED_RGB8_ETC2,
+    (CompressedFormat::ETC2RGB8, true) => gl::COMPRESSED_SRGB8_ETC2,
+    (CompressedFormat::ETC2RGBA8, false) => gl::COMPRESSED_RGBA8_ETC2_EAC,
+    (CompressedFormat::ETC2RGBA8, true) => gl::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
+    // astc formats are in the order of the block sizes
+    (CompressedFormat::ASTC(_width, _height), srgb) => {
+      let base = match srgb {
+        true => COMPRESSED_SRGB8_ALPHA8_ASTC_4X4,
+        false => COMPRESSED_RGBA_ASTC_4X4
+      };
+      base + a_format.get_astc_index().unwrap_or(0) as gl::types::GLenum
     }
   }
 }
 
-impl Drop for VerticesOpenGL {
-  fn drop(&mut self) {
-    unsafe {
-      gl::DeleteBuffers(1, &mut self.id);
-    }
+fn get_texture_target(a_dimension: TextureDimension) -> gl::types::GLenum {
+  match a_dimension {
+    TextureDimension::D2 => gl::TEXTURE_2D,
+    TextureDimension::D3 => gl::TEXTURE_3D,
+    TextureDimension::Cube => gl::TEXTURE_CUBE_MAP,
+    TextureDimension::D2Array => gl::TEXTURE_2D_ARRAY
   }
 }
 
-impl Drop for TextureOpenGL {
-  fn drop(&mut self) {
-    unsafe {
-      gl::DeleteTextures(1, &mut self.id);
-    }
+// gles only generates mipmaps for formats it can render to, which excludes floats without extensions
+fn is_mipmap_supported(a_format: TextureFormat, a_is_gles: bool) -> bool {
+  match a_format {
+    TextureFormat::R8 | TextureFormat::RG8 | TextureFormat::RGBA8 | TextureFormat::SRGB8A8 => true,
+    TextureFormat::R16 | TextureFormat::RG16 | TextureFormat::RGBA16 |
+    TextureFormat::R16F | TextureFormat::RG16F | TextureFormat::RGBA16F | TextureFormat::RGBA32F => !a_is_gles,
+    _ => false
+  }
+}
+
+// single level so the texture is complete without mipmaps
+fn gen_attachment_texture(a_format: TextureFormat, a_width: u32, a_height: u32) -> TextureOpenGL {
+  let (internal_format, format, data_type) = get_texture_format(a_format);
+
+  let mut id: gl::types::GLuint = 0;
+  unsafe {
+    gl::GenTextures(1, &mut id);
+    gl::BindTexture(gl::TEXTURE_2D, id);
+    gl::TexImage2D(gl::TEXTURE_2D, 0, internal_format as i32, a_width as i32, a_height as i32, 0,
+      format, data_type, std::ptr::null());
+
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
+    gl::BindTexture(gl::TEXTURE_2D, 0);
   }
+
+  TextureOpenGL{id: id, width: a_width, height: a_height, target: Cell::new(gl::TEXTURE_2D)}
 }
 
 fn get_gl_version_major(a_version: VersionNum) -> i32 {
@@ -889,6 +2970,9 @@ fn init_gl_context(
   };
 
   let gl_attr = a_video_subsystem.gl_attr();
+  // the same depth stencil format as headless renderers so stencil state works on windows
+  gl_attr.set_depth_size(24);
+  gl_attr.set_stencil_size(8);
 
   loop {
     if version_major > 2 {
@@ -942,6 +3026,9 @@ fn init_gles_context(
   };
 
   let gl_attr = a_video_subsystem.gl_attr();
+  // the same depth stencil format as headless renderers so stencil state works on windows
+  gl_attr.set_depth_size(24);
+  gl_attr.set_stencil_size(8);
 
   loop {
     if version_major > 2 {
@@ -974,3 +3061,59 @@ fn init_gles_context(
     }
   }
 }
+
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten"))))]
+fn init_egl_context(
+  a_min_version: Version, 
+  a_max_version: Version, 
+  a_is_gles: bool) -> Result<ContextEGL, RendererError> 
+{
+  let max_minor_versions: &[i32] = match a_is_gles {
+    true => &RendererOpenGL::GLES_MAX_VERSION_MINOR,
+    false => &RendererOpenGL::GL_MAX_VERSION_MINOR
+  };
+
+  let (mut version_major, min_version_major) = match a_is_gles {
+    true => (get_gles_version_major(a_max_version.major), get_gles_version_major(a_min_version.major)),
+    false => (get_gl_version_major(a_max_version.major), get_gl_version_major(a_min_version.major))
+  };
+
+  let (version_minor, min_version_minor) = match a_is_gles {
+    true => (get_gles_version_minor(version_major, a_max_version.minor), get_gles_version_minor(min_version_major, a_min_version.minor)),
+    false => (get_gl_version_minor(version_major, a_max_version.minor), get_gl_version_minor(min_version_major, a_min_version.minor))
+  };
+
+  let mut version_minor = match version_minor {
+    Ok(res) => res,
+    Err(res) => return Err(res)
+  };
+
+  let min_version_minor = match min_version_minor {
+    Ok(res) => res,
+    Err(res) => return Err(res)
+  };
+
+  loop {
+    match ContextEGL::new(version_major, version_minor, a_is_gles) {
+      Ok(res) => {
+        return Ok(res);
+      },
+      Err(RendererError::InvalidVersion) => {
+        //try lower version of gl
+        if version_minor > 0 {
+          version_minor -= 1;
+        }
+        else if version_minor == 0 && version_major > 0{
+          version_major -= 1;
+          version_minor = max_minor_versions[version_major as usize];
+        }
+
+        //check if we go below min version
+        if (version_major < min_version_major) || (version_major == min_version_major && version_minor < min_version_minor){
+          return Err(RendererError::Error)
+        }
+      },
+      Err(res) => return Err(res)
+    }
+  }
+}
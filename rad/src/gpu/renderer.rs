@@ -6,6 +6,7 @@ use crate::gpu::uniforms::*;
 use crate::gpu::material::*;
 use crate::gpu::camera::*;
 use crate::gpu::image::*;
+use crate::gpu::compressed::*;
 
 use glam::*;
 use std::rc::Rc;
@@ -14,11 +15,15 @@ pub trait Program{
   fn any(&self) -> &dyn std::any::Any;
 
   fn get_uniform(&self, a_name: &str, a_data: UniformData) -> Box<dyn Uniform>;
+  // the active uniforms, blocks, samplers and attributes found when linking
+  fn get_reflection(&self) -> &ProgramReflection;
 }
 
 
 pub trait Shader{
   fn any(&self) -> &dyn std::any::Any;
+
+  fn get_type(&self) -> ShaderType;
 }
 
 
@@ -32,8 +37,118 @@ pub trait Vertices{
 }
 
 
+pub trait Indices{
+  fn any(&self) -> &dyn std::any::Any;
+}
+
+
+// per instance attributes read from one buffer, drawn with any geometry that does not use the same locations
+pub trait Instances{
+  fn any(&self) -> &dyn std::any::Any;
+
+  fn get_num_instances(&self) -> u32;
+}
+
+
+// buffer read and written by compute shaders
+pub trait StorageBuffer{
+  fn any(&self) -> &dyn std::any::Any;
+
+  fn get_size(&self) -> usize;
+}
+
+
+// buffer backing a uniform block, can be bound for many programs at once
+pub trait UniformBuffer{
+  fn any(&self) -> &dyn std::any::Any;
+
+  fn get_layout(&self) -> &UniformBlockLayout;
+}
+
+
 pub trait Geometry{
   fn any(&self) -> &dyn std::any::Any;
+
+  // number of indices, or vertices when the geometry has no indices
+  fn get_num_elements(&self) -> u32;
+
+  // triangles unless set, used by every draw of the geometry
+  fn get_topology(&self) -> PrimitiveTopology;
+  fn set_topology(&mut self, a_topology: PrimitiveTopology);
+}
+
+// Offscreen framebuffer. Attachments are textures so they can be sampled once rendering to them has finished
+pub trait RenderTarget{
+  fn any(&self) -> &dyn std::any::Any;
+
+  fn get_width(&self) -> u32;
+  fn get_height(&self) -> u32;
+
+  fn num_color_attachments(&self) -> usize;
+  fn get_color_texture(&self, a_index: usize) -> Rc<dyn Texture>;
+  // None when there is no depth attachment or the renderer is unable to sample it
+  fn get_depth_texture(&self) -> Option<Rc<dyn Texture>>;
+}
+
+// Shader stages to link into one program with Renderer::load_program
+pub struct ProgramBuilder{
+  shaders: Vec<Box<dyn Shader>>
+}
+
+impl ProgramBuilder{
+  pub fn new() -> ProgramBuilder{
+    ProgramBuilder{shaders: Vec::new()}
+  }
+
+  pub fn stage(mut self, a_shader: Box<dyn Shader>) -> ProgramBuilder{
+    self.shaders.push(a_shader);
+    self
+  }
+
+  pub fn get_shaders(&self) -> &Vec<Box<dyn Shader>>{
+    &self.shaders
+  }
+
+  pub fn has_stage(&self, a_shader_type: ShaderType) -> bool{
+    self.shaders.iter().any(|shader| shader.get_type() == a_shader_type)
+  }
+
+  // checks the stage combination, backends also check the stages are supported by the context.
+  // compute is on its own, otherwise a vertex stage is needed and tesselation control needs evaluation
+  pub fn validate(&self) -> Result<(), RendererError>{
+    for (i, shader) in self.shaders.iter().enumerate() {
+      if self.shaders[..i].iter().any(|other| other.get_type() == shader.get_type()) {
+        return Err(RendererError::Error)
+      }
+    }
+
+    if self.has_stage(ShaderType::Compute) {
+      return match self.shaders.len() {
+        1 => Ok(()),
+        _ => Err(RendererError::Error)
+      }
+    }
+
+    if !self.has_stage(ShaderType::Vertex) {
+      return Err(RendererError::Error)
+    }
+
+    if self.has_stage(ShaderType::TesselationControl) && !self.has_stage(ShaderType::TesselationEvaluation) {
+      return Err(RendererError::Error)
+    }
+
+    Ok(())
+  }
+
+  pub fn build(self, a_renderer: &mut dyn Renderer) -> Result<Box<dyn Program>, RendererError>{
+    a_renderer.load_program(self)
+  }
+}
+
+impl Default for ProgramBuilder{
+  fn default() -> Self{
+    ProgramBuilder::new()
+  }
 }
 
 pub struct Mesh{
@@ -70,39 +185,103 @@ pub trait Renderer {
   fn set_viewport(&mut self, a_pos: IVec2, a_size: IVec2);
   fn get_viewport_pos(&self) -> IVec2;
   fn get_viewport_size(&self) -> IVec2;
+
+  // blending, depth, stencil, culling, write masks and scissor used by following draws. draw_mesh sets the
+  // material's state. clears are not affected
+  fn set_render_state(&mut self, a_state: &RenderState);
+  fn get_render_state(&self) -> &RenderState;
   
   fn load_shader(&mut self, a_shader_type: ShaderType, a_source: &str) -> Result<Box<dyn Shader>, RendererError>;
+  // vulkan glsl compiled by naga to the renderer's shading language, see shader_compiler
+  fn load_shader_neutral(&mut self, a_shader_type: ShaderType, a_source: &str) -> Result<Box<dyn Shader>, RendererError>;
   fn load_program_vert_frag(&mut self, a_shader_vert: Box<dyn Shader>, a_shader_frag: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>;
+  // links any valid stage combination, stages the context does not support return UnsupportedAPI
+  fn load_program(&mut self, a_builder: ProgramBuilder) -> Result<Box<dyn Program>, RendererError>;
+  // number of vertices in each patch drawn with a tesselation program, triangles use 3
+  fn set_patch_vertices(&mut self, a_count: u32);
 
   fn get_uniform(&mut self, a_shader: &mut Box<dyn Program>, a_name: &str) -> Box<dyn UniformShader>;
   //fn set_uniform(&mut self, a_uniform: &Box<dyn Uniform>);
   //fn set_texture(&mut self, a_texture: &Box<dyn Texture>);
-  /*
+
+  // compute needs gl 4.3 or gles 3.1, older contexts return UnsupportedAPI
   fn load_program_compute(&mut self, a_shader: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>;
-  */
+  // runs the current program, which must be a compute program, for a_x * a_y * a_z work groups
+  fn dispatch(&mut self, a_x: u32, a_y: u32, a_z: u32);
+  // makes writes from previous dispatches visible to the uses given
+  fn memory_barrier(&mut self, a_barrier: BarrierType);
+
+  fn gen_buffer_storage(&mut self, a_data: &[u8]) -> Result<Box<dyn StorageBuffer>, RendererError>;
+  // replaces all data, the size may change
+  fn update_buffer_storage(&mut self, a_buffer: &mut Box<dyn StorageBuffer>, a_data: &[u8]);
+  // waits for writes to finish, a BUFFER_UPDATE barrier is needed after dispatches writing the buffer
+  fn read_buffer_storage(&mut self, a_buffer: &Box<dyn StorageBuffer>) -> Vec<u8>;
+  // binds to the shader storage block with layout(binding = a_binding)
+  fn bind_buffer_storage(&mut self, a_binding: u32, a_buffer: &Box<dyn StorageBuffer>);
+
+  // texture with a fixed size and format, usable as a storage image. contents are undefined until written
+  fn gen_texture_storage(&mut self, a_width: u32, a_height: u32, a_format: TextureFormat) -> Result<Box<dyn Texture>, RendererError>;
+  // binds level 0 of the texture to the image unit with layout(binding = a_unit), the format must match the shader's
+  fn bind_image_storage(&mut self, a_unit: u32, a_texture: &dyn Texture, a_access: StorageAccess, a_format: TextureFormat);
+
+  // uniform blocks are only implemented for gl 3.1 and gles 3.0 and newer, the layout has to be std140.
+  // other renderers return Unimplemented, vulkan programs take uniforms as push constants
+  fn gen_buffer_uniform(&mut self, a_layout: &UniformBlockLayout, a_block: &dyn UniformBlock) -> Result<Box<dyn UniformBuffer>, RendererError>;
+  // a block that doesn't match the buffer's layout is InvalidUniform and leaves the buffer as it was
+  fn update_buffer_uniform(&mut self, a_buffer: &mut Box<dyn UniformBuffer>, a_block: &dyn UniformBlock) -> Result<(), RendererError>;
+  fn bind_buffer_uniform(&mut self, a_binding: u32, a_buffer: &Box<dyn UniformBuffer>);
+  // checks the program's block matches the layout and makes it read from the buffer bound to a_binding
+  fn bind_uniform_block(&mut self, a_program: &Box<dyn Program>, a_block_name: &str, a_binding: u32, a_layout: &UniformBlockLayout) -> Result<(), RendererError>;
 
   fn gen_buffer_vertex(&mut self, a_verts: &std::vec::Vec<f32>) -> Box<dyn Vertices>;
+  // vertices with attributes that are not all floats
+  fn gen_buffer_vertex_bytes(&mut self, a_data: &[u8]) -> Box<dyn Vertices>;
 
+  // vec2 position at location 0 and vec2 uv at location 1 from one buffer
   fn gen_geometry(&mut self, a_buffer: &Box<dyn Vertices>) -> Box<dyn Geometry>;
+  // a buffer for each buffer layout, the vertex count is the smallest of the per vertex buffers
+  fn gen_geometry_layout(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout) -> Result<Box<dyn Geometry>, RendererError>;
+
+  fn gen_buffer_index(&mut self, a_indices: IndexData) -> Box<dyn Indices>;
+  // triangles are read from the index buffer, vertices are read the same as gen_geometry_layout
+  fn gen_geometry_indexed(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout, a_indices: &Box<dyn Indices>) -> Result<Box<dyn Geometry>, RendererError>;
 
   fn gen_mesh(&mut self, a_geometry: Box<dyn Geometry>, a_material: Box<dyn Material>) -> Box<Mesh>;
 
-  //fn gen_instances(&mut self, Box<Mesh>, u32 a_num_instances) -> Box<Instances>;  //should return instances object, or vector of instances?
+  // the layout's step must be per instance, the number of instances is the data size over the stride
+  fn gen_instances(&mut self, a_layout: &VertexBufferLayout, a_data: &[u8]) -> Result<Box<dyn Instances>, RendererError>;
+  // replaces all instance data, may be called every frame
+  fn update_instances(&mut self, a_instances: &mut Box<dyn Instances>, a_data: &[u8]);
 
   fn gen_buffer_texture(&mut self) -> Box<dyn Texture>;
 
-  fn gen_sampler(&mut self, a_texture: Rc<dyn Texture>) -> Box<dyn Sampler>;
+  fn gen_sampler(&mut self, a_texture: Rc<dyn Texture>, a_desc: &SamplerDesc) -> Box<dyn Sampler>;
 
+  // uploads the image in the format keeping its precision, see TextureFormat::from_image
   fn load_texture(&mut self, a_image: &image::DynamicImage, a_texture: &mut Box<dyn Texture>);
+  // converts the image to the format before uploading. filterable color formats get mipmaps
+  fn load_texture_format(&mut self, a_image: &image::DynamicImage, a_format: TextureFormat, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>;
+  // cubemaps take six faces, 3d textures a slice and arrays a layer from each image. see equirectangular_to_cube for panoramas
+  fn load_texture_layers(&mut self, a_images: &[image::DynamicImage], a_dimension: TextureDimension, a_format: TextureFormat, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>;
+  // uploads every level when the format is supported, otherwise the first level is decompressed and mipmapped
+  fn load_texture_compressed(&mut self, a_image: &CompressedImage, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>;
+  fn is_compressed_format_supported(&self, a_format: CompressedFormat, a_srgb: bool) -> bool;
 
   fn use_program(&mut self, a_program: &Box<dyn Program>);
 
   fn draw_geometry(&mut self, a_geometry: &Box<dyn Geometry>);
+  // draws part of a geometry with the current program
+  fn draw_geometry_range(&mut self, a_geometry: &Box<dyn Geometry>, a_range: DrawRange);
   fn draw_mesh(&mut self, a_camera: &Camera, a_mesh: &mut Box<Mesh>);
+  // draws the mesh once for each instance with a single draw call
+  fn draw_mesh_instanced(&mut self, a_camera: &Camera, a_mesh: &mut Box<Mesh>, a_instances: &Box<dyn Instances>);
 
+  // reads the first color attachment of the current render target as rgba8, rows are bottom to top
   fn read_render_buffer(&mut self) -> Image;
 
-  //fn gen_render_target
-  //fn set_render_target
-  //fn clear_render_target
+  fn gen_render_target(&mut self, a_desc: &RenderTargetDesc) -> Result<Box<dyn RenderTarget>, RendererError>;
+  // None renders to the window, or the offscreen buffer of a headless renderer. The viewport is left unchanged
+  fn set_render_target(&mut self, a_target: Option<&Box<dyn RenderTarget>>);
+  // clears a target with the current clear values without binding it
+  fn clear_render_target(&mut self, a_target: &Box<dyn RenderTarget>, a_clear: RendererClearType);
 }
@@ -4,6 +4,8 @@ extern crate glam;
 use bitflags::bitflags;
 use glam::*;
 use std::fmt;
+use crate::gpu::shader_log::ShaderLog;
+use crate::gpu::uniforms::UniformType;
 use strum_macros::EnumIter;
 
 #[allow(dead_code)]
@@ -37,11 +39,12 @@ pub enum RendererType {
   DirectX,
   Vulkan,
   Metal,
-  WebGpu
+  WebGpu,
+  Software
 }
 
 #[allow(dead_code)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum ShaderType{
   Vertex,
   TesselationControl,
@@ -55,7 +58,10 @@ pub enum ShaderType{
 #[derive(Debug, Clone)]
 pub enum RendererError {
   Error,
-  ShaderCompile,
+  ShaderCompile(ShaderLog),
+  ShaderLink(ShaderLog),
+  // the program has no active uniform or sampler with the name and type
+  InvalidUniform(String),
   InvalidCast,
   InvalidVersion,
   UnsupportedAPI,
@@ -64,7 +70,7 @@ pub enum RendererError {
 
 
 #[allow(dead_code)]
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 #[repr(u8)]
 pub enum ElementType {
   Float16,
@@ -77,12 +83,13 @@ pub enum ElementType {
   Uint8,
   Uint16,
   Uint32,
-  Uint64
+  Uint64,
+  Bool
 }
 
 
 #[allow(dead_code)]
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 #[repr(u8)]
 pub enum ContainerType {
   Single,
@@ -94,74 +101,862 @@ pub enum ContainerType {
   Mat4x4
 }
 
+impl ElementType {
+  pub fn get_size(&self) -> u32{
+    match self {
+      ElementType::Int8 | ElementType::Uint8 | ElementType::Bool => 1,
+      ElementType::Float16 | ElementType::Int16 | ElementType::Uint16 => 2,
+      ElementType::Float32 | ElementType::Int32 | ElementType::Uint32 => 4,
+      ElementType::Float64 | ElementType::Int64 | ElementType::Uint64 => 8
+    }
+  }
+
+  pub fn is_integer(&self) -> bool{
+    !matches!(self, ElementType::Float16 | ElementType::Float32 | ElementType::Float64)
+  }
+}
+
+impl ContainerType {
+  // matrices are stored as columns
+  pub fn get_num_columns(&self) -> u32{
+    match self {
+      ContainerType::Mat2x2 => 2,
+      ContainerType::Mat3x3 => 3,
+      ContainerType::Mat4x4 => 4,
+      _ => 1
+    }
+  }
+
+  pub fn get_num_rows(&self) -> u32{
+    match self {
+      ContainerType::Single => 1,
+      ContainerType::Vec2 | ContainerType::Mat2x2 => 2,
+      ContainerType::Vec3 | ContainerType::Mat3x3 => 3,
+      ContainerType::Vec4 | ContainerType::Mat4x4 => 4
+    }
+  }
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum VertexSemantic {
+  Position,
+  Normal,
+  Tangent,
+  Color,
+  TexCoord,
+  // model matrix of an instance
+  Transform,
+  Custom
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum VertexStep {
+  PerVertex,
+  PerInstance
+}
+
+// matrices use one location for each column starting at location.
+// integers that are not normalized are read by shaders as integers
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct VertexAttribute {
+  pub location: u32,
+  pub semantic: VertexSemantic,
+  pub element_type: ElementType,
+  pub container_type: ContainerType,
+  pub normalized: bool,
+  pub offset: u32,
+}
+
+// attributes read from a single vertex buffer
+#[derive(Clone, PartialEq, Debug)]
+pub struct VertexBufferLayout {
+  pub stride: u32,
+  pub step: VertexStep,
+  pub attributes: Vec<VertexAttribute>,
+}
+
+// a buffer layout for each vertex buffer of a geometry
+#[derive(Clone, PartialEq, Debug)]
+pub struct VertexLayout {
+  pub buffers: Vec<VertexBufferLayout>,
+}
+
+impl VertexLayout {
+  // vec2 position and vec2 uv interleaved in one buffer, the layout used by gen_geometry
+  pub fn position_uv() -> VertexLayout{
+    VertexLayout{buffers: vec![VertexBufferLayout{
+      stride: 16,
+      step: VertexStep::PerVertex,
+      attributes: vec![
+        VertexAttribute{location: 0, semantic: VertexSemantic::Position, element_type: ElementType::Float32, container_type: ContainerType::Vec2, normalized: false, offset: 0},
+        VertexAttribute{location: 1, semantic: VertexSemantic::TexCoord, element_type: ElementType::Float32, container_type: ContainerType::Vec2, normalized: false, offset: 8}
+      ]
+    }]}
+  }
+}
+
+// indices used by an index buffer
+pub enum IndexData<'a> {
+  Uint16(&'a [u16]),
+  Uint32(&'a [u32])
+}
+
+impl IndexData<'_> {
+  pub fn len(&self) -> usize{
+    match self {
+      IndexData::Uint16(res) => res.len(),
+      IndexData::Uint32(res) => res.len()
+    }
+  }
+
+  pub fn is_empty(&self) -> bool{
+    self.len() == 0
+  }
+
+  pub fn get_element_type(&self) -> ElementType{
+    match self {
+      IndexData::Uint16(_res) => ElementType::Uint16,
+      IndexData::Uint32(_res) => ElementType::Uint32
+    }
+  }
+
+  pub fn to_bytes(&self) -> Vec<u8>{
+    match self {
+      IndexData::Uint16(res) => res.iter().flat_map(|i| i.to_ne_bytes()).collect(),
+      IndexData::Uint32(res) => res.iter().flat_map(|i| i.to_ne_bytes()).collect()
+    }
+  }
+}
+
+// part of a geometry to draw. first and count are indices for indexed geometry, otherwise vertices.
+// base_vertex is added to each index before vertices are read and is ignored without indices
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DrawRange {
+  pub first: u32,
+  pub count: u32,
+  pub base_vertex: i32,
+}
+
+// how the vertices of a draw are assembled into primitives, strips and fans share vertices with the previous primitive
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PrimitiveTopology {
+  Points,
+  Lines,
+  LineStrip,
+  // a line strip with the last vertex joined back to the first
+  LineLoop,
+  Triangles,
+  TriangleStrip,
+  TriangleFan,
+  // groups of the renderer's patch vertices for tesselation programs, which draw patches with any topology
+  Patches
+}
+
+pub fn half_to_f32(a_half: u16) -> f32 {
+  let sign = match (a_half & 0x8000) != 0 {
+    true => -1.0,
+    false => 1.0
+  };
+  let exponent = ((a_half >> 10) & 0x1f) as i32;
+  let mantissa = (a_half & 0x3ff) as f32;
+
+  match exponent {
+    0 => sign * mantissa * 2.0f32.powi(-24),
+    31 => match mantissa == 0.0 {
+      true => sign * f32::INFINITY,
+      false => f32::NAN
+    },
+    _ => sign * (1.0 + mantissa / 1024.0) * 2.0f32.powi(exponent - 15)
+  }
+}
+
 pub trait GetType{
   fn get_element_type(&self) -> ElementType;
   fn get_container_type(&self) -> ContainerType;
+  // number of array elements
+  fn get_num_components(&self) -> u16{
+    1
+  }
+  // writes the value at the start of a_data laid out for a block, padding is left as it is
+  fn write_layout(&self, a_layout: BlockLayout, a_data: &mut [u8]);
 }
 
-impl GetType for f32{
-  fn get_element_type(&self) -> ElementType{
-    ElementType::Float32
+// types that can be elements of uniform arrays, arrays of arrays aren't uniforms
+pub trait UniformElement: GetType{}
+
+// the components of values, bools are 4 bytes in blocks
+trait BlockScalar{
+  fn write_scalar(&self, a_data: &mut [u8]);
+}
+
+macro_rules! impl_block_scalar {
+  ($type:ty) => {
+    impl BlockScalar for $type{
+      fn write_scalar(&self, a_data: &mut [u8]){
+        a_data[..std::mem::size_of::<$type>()].copy_from_slice(&self.to_ne_bytes());
+      }
+    }
+  };
+}
+
+impl_block_scalar!(f32);
+impl_block_scalar!(f64);
+impl_block_scalar!(i32);
+impl_block_scalar!(u32);
+
+impl BlockScalar for bool{
+  fn write_scalar(&self, a_data: &mut [u8]){
+    a_data[..4].copy_from_slice(&(*self as u32).to_ne_bytes());
   }
-  fn get_container_type(&self) -> ContainerType{
-    ContainerType::Single
+}
+
+// components are column major, each column starts at the alignment of the type
+fn write_components<S: BlockScalar>(a_components: &[S], a_info: &UniformType, a_layout: BlockLayout, a_data: &mut [u8]){
+  let rows = a_info.container_type.get_num_rows() as usize;
+  let size = a_info.get_element_size() as usize;
+  let column_stride = a_info.get_alignment(a_layout) as usize;
+
+  for (index, component) in a_components.iter().enumerate() {
+    let offset = (index / rows) * column_stride + (index % rows) * size;
+    component.write_scalar(&mut a_data[offset..offset + size]);
   }
 }
 
-impl GetType for Vec2{
+macro_rules! impl_get_type {
+  ($type:ty, $element_type:ident, $container_type:ident, $components:expr) => {
+    impl GetType for $type{
+      fn get_element_type(&self) -> ElementType{
+        ElementType::$element_type
+      }
+      fn get_container_type(&self) -> ContainerType{
+        ContainerType::$container_type
+      }
+      fn write_layout(&self, a_layout: BlockLayout, a_data: &mut [u8]){
+        write_components(&($components)(self), &UniformType::from_value(self), a_layout, a_data);
+      }
+    }
+
+    impl UniformElement for $type{}
+  };
+}
+
+impl_get_type!(f32, Float32, Single, |value: &f32| [*value]);
+impl_get_type!(Vec2, Float32, Vec2, |value: &Vec2| value.to_array());
+impl_get_type!(Vec3, Float32, Vec3, |value: &Vec3| value.to_array());
+impl_get_type!(Vec4, Float32, Vec4, |value: &Vec4| value.to_array());
+impl_get_type!(Mat2, Float32, Mat2x2, |value: &Mat2| value.to_cols_array());
+impl_get_type!(Mat3, Float32, Mat3x3, |value: &Mat3| value.to_cols_array());
+impl_get_type!(Mat4, Float32, Mat4x4, |value: &Mat4| value.to_cols_array());
+impl_get_type!(f64, Float64, Single, |value: &f64| [*value]);
+impl_get_type!(DVec2, Float64, Vec2, |value: &DVec2| value.to_array());
+impl_get_type!(DVec3, Float64, Vec3, |value: &DVec3| value.to_array());
+impl_get_type!(DVec4, Float64, Vec4, |value: &DVec4| value.to_array());
+impl_get_type!(i32, Int32, Single, |value: &i32| [*value]);
+impl_get_type!(IVec2, Int32, Vec2, |value: &IVec2| value.to_array());
+impl_get_type!(IVec3, Int32, Vec3, |value: &IVec3| value.to_array());
+impl_get_type!(IVec4, Int32, Vec4, |value: &IVec4| value.to_array());
+impl_get_type!(u32, Uint32, Single, |value: &u32| [*value]);
+impl_get_type!(UVec2, Uint32, Vec2, |value: &UVec2| value.to_array());
+impl_get_type!(UVec3, Uint32, Vec3, |value: &UVec3| value.to_array());
+impl_get_type!(UVec4, Uint32, Vec4, |value: &UVec4| value.to_array());
+impl_get_type!(bool, Bool, Single, |value: &bool| [*value]);
+impl_get_type!(BVec2, Bool, Vec2, |value: &BVec2| <[bool; 2]>::from(*value));
+impl_get_type!(BVec3, Bool, Vec3, |value: &BVec3| <[bool; 3]>::from(*value));
+impl_get_type!(BVec4, Bool, Vec4, |value: &BVec4| <[bool; 4]>::from(*value));
+
+// arrays are a single uniform, the type comes from T::default() so empty arrays have one
+impl<T: UniformElement + Default, const N: usize> GetType for [T; N]{
   fn get_element_type(&self) -> ElementType{
-    ElementType::Float32
+    T::default().get_element_type()
   }
   fn get_container_type(&self) -> ContainerType{
-    ContainerType::Vec2
+    T::default().get_container_type()
+  }
+  fn get_num_components(&self) -> u16{
+    const { assert!(N <= u16::MAX as usize, "uniform arrays have at most 65535 elements") };
+    N as u16
+  }
+  fn write_layout(&self, a_layout: BlockLayout, a_data: &mut [u8]){
+    let stride = UniformType::from_value(self).get_stride(a_layout) as usize;
+
+    for (index, element) in self.iter().enumerate() {
+      element.write_layout(a_layout, &mut a_data[index * stride..]);
+    }
   }
 }
 
-impl GetType for Vec3{
-  fn get_element_type(&self) -> ElementType{
-    ElementType::Float32
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TextureFormat {
+  R8,
+  RG8,
+  RGBA8,
+  // rgb is converted from srgb to linear when sampled
+  SRGB8A8,
+  // 16 bit unsigned normalized
+  R16,
+  RG16,
+  RGBA16,
+  R16F,
+  RG16F,
+  RGBA16F,
+  RGBA32F,
+  Depth16,
+  Depth24,
+  Depth24Stencil8,
+  Depth32F
+}
+
+impl TextureFormat {
+  pub fn is_depth(&self) -> bool{
+    matches!(self, TextureFormat::Depth16 | TextureFormat::Depth24 | TextureFormat::Depth24Stencil8 | TextureFormat::Depth32F)
   }
-  fn get_container_type(&self) -> ContainerType{
-    ContainerType::Vec3
+
+  pub fn has_stencil(&self) -> bool{
+    matches!(self, TextureFormat::Depth24Stencil8)
+  }
+
+  pub fn is_float(&self) -> bool{
+    matches!(self, TextureFormat::R16F | TextureFormat::RG16F | TextureFormat::RGBA16F | TextureFormat::RGBA32F | TextureFormat::Depth32F)
+  }
+
+  pub fn is_unorm16(&self) -> bool{
+    matches!(self, TextureFormat::R16 | TextureFormat::RG16 | TextureFormat::RGBA16)
+  }
+
+  pub fn get_texel_size(&self) -> u32{
+    match self {
+      TextureFormat::R8 => 1,
+      TextureFormat::RG8 | TextureFormat::R16 | TextureFormat::R16F | TextureFormat::Depth16 => 2,
+      TextureFormat::RGBA8 | TextureFormat::SRGB8A8 | TextureFormat::RG16 | TextureFormat::RG16F | TextureFormat::Depth24 | TextureFormat::Depth24Stencil8 | TextureFormat::Depth32F => 4,
+      TextureFormat::RGBA16 | TextureFormat::RGBA16F => 8,
+      TextureFormat::RGBA32F => 16
+    }
+  }
+
+  // the format keeping the precision and channels of the image. grayscale images are in red, with alpha in
+  // green, and rgb images are rgba
+  pub fn from_image(a_image: &image::DynamicImage) -> TextureFormat{
+    match a_image {
+      image::DynamicImage::ImageLuma8(_) => TextureFormat::R8,
+      image::DynamicImage::ImageLumaA8(_) => TextureFormat::RG8,
+      image::DynamicImage::ImageLuma16(_) => TextureFormat::R16,
+      image::DynamicImage::ImageLumaA16(_) => TextureFormat::RG16,
+      image::DynamicImage::ImageRgb16(_) | image::DynamicImage::ImageRgba16(_) => TextureFormat::RGBA16,
+      image::DynamicImage::ImageRgb32F(_) | image::DynamicImage::ImageRgba32F(_) => TextureFormat::RGBA32F,
+      _ => TextureFormat::RGBA8
+    }
+  }
+
+  // the format to load images in when the format is unsupported. 16 bit unorm keeps its channels and precision
+  pub fn get_fallback(&self) -> TextureFormat{
+    match self {
+      TextureFormat::R16 => TextureFormat::R16F,
+      TextureFormat::RG16 => TextureFormat::RG16F,
+      TextureFormat::RGBA16 => TextureFormat::RGBA16F,
+      _ => TextureFormat::RGBA8
+    }
+  }
+
+  // the channels stored by the format, missing channels are 0 and alpha is 1 the same as sampling in gl.
+  // single channel formats keep red, except luma alpha images which fill red and green with luma and alpha.
+  // depth is in red
+  fn get_channels(&self, a_image: &image::DynamicImage) -> Vec<Vec4>{
+    let luma_alpha = a_image.color().channel_count() == 2;
+
+    a_image.to_rgba32f().pixels().map(|pixel| {
+      let [r, g, b, a] = pixel.0;
+      match self {
+        TextureFormat::R8 | TextureFormat::R16 | TextureFormat::R16F => Vec4::new(r, 0.0, 0.0, 1.0),
+        TextureFormat::RG8 | TextureFormat::RG16 | TextureFormat::RG16F if luma_alpha => Vec4::new(r, a, 0.0, 1.0),
+        TextureFormat::RG8 | TextureFormat::RG16 | TextureFormat::RG16F => Vec4::new(r, g, 0.0, 1.0),
+        TextureFormat::RGBA8 | TextureFormat::SRGB8A8 | TextureFormat::RGBA16 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => Vec4::new(r, g, b, a),
+        _ => Vec4::new(r.clamp(0.0, 1.0), 0.0, 0.0, 1.0)
+      }
+    }).collect()
+  }
+
+  // the linear colors a shader reads from each texel of the image, rows start at v = 0
+  pub fn get_texels(&self, a_image: &image::DynamicImage) -> Vec<Vec4>{
+    let texels = self.get_channels(a_image);
+
+    match self {
+      TextureFormat::SRGB8A8 => texels.iter().map(|texel| {
+        Vec4::new(srgb_to_linear(texel.x), srgb_to_linear(texel.y), srgb_to_linear(texel.z), texel.w)
+      }).collect(),
+      _ => texels
+    }
+  }
+
+  // tightly packed texels in the layout uploaded to the gpu. depth 24 is normalized to the full 32 bits,
+  // with the stencil in the lowest 8 bits for depth stencil
+  pub fn get_bytes(&self, a_image: &image::DynamicImage) -> Vec<u8>{
+    let texels = self.get_channels(a_image);
+    let mut bytes: Vec<u8> = Vec::with_capacity(texels.len() * self.get_texel_size() as usize);
+
+    let unorm8 = |a_value: f32| (a_value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+    let unorm16 = |a_value: f32| (a_value.clamp(0.0, 1.0) * 65535.0 + 0.5) as u16;
+
+    for texel in texels {
+      match self {
+        TextureFormat::R8 => bytes.push(unorm8(texel.x)),
+        TextureFormat::RG8 => bytes.extend_from_slice(&[unorm8(texel.x), unorm8(texel.y)]),
+        TextureFormat::RGBA8 | TextureFormat::SRGB8A8 => bytes.extend_from_slice(&[unorm8(texel.x), unorm8(texel.y), unorm8(texel.z), unorm8(texel.w)]),
+        TextureFormat::R16 | TextureFormat::RG16 | TextureFormat::RGBA16 => {
+          for value in &texel.to_array()[..self.get_texel_size() as usize / 2] {
+            bytes.extend_from_slice(&unorm16(*value).to_ne_bytes());
+          }
+        },
+        TextureFormat::R16F => bytes.extend_from_slice(&f32_to_f16(texel.x).to_ne_bytes()),
+        TextureFormat::RG16F => {
+          bytes.extend_from_slice(&f32_to_f16(texel.x).to_ne_bytes());
+          bytes.extend_from_slice(&f32_to_f16(texel.y).to_ne_bytes());
+        },
+        TextureFormat::RGBA16F => {
+          for value in texel.to_array() {
+            bytes.extend_from_slice(&f32_to_f16(value).to_ne_bytes());
+          }
+        },
+        TextureFormat::RGBA32F => {
+          for value in texel.to_array() {
+            bytes.extend_from_slice(&value.to_ne_bytes());
+          }
+        },
+        TextureFormat::Depth16 => bytes.extend_from_slice(&((texel.x * 65535.0 + 0.5) as u16).to_ne_bytes()),
+        TextureFormat::Depth24 | TextureFormat::Depth24Stencil8 => bytes.extend_from_slice(&(((texel.x * 16777215.0 + 0.5) as u32) << 8).to_ne_bytes()),
+        TextureFormat::Depth32F => bytes.extend_from_slice(&texel.x.to_ne_bytes())
+      }
+    }
+
+    bytes
   }
 }
 
-impl GetType for Vec4{
-  fn get_element_type(&self) -> ElementType{
-    ElementType::Float32
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TextureDimension {
+  D2,
+  // one image for each depth slice
+  D3,
+  // six square faces in the order +x, -x, +y, -y, +z, -z
+  Cube,
+  // one image for each layer
+  D2Array
+}
+
+impl TextureDimension {
+  // checks the number of images for the dimension and that every image has the same size
+  pub fn validate_images(&self, a_images: &[image::DynamicImage]) -> Result<(), RendererError>{
+    let first = match a_images.first() {
+      Some(res) => res,
+      None => return Err(RendererError::Error)
+    };
+
+    let valid = match self {
+      TextureDimension::D2 => a_images.len() == 1,
+      TextureDimension::Cube => a_images.len() == 6 && first.width() == first.height(),
+      TextureDimension::D3 | TextureDimension::D2Array => true
+    };
+
+    if !valid || a_images.iter().any(|image| image.width() != first.width() || image.height() != first.height()) {
+      return Err(RendererError::Error)
+    }
+
+    Ok(())
   }
-  fn get_container_type(&self) -> ContainerType{
-    ContainerType::Vec4
+}
+
+// block compressed formats of ktx2 and dds files
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CompressedFormat {
+  // rgb with 1 bit alpha
+  BC1,
+  BC2,
+  BC3,
+  // red
+  BC4,
+  // red and green
+  BC5,
+  BC7,
+  ETC2RGB8,
+  ETC2RGBA8,
+  // block width and height, see ASTC_BLOCK_SIZES
+  ASTC(u32, u32)
+}
+
+// every 2d astc block size in the order of the gl and vulkan formats
+pub const ASTC_BLOCK_SIZES: [(u32, u32); 14] = [
+  (4, 4), (5, 4), (5, 5), (6, 5), (6, 6), (8, 5), (8, 6), (8, 8), (10, 5), (10, 6), (10, 8), (10, 10), (12, 10), (12, 12)
+];
+
+impl CompressedFormat {
+  pub fn get_block_size(&self) -> (u32, u32){
+    match self {
+      CompressedFormat::ASTC(width, height) => (*width, *height),
+      _ => (4, 4)
+    }
+  }
+
+  pub fn get_block_bytes(&self) -> u32{
+    match self {
+      CompressedFormat::BC1 | CompressedFormat::BC4 | CompressedFormat::ETC2RGB8 => 8,
+      _ => 16
+    }
+  }
+
+  // bytes of a level, partial blocks at the edges are whole blocks
+  pub fn get_level_bytes(&self, a_width: u32, a_height: u32) -> usize{
+    let (block_width, block_height) = self.get_block_size();
+    let blocks_x = ((a_width + block_width - 1) / block_width) as usize;
+    let blocks_y = ((a_height + block_height - 1) / block_height) as usize;
+    blocks_x * blocks_y * self.get_block_bytes() as usize
+  }
+
+  // index into ASTC_BLOCK_SIZES
+  pub fn get_astc_index(&self) -> Option<usize>{
+    match self {
+      CompressedFormat::ASTC(width, height) => ASTC_BLOCK_SIZES.iter().position(|size| *size == (*width, *height)),
+      _ => None
+    }
   }
 }
 
-impl GetType for Mat2{
-  fn get_element_type(&self) -> ElementType{
-    ElementType::Float32
+pub fn srgb_to_linear(a_value: f32) -> f32{
+  if a_value <= 0.04045 {
+    a_value / 12.92
   }
-  fn get_container_type(&self) -> ContainerType{
-    ContainerType::Mat2x2
+  else {
+    ((a_value + 0.055) / 1.055).powf(2.4)
   }
 }
 
-impl GetType for Mat3{
-  fn get_element_type(&self) -> ElementType{
-    ElementType::Float32
+// half floats rounded to nearest, nan stays nan and values too large become infinity
+pub fn f32_to_f16(a_value: f32) -> u16{
+  let bits = a_value.to_bits();
+  let sign = ((bits >> 16) & 0x8000) as u16;
+  let exponent = ((bits >> 23) & 0xff) as i32;
+  let mantissa = bits & 0x7fffff;
+
+  if exponent == 0xff {
+    return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 }
   }
-  fn get_container_type(&self) -> ContainerType{
-    ContainerType::Mat3x3
+
+  let exponent = exponent - 127 + 15;
+
+  if exponent >= 0x1f {
+    return sign | 0x7c00
+  }
+
+  // subnormal halfs keep the implicit leading bit in the mantissa
+  if exponent <= 0 {
+    if exponent < -10 {
+      return sign
+    }
+
+    let mantissa = mantissa | 0x800000;
+    let shift = (14 - exponent) as u32;
+    return sign | ((mantissa >> shift) + ((mantissa >> (shift - 1)) & 1)) as u16
   }
+
+  // a carry from rounding moves into the exponent which is still correct
+  sign | ((((exponent as u32) << 10) | (mantissa >> 13)) + ((mantissa >> 12) & 1)) as u16
 }
 
-impl GetType for Mat4{
-  fn get_element_type(&self) -> ElementType{
-    ElementType::Float32
+// color attachments are written in order, location 0 of the fragment shader goes to the first
+#[derive(Clone, PartialEq, Debug)]
+pub struct RenderTargetDesc {
+  pub width: u32,
+  pub height: u32,
+  pub color_formats: Vec<TextureFormat>,
+  pub depth_stencil_format: Option<TextureFormat>,
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WrapMode {
+  Repeat,
+  MirroredRepeat,
+  ClampToEdge,
+  // gles before 3.2 clamps to the edge instead
+  ClampToBorder
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FilterMode {
+  Nearest,
+  Linear
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CompareFunction {
+  Never,
+  Less,
+  Equal,
+  LessEqual,
+  Greater,
+  NotEqual,
+  GreaterEqual,
+  Always
+}
+
+// how a texture is read. renderers without sampler objects apply it to the texture when it is bound
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SamplerDesc {
+  pub wrap_u: WrapMode,
+  pub wrap_v: WrapMode,
+  pub wrap_w: WrapMode,
+  pub min_filter: FilterMode,
+  pub mag_filter: FilterMode,
+  // None only reads the first level
+  pub mip_filter: Option<FilterMode>,
+  // 1 is off, larger values are clamped to what the device supports
+  pub max_anisotropy: f32,
+  pub lod_bias: f32,
+  pub min_lod: f32,
+  pub max_lod: f32,
+  pub border_color: Vec4,
+  // depth textures return the comparison of the reference value against the depth, for shadow maps
+  pub compare: Option<CompareFunction>,
+}
+
+impl Default for SamplerDesc {
+  fn default() -> Self{
+    SamplerDesc{
+      wrap_u: WrapMode::Repeat,
+      wrap_v: WrapMode::Repeat,
+      wrap_w: WrapMode::Repeat,
+      min_filter: FilterMode::Linear,
+      mag_filter: FilterMode::Linear,
+      mip_filter: Some(FilterMode::Linear),
+      max_anisotropy: 1.0,
+      lod_bias: 0.0,
+      min_lod: 0.0,
+      max_lod: 1000.0,
+      border_color: Vec4::ZERO,
+      compare: None
+    }
   }
-  fn get_container_type(&self) -> ContainerType{
-    ContainerType::Mat4x4
+}
+
+impl SamplerDesc {
+  // unfiltered texels for pixel art and ui
+  pub fn nearest() -> SamplerDesc{
+    SamplerDesc{
+      wrap_u: WrapMode::ClampToEdge,
+      wrap_v: WrapMode::ClampToEdge,
+      wrap_w: WrapMode::ClampToEdge,
+      min_filter: FilterMode::Nearest,
+      mag_filter: FilterMode::Nearest,
+      mip_filter: None,
+      ..SamplerDesc::default()
+    }
   }
 }
 
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BlendFactor {
+  Zero,
+  One,
+  SrcColor,
+  OneMinusSrcColor,
+  DstColor,
+  OneMinusDstColor,
+  SrcAlpha,
+  OneMinusSrcAlpha,
+  DstAlpha,
+  OneMinusDstAlpha
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BlendOp {
+  Add,
+  Subtract,
+  ReverseSubtract,
+  // factors are ignored for min and max
+  Min,
+  Max
+}
+
+// the fragment color is the source, the color already in the target is the destination
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BlendState {
+  pub color_src: BlendFactor,
+  pub color_dst: BlendFactor,
+  pub color_op: BlendOp,
+  pub alpha_src: BlendFactor,
+  pub alpha_dst: BlendFactor,
+  pub alpha_op: BlendOp,
+}
+
+// writes the source unchanged, the same as drawing without blending
+impl Default for BlendState {
+  fn default() -> Self{
+    BlendState{
+      color_src: BlendFactor::One,
+      color_dst: BlendFactor::Zero,
+      color_op: BlendOp::Add,
+      alpha_src: BlendFactor::One,
+      alpha_dst: BlendFactor::Zero,
+      alpha_op: BlendOp::Add
+    }
+  }
+}
+
+impl BlendState {
+  // transparency for colors that are not premultiplied, alpha accumulates coverage
+  pub fn alpha() -> BlendState{
+    BlendState{
+      color_src: BlendFactor::SrcAlpha,
+      color_dst: BlendFactor::OneMinusSrcAlpha,
+      color_op: BlendOp::Add,
+      alpha_src: BlendFactor::One,
+      alpha_dst: BlendFactor::OneMinusSrcAlpha,
+      alpha_op: BlendOp::Add
+    }
+  }
+
+  pub fn premultiplied() -> BlendState{
+    BlendState{
+      color_src: BlendFactor::One,
+      ..BlendState::alpha()
+    }
+  }
+
+  pub fn additive() -> BlendState{
+    BlendState{
+      color_src: BlendFactor::One,
+      color_dst: BlendFactor::One,
+      color_op: BlendOp::Add,
+      alpha_src: BlendFactor::One,
+      alpha_dst: BlendFactor::One,
+      alpha_op: BlendOp::Add
+    }
+  }
+}
+
+// fragments pass when the compare of their depth against the stored depth is true
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DepthState {
+  pub compare: CompareFunction,
+  pub write: bool,
+}
+
+impl Default for DepthState {
+  fn default() -> Self{
+    DepthState{compare: CompareFunction::Less, write: true}
+  }
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StencilOp {
+  Keep,
+  Zero,
+  Replace,
+  IncrementClamp,
+  DecrementClamp,
+  Invert,
+  IncrementWrap,
+  DecrementWrap
+}
+
+// the compare is of the reference against the stored value, both masked by the read mask
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct StencilFaceState {
+  pub compare: CompareFunction,
+  pub fail: StencilOp,
+  pub depth_fail: StencilOp,
+  pub pass: StencilOp,
+}
+
+impl Default for StencilFaceState {
+  fn default() -> Self{
+    StencilFaceState{compare: CompareFunction::Always, fail: StencilOp::Keep, depth_fail: StencilOp::Keep, pass: StencilOp::Keep}
+  }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct StencilState {
+  pub front: StencilFaceState,
+  pub back: StencilFaceState,
+  pub reference: u32,
+  pub read_mask: u32,
+  pub write_mask: u32,
+}
+
+impl Default for StencilState {
+  fn default() -> Self{
+    StencilState{
+      front: StencilFaceState::default(),
+      back: StencilFaceState::default(),
+      reference: 0,
+      read_mask: 0xff,
+      write_mask: 0xff
+    }
+  }
+}
+
+impl StencilState {
+  // the same test and operations for both faces
+  pub fn both(a_face: StencilFaceState, a_reference: u32) -> StencilState{
+    StencilState{front: a_face, back: a_face, reference: a_reference, ..StencilState::default()}
+  }
+}
+
+// front faces are counter clockwise in window coordinates, the same as gl
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CullMode {
+  None,
+  Front,
+  Back
+}
+
+// fixed function state used by draws. the default matches a new gl context, everything is off.
+// None disables blending, the depth test, the stencil test or the scissor test
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RenderState {
+  pub blend: Option<BlendState>,
+  pub depth: Option<DepthState>,
+  pub stencil: Option<StencilState>,
+  pub cull_mode: CullMode,
+  pub color_write: ColorWriteMask,
+  // position and size in pixels from the bottom left, the same as the viewport
+  pub scissor: Option<(IVec2, IVec2)>,
+}
+
+impl Default for RenderState {
+  fn default() -> Self{
+    RenderState{
+      blend: None,
+      depth: None,
+      stencil: None,
+      cull_mode: CullMode::None,
+      color_write: ColorWriteMask::ALL,
+      scissor: None
+    }
+  }
+}
+
+impl RenderState {
+  // sprites and ui drawn back to front
+  pub fn transparent() -> RenderState{
+    RenderState{blend: Some(BlendState::alpha()), ..RenderState::default()}
+  }
+
+  // depth tested with back faces culled
+  pub fn opaque() -> RenderState{
+    RenderState{depth: Some(DepthState::default()), cull_mode: CullMode::Back, ..RenderState::default()}
+  }
+}
 
 /*
 #[derive(Copy, Clone)]
@@ -183,6 +978,56 @@ bitflags! {
   }
 }
 
+bitflags! {
+  #[allow(dead_code)]
+  #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+  pub struct ColorWriteMask: u32 {
+    const NONE = 0;
+    const RED = 1 << 0;
+    const GREEN = 1 << 1;
+    const BLUE = 1 << 2;
+    const ALPHA = 1 << 3;
+    const ALL = 0xf;
+  }
+}
+
+bitflags! {
+  // what memory written by compute shaders is about to be used for
+  #[allow(dead_code)]
+  #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+  pub struct BarrierType: u32 {
+    const NONE = 0;
+    const VERTEX_ATTRIBUTE = 1 << 0;
+    const INDEX = 1 << 1;
+    const UNIFORM = 1 << 2;
+    const TEXTURE_FETCH = 1 << 3;
+    const STORAGE_IMAGE = 1 << 4;
+    const STORAGE_BUFFER = 1 << 5;
+    const BUFFER_UPDATE = 1 << 6;
+    const TEXTURE_UPDATE = 1 << 7;
+    const FRAMEBUFFER = 1 << 8;
+    const ALL = 0x1ff;
+  }
+}
+
+// memory layout of uniform blocks and storage buffers
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BlockLayout {
+  // arrays and matrix columns are aligned to 16 bytes, needed by uniform blocks
+  Std140,
+  // only vec3 columns are padded, used by storage buffers and push constants
+  Std430
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StorageAccess {
+  ReadOnly,
+  WriteOnly,
+  ReadWrite
+}
+
 impl std::error::Error for RendererError {}
 
 impl fmt::Display for RendererError {
@@ -191,7 +1036,9 @@ impl fmt::Display for RendererError {
       RendererError::Error => write!(f, "Error"),
       RendererError::InvalidCast => write!(f, "Error InvalidCast"),
       RendererError::InvalidVersion => write!(f, "Error InvalidVersion"),
-      RendererError::ShaderCompile => write!(f, "Error ShaderCompile"),
+      RendererError::ShaderCompile(res) => write!(f, "Error ShaderCompile\n{}", res.log),
+      RendererError::ShaderLink(res) => write!(f, "Error ShaderLink\n{}", res.log),
+      RendererError::InvalidUniform(res) => write!(f, "Error InvalidUniform {}", res),
       RendererError::UnsupportedAPI => write!(f, "Error UnsupportedAPI"),
       RendererError::Unimplemented => write!(f, "Error Unimplemented"),
     }
@@ -207,6 +1054,7 @@ impl fmt::Display for RendererType {
       RendererType::Vulkan => write!(f, "Vulkan"),
       RendererType::Metal => write!(f, "Metal"),
       RendererType::WebGpu => write!(f, "WebGpu"),
+      RendererType::Software => write!(f, "Software"),
     }
   }
 }
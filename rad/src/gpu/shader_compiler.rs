@@ -0,0 +1,287 @@
+extern crate naga;
+
+use crate::gpu::renderer_types::*;
+use crate::gpu::shader_log::*;
+use crate::gpu::shader_text::*;
+
+// the register space of the root constants push constant blocks become in hlsl, sets use the space of their number
+pub const HLSL_PUSH_CONSTANT_SPACE: u8 = 16;
+
+// the push constant block is renamed to these before writing glsl so its declaration and accesses can be found
+const PUSH_CONSTANT_NAME: &str = "rad_push_constants";
+const PUSH_CONSTANT_TYPE_NAME: &str = "RadPushConstants";
+
+// shaders are written once in vulkan glsl, with push constants and separate textures and samplers, and
+// written by naga in the language of each renderer. only vertex, fragment and compute shaders can be compiled
+pub struct ParsedShader {
+  pub shader_type: ShaderType,
+  pub module: naga::Module,
+  pub info: naga::valid::ModuleInfo,
+}
+
+#[allow(dead_code)]
+impl ParsedShader {
+  // parse and validation errors are ShaderCompile
+  pub fn parse(a_shader_type: ShaderType, a_source: &str) -> Result<ParsedShader, RendererError> {
+    let stage = match get_naga_stage(a_shader_type) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let mut frontend = naga::front::glsl::Frontend::default();
+    let module = match frontend.parse(&naga::front::glsl::Options::from(stage), a_source) {
+      Ok(res) => res,
+      Err(res) => return Err(RendererError::ShaderCompile(get_parse_log(a_shader_type, a_source, &res)))
+    };
+
+    match validate(a_shader_type, &module) {
+      Ok(res) => Ok(ParsedShader{shader_type: a_shader_type, module: module, info: res}),
+      Err(res) => Err(res)
+    }
+  }
+
+  // y is left unflipped, the renderers store images bottom row first the same as gl
+  pub fn to_spirv(&self) -> Result<Vec<u32>, RendererError> {
+    let options = naga::back::spv::Options{
+      lang_version: (1, 0),
+      flags: naga::back::spv::Options::default().flags - naga::back::spv::WriterFlags::ADJUST_COORDINATE_SPACE,
+      ..Default::default()
+    };
+
+    match naga::back::spv::write_vec(&self.module, &self.info, &options, None) {
+      Ok(res) => Ok(res),
+      Err(res) => Err(RendererError::ShaderCompile(ShaderLog::from_message(Some(self.shader_type), &res.to_string())))
+    }
+  }
+
+  // glsl for a gl or gles context of the version. the members of push constant blocks become uniforms and
+  // combined samplers are named after their texture so both are set by the names used in the source.
+  // needs gl 3.3 or gles 3.0, newer versions than naga writes use the newest it does. members that can't
+  // be uniforms, such as structs, are ShaderCompile
+  pub fn to_glsl(&self, a_gles: bool, a_version: (i32, i32)) -> Result<String, RendererError> {
+    let version = match get_glsl_version(a_gles, a_version) {
+      Some(res) => res,
+      None => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let options = naga::back::glsl::Options{
+      version: version,
+      writer_flags: naga::back::glsl::WriterFlags::empty(),
+      ..Default::default()
+    };
+
+    let pipeline_options = naga::back::glsl::PipelineOptions{
+      shader_stage: self.module.entry_points[0].stage,
+      entry_point: self.module.entry_points[0].name.clone(),
+      multiview: None
+    };
+
+    let (module, members) = match get_push_constant_uniforms(&self.module) {
+      Ok(res) => res,
+      Err(res) => return Err(RendererError::ShaderCompile(ShaderLog::from_message(Some(self.shader_type), &res)))
+    };
+
+    let mut source = String::new();
+    let reflection = match naga::back::glsl::Writer::new(&mut source, &module, &self.info, &options, &pipeline_options, naga::proc::BoundsCheckPolicies::default()) {
+      Ok(mut res) => res.write(),
+      Err(res) => Err(res)
+    };
+
+    let reflection = match reflection {
+      Ok(res) => res,
+      Err(res) => return Err(RendererError::ShaderCompile(ShaderLog::from_message(Some(self.shader_type), &res.to_string())))
+    };
+
+    // a texture sampled with more than one sampler keeps the generated names
+    for (name, mapping) in reflection.texture_mapping.iter() {
+      let texture_name = match &self.module.global_variables[mapping.texture].name {
+        Some(res) => res,
+        None => continue
+      };
+
+      if reflection.texture_mapping.values().filter(|other| other.texture == mapping.texture).count() == 1 {
+        source = source.lines().map(|line| replace_word(line, name, texture_name) + "\n").collect();
+      }
+    }
+
+    match members.is_empty() {
+      true => Ok(source),
+      false => match flatten_push_constants(&source, &members) {
+        Some(res) => Ok(res),
+        None => Err(RendererError::ShaderCompile(ShaderLog::from_message(Some(self.shader_type), "push constant block not written as a uniform struct")))
+      }
+    }
+  }
+
+  // hlsl for shader model 5.1. resources use the register of their binding in the space of their set
+  pub fn to_hlsl(&self) -> Result<String, RendererError> {
+    let options = naga::back::hlsl::Options{
+      shader_model: naga::back::hlsl::ShaderModel::V5_1,
+      push_constants_target: Some(naga::back::hlsl::BindTarget{space: HLSL_PUSH_CONSTANT_SPACE, register: 0, binding_array_size: None}),
+      ..Default::default()
+    };
+
+    let mut source = String::new();
+    let mut writer = naga::back::hlsl::Writer::new(&mut source, &options);
+
+    match writer.write(&self.module, &self.info) {
+      Ok(_res) => Ok(source),
+      Err(res) => Err(RendererError::ShaderCompile(ShaderLog::from_message(Some(self.shader_type), &res.to_string())))
+    }
+  }
+}
+
+pub fn get_naga_stage(a_shader_type: ShaderType) -> Result<naga::ShaderStage, RendererError> {
+  match a_shader_type {
+    ShaderType::Vertex => Ok(naga::ShaderStage::Vertex),
+    ShaderType::Fragment => Ok(naga::ShaderStage::Fragment),
+    ShaderType::Compute => Ok(naga::ShaderStage::Compute),
+    _ => Err(RendererError::UnsupportedAPI)
+  }
+}
+
+// validation errors only have spans with naga's span feature so they have no line
+fn validate(a_shader_type: ShaderType, a_module: &naga::Module) -> Result<naga::valid::ModuleInfo, RendererError> {
+  match naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::PUSH_CONSTANT).validate(a_module) {
+    Ok(res) => Ok(res),
+    Err(res) => Err(RendererError::ShaderCompile(ShaderLog::from_message(Some(a_shader_type), &res.to_string())))
+  }
+}
+
+fn get_parse_log(a_shader_type: ShaderType, a_source: &str, a_errors: &[naga::front::glsl::Error]) -> ShaderLog {
+  let mut log = ShaderLog{shader_type: Some(a_shader_type), log: String::new(), diagnostics: Vec::new()};
+
+  for error in a_errors {
+    let (line, column) = match error.meta.is_defined() {
+      true => {
+        let location = error.meta.location(a_source);
+        (Some(location.line_number), Some(location.line_position))
+      },
+      false => (None, None)
+    };
+
+    // formatted like the gl logs so both can be shown the same way
+    match line {
+      Some(res) => log.log.push_str(&format!("0:{}: error: {}\n", res, error.kind)),
+      None => log.log.push_str(&format!("error: {}\n", error.kind))
+    }
+
+    log.diagnostics.push(ShaderDiagnostic{severity: ShaderDiagnosticSeverity::Error, file: None, line: line, column: column, message: error.kind.to_string()});
+  }
+
+  log
+}
+
+fn get_glsl_version(a_gles: bool, a_version: (i32, i32)) -> Option<naga::back::glsl::Version> {
+  let (major, minor) = a_version;
+  let version = std::cmp::max(major * 100 + minor * 10, 0) as u16;
+
+  match a_gles {
+    true if version >= 300 => Some(naga::back::glsl::Version::Embedded{
+      version: std::cmp::min(version, 320),
+      is_webgl: cfg!(target_arch = "wasm32")
+    }),
+    false if version >= 400 => Some(naga::back::glsl::Version::Desktop(std::cmp::min(version, 450))),
+    false if version >= 330 => Some(naga::back::glsl::Version::Desktop(330)),
+    _ => None
+  }
+}
+
+// a push constant member written as a uniform
+struct PushConstantUniform {
+  name: String,
+  glsl_type: String,
+  array: String
+}
+
+// the module with the push constant block and its members renamed, and the uniform of each member. naga
+// writes a push constant as a uniform of its struct type, and one push constant a stage
+fn get_push_constant_uniforms(a_module: &naga::Module) -> Result<(naga::Module, Vec<PushConstantUniform>), String> {
+  let mut module = a_module.clone();
+
+  let (handle, variable) = match a_module.global_variables.iter().find(|(_handle, variable)| variable.space == naga::AddressSpace::PushConstant) {
+    Some(res) => res,
+    None => return Ok((module, Vec::new()))
+  };
+
+  let mut block_type = module.types[variable.ty].clone();
+  let members = match &mut block_type.inner {
+    naga::TypeInner::Struct{members, ..} => members,
+    _ => return Err(format!("push constant {} is not a block", variable.name.clone().unwrap_or_default()))
+  };
+
+  let mut uniforms = Vec::new();
+  for (index, member) in members.iter_mut().enumerate() {
+    let name = member.name.clone().unwrap_or_default();
+    let (glsl_type, array) = match get_glsl_type(&module.types, member.ty) {
+      Some(res) => res,
+      None => return Err(format!("push constant member {} can't be a uniform", name))
+    };
+
+    uniforms.push(PushConstantUniform{name: name, glsl_type: glsl_type, array: array});
+    member.name = Some(format!("member_{}_value", index));
+  }
+
+  block_type.name = Some(String::from(PUSH_CONSTANT_TYPE_NAME));
+  module.types.replace(variable.ty, block_type);
+  module.global_variables[handle].name = Some(String::from(PUSH_CONSTANT_NAME));
+
+  Ok((module, uniforms))
+}
+
+// the glsl type of a uniform and the size of it if it's an array
+fn get_glsl_type(a_types: &naga::UniqueArena<naga::Type>, a_type: naga::Handle<naga::Type>) -> Option<(String, String)> {
+  // the name of the scalar and the prefix of its vectors and matrices
+  let scalar = |kind: naga::ScalarKind, width: naga::Bytes| match (kind, width) {
+    (naga::ScalarKind::Float, 4) => Some(("float", "")),
+    (naga::ScalarKind::Float, 8) => Some(("double", "d")),
+    (naga::ScalarKind::Sint, 4) => Some(("int", "i")),
+    (naga::ScalarKind::Uint, 4) => Some(("uint", "u")),
+    (naga::ScalarKind::Bool, _) => Some(("bool", "b")),
+    _ => None
+  };
+
+  match a_types[a_type].inner {
+    naga::TypeInner::Scalar{kind, width} => scalar(kind, width).map(|(name, _prefix)| (String::from(name), String::new())),
+    naga::TypeInner::Vector{size, kind, width} => scalar(kind, width).map(|(_name, prefix)| (format!("{}vec{}", prefix, size as u8), String::new())),
+    naga::TypeInner::Matrix{columns, rows, width} => scalar(naga::ScalarKind::Float, width).map(|(_name, prefix)| (format!("{}mat{}x{}", prefix, columns as u8, rows as u8), String::new())),
+    naga::TypeInner::Array{base, size: naga::ArraySize::Constant(size), ..} => match get_glsl_type(a_types, base) {
+      Some((res, array)) if array.is_empty() => Some((res, format!("[{}]", size))),
+      _ => None
+    },
+    _ => None
+  }
+}
+
+// the declaration of the renamed block becomes a uniform for each member named as in the source, accesses
+// of a member become the member's uniform and loads of the whole block construct the struct from them.
+// None if naga didn't write the block as expected
+fn flatten_push_constants(a_source: &str, a_members: &[PushConstantUniform]) -> Option<String> {
+  let declaration = format!("uniform {} {};", PUSH_CONSTANT_TYPE_NAME, PUSH_CONSTANT_NAME);
+  if a_source.lines().filter(|line| line.trim() == declaration).count() != 1 {
+    return None
+  }
+
+  let names: Vec<&str> = a_members.iter().map(|member| member.name.as_str()).collect();
+  let constructor = format!("{}({})", PUSH_CONSTANT_TYPE_NAME, names.join(", "));
+  let mut source = String::new();
+
+  for line in a_source.lines() {
+    if line.trim() == declaration {
+      for member in a_members {
+        source.push_str(&format!("uniform {} {}{};\n", member.glsl_type, member.name, member.array));
+      }
+      continue;
+    }
+
+    let mut line = String::from(line);
+    for (index, member) in a_members.iter().enumerate() {
+      line = replace_member(&line, PUSH_CONSTANT_NAME, &format!("member_{}_value", index), &member.name);
+    }
+
+    source.push_str(&replace_word(&line, PUSH_CONSTANT_NAME, &constructor));
+    source.push('\n');
+  }
+
+  Some(source)
+}
@@ -0,0 +1,142 @@
+use crate::gpu::renderer_types::*;
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ShaderDiagnosticSeverity {
+  Error,
+  Warning
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShaderDiagnostic {
+  pub severity: ShaderDiagnosticSeverity,
+  // the include the line is in, none for the source given to load_shader
+  pub file: Option<String>,
+  // 1 based line of the source given to load_shader. none when the log has no line or it is in lines the renderer prepended
+  pub line: Option<u32>,
+  pub column: Option<u32>,
+  pub message: String,
+}
+
+// what the compiler or linker reported for a failed shader or program
+#[derive(Clone, Debug)]
+pub struct ShaderLog {
+  // none when linking a program fails
+  pub shader_type: Option<ShaderType>,
+  pub log: String,
+  pub diagnostics: Vec<ShaderDiagnostic>,
+}
+
+#[allow(dead_code)]
+impl ShaderLog {
+  // parses a gl info log. lines are moved up by the lines prepended to the source before it was compiled
+  pub fn from_info_log(a_shader_type: Option<ShaderType>, a_log: &str, a_prepended_lines: u32) -> ShaderLog {
+    let mut diagnostics = Vec::new();
+
+    for line in a_log.lines() {
+      if let Some(mut diagnostic) = parse_info_log_line(line.trim()) {
+        diagnostic.line = match diagnostic.line {
+          Some(res) if res > a_prepended_lines => Some(res - a_prepended_lines),
+          _ => None
+        };
+        diagnostics.push(diagnostic);
+      }
+    }
+
+    ShaderLog{shader_type: a_shader_type, log: String::from(a_log.trim_end_matches(['\0', ' ', '\n'])), diagnostics: diagnostics}
+  }
+
+  // a single error without a line, for failures the compiler didn't describe
+  pub fn from_message(a_shader_type: Option<ShaderType>, a_message: &str) -> ShaderLog {
+    let diagnostic = ShaderDiagnostic{severity: ShaderDiagnosticSeverity::Error, file: None, line: None, column: None, message: String::from(a_message)};
+    ShaderLog{shader_type: a_shader_type, log: String::from(a_message), diagnostics: vec![diagnostic]}
+  }
+
+  pub fn get_errors(&self) -> impl Iterator<Item = &ShaderDiagnostic> {
+    self.diagnostics.iter().filter(|diagnostic| diagnostic.severity == ShaderDiagnosticSeverity::Error)
+  }
+}
+
+// drivers disagree on the format, this covers mesa 0:12(5): error: ..., nvidia 0(12) : error C0000: ...
+// and the ERROR: 0:12: ... of amd, intel, apple and angle
+fn parse_info_log_line(a_line: &str) -> Option<ShaderDiagnostic> {
+  let mut severity = None;
+  let mut rest = a_line;
+
+  for (prefix, prefix_severity) in [("ERROR:", ShaderDiagnosticSeverity::Error), ("WARNING:", ShaderDiagnosticSeverity::Warning)] {
+    if let Some(res) = rest.strip_prefix(prefix) {
+      severity = Some(prefix_severity);
+      rest = res.trim_start();
+    }
+  }
+
+  let (line, column) = match parse_location(rest) {
+    Some((line, column, res)) => {
+      rest = res.trim_start_matches([' ', ':']);
+      (Some(line), column)
+    },
+    None => (None, None)
+  };
+
+  if severity.is_none() {
+    let lower = rest.to_lowercase();
+    if lower.starts_with("error") {
+      severity = Some(ShaderDiagnosticSeverity::Error);
+    }
+    else if lower.starts_with("warning") {
+      severity = Some(ShaderDiagnosticSeverity::Warning);
+    }
+    else {
+      return None
+    }
+
+    // skips the word and any code after it such as C1008
+    rest = match rest.split_once(':') {
+      Some((_severity, res)) => res.trim_start(),
+      None => ""
+    };
+  }
+
+  // the summary some drivers end the log with
+  if line.is_none() && rest.contains("compilation errors") {
+    return None
+  }
+
+  match severity {
+    Some(res) => Some(ShaderDiagnostic{severity: res, file: None, line: line, column: column, message: String::from(rest)}),
+    None => None
+  }
+}
+
+// the source string number followed by the line and maybe a column, as 0:12(5), 0(12) or 0:12
+fn parse_location(a_text: &str) -> Option<(u32, Option<u32>, &str)> {
+  let rest = match parse_number(a_text) {
+    Some((_string, res)) => res,
+    None => return None
+  };
+
+  if let Some(res) = rest.strip_prefix('(') {
+    return match parse_number(res) {
+      Some((line, res)) => res.strip_prefix(')').map(|res| (line, None, res)),
+      None => None
+    }
+  }
+
+  let (line, rest) = match rest.strip_prefix(':').and_then(parse_number) {
+    Some(res) => res,
+    None => return None
+  };
+
+  match rest.strip_prefix('(').and_then(parse_number) {
+    Some((column, res)) => res.strip_prefix(')').map(|res| (line, Some(column), res)),
+    None => Some((line, None, rest))
+  }
+}
+
+fn parse_number(a_text: &str) -> Option<(u32, &str)> {
+  let end = a_text.find(|c: char| !c.is_ascii_digit()).unwrap_or(a_text.len());
+  match a_text[..end].parse::<u32>() {
+    Ok(res) => Some((res, &a_text[end..])),
+    Err(_res) => None
+  }
+}
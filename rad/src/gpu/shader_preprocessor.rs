@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::gpu::renderer::*;
+use crate::gpu::renderer_types::*;
+use crate::gpu::shader_log::*;
+
+// a shader file with the defines it is compiled with, the key sources are cached by
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ShaderPermutation{
+  path: String,
+  // sorted by name so the order they are added in doesn't make another permutation
+  defines: Vec<(String, String)>
+}
+
+#[allow(dead_code)]
+impl ShaderPermutation{
+  pub fn new(a_path: &str) -> ShaderPermutation{
+    ShaderPermutation{path: String::from(a_path), defines: Vec::new()}
+  }
+
+  // an empty value defines the name without one, a define with the same name is replaced
+  pub fn define(mut self, a_name: &str, a_value: &str) -> ShaderPermutation{
+    self.defines.retain(|(name, _value)| name != a_name);
+    self.defines.push((String::from(a_name), String::from(a_value)));
+    self.defines.sort();
+    self
+  }
+
+  pub fn get_path(&self) -> &str{
+    &self.path
+  }
+
+  pub fn get_defines(&self) -> &Vec<(String, String)>{
+    &self.defines
+  }
+}
+
+// the expanded source and the file and line every line came from
+pub struct ShaderSource{
+  pub source: String,
+  // none for the injected defines
+  locations: Vec<Option<(String, u32)>>,
+  // every file read expanding the source, the shader first
+  files: Vec<String>
+}
+
+#[allow(dead_code)]
+impl ShaderSource{
+  // file and line of a 1 based line of the source
+  pub fn get_location(&self, a_line: u32) -> Option<(&str, u32)>{
+    match self.locations.get((a_line as usize).wrapping_sub(1)) {
+      Some(Some((file, line))) => Some((file.as_str(), *line)),
+      _ => None
+    }
+  }
+
+  pub fn get_files(&self) -> &Vec<String>{
+    &self.files
+  }
+
+  // points the diagnostics of a log of the expanded source at the files they came from
+  pub fn map_log(&self, a_log: &mut ShaderLog){
+    for diagnostic in a_log.diagnostics.iter_mut() {
+      let location = match diagnostic.line {
+        Some(res) => self.get_location(res),
+        None => None
+      };
+
+      match location {
+        Some((file, line)) => {
+          diagnostic.file = Some(String::from(file));
+          diagnostic.line = Some(line);
+        },
+        None => diagnostic.line = None
+      };
+    }
+  }
+}
+
+// expands #include "file" relative to the root and injects defines after the #version line. every file is
+// included once so include guards aren't needed. includes are expanded even inside #if blocks, the rest of
+// the preprocessing is left to the shader compiler
+pub struct ShaderPreprocessor{
+  root: String,
+  defines: Vec<(String, String)>,
+  sources: HashMap<ShaderPermutation, Rc<ShaderSource>>
+}
+
+#[allow(dead_code)]
+impl ShaderPreprocessor{
+  pub fn new(a_root: &str) -> ShaderPreprocessor{
+    ShaderPreprocessor{root: String::from(a_root), defines: Vec::new(), sources: HashMap::new()}
+  }
+
+  pub fn get_root(&self) -> &str{
+    &self.root
+  }
+
+  // defined in every shader before the permutation's defines. sources expanded already are dropped
+  pub fn add_define(&mut self, a_name: &str, a_value: &str){
+    self.defines.retain(|(name, _value)| name != a_name);
+    self.defines.push((String::from(a_name), String::from(a_value)));
+    self.sources.clear();
+  }
+
+  // drops every expanded source so files are read again
+  pub fn clear_cache(&mut self){
+    self.sources.clear();
+  }
+
+  pub fn get_source(&mut self, a_permutation: &ShaderPermutation) -> Result<Rc<ShaderSource>, RendererError>{
+    if let Some(res) = self.sources.get(a_permutation) {
+      return Ok(res.clone())
+    }
+
+    let mut lines = Vec::new();
+    let mut included = vec![a_permutation.path.clone()];
+
+    match self.expand(&a_permutation.path, None, &mut lines, &mut included) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    // the version has to come first
+    let index = match lines.iter().position(|(_location, line)| line.trim_start().starts_with("#version")) {
+      Some(res) => res + 1,
+      None => 0
+    };
+
+    let defines = self.defines.iter().chain(a_permutation.defines.iter())
+      .map(|(name, value)| (None, format!("#define {} {}", name, value).trim_end().to_owned()));
+    lines.splice(index..index, defines);
+
+    let mut source = ShaderSource{source: String::new(), locations: Vec::new(), files: included};
+    for (location, line) in lines {
+      source.source.push_str(&line);
+      source.source.push('\n');
+      source.locations.push(location);
+    }
+
+    let source = Rc::new(source);
+    self.sources.insert(a_permutation.clone(), source.clone());
+
+    Ok(source)
+  }
+
+  // compile errors have their lines mapped to the files they are in
+  pub fn load_shader(&mut self, a_renderer: &mut dyn Renderer, a_shader_type: ShaderType, a_permutation: &ShaderPermutation) -> Result<Box<dyn Shader>, RendererError>{
+    let source = match self.get_source(a_permutation) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    match a_renderer.load_shader(a_shader_type, &source.source) {
+      Err(RendererError::ShaderCompile(mut res)) => {
+        source.map_log(&mut res);
+        Err(RendererError::ShaderCompile(res))
+      },
+      res => res
+    }
+  }
+
+  // appends the lines of the file with their locations
+  fn expand(&self, a_path: &str, a_from: Option<(&str, u32)>, a_lines: &mut Vec<(Option<(String, u32)>, String)>, a_included: &mut Vec<String>) -> Result<(), RendererError>{
+    let text = match crate::core::filesystem::filesystem::read_text_file_immediate(&(self.root.clone() + a_path)) {
+      Ok(res) => res,
+      Err(_res) => {
+        let mut log = ShaderLog::from_message(None, &format!("unable to read {}", a_path));
+        if let Some((file, line)) = a_from {
+          log.diagnostics[0].file = Some(String::from(file));
+          log.diagnostics[0].line = Some(line);
+        }
+        return Err(RendererError::ShaderCompile(log))
+      }
+    };
+
+    for (index, line) in text.lines().enumerate() {
+      let include = match parse_include(line) {
+        Some(res) => res,
+        None => {
+          a_lines.push((Some((String::from(a_path), index as u32 + 1)), String::from(line)));
+          continue;
+        }
+      };
+
+      if a_included.iter().any(|path| path == include) {
+        continue;
+      }
+
+      a_included.push(String::from(include));
+
+      match self.expand(include, Some((a_path, index as u32 + 1)), a_lines, a_included) {
+        Ok(res) => res,
+        Err(res) => return Err(res)
+      };
+    }
+
+    Ok(())
+  }
+}
+
+// the path of #include "path" or #include <path>
+fn parse_include(a_line: &str) -> Option<&str>{
+  let rest = match a_line.trim().strip_prefix("#include") {
+    Some(res) => res.trim(),
+    None => return None
+  };
+
+  match (rest.chars().next(), rest.chars().last()) {
+    (Some('"'), Some('"')) | (Some('<'), Some('>')) if rest.len() > 2 => Some(&rest[1..rest.len() - 1]),
+    _ => None
+  }
+}
@@ -0,0 +1,185 @@
+use std::io::Cursor;
+use murmur3::murmur3_32;
+
+use crate::gpu::renderer::*;
+use crate::gpu::renderer_types::*;
+use crate::gpu::material::*;
+use crate::gpu::shader_preprocessor::*;
+
+// given with a program loaded through the reloader, then with the material the program is in
+pub type ShaderReloadId = usize;
+
+// a program that changed but failed to compile, the material keeps the program it had
+pub struct ShaderReloadError{
+  pub id: ShaderReloadId,
+  pub error: RendererError
+}
+
+struct WatchedProgram{
+  stages: Vec<(ShaderType, ShaderPermutation)>,
+  // every file the stages were expanded from and a hash of it, none when it couldn't be read
+  files: Vec<(String, Option<u32>)>
+}
+
+// recompiles programs loaded through it when the files they were expanded from change and swaps them into
+// their materials. files are read again through core::filesystem and compared by contents so packed assets
+// work the same as files on disk. every file is read when checking, so update every second or so rather
+// than every frame
+pub struct ShaderReloader{
+  preprocessor: ShaderPreprocessor,
+  programs: Vec<WatchedProgram>
+}
+
+#[allow(dead_code)]
+impl ShaderReloader{
+  pub fn new(a_preprocessor: ShaderPreprocessor) -> ShaderReloader{
+    ShaderReloader{preprocessor: a_preprocessor, programs: Vec::new()}
+  }
+
+  pub fn get_preprocessor(&mut self) -> &mut ShaderPreprocessor{
+    &mut self.preprocessor
+  }
+
+  // links the stages expanded by the preprocessor, a program that fails to load isn't watched
+  pub fn load_program(&mut self, a_renderer: &mut dyn Renderer, a_stages: &[(ShaderType, ShaderPermutation)]) -> Result<(ShaderReloadId, Box<dyn Program>), RendererError>{
+    let mut watched = WatchedProgram{stages: a_stages.to_vec(), files: Vec::new()};
+
+    match compile(&mut self.preprocessor, a_renderer, &mut watched) {
+      Ok(res) => {
+        self.programs.push(watched);
+        Ok((self.programs.len() - 1, res))
+      },
+      Err(res) => Err(res)
+    }
+  }
+
+  // recompiles the programs of the materials given whose files changed, once for each program. failures are
+  // returned once for each program, until the files change again. materials sharing a program each get their
+  // own, linked from the same stages, and are only swapped once every one has linked so they all keep the
+  // same program. call between frames, replaced programs are dropped
+  pub fn update(&mut self, a_renderer: &mut dyn Renderer, a_materials: &mut [(ShaderReloadId, &mut dyn Material)]) -> Vec<ShaderReloadError>{
+    let mut changed: Vec<ShaderReloadId> = Vec::new();
+
+    for (id, _material) in a_materials.iter() {
+      if changed.contains(id) {
+        continue;
+      }
+
+      let watched = match self.programs.get(*id) {
+        Some(res) => res,
+        None => continue
+      };
+
+      let root = self.preprocessor.get_root();
+      if watched.files.iter().any(|(path, hash)| hash_file(&(root.to_owned() + path)) != *hash) {
+        changed.push(*id);
+      }
+    }
+
+    let mut errors = Vec::new();
+
+    if changed.is_empty() {
+      return errors
+    }
+
+    // cached includes could be the files that changed
+    self.preprocessor.clear_cache();
+
+    for id in changed {
+      let program = match compile(&mut self.preprocessor, a_renderer, &mut self.programs[id]) {
+        Ok(res) => res,
+        Err(res) => {
+          errors.push(ShaderReloadError{id: id, error: res});
+          continue;
+        }
+      };
+
+      // the stages compiled so the rest only fail if linking does
+      let count = a_materials.iter().filter(|(material_id, _material)| *material_id == id).count();
+      let mut programs = vec![program];
+
+      for _index in 1..count {
+        match link(&mut self.preprocessor, a_renderer, &self.programs[id].stages) {
+          Ok((res, _files)) => programs.push(res),
+          Err(res) => {
+            errors.push(ShaderReloadError{id: id, error: res});
+            break;
+          }
+        };
+      }
+
+      if programs.len() < count {
+        continue;
+      }
+
+      let materials = a_materials.iter_mut().filter(|(material_id, _material)| *material_id == id);
+      for ((_id, material), program) in materials.zip(programs) {
+        material.set_program(program);
+      }
+    }
+
+    errors
+  }
+}
+
+// the files are hashed before compiling so a failure isn't compiled again until they change
+fn compile(a_preprocessor: &mut ShaderPreprocessor, a_renderer: &mut dyn Renderer, a_watched: &mut WatchedProgram) -> Result<Box<dyn Program>, RendererError>{
+  let root = a_preprocessor.get_root().to_owned();
+
+  for (path, hash) in a_watched.files.iter_mut() {
+    *hash = hash_file(&(root.clone() + path));
+  }
+
+  let (program, files) = match link(a_preprocessor, a_renderer, &a_watched.stages) {
+    Ok(res) => res,
+    Err(res) => return Err(res)
+  };
+
+  // includes added since the last compile are watched too
+  for file in files {
+    if !a_watched.files.iter().any(|(path, _hash)| *path == file) {
+      let hash = hash_file(&(root.clone() + &file));
+      a_watched.files.push((file, hash));
+    }
+  }
+
+  Ok(program)
+}
+
+// the program of the stages and every file they were expanded from
+fn link(a_preprocessor: &mut ShaderPreprocessor, a_renderer: &mut dyn Renderer, a_stages: &[(ShaderType, ShaderPermutation)]) -> Result<(Box<dyn Program>, Vec<String>), RendererError>{
+  let mut builder = ProgramBuilder::new();
+  let mut files: Vec<String> = Vec::new();
+
+  for (shader_type, permutation) in a_stages {
+    let source = match a_preprocessor.get_source(permutation) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    for file in source.get_files() {
+      if !files.contains(file) {
+        files.push(file.clone());
+      }
+    }
+
+    let shader = match a_preprocessor.load_shader(a_renderer, *shader_type, permutation) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    builder = builder.stage(shader);
+  }
+
+  match a_renderer.load_program(builder) {
+    Ok(res) => Ok((res, files)),
+    Err(res) => Err(res)
+  }
+}
+
+fn hash_file(a_path: &str) -> Option<u32>{
+  match crate::core::filesystem::filesystem::read_file_immediate::<u8>(a_path) {
+    Ok(res) => murmur3_32(&mut Cursor::new(res), 0).ok(),
+    Err(_res) => None
+  }
+}
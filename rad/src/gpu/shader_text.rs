@@ -0,0 +1,35 @@
+// edits of glsl source that keep identifiers whole, used by the translation and the compiler
+
+// replaces the word where it isn't part of a longer identifier
+pub fn replace_word(a_line: &str, a_word: &str, a_with: &str) -> String {
+  a_line.split_inclusive(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+    .map(|part| match part.strip_prefix(a_word) {
+      Some(rest) if rest.len() <= 1 && !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') => String::from(a_with) + rest,
+      _ => String::from(part)
+    })
+    .collect()
+}
+
+// replaces a_block.a_member where both are whole words
+pub fn replace_member(a_line: &str, a_block: &str, a_member: &str, a_with: &str) -> String {
+  let access = format!("{}.{}", a_block, a_member);
+  let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+  let mut result = String::new();
+  let mut rest = a_line;
+
+  while let Some(index) = rest.find(&access) {
+    let after = &rest[index + access.len()..];
+    result.push_str(&rest[..index]);
+
+    match rest[..index].ends_with(is_word) || after.starts_with(is_word) {
+      true => result.push_str(&access),
+      false => result.push_str(a_with)
+    }
+
+    rest = after;
+  }
+
+  result.push_str(rest);
+  result
+}
@@ -0,0 +1 @@
+pub mod renderer_software;
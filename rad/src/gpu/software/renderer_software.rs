@@ -0,0 +1,1564 @@
+use glam::*;
+
+use crate::gpu::renderer::*;
+use crate::gpu::renderer_types::*;
+use crate::gpu::material::*;
+use crate::gpu::camera::*;
+use crate::gpu::uniforms::*;
+use crate::gpu::image::*;
+use crate::gpu::compressed::*;
+use crate::gpu::shader_compiler::*;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+// vertices nearer the eye than this w are clipped so dividing by w stays finite
+const MIN_CLIP_W: f32 = 1.0e-5;
+
+// Cpu only renderer. Shaders are not executed, instead every program evaluates the basic material:
+// position = u_mvp * vec4(i_position, 0.0, 1.0) and color = texture(u_texture, i_uv)
+// Instances are read by semantic, a Transform multiplies the position and a Color multiplies the color.
+// Output matches the OpenGL renderer so renderer tests can run without a gpu
+
+// locations of the uniforms understood by the fixed function program
+const UNIFORM_LOCATION_MVP: i32 = 0;
+
+pub struct SamplerSoftware{
+  name: String,
+  texture: Rc<dyn Texture>,
+  desc: SamplerDesc,
+}
+
+impl Sampler for SamplerSoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn set_name(&mut self, a_name: &str){
+    self.name = String::from(a_name);
+  }
+
+  fn get_name(&self) -> &str{
+    &self.name
+  }
+}
+
+// uniform values are stored with the program the same as gl
+pub struct ProgramStateSoftware {
+  mvp: Cell<Mat4>,
+  reflection: ProgramReflection,
+}
+
+pub struct ProgramSoftware {
+  state: Rc<ProgramStateSoftware>,
+}
+
+impl Program for ProgramSoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_uniform(&self, a_name: &str, a_data: UniformData) -> Box<dyn Uniform>{
+    Box::new(UniformSoftware{
+      name: UniformName::new(a_name),
+      data: a_data,
+      id: get_uniform_location(a_name),
+      modified: true
+    })
+  }
+
+  fn get_reflection(&self) -> &ProgramReflection{
+    &self.state.reflection
+  }
+}
+
+#[allow(dead_code)]
+pub struct ShaderSoftware {
+  shader_type: ShaderType,
+}
+
+impl Shader for ShaderSoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_type(&self) -> ShaderType{
+    self.shader_type
+  }
+}
+
+pub struct VerticesSoftware {
+  data: Rc<Vec<u8>>,
+}
+
+impl Vertices for VerticesSoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+}
+
+pub struct InstancesSoftware {
+  layout: VertexBufferLayout,
+  data: RefCell<Vec<u8>>,
+}
+
+impl Instances for InstancesSoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_num_instances(&self) -> u32{
+    (self.data.borrow().len() / self.layout.stride as usize) as u32
+  }
+}
+
+pub struct IndicesSoftware {
+  data: Rc<Vec<u32>>,
+}
+
+impl Indices for IndicesSoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+}
+
+// attributes are decoded when the geometry is created, location 0 is the position and location 1 the uv
+pub struct GeometrySoftware {
+  positions: Vec<Vec4>,
+  uvs: Vec<Vec2>,
+  indices: Option<Rc<Vec<u32>>>,
+  num: usize,
+  topology: PrimitiveTopology,
+}
+
+impl Geometry for GeometrySoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_num_elements(&self) -> u32{
+    match &self.indices {
+      Some(res) => res.len() as u32,
+      None => self.num as u32
+    }
+  }
+
+  fn get_topology(&self) -> PrimitiveTopology{
+    self.topology
+  }
+
+  fn set_topology(&mut self, a_topology: PrimitiveTopology){
+    self.topology = a_topology;
+  }
+}
+
+pub struct TextureDataSoftware {
+  width: u32,
+  height: u32,
+  pixels: Vec<u8>,
+}
+
+pub struct TextureSoftware {
+  data: RefCell<TextureDataSoftware>,
+}
+
+impl Texture for TextureSoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+}
+
+// rows are stored bottom to top to match gl::ReadPixels. colors are rgba8 textures so a target can be sampled
+// directly. stencil values are 8 bit the same as the gl and vulkan depth stencil formats
+pub struct RenderTargetStateSoftware {
+  width: u32,
+  height: u32,
+  colors: Vec<Rc<TextureSoftware>>,
+  depth: RefCell<Vec<f32>>,
+  stencil: RefCell<Vec<i32>>,
+}
+
+impl RenderTargetStateSoftware {
+  pub fn new(a_width: u32, a_height: u32, a_num_colors: usize, a_depth_stencil: bool) -> RenderTargetStateSoftware{
+    let num_pixels = (a_width * a_height) as usize;
+    let num_depth = match a_depth_stencil {
+      true => num_pixels,
+      false => 0
+    };
+
+    RenderTargetStateSoftware{
+      width: a_width,
+      height: a_height,
+      colors: (0..a_num_colors).map(|_i| Rc::new(TextureSoftware{
+        data: RefCell::new(TextureDataSoftware{width: a_width, height: a_height, pixels: vec![0u8; num_pixels * 4]})
+      })).collect(),
+      depth: RefCell::new(vec![1.0; num_depth]),
+      stencil: RefCell::new(vec![0; num_depth])
+    }
+  }
+}
+
+pub struct RenderTargetSoftware {
+  state: Rc<RenderTargetStateSoftware>,
+}
+
+impl RenderTarget for RenderTargetSoftware {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_width(&self) -> u32{
+    self.state.width
+  }
+
+  fn get_height(&self) -> u32{
+    self.state.height
+  }
+
+  fn num_color_attachments(&self) -> usize{
+    self.state.colors.len()
+  }
+
+  fn get_color_texture(&self, a_index: usize) -> Rc<dyn Texture>{
+    self.state.colors[a_index].clone()
+  }
+
+  // depth is stored as floats which textures are unable to hold
+  fn get_depth_texture(&self) -> Option<Rc<dyn Texture>>{
+    None
+  }
+}
+
+#[allow(dead_code)]
+pub struct UniformSoftware {
+  name: UniformName,
+  data: UniformData,
+  id: i32,
+  modified: bool,
+}
+
+#[allow(dead_code)]
+impl Uniform for UniformSoftware {
+  fn any(&mut self) -> &mut dyn std::any::Any{
+    self
+  }
+
+  fn set_f32(&mut self, a: f32){
+    self.data.set::<f32>(a);
+    self.modified = true;
+  }
+
+  fn get_f32(&self) -> f32{
+    self.data.get::<f32>()
+  }
+
+  fn get_data(&self) -> &UniformData{
+    &self.data
+  }
+
+  fn get_data_mut(&mut self) -> &mut UniformData{
+    self.modified = true;
+    &mut self.data
+  }
+
+  fn get_name(&self) -> &str{
+    &self.name.get_name()
+  }
+
+  fn set_name(&mut self, a_name: &str){
+    self.name.set_name(a_name);
+    self.id = get_uniform_location(a_name);
+  }
+}
+
+#[allow(dead_code)]
+pub struct UniformShaderSoftware {
+  name: UniformName,
+  id: i32
+}
+
+impl UniformShader for UniformShaderSoftware {
+  fn any(&mut self) -> &mut dyn std::any::Any{
+    self
+  }
+}
+
+#[derive(Copy, Clone)]
+struct VertexSoftware {
+  position: Vec4,
+  uv: Vec2,
+}
+
+// what fragments of a draw read and write
+struct FragmentTargetSoftware<'a> {
+  texture: Option<&'a TextureDataSoftware>,
+  tint: Vec4,
+  color: &'a mut TextureDataSoftware,
+  depth: &'a mut [f32],
+  stencil: &'a mut [i32],
+}
+
+pub struct RendererSoftware {
+  default_target: Rc<RenderTargetStateSoftware>,
+  target: Rc<RenderTargetStateSoftware>,
+
+  clear_color: Vec4,
+  clear_depth: f32,
+  clear_stencil: i32,
+
+  viewport_pos: IVec2,
+  viewport_size: IVec2,
+
+  program: Option<Rc<ProgramStateSoftware>>,
+  texture: Option<Rc<dyn Texture>>,
+  sampler_desc: SamplerDesc,
+  render_state: RenderState,
+}
+
+#[allow(dead_code)]
+impl Renderer for RendererSoftware {
+  fn name(&self) -> String{
+    String::from("Software")
+  }
+
+  fn get_type(&self) -> RendererType{
+    RendererType::Software
+  }
+
+  fn begin_frame(&mut self, a_clear: RendererClearType){
+    self.clear(a_clear);
+  }
+
+  // nothing is presented, results are retrieved with read_render_buffer
+  fn end_frame(&mut self){}
+
+  //clear immediatly
+  fn clear(&mut self, a_clear: RendererClearType){
+    let target = self.target.clone();
+    self.clear_target(&target, a_clear);
+  }
+
+  // Get and set clear values may be called before BeginFrame
+  fn set_clear_color(&mut self, a_color: Vec4){
+    self.clear_color = a_color;
+  }
+
+  fn set_clear_depth(&mut self, a_depth: f32){
+    self.clear_depth = a_depth;
+  }
+
+  fn set_clear_stencil(&mut self, a_stencil: i32){
+    self.clear_stencil = a_stencil;
+  }
+
+  fn get_clear_color(&self) -> Vec4{
+    self.clear_color
+  }
+
+  fn get_clear_depth(&self) -> f32{
+    self.clear_depth
+  }
+
+  fn get_clear_stencil(&self) -> i32{
+    self.clear_stencil
+  }
+
+  fn set_viewport(&mut self, a_pos: IVec2, a_size: IVec2){
+    self.viewport_pos = a_pos;
+    self.viewport_size = a_size;
+  }
+
+  fn get_viewport_pos(&self) -> IVec2{
+    self.viewport_pos
+  }
+  fn get_viewport_size(&self) -> IVec2{
+    self.viewport_size
+  }
+
+  fn set_render_state(&mut self, a_state: &RenderState){
+    self.render_state = *a_state;
+  }
+
+  fn get_render_state(&self) -> &RenderState{
+    &self.render_state
+  }
+
+  // source is not compiled, all programs run the fixed function basic material
+  fn load_shader(&mut self, a_shader_type: ShaderType, _source: &str) -> Result<Box<dyn Shader>, RendererError>{
+    match a_shader_type {
+      ShaderType::Vertex | ShaderType::Fragment => Ok(Box::new(ShaderSoftware{shader_type: a_shader_type})),
+      _ => Err(RendererError::UnsupportedAPI)
+    }
+  }
+
+  // parsed only so invalid sources fail the same as on other renderers
+  fn load_shader_neutral(&mut self, a_shader_type: ShaderType, a_source: &str) -> Result<Box<dyn Shader>, RendererError>{
+    match ParsedShader::parse(a_shader_type, a_source) {
+      Ok(_) => self.load_shader(a_shader_type, a_source),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn load_program_vert_frag(&mut self, a_shader_vert: Box<dyn Shader>, a_shader_frag: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
+    let shader_vert = match a_shader_vert.any().downcast_ref::<ShaderSoftware>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    let shader_frag = match a_shader_frag.any().downcast_ref::<ShaderSoftware>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    if !matches!(shader_vert.shader_type, ShaderType::Vertex) || !matches!(shader_frag.shader_type, ShaderType::Fragment) {
+      return Err(RendererError::Error)
+    }
+
+    Ok(Box::new(ProgramSoftware{state: Rc::new(ProgramStateSoftware{mvp: Cell::new(Mat4::IDENTITY), reflection: reflect_program()})}))
+  }
+
+  // only vertex and fragment shaders can be loaded
+  fn load_program(&mut self, a_builder: ProgramBuilder) -> Result<Box<dyn Program>, RendererError>{
+    match a_builder.validate() {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    for shader in a_builder.get_shaders() {
+      if shader.any().downcast_ref::<ShaderSoftware>().is_none() {
+        return Err(RendererError::InvalidCast)
+      }
+    }
+
+    if !a_builder.has_stage(ShaderType::Fragment) {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    Ok(Box::new(ProgramSoftware{state: Rc::new(ProgramStateSoftware{mvp: Cell::new(Mat4::IDENTITY), reflection: reflect_program()})}))
+  }
+
+  fn set_patch_vertices(&mut self, _a_count: u32){
+
+  }
+
+  fn get_uniform(&mut self, _shader: &mut Box<dyn Program>, a_name: &str) -> Box<dyn UniformShader>{
+    Box::new(UniformShaderSoftware{
+      name: UniformName::new(a_name),
+      id: get_uniform_location(a_name)
+    })
+  }
+
+  // there is no shader execution so compute is unsupported
+  fn load_program_compute(&mut self, _a_shader: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
+    Err(RendererError::UnsupportedAPI)
+  }
+
+  fn dispatch(&mut self, _a_x: u32, _a_y: u32, _a_z: u32){
+
+  }
+
+  fn memory_barrier(&mut self, _a_barrier: BarrierType){
+
+  }
+
+  fn gen_buffer_storage(&mut self, _a_data: &[u8]) -> Result<Box<dyn StorageBuffer>, RendererError>{
+    Err(RendererError::UnsupportedAPI)
+  }
+
+  fn update_buffer_storage(&mut self, _a_buffer: &mut Box<dyn StorageBuffer>, _a_data: &[u8]){
+
+  }
+
+  fn read_buffer_storage(&mut self, _a_buffer: &Box<dyn StorageBuffer>) -> Vec<u8>{
+    Vec::new()
+  }
+
+  fn bind_buffer_storage(&mut self, _a_binding: u32, _a_buffer: &Box<dyn StorageBuffer>){
+
+  }
+
+  fn gen_texture_storage(&mut self, _a_width: u32, _a_height: u32, _a_format: TextureFormat) -> Result<Box<dyn Texture>, RendererError>{
+    Err(RendererError::UnsupportedAPI)
+  }
+
+  fn bind_image_storage(&mut self, _a_unit: u32, _a_texture: &dyn Texture, _a_access: StorageAccess, _a_format: TextureFormat){
+
+  }
+
+  fn gen_buffer_uniform(&mut self, _a_layout: &UniformBlockLayout, _a_block: &dyn UniformBlock) -> Result<Box<dyn UniformBuffer>, RendererError>{
+    Err(RendererError::UnsupportedAPI)
+  }
+
+  fn update_buffer_uniform(&mut self, _a_buffer: &mut Box<dyn UniformBuffer>, _a_block: &dyn UniformBlock) -> Result<(), RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn bind_buffer_uniform(&mut self, _a_binding: u32, _a_buffer: &Box<dyn UniformBuffer>){
+
+  }
+
+  fn bind_uniform_block(&mut self, _a_program: &Box<dyn Program>, _a_block_name: &str, _a_binding: u32, _a_layout: &UniformBlockLayout) -> Result<(), RendererError>{
+    Err(RendererError::UnsupportedAPI)
+  }
+
+  fn gen_buffer_vertex(&mut self, a_verts: &std::vec::Vec<f32>) -> Box<dyn Vertices>{
+    Box::new(VerticesSoftware{data: Rc::new(a_verts.iter().flat_map(|v| v.to_ne_bytes()).collect())})
+  }
+
+  fn gen_buffer_vertex_bytes(&mut self, a_data: &[u8]) -> Box<dyn Vertices>{
+    Box::new(VerticesSoftware{data: Rc::new(a_data.to_vec())})
+  }
+
+  fn gen_geometry(&mut self, a_buffer: &Box<dyn Vertices>) -> Box<dyn Geometry>{
+    match self.gen_geometry_layout(&[a_buffer], &VertexLayout::position_uv()) {
+      Ok(res) => res,
+      Err(_res) => panic!("Invalid vertex")
+    }
+  }
+
+  fn gen_geometry_layout(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout) -> Result<Box<dyn Geometry>, RendererError>{
+    match gen_vertices(a_buffers, a_layout, None) {
+      Ok(res) => Ok(Box::new(res)),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn gen_buffer_index(&mut self, a_indices: IndexData) -> Box<dyn Indices>{
+    let data = match a_indices {
+      IndexData::Uint16(res) => res.iter().map(|i| *i as u32).collect(),
+      IndexData::Uint32(res) => res.to_vec()
+    };
+
+    Box::new(IndicesSoftware{data: Rc::new(data)})
+  }
+
+  fn gen_geometry_indexed(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout, a_indices: &Box<dyn Indices>) -> Result<Box<dyn Geometry>, RendererError>{
+    let indices = match a_indices.any().downcast_ref::<IndicesSoftware>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    match gen_vertices(a_buffers, a_layout, Some(indices.data.clone())) {
+      Ok(res) => Ok(Box::new(res)),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn gen_instances(&mut self, a_layout: &VertexBufferLayout, a_data: &[u8]) -> Result<Box<dyn Instances>, RendererError>{
+    if a_layout.step != VertexStep::PerInstance || a_layout.stride == 0 {
+      return Err(RendererError::Error)
+    }
+
+    Ok(Box::new(InstancesSoftware{layout: a_layout.clone(), data: RefCell::new(a_data.to_vec())}))
+  }
+
+  fn update_instances(&mut self, a_instances: &mut Box<dyn Instances>, a_data: &[u8]){
+    let instances = match a_instances.any().downcast_ref::<InstancesSoftware>() {
+      Some(res) => res,
+      None => panic!("Invalid instances")
+    };
+
+    *instances.data.borrow_mut() = a_data.to_vec();
+  }
+
+  fn gen_mesh(&mut self, a_geometry: Box<dyn Geometry>, a_material: Box<dyn Material>) -> Box<Mesh>{
+    Box::new(Mesh{
+      geometry: a_geometry,
+      material: a_material
+      })
+  }
+
+  fn gen_buffer_texture(&mut self) -> Box<dyn Texture>{
+    Box::new(TextureSoftware{
+      data: RefCell::new(TextureDataSoftware{width: 0, height: 0, pixels: Vec::new()})
+    })
+  }
+
+  fn gen_sampler(&mut self, a_texture: Rc<dyn Texture>, a_desc: &SamplerDesc) -> Box<dyn Sampler>{
+    Box::new(SamplerSoftware{name: String::from(""), texture: a_texture, desc: *a_desc})
+  }
+
+  fn load_texture(&mut self, a_image: &image::DynamicImage, a_texture: &mut Box<dyn Texture>){
+    if let Err(res) = self.load_texture_format(a_image, TextureFormat::from_image(a_image), a_texture) {
+      eprintln!("Error {}", res);
+    }
+  }
+
+  // texels are stored as the rgba8 color sampled from the format
+  fn load_texture_format(&mut self, a_image: &image::DynamicImage, a_format: TextureFormat, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    self.load_texture_layers(std::slice::from_ref(a_image), TextureDimension::D2, a_format, a_texture)
+  }
+
+  // the fixed function program only samples 2d textures
+  fn load_texture_layers(&mut self, a_images: &[image::DynamicImage], a_dimension: TextureDimension, a_format: TextureFormat, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    let texture = match a_texture.any().downcast_ref::<TextureSoftware>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    if a_dimension != TextureDimension::D2 {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    match a_dimension.validate_images(a_images) {
+      Ok(_res) => {},
+      Err(res) => return Err(res)
+    };
+
+    let mut data = texture.data.borrow_mut();
+    data.width = a_images[0].width();
+    data.height = a_images[0].height();
+    data.pixels = a_format.get_texels(&a_images[0]).iter().flat_map(|texel| {
+      [to_unorm8(texel.x), to_unorm8(texel.y), to_unorm8(texel.z), to_unorm8(texel.w)]
+    }).collect();
+
+    Ok(())
+  }
+
+  // textures are always rgba8
+  fn load_texture_compressed(&mut self, a_image: &CompressedImage, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    let image = match a_image.decompress(0) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    self.load_texture_format(&image, a_image.get_decompressed_format(), a_texture)
+  }
+
+  fn is_compressed_format_supported(&self, _a_format: CompressedFormat, _a_srgb: bool) -> bool{
+    false
+  }
+
+  fn use_program(&mut self, a_program: &Box<dyn Program>){
+    let program = match a_program.any().downcast_ref::<ProgramSoftware>() {
+      Some(res) => res,
+      None => return
+    };
+
+    self.program = Some(program.state.clone());
+  }
+
+  fn draw_geometry(&mut self, a_geometry: &Box<dyn Geometry>){
+    let geometry = match a_geometry.any().downcast_ref::<GeometrySoftware>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.draw_primitives(geometry, DrawRange{first: 0, count: geometry.get_num_elements(), base_vertex: 0}, &[(Mat4::IDENTITY, Vec4::ONE)]);
+  }
+
+  fn draw_geometry_range(&mut self, a_geometry: &Box<dyn Geometry>, a_range: DrawRange){
+    let geometry = match a_geometry.any().downcast_ref::<GeometrySoftware>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.draw_primitives(geometry, a_range, &[(Mat4::IDENTITY, Vec4::ONE)]);
+  }
+
+  fn draw_mesh(&mut self, _camera: &Camera, a_mesh: &mut Box<Mesh>){
+    self.use_program(a_mesh.material.get_program());
+    self.set_render_state(a_mesh.material.get_render_state());
+
+    let num_uniforms = a_mesh.material.num_uniforms();
+    for i in 0..num_uniforms {
+      self.update_uniform(a_mesh.material.get_uniform(i));
+    }
+
+    let num_samplers = a_mesh.material.num_samplers();
+    for i in 0..num_samplers {
+      self.update_sampler(a_mesh.material.get_sampler(i));
+    }
+
+    let geometry = match a_mesh.geometry.any().downcast_ref::<GeometrySoftware>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.draw_primitives(geometry, DrawRange{first: 0, count: geometry.get_num_elements(), base_vertex: 0}, &[(Mat4::IDENTITY, Vec4::ONE)]);
+  }
+
+  fn draw_mesh_instanced(&mut self, _camera: &Camera, a_mesh: &mut Box<Mesh>, a_instances: &Box<dyn Instances>){
+    let instances = match a_instances.any().downcast_ref::<InstancesSoftware>() {
+      Some(res) => res,
+      None => panic!("Invalid instances")
+    };
+
+    let data = instances.data.borrow();
+    let mut transforms = Vec::new();
+    for i in 0..instances.get_num_instances() as usize {
+      match read_instance(&data, i * instances.layout.stride as usize, &instances.layout) {
+        Some(res) => transforms.push(res),
+        None => return
+      }
+    }
+
+    self.use_program(a_mesh.material.get_program());
+    self.set_render_state(a_mesh.material.get_render_state());
+
+    let num_uniforms = a_mesh.material.num_uniforms();
+    for i in 0..num_uniforms {
+      self.update_uniform(a_mesh.material.get_uniform(i));
+    }
+
+    let num_samplers = a_mesh.material.num_samplers();
+    for i in 0..num_samplers {
+      self.update_sampler(a_mesh.material.get_sampler(i));
+    }
+
+    let geometry = match a_mesh.geometry.any().downcast_ref::<GeometrySoftware>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.draw_primitives(geometry, DrawRange{first: 0, count: geometry.get_num_elements(), base_vertex: 0}, &transforms);
+  }
+
+  fn read_render_buffer(&mut self) -> Image {
+    let pixels = match self.target.colors.first() {
+      Some(res) => res.data.borrow().pixels.clone(),
+      None => vec![0u8; (self.target.width * self.target.height * 4) as usize]
+    };
+
+    Image{
+      width: self.target.width,
+      height: self.target.height,
+      pitch: self.target.width * 4,
+      pixels: pixels
+    }
+  }
+
+  fn gen_render_target(&mut self, a_desc: &RenderTargetDesc) -> Result<Box<dyn RenderTarget>, RendererError>{
+    if a_desc.width == 0 || a_desc.height == 0 {
+      return Err(RendererError::Error)
+    }
+
+    if a_desc.color_formats.iter().any(|format| *format != TextureFormat::RGBA8) {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    let depth_stencil = match a_desc.depth_stencil_format {
+      Some(res) if !res.is_depth() => return Err(RendererError::Error),
+      Some(_res) => true,
+      None => false
+    };
+
+    Ok(Box::new(RenderTargetSoftware{
+      state: Rc::new(RenderTargetStateSoftware::new(a_desc.width, a_desc.height, a_desc.color_formats.len(), depth_stencil))
+    }))
+  }
+
+  fn set_render_target(&mut self, a_target: Option<&Box<dyn RenderTarget>>){
+    self.target = match a_target {
+      Some(target) => match target.any().downcast_ref::<RenderTargetSoftware>() {
+        Some(res) => res.state.clone(),
+        None => panic!("Invalid render target")
+      },
+      None => self.default_target.clone()
+    };
+  }
+
+  fn clear_render_target(&mut self, a_target: &Box<dyn RenderTarget>, a_clear: RendererClearType){
+    let target = match a_target.any().downcast_ref::<RenderTargetSoftware>() {
+      Some(res) => res.state.clone(),
+      None => panic!("Invalid render target")
+    };
+
+    self.clear_target(&target, a_clear);
+  }
+}
+
+#[allow(dead_code)]
+impl RendererSoftware {
+  pub fn new(a_width: u32, a_height: u32) -> Result<Self, RendererError>{
+    if a_width == 0 || a_height == 0 {
+      return Err(RendererError::Error)
+    }
+
+    let target = Rc::new(RenderTargetStateSoftware::new(a_width, a_height, 1, true));
+
+    Ok(Self {
+      default_target: target.clone(),
+      target: target,
+      clear_color: Vec4::new(0.0, 0.0, 0.0, 0.0),
+      clear_depth: 1.0,
+      clear_stencil: 0,
+      viewport_pos: IVec2::new(0, 0),
+      viewport_size: IVec2::new(a_width as i32, a_height as i32),
+      program: None,
+      texture: None,
+      sampler_desc: SamplerDesc::default(),
+      render_state: RenderState::default()
+    })
+  }
+
+  pub fn update_uniform(&self, a_uniform: &mut Box<dyn Uniform>){
+    let uniform = match a_uniform.any().downcast_mut::<UniformSoftware>() {
+      Some(res) => res,
+      None => panic!("Invalid uniform cast")
+    };
+
+    let program = match &self.program {
+      Some(res) => res,
+      None => return
+    };
+
+    if uniform.modified {
+      if uniform.id == UNIFORM_LOCATION_MVP &&
+        uniform.data.info.element_type == ElementType::Float32 &&
+        uniform.data.info.container_type == ContainerType::Mat4x4
+      {
+        program.mvp.set(uniform.data.get::<Mat4>());
+      }
+
+      uniform.modified = false;
+    }
+  }
+
+  pub fn update_sampler(&mut self, a_sampler: &Box<dyn Sampler>){
+    let sampler = match a_sampler.any().downcast_ref::<SamplerSoftware>() {
+      Some(res) => res,
+      None => panic!("Invalid sampler cast")
+    };
+
+    // the fixed function program only reads u_texture
+    if sampler.name != "u_texture" {
+      return
+    }
+
+    self.texture = Some(sampler.texture.clone());
+    self.sampler_desc = sampler.desc;
+  }
+
+  fn clear_target(&self, a_target: &RenderTargetStateSoftware, a_clear: RendererClearType){
+    if (a_clear & RendererClearType::COLOR) == RendererClearType::COLOR {
+      let color = [
+        to_unorm8(self.clear_color.x),
+        to_unorm8(self.clear_color.y),
+        to_unorm8(self.clear_color.z),
+        to_unorm8(self.clear_color.w)];
+
+      for texture in &a_target.colors {
+        for pixel in texture.data.borrow_mut().pixels.chunks_exact_mut(4){
+          pixel.copy_from_slice(&color);
+        }
+      }
+    }
+
+    if (a_clear & RendererClearType::DEPTH) == RendererClearType::DEPTH {
+      let depth = self.clear_depth.clamp(0.0, 1.0);
+      a_target.depth.borrow_mut().fill(depth);
+    }
+
+    if (a_clear & RendererClearType::STENCIL) == RendererClearType::STENCIL {
+      a_target.stencil.borrow_mut().fill(self.clear_stencil);
+    }
+  }
+
+  // the range is clamped to the geometry. primitives with vertices outside the geometry are skipped.
+  // the geometry is drawn once for each instance transform and color
+  fn draw_primitives(&mut self, a_geometry: &GeometrySoftware, a_range: DrawRange, a_instances: &[(Mat4, Vec4)]){
+    let mvp = match &self.program {
+      Some(res) => res.mvp.get(),
+      None => return
+    };
+
+    let texture = self.texture.clone();
+    let texture_data = match &texture {
+      Some(res) => match res.any().downcast_ref::<TextureSoftware>() {
+        Some(res) => Some(res.data.borrow()),
+        None => panic!("Invalid texture")
+      },
+      None => None
+    };
+
+    // only the first attachment is written, the same as a gl fragment shader with a single output.
+    // fails if the target is also being sampled which gl leaves undefined
+    let target = self.target.clone();
+    let mut color = match target.colors.first() {
+      Some(res) => match res.data.try_borrow_mut() {
+        Ok(res) => res,
+        Err(_res) => return
+      },
+      None => return
+    };
+    let mut depth = target.depth.borrow_mut();
+    let mut stencil = target.stencil.borrow_mut();
+
+    let num = a_geometry.get_num_elements() as usize;
+    let first = std::cmp::min(a_range.first as usize, num);
+    let count = std::cmp::min(a_range.count as usize, num - first);
+
+    let (size, primitives) = match get_primitives(a_geometry.topology, count) {
+      Some(res) => res,
+      None => return
+    };
+
+    for (transform, tint) in a_instances {
+      let instance_mvp = mvp * *transform;
+      let mut fragments = FragmentTargetSoftware{
+        texture: texture_data.as_deref(),
+        tint: *tint,
+        color: &mut color,
+        depth: &mut depth,
+        stencil: &mut stencil
+      };
+
+      'primitives: for primitive in primitives.chunks_exact(size) {
+        let mut clipped: Vec<VertexSoftware> = Vec::with_capacity(9);
+
+        for i in primitive {
+          let element = first + i;
+          let index = match &a_geometry.indices {
+            Some(res) => res[element] as i64 + a_range.base_vertex as i64,
+            None => element as i64
+          };
+
+          if index < 0 || index >= a_geometry.num as i64 {
+            continue 'primitives
+          }
+
+          clipped.push(VertexSoftware{
+            position: instance_mvp * a_geometry.positions[index as usize],
+            uv: a_geometry.uvs[index as usize]
+          });
+        }
+
+        // only near, far and w need clipping, x and y are limited by the viewport when rasterizing.
+        // a clipped line keeps its first two vertices
+        clipped = clip_polygon(&clipped, |v| v.position.w - MIN_CLIP_W);
+        clipped = clip_polygon(&clipped, |v| v.position.w + v.position.z);
+        clipped = clip_polygon(&clipped, |v| v.position.w - v.position.z);
+
+        match size {
+          1 => {
+            if let Some(vertex) = clipped.first() {
+              self.rasterize_point(*vertex, &mut fragments);
+            }
+          },
+          2 => {
+            if clipped.len() >= 2 {
+              self.rasterize_line([clipped[0], clipped[1]], &mut fragments);
+            }
+          },
+          _ => {
+            for i in 1..clipped.len().saturating_sub(1) {
+              self.rasterize_triangle([clipped[0], clipped[i], clipped[i + 1]], &mut fragments);
+            }
+          }
+        }
+      }
+    }
+  }
+
+  // window coordinates with 1/w for perspective correct interpolation, and depth in 0 to 1
+  fn to_window(&self, a_vertex: &VertexSoftware) -> (Vec3, f32){
+    let viewport_pos = self.viewport_pos.as_vec2();
+    let viewport_size = self.viewport_size.as_vec2();
+
+    let inv_w = 1.0 / a_vertex.position.w;
+    let ndc = a_vertex.position.truncate() * inv_w;
+    let window = Vec3::new(
+      viewport_pos.x + (ndc.x + 1.0) * 0.5 * viewport_size.x,
+      viewport_pos.y + (ndc.y + 1.0) * 0.5 * viewport_size.y,
+      inv_w);
+
+    (window, ndc.z * 0.5 + 0.5)
+  }
+
+  // pixels fragments may be written to, the viewport and the scissor clamped to the target
+  fn get_clip_rect(&self, a_fragments: &FragmentTargetSoftware) -> (Vec2, Vec2){
+    let viewport_pos = self.viewport_pos.as_vec2();
+    let viewport_size = self.viewport_size.as_vec2();
+
+    let mut clip_min = viewport_pos.max(Vec2::ZERO);
+    let mut clip_max = (viewport_pos + viewport_size).min(Vec2::new(a_fragments.color.width as f32, a_fragments.color.height as f32));
+    if let Some((pos, size)) = self.render_state.scissor {
+      clip_min = clip_min.max(pos.as_vec2());
+      clip_max = clip_max.min((pos + size).as_vec2());
+    }
+
+    (clip_min, clip_max)
+  }
+
+  // points are a single pixel, the same as the default gl point size
+  fn rasterize_point(&self, a_vertex: VertexSoftware, a_fragments: &mut FragmentTargetSoftware){
+    let (window, depth) = self.to_window(&a_vertex);
+    let (clip_min, clip_max) = self.get_clip_rect(a_fragments);
+
+    let pixel = window.truncate().floor();
+    if pixel.x < clip_min.x || pixel.y < clip_min.y || pixel.x >= clip_max.x || pixel.y >= clip_max.y {
+      return
+    }
+
+    self.shade_fragment(pixel.x as i32, pixel.y as i32, depth, true, a_vertex.uv, a_fragments);
+  }
+
+  // one pixel wide lines. the pixel centers along the major axis from the start up to the end are drawn,
+  // so the last pixel is left for the next line of a strip
+  fn rasterize_line(&self, a_verts: [VertexSoftware; 2], a_fragments: &mut FragmentTargetSoftware){
+    let (start, start_depth) = self.to_window(&a_verts[0]);
+    let (end, end_depth) = self.to_window(&a_verts[1]);
+    let (clip_min, clip_max) = self.get_clip_rect(a_fragments);
+
+    let delta = end.truncate() - start.truncate();
+    let x_major = delta.x.abs() >= delta.y.abs();
+    let length = match x_major {
+      true => delta.x,
+      false => delta.y
+    };
+
+    if length == 0.0 {
+      return
+    }
+
+    // pixel centers along the major axis, limited to the clip rect
+    let (major_start, major_end, major_min, major_max) = match x_major {
+      true => (start.x, end.x, clip_min.x, clip_max.x),
+      false => (start.y, end.y, clip_min.y, clip_max.y)
+    };
+    let first = (major_start.min(major_end) - 0.5).ceil().max(major_min);
+    let last = (major_start.max(major_end) - 0.5).ceil().min(major_max);
+
+    let mut major = first;
+    while major < last {
+      let t = (major + 0.5 - major_start) / length;
+      // the end pixel belongs to the next line
+      if t < 0.0 || t >= 1.0 {
+        major += 1.0;
+        continue
+      }
+
+      let minor = match x_major {
+        true => start.y + delta.y * t,
+        false => start.x + delta.x * t
+      }.floor();
+
+      let (x, y) = match x_major {
+        true => (major, minor),
+        false => (minor, major)
+      };
+
+      if x >= clip_min.x && y >= clip_min.y && x < clip_max.x && y < clip_max.y {
+        // perspective correct the same as triangles
+        let inv_w = start.z + (end.z - start.z) * t;
+        let weight = t * end.z / inv_w;
+        let uv = a_verts[0].uv.lerp(a_verts[1].uv, weight);
+        let depth = start_depth + (end_depth - start_depth) * t;
+
+        self.shade_fragment(x as i32, y as i32, depth, true, uv, a_fragments);
+      }
+
+      major += 1.0;
+    }
+  }
+
+  fn rasterize_triangle(&self, a_verts: [VertexSoftware; 3], a_fragments: &mut FragmentTargetSoftware){
+    let mut window = [Vec3::ZERO; 3];
+    let mut window_depth = [0.0; 3];
+    for i in 0..3 {
+      (window[i], window_depth[i]) = self.to_window(&a_verts[i]);
+    }
+
+    let area = edge(window[0].truncate(), window[1].truncate(), window[2].truncate());
+    if area == 0.0 {
+      return
+    }
+
+    // counter clockwise triangles have a positive area and are front facing
+    let front = area > 0.0;
+    match self.render_state.cull_mode {
+      CullMode::Front if front => return,
+      CullMode::Back if !front => return,
+      _ => ()
+    }
+
+    let (clip_min, clip_max) = self.get_clip_rect(a_fragments);
+
+    let min_x = window.iter().fold(f32::MAX, |acc, v| acc.min(v.x)).floor().max(clip_min.x) as i32;
+    let max_x = window.iter().fold(f32::MIN, |acc, v| acc.max(v.x)).ceil().min(clip_max.x) as i32;
+    let min_y = window.iter().fold(f32::MAX, |acc, v| acc.min(v.y)).floor().max(clip_min.y) as i32;
+    let max_y = window.iter().fold(f32::MIN, |acc, v| acc.max(v.y)).ceil().min(clip_max.y) as i32;
+
+    for y in min_y..max_y {
+      for x in min_x..max_x {
+        let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+
+        let mut weights = [
+          edge(window[1].truncate(), window[2].truncate(), point) / area,
+          edge(window[2].truncate(), window[0].truncate(), point) / area,
+          edge(window[0].truncate(), window[1].truncate(), point) / area];
+
+        // top left fill rule so shared edges are only drawn once
+        let mut inside = true;
+        for i in 0..3 {
+          let start = window[(i + 1) % 3].truncate();
+          let end = window[(i + 2) % 3].truncate();
+          if weights[i] < 0.0 || (weights[i] == 0.0 && !is_top_left(start, end, area > 0.0)) {
+            inside = false;
+          }
+        }
+
+        if !inside {
+          continue
+        }
+
+        // depth is interpolated linearly in window space before perspective correction
+        let depth = weights[0] * window_depth[0] + weights[1] * window_depth[1] + weights[2] * window_depth[2];
+
+        let inv_w = weights[0] * window[0].z + weights[1] * window[1].z + weights[2] * window[2].z;
+        for i in 0..3 {
+          weights[i] *= window[i].z / inv_w;
+        }
+
+        let uv = a_verts[0].uv * weights[0] + a_verts[1].uv * weights[1] + a_verts[2].uv * weights[2];
+
+        self.shade_fragment(x, y, depth, front, uv, a_fragments);
+      }
+    }
+  }
+
+  // runs the stencil and depth tests then blends the sampled color into the target. the depth and stencil
+  // buffers are empty for targets without a depth attachment, both tests then pass
+  fn shade_fragment(&self, a_x: i32, a_y: i32, a_depth: f32, a_front: bool, a_uv: Vec2, a_fragments: &mut FragmentTargetSoftware){
+    let state = &self.render_state;
+    let pixel = (a_y as u32 * a_fragments.color.width + a_x as u32) as usize;
+    let has_depth = pixel < a_fragments.depth.len();
+    let depth = a_depth.clamp(0.0, 1.0);
+
+    let stencil_face = match state.stencil {
+      Some(stencil) if has_depth => Some(match a_front {
+        true => (stencil, stencil.front),
+        false => (stencil, stencil.back)
+      }),
+      _ => None
+    };
+
+    if let Some((stencil, face)) = stencil_face {
+      let reference = (stencil.reference & stencil.read_mask & 0xff) as i32;
+      let stored = a_fragments.stencil[pixel] & (stencil.read_mask & 0xff) as i32;
+      if !compare(face.compare, reference, stored) {
+        a_fragments.stencil[pixel] = apply_stencil_op(face.fail, a_fragments.stencil[pixel], &stencil);
+        return
+      }
+    }
+
+    if let Some(depth_state) = state.depth {
+      if has_depth {
+        if !compare(depth_state.compare, depth, a_fragments.depth[pixel]) {
+          if let Some((stencil, face)) = stencil_face {
+            a_fragments.stencil[pixel] = apply_stencil_op(face.depth_fail, a_fragments.stencil[pixel], &stencil);
+          }
+          return
+        }
+
+        if depth_state.write {
+          a_fragments.depth[pixel] = depth;
+        }
+      }
+    }
+
+    if let Some((stencil, face)) = stencil_face {
+      a_fragments.stencil[pixel] = apply_stencil_op(face.pass, a_fragments.stencil[pixel], &stencil);
+    }
+
+    let color = match a_fragments.texture {
+      Some(res) => sample_texture(res, &self.sampler_desc, a_uv),
+      // unbound samplers read as black in gl
+      None => Vec4::new(0.0, 0.0, 0.0, 1.0)
+    } * a_fragments.tint;
+
+    let offset = pixel * 4;
+    let pixels = &mut a_fragments.color.pixels;
+    let dst = Vec4::new(pixels[offset] as f32, pixels[offset + 1] as f32, pixels[offset + 2] as f32, pixels[offset + 3] as f32) / 255.0;
+
+    let color = match state.blend {
+      Some(blend) => blend_color(&blend, color.clamp(Vec4::ZERO, Vec4::ONE), dst),
+      None => color
+    };
+
+    let masks = [ColorWriteMask::RED, ColorWriteMask::GREEN, ColorWriteMask::BLUE, ColorWriteMask::ALPHA];
+    for i in 0..4 {
+      if state.color_write.contains(masks[i]) {
+        pixels[offset + i] = to_unorm8(color[i]);
+      }
+    }
+  }
+}
+
+// elements of each primitive relative to the first element of the draw, with the number of elements in a primitive.
+// odd triangles of a strip are reordered to keep the winding of the first. None for patches
+fn get_primitives(a_topology: PrimitiveTopology, a_count: usize) -> Option<(usize, Vec<usize>)>{
+  let primitives = match a_topology {
+    PrimitiveTopology::Points => (1, (0..a_count).collect()),
+    PrimitiveTopology::Lines => (2, (0..a_count / 2 * 2).collect()),
+    PrimitiveTopology::LineStrip => (2, (1..a_count).flat_map(|i| [i - 1, i]).collect()),
+    PrimitiveTopology::LineLoop => {
+      let mut lines: Vec<usize> = (1..a_count).flat_map(|i| [i - 1, i]).collect();
+      if a_count > 1 {
+        lines.extend([a_count - 1, 0]);
+      }
+      (2, lines)
+    },
+    PrimitiveTopology::Triangles => (3, (0..a_count / 3 * 3).collect()),
+    PrimitiveTopology::TriangleStrip => (3, (2..a_count).flat_map(|i| match i % 2 {
+      0 => [i - 2, i - 1, i],
+      _ => [i - 1, i - 2, i]
+    }).collect()),
+    PrimitiveTopology::TriangleFan => (3, (2..a_count).flat_map(|i| [0, i - 1, i]).collect()),
+    PrimitiveTopology::Patches => return None
+  };
+
+  Some(primitives)
+}
+
+// every program is the fixed function one, named the same as the basic shaders
+fn reflect_program() -> ProgramReflection{
+  let vec2 = UniformType{element_type: ElementType::Float32, container_type: ContainerType::Vec2, num_components: 1};
+
+  ProgramReflection{
+    uniforms: vec![UniformReflection{name: String::from("u_mvp"), info: UniformType{element_type: ElementType::Float32, container_type: ContainerType::Mat4x4, num_components: 1}, location: UNIFORM_LOCATION_MVP}],
+    blocks: Vec::new(),
+    samplers: vec![SamplerReflection{name: String::from("u_texture"), dimension: TextureDimension::D2, count: 1, unit: 0}],
+    attributes: vec![
+      AttributeReflection{name: String::from("i_position"), info: vec2, location: 0},
+      AttributeReflection{name: String::from("i_uv"), info: vec2, location: 1}]
+  }
+}
+
+fn get_uniform_location(a_name: &str) -> i32{
+  match a_name {
+    "u_mvp" => UNIFORM_LOCATION_MVP,
+    _ => -1
+  }
+}
+
+fn to_unorm8(a_value: f32) -> u8{
+  (a_value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+}
+
+fn compare<T: PartialOrd>(a_compare: CompareFunction, a: T, b: T) -> bool{
+  match a_compare {
+    CompareFunction::Never => false,
+    CompareFunction::Less => a < b,
+    CompareFunction::Equal => a == b,
+    CompareFunction::LessEqual => a <= b,
+    CompareFunction::Greater => a > b,
+    CompareFunction::NotEqual => a != b,
+    CompareFunction::GreaterEqual => a >= b,
+    CompareFunction::Always => true
+  }
+}
+
+// only bits in the write mask are changed
+fn apply_stencil_op(a_op: StencilOp, a_value: i32, a_stencil: &StencilState) -> i32{
+  let value = match a_op {
+    StencilOp::Keep => a_value,
+    StencilOp::Zero => 0,
+    StencilOp::Replace => (a_stencil.reference & 0xff) as i32,
+    StencilOp::IncrementClamp => std::cmp::min(a_value + 1, 0xff),
+    StencilOp::DecrementClamp => std::cmp::max(a_value - 1, 0),
+    StencilOp::Invert => !a_value & 0xff,
+    StencilOp::IncrementWrap => (a_value + 1) & 0xff,
+    StencilOp::DecrementWrap => (a_value - 1) & 0xff
+  };
+
+  let write_mask = (a_stencil.write_mask & 0xff) as i32;
+  (a_value & !write_mask) | (value & write_mask)
+}
+
+fn get_blend_factor(a_factor: BlendFactor, a_src: Vec4, a_dst: Vec4) -> Vec4{
+  match a_factor {
+    BlendFactor::Zero => Vec4::ZERO,
+    BlendFactor::One => Vec4::ONE,
+    BlendFactor::SrcColor => a_src,
+    BlendFactor::OneMinusSrcColor => Vec4::ONE - a_src,
+    BlendFactor::DstColor => a_dst,
+    BlendFactor::OneMinusDstColor => Vec4::ONE - a_dst,
+    BlendFactor::SrcAlpha => Vec4::splat(a_src.w),
+    BlendFactor::OneMinusSrcAlpha => Vec4::splat(1.0 - a_src.w),
+    BlendFactor::DstAlpha => Vec4::splat(a_dst.w),
+    BlendFactor::OneMinusDstAlpha => Vec4::splat(1.0 - a_dst.w)
+  }
+}
+
+fn get_blend_op(a_op: BlendOp, a_src: Vec4, a_dst: Vec4, a_src_factor: Vec4, a_dst_factor: Vec4) -> Vec4{
+  match a_op {
+    BlendOp::Add => a_src * a_src_factor + a_dst * a_dst_factor,
+    BlendOp::Subtract => a_src * a_src_factor - a_dst * a_dst_factor,
+    BlendOp::ReverseSubtract => a_dst * a_dst_factor - a_src * a_src_factor,
+    BlendOp::Min => a_src.min(a_dst),
+    BlendOp::Max => a_src.max(a_dst)
+  }
+}
+
+// rgb and alpha are blended separately with their own factors and operations
+fn blend_color(a_blend: &BlendState, a_src: Vec4, a_dst: Vec4) -> Vec4{
+  let color = get_blend_op(a_blend.color_op, a_src, a_dst,
+    get_blend_factor(a_blend.color_src, a_src, a_dst),
+    get_blend_factor(a_blend.color_dst, a_src, a_dst));
+
+  let alpha = get_blend_op(a_blend.alpha_op, a_src, a_dst,
+    get_blend_factor(a_blend.alpha_src, a_src, a_dst),
+    get_blend_factor(a_blend.alpha_dst, a_src, a_dst));
+
+  color.truncate().extend(alpha.w)
+}
+
+fn edge(a: Vec2, b: Vec2, c: Vec2) -> f32{
+  (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// y is up in window coordinates so a top edge runs right to left for counter clockwise triangles
+fn is_top_left(a_start: Vec2, a_end: Vec2, a_ccw: bool) -> bool{
+  let dir = if a_ccw { a_end - a_start } else { a_start - a_end };
+  (dir.y == 0.0 && dir.x < 0.0) || dir.y < 0.0
+}
+
+// sutherland hodgman against a single plane, vertices with a_distance >= 0 are kept
+fn clip_polygon<F: Fn(&VertexSoftware) -> f32>(a_verts: &Vec<VertexSoftware>, a_distance: F) -> Vec<VertexSoftware>{
+  let mut result = Vec::with_capacity(a_verts.len() + 1);
+
+  for i in 0..a_verts.len() {
+    let current = a_verts[i];
+    let next = a_verts[(i + 1) % a_verts.len()];
+    let current_distance = a_distance(&current);
+    let next_distance = a_distance(&next);
+
+    if current_distance >= 0.0 {
+      result.push(current);
+    }
+
+    if (current_distance >= 0.0) != (next_distance >= 0.0) {
+      let t = current_distance / (current_distance - next_distance);
+      result.push(VertexSoftware{
+        position: current.position.lerp(next.position, t),
+        uv: current.uv.lerp(next.uv, t)
+      });
+    }
+  }
+
+  result
+}
+
+// None when the coordinate reads the border color
+fn wrap_texel(a_coord: i32, a_size: u32, a_wrap: WrapMode) -> Option<u32>{
+  let size = a_size as i32;
+  match a_wrap {
+    WrapMode::Repeat => Some(a_coord.rem_euclid(size) as u32),
+    WrapMode::MirroredRepeat => {
+      let coord = a_coord.rem_euclid(size * 2);
+      Some(if coord < size { coord } else { size * 2 - 1 - coord } as u32)
+    },
+    WrapMode::ClampToEdge => Some(a_coord.clamp(0, size - 1) as u32),
+    WrapMode::ClampToBorder => if a_coord < 0 || a_coord >= size { None } else { Some(a_coord as u32) }
+  }
+}
+
+fn fetch_texel(a_texture: &TextureDataSoftware, a_desc: &SamplerDesc, a_x: i32, a_y: i32) -> Vec4{
+  let x = wrap_texel(a_x, a_texture.width, a_desc.wrap_u);
+  let y = wrap_texel(a_y, a_texture.height, a_desc.wrap_v);
+  let (x, y) = match (x, y) {
+    (Some(x), Some(y)) => (x, y),
+    _ => return a_desc.border_color
+  };
+
+  let offset = ((y * a_texture.width + x) * 4) as usize;
+  let pixel = &a_texture.pixels[offset..offset + 4];
+  Vec4::new(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32, pixel[3] as f32) / 255.0
+}
+
+// textures have a single level and no derivatives are known, so the mag filter is used for every pixel.
+// anisotropy, lod and compare settings are ignored
+fn sample_texture(a_texture: &TextureDataSoftware, a_desc: &SamplerDesc, a_uv: Vec2) -> Vec4{
+  if a_texture.width == 0 || a_texture.height == 0 {
+    return Vec4::new(0.0, 0.0, 0.0, 1.0)
+  }
+
+  let x = a_uv.x * a_texture.width as f32;
+  let y = a_uv.y * a_texture.height as f32;
+
+  if a_desc.mag_filter == FilterMode::Nearest {
+    return fetch_texel(a_texture, a_desc, x.floor() as i32, y.floor() as i32)
+  }
+
+  let x = x - 0.5;
+  let y = y - 0.5;
+  let x0 = x.floor();
+  let y0 = y.floor();
+  let fx = x - x0;
+  let fy = y - y0;
+
+  let c00 = fetch_texel(a_texture, a_desc, x0 as i32, y0 as i32);
+  let c10 = fetch_texel(a_texture, a_desc, x0 as i32 + 1, y0 as i32);
+  let c01 = fetch_texel(a_texture, a_desc, x0 as i32, y0 as i32 + 1);
+  let c11 = fetch_texel(a_texture, a_desc, x0 as i32 + 1, y0 as i32 + 1);
+
+  c00.lerp(c10, fx).lerp(c01.lerp(c11, fx), fy)
+}
+
+// decodes the position and uv of every vertex
+fn gen_vertices(a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout, a_indices: Option<Rc<Vec<u32>>>) -> Result<GeometrySoftware, RendererError> {
+  if a_buffers.len() != a_layout.buffers.len() {
+    return Err(RendererError::Error)
+  }
+
+  let mut buffers = Vec::new();
+  for buffer in a_buffers {
+    match buffer.any().downcast_ref::<VerticesSoftware>() {
+      Some(res) => buffers.push(res),
+      None => return Err(RendererError::InvalidCast)
+    }
+  }
+
+  let mut num: Option<usize> = None;
+  for (buffer, layout) in buffers.iter().zip(a_layout.buffers.iter()) {
+    if layout.step == VertexStep::PerVertex && layout.stride > 0 {
+      let count = buffer.data.len() / layout.stride as usize;
+      num = Some(match num {
+        Some(res) => res.min(count),
+        None => count
+      });
+    }
+  }
+  let num = num.unwrap_or(0);
+
+  // unset attributes default to (0, 0, 0, 1) the same as gl
+  let mut positions = vec![Vec4::W; num];
+  let mut uvs = vec![Vec2::ZERO; num];
+
+  for (buffer, layout) in buffers.iter().zip(a_layout.buffers.iter()) {
+    for attribute in &layout.attributes {
+      if attribute.location > 1 {
+        continue
+      }
+
+      for i in 0..num {
+        // there is no instancing so per instance attributes use the first instance
+        let index = match layout.step {
+          VertexStep::PerVertex => i,
+          VertexStep::PerInstance => 0
+        };
+
+        let value = match read_attribute(&buffer.data, index * layout.stride as usize, attribute) {
+          Some(res) => res,
+          None => return Err(RendererError::Error)
+        };
+
+        match attribute.location {
+          0 => positions[i] = value,
+          _ => uvs[i] = value.truncate().truncate()
+        }
+      }
+    }
+  }
+
+  Ok(GeometrySoftware{positions: positions, uvs: uvs, indices: a_indices, num: num, topology: PrimitiveTopology::Triangles})
+}
+
+// transform and color of an instance, attributes with other semantics are ignored
+fn read_instance(a_data: &[u8], a_offset: usize, a_layout: &VertexBufferLayout) -> Option<(Mat4, Vec4)> {
+  let mut transform = Mat4::IDENTITY;
+  let mut color = Vec4::ONE;
+
+  for attribute in &a_layout.attributes {
+    match attribute.semantic {
+      VertexSemantic::Transform => {
+        let mut columns = [Vec4::X, Vec4::Y, Vec4::Z, Vec4::W];
+        let rows = attribute.container_type.get_num_rows();
+
+        for (i, column) in columns.iter_mut().enumerate().take(attribute.container_type.get_num_columns() as usize) {
+          let column_attribute = VertexAttribute{
+            offset: attribute.offset + i as u32 * rows * attribute.element_type.get_size(),
+            ..*attribute
+          };
+
+          let value = match read_attribute(a_data, a_offset, &column_attribute) {
+            Some(res) => res,
+            None => return None
+          };
+
+          // missing rows keep the identity
+          for row in 0..rows as usize {
+            column[row] = value[row];
+          }
+        }
+
+        transform = Mat4::from_cols(columns[0], columns[1], columns[2], columns[3]);
+      },
+      VertexSemantic::Color => {
+        color = match read_attribute(a_data, a_offset, attribute) {
+          Some(res) => res,
+          None => return None
+        };
+      },
+      _ => {}
+    }
+  }
+
+  Some((transform, color))
+}
+
+// reads the first column of an attribute as floats, missing components default to (0, 0, 0, 1).
+// None if the attribute is outside the buffer
+fn read_attribute(a_data: &[u8], a_offset: usize, a_attribute: &VertexAttribute) -> Option<Vec4> {
+  let size = a_attribute.element_type.get_size() as usize;
+  let rows = a_attribute.container_type.get_num_rows() as usize;
+  let start = a_offset + a_attribute.offset as usize;
+
+  let bytes = match a_data.get(start..(start + size * rows)) {
+    Some(res) => res,
+    None => return None
+  };
+
+  let mut value = Vec4::W;
+  for (row, element) in bytes.chunks_exact(size).enumerate() {
+    let normalized = a_attribute.normalized;
+    value[row] = match a_attribute.element_type {
+      ElementType::Float16 => half_to_f32(u16::from_ne_bytes([element[0], element[1]])),
+      ElementType::Float32 => f32::from_ne_bytes([element[0], element[1], element[2], element[3]]),
+      ElementType::Float64 => f64::from_ne_bytes(element.try_into().unwrap()) as f32,
+      ElementType::Int8 => match normalized {
+        true => (element[0] as i8 as f32 / i8::MAX as f32).max(-1.0),
+        false => element[0] as i8 as f32
+      },
+      ElementType::Uint8 => match normalized {
+        true => element[0] as f32 / u8::MAX as f32,
+        false => element[0] as f32
+      },
+      ElementType::Int16 => {
+        let v = i16::from_ne_bytes([element[0], element[1]]) as f32;
+        match normalized {
+          true => (v / i16::MAX as f32).max(-1.0),
+          false => v
+        }
+      },
+      ElementType::Uint16 => {
+        let v = u16::from_ne_bytes([element[0], element[1]]) as f32;
+        match normalized {
+          true => v / u16::MAX as f32,
+          false => v
+        }
+      },
+      ElementType::Int32 => {
+        let v = i32::from_ne_bytes(element.try_into().unwrap()) as f64;
+        match normalized {
+          true => (v / i32::MAX as f64).max(-1.0) as f32,
+          false => v as f32
+        }
+      },
+      ElementType::Uint32 => {
+        let v = u32::from_ne_bytes(element.try_into().unwrap()) as f64;
+        match normalized {
+          true => (v / u32::MAX as f64) as f32,
+          false => v as f32
+        }
+      },
+      ElementType::Int64 => i64::from_ne_bytes(element.try_into().unwrap()) as f32,
+      ElementType::Uint64 => u64::from_ne_bytes(element.try_into().unwrap()) as f32,
+      ElementType::Bool => (element[0] != 0) as u8 as f32
+    };
+  }
+
+  Some(value)
+}
@@ -1,6 +1,7 @@
 extern crate static_assertions as sa;
 
 use crate::gpu::renderer_types::*;
+use glam::*;
 use murmur3::murmur3_32;
 use std::io::Cursor;
 
@@ -9,6 +10,7 @@ pub trait UniformBase{
 }
 
 #[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct UniformType{
   pub element_type: ElementType,
   pub container_type: ContainerType,
@@ -17,6 +19,51 @@ pub struct UniformType{
 
 sa::const_assert!(std::mem::size_of::<UniformType>() == 4);
 
+impl UniformType{
+  pub fn from_value<T: GetType + ?Sized>(a_value: &T) -> UniformType{
+    UniformType{element_type: a_value.get_element_type(), container_type: a_value.get_container_type(), num_components: a_value.get_num_components()}
+  }
+
+  // bools are 4 bytes in blocks
+  pub fn get_element_size(&self) -> u32{
+    match self.element_type {
+      ElementType::Bool => 4,
+      res => res.get_size()
+    }
+  }
+
+  // single values are not arrays, so arrays of one element are laid out as a value
+  pub fn is_array_or_matrix(&self) -> bool{
+    self.num_components > 1 || self.container_type.get_num_columns() > 1
+  }
+
+  // alignment of the value, which is also the distance between matrix columns
+  pub fn get_alignment(&self, a_layout: BlockLayout) -> u32{
+    let alignment = match self.container_type.get_num_rows() {
+      3 => 4,
+      res => res
+    } * self.get_element_size();
+
+    match a_layout {
+      BlockLayout::Std140 if self.is_array_or_matrix() => alignment.next_multiple_of(16),
+      _ => alignment
+    }
+  }
+
+  // distance between array elements
+  pub fn get_stride(&self, a_layout: BlockLayout) -> u32{
+    self.container_type.get_num_columns() * self.get_alignment(a_layout)
+  }
+
+  // bytes taken in a block, arrays and matrices include the padding of their last element
+  pub fn get_block_size(&self, a_layout: BlockLayout) -> u32{
+    match self.is_array_or_matrix() {
+      true => self.num_components as u32 * self.get_stride(a_layout),
+      false => self.container_type.get_num_rows() * self.get_element_size()
+    }
+  }
+}
+
 type UniformHash = u32;
 
 pub struct UniformName{
@@ -62,19 +109,28 @@ impl PartialEq for UniformName {
 pub trait UniformDataDyn{
   fn any_mut(&mut self) -> &mut dyn std::any::Any;
   fn any(&self) -> &dyn std::any::Any;
+  fn as_bytes(&self) -> &[u8];
+  fn write_layout(&self, a_layout: BlockLayout, a_data: &mut [u8]);
 }
 
 pub struct UniformDataGen<T>{
   pub data: T,
 }
 
-impl<T: 'static> UniformDataDyn for UniformDataGen<T>{
+impl<T: 'static + GetType> UniformDataDyn for UniformDataGen<T>{
   fn any_mut(&mut self) -> &mut dyn std::any::Any{
     self
   }
   fn any(&self) -> &dyn std::any::Any{
     self
   }
+  // the GetType types are plain scalars, vectors and matrices without padding
+  fn as_bytes(&self) -> &[u8]{
+    unsafe { std::slice::from_raw_parts(&self.data as *const T as *const u8, std::mem::size_of::<T>()) }
+  }
+  fn write_layout(&self, a_layout: BlockLayout, a_data: &mut [u8]){
+    self.data.write_layout(a_layout, a_data);
+  }
 }
 
 pub struct UniformData{
@@ -85,11 +141,45 @@ pub struct UniformData{
 impl UniformData{
   pub fn new<T: 'static + GetType>(a: T) -> UniformData{
     UniformData{
-      info: UniformType{element_type: a.get_element_type(), container_type: a.get_container_type(), num_components: 1},
+      info: UniformType{element_type: a.get_element_type(), container_type: a.get_container_type(), num_components: a.get_num_components()},
       data: Box::new(UniformDataGen::<T>{data: a})
     }
   }
 
+  // the default value of a type, matrices are the identity. arrays can't be made without knowing their size at compile time
+  pub fn from_type(a_info: &UniformType) -> Option<UniformData>{
+    if a_info.num_components != 1 {
+      return None
+    }
+
+    match (a_info.element_type, a_info.container_type) {
+      (ElementType::Float32, ContainerType::Single) => Some(UniformData::new(f32::default())),
+      (ElementType::Float32, ContainerType::Vec2) => Some(UniformData::new(Vec2::default())),
+      (ElementType::Float32, ContainerType::Vec3) => Some(UniformData::new(Vec3::default())),
+      (ElementType::Float32, ContainerType::Vec4) => Some(UniformData::new(Vec4::default())),
+      (ElementType::Float32, ContainerType::Mat2x2) => Some(UniformData::new(Mat2::default())),
+      (ElementType::Float32, ContainerType::Mat3x3) => Some(UniformData::new(Mat3::default())),
+      (ElementType::Float32, ContainerType::Mat4x4) => Some(UniformData::new(Mat4::default())),
+      (ElementType::Float64, ContainerType::Single) => Some(UniformData::new(f64::default())),
+      (ElementType::Float64, ContainerType::Vec2) => Some(UniformData::new(DVec2::default())),
+      (ElementType::Float64, ContainerType::Vec3) => Some(UniformData::new(DVec3::default())),
+      (ElementType::Float64, ContainerType::Vec4) => Some(UniformData::new(DVec4::default())),
+      (ElementType::Int32, ContainerType::Single) => Some(UniformData::new(i32::default())),
+      (ElementType::Int32, ContainerType::Vec2) => Some(UniformData::new(IVec2::default())),
+      (ElementType::Int32, ContainerType::Vec3) => Some(UniformData::new(IVec3::default())),
+      (ElementType::Int32, ContainerType::Vec4) => Some(UniformData::new(IVec4::default())),
+      (ElementType::Uint32, ContainerType::Single) => Some(UniformData::new(u32::default())),
+      (ElementType::Uint32, ContainerType::Vec2) => Some(UniformData::new(UVec2::default())),
+      (ElementType::Uint32, ContainerType::Vec3) => Some(UniformData::new(UVec3::default())),
+      (ElementType::Uint32, ContainerType::Vec4) => Some(UniformData::new(UVec4::default())),
+      (ElementType::Bool, ContainerType::Single) => Some(UniformData::new(bool::default())),
+      (ElementType::Bool, ContainerType::Vec2) => Some(UniformData::new(BVec2::default())),
+      (ElementType::Bool, ContainerType::Vec3) => Some(UniformData::new(BVec3::default())),
+      (ElementType::Bool, ContainerType::Vec4) => Some(UniformData::new(BVec4::default())),
+      _ => None
+    }
+  }
+
   pub fn set<T: 'static>(&mut self, a: T){
     let mut uniform = match self.data.any_mut().downcast_mut::<UniformDataGen<T>>() {
       Some(res) => res,
@@ -108,6 +198,226 @@ impl UniformData{
     let data = uniform.data;
     data
   }
+
+  // tightly packed values, matrices are column major and bools are one byte
+  pub fn as_bytes(&self) -> &[u8]{
+    self.data.as_bytes()
+  }
+
+  // value laid out for a block. trailing padding is left out so members packed after it are not overwritten
+  pub fn to_layout(&self, a_layout: BlockLayout) -> Vec<u8>{
+    let mut data = vec![0u8; get_layout_size(&self.info, a_layout)];
+    self.data.write_layout(a_layout, &mut data);
+    data
+  }
+}
+
+// bytes written by GetType::write_layout
+fn get_layout_size(a_info: &UniformType, a_layout: BlockLayout) -> usize{
+  let num = a_info.num_components as usize;
+  let columns = a_info.container_type.get_num_columns() as usize;
+  let rows = a_info.container_type.get_num_rows() as usize;
+
+  if num == 0 {
+    return 0
+  }
+
+  (num - 1) * a_info.get_stride(a_layout) as usize + (columns - 1) * a_info.get_alignment(a_layout) as usize + rows * a_info.get_element_size() as usize
+}
+
+// values of a struct matching a uniform block or storage buffer. every member is passed to the writer in
+// declaration order, both to make the layout and to write the block. members that don't match the layout
+// are reported by UniformBlockLayout::write
+pub trait UniformBlock{
+  fn write(&self, a_writer: &mut UniformBlockWriter);
+}
+
+enum UniformBlockTarget<'a>{
+  Layout(&'a mut Vec<UniformBlockMember>),
+  Data(&'a [UniformBlockMember], &'a mut [u8])
+}
+
+// lays out the members of a block, or writes them at the offsets of a layout made from the same block
+pub struct UniformBlockWriter<'a>{
+  layout: BlockLayout,
+  target: UniformBlockTarget<'a>,
+  index: usize,
+  offset: u32,
+  alignment: u32,
+  // the first member that didn't match the layout
+  error: Option<RendererError>
+}
+
+impl UniformBlockWriter<'_>{
+  pub fn member<T: 'static + GetType>(&mut self, a_name: &'static str, a_value: &T){
+    let info = UniformType::from_value(a_value);
+    let index = self.index;
+    self.index += 1;
+
+    match &mut self.target {
+      UniformBlockTarget::Layout(members) => {
+        let alignment = info.get_alignment(self.layout);
+        self.offset = u32::next_multiple_of(self.offset, alignment);
+        self.alignment = std::cmp::max(self.alignment, alignment);
+
+        members.push(UniformBlockMember{name: String::from(a_name), info: info, offset: self.offset});
+        self.offset += info.get_block_size(self.layout);
+      },
+      UniformBlockTarget::Data(members, data) => {
+        match members.get(index) {
+          Some(res) if res.name == a_name && res.info == info => a_value.write_layout(self.layout, &mut data[res.offset as usize..]),
+          _ => {
+            if self.error.is_none() {
+              self.error = Some(RendererError::InvalidUniform(String::from(a_name)));
+            }
+          }
+        };
+      }
+    };
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct UniformBlockMember{
+  pub name: String,
+  pub info: UniformType,
+  pub offset: u32
+}
+
+// offsets of the members of a block, checked against the program when bound
+#[derive(Clone, Debug)]
+pub struct UniformBlockLayout{
+  pub layout: BlockLayout,
+  pub members: Vec<UniformBlockMember>,
+  pub size: u32
+}
+
+impl UniformBlockLayout{
+  pub fn new(a_layout: BlockLayout, a_block: &dyn UniformBlock) -> UniformBlockLayout{
+    let mut members = Vec::new();
+    let mut writer = UniformBlockWriter{layout: a_layout, target: UniformBlockTarget::Layout(&mut members), index: 0, offset: 0, alignment: 1, error: None};
+    a_block.write(&mut writer);
+
+    // std140 rounds the size of structs up to a vec4
+    let size = match a_layout {
+      BlockLayout::Std140 => writer.offset.next_multiple_of(16),
+      BlockLayout::Std430 => writer.offset.next_multiple_of(writer.alignment)
+    };
+
+    UniformBlockLayout{layout: a_layout, members: members, size: size}
+  }
+
+  // the block has to have the members the layout was made from, the first one that doesn't is InvalidUniform
+  pub fn write(&self, a_block: &dyn UniformBlock) -> Result<Vec<u8>, RendererError>{
+    let mut data = vec![0u8; self.size as usize];
+
+    match self.write_to(a_block, &mut data) {
+      Ok(_res) => Ok(data),
+      Err(res) => Err(res)
+    }
+  }
+
+  // a_data has to be at least the size of the layout, padding is left as it is. a_data is partly written
+  // when a member doesn't match
+  pub fn write_to(&self, a_block: &dyn UniformBlock, a_data: &mut [u8]) -> Result<(), RendererError>{
+    if a_data.len() < self.size as usize {
+      return Err(RendererError::Error)
+    }
+
+    let mut writer = UniformBlockWriter{layout: self.layout, target: UniformBlockTarget::Data(&self.members, a_data), index: 0, offset: 0, alignment: 1, error: None};
+    a_block.write(&mut writer);
+
+    if let Some(res) = writer.error {
+      return Err(res)
+    }
+
+    // members left out of the block
+    match self.members.get(writer.index) {
+      Some(res) => Err(RendererError::InvalidUniform(res.name.clone())),
+      None => Ok(())
+    }
+  }
+}
+
+// an active uniform outside of blocks. arrays have num_components elements, location is the gl location
+// or the vulkan push constant offset
+#[derive(Clone, Debug)]
+pub struct UniformReflection{
+  pub name: String,
+  pub info: UniformType,
+  pub location: i32
+}
+
+#[derive(Clone, Debug)]
+pub struct UniformBlockReflection{
+  pub name: String,
+  pub size: u32,
+  pub members: Vec<UniformBlockMember>
+}
+
+// unit is the texture unit or the vulkan binding of the texture
+#[derive(Clone, Debug)]
+pub struct SamplerReflection{
+  pub name: String,
+  pub dimension: TextureDimension,
+  pub count: u32,
+  pub unit: u32
+}
+
+#[derive(Clone, Debug)]
+pub struct AttributeReflection{
+  pub name: String,
+  pub info: UniformType,
+  pub location: u32
+}
+
+// what a program uses once linked, names of arrays are without [0]. types without a UniformType such as
+// non-square matrices and structs are left out
+#[derive(Clone, Debug, Default)]
+pub struct ProgramReflection{
+  pub uniforms: Vec<UniformReflection>,
+  pub blocks: Vec<UniformBlockReflection>,
+  pub samplers: Vec<SamplerReflection>,
+  pub attributes: Vec<AttributeReflection>
+}
+
+#[allow(dead_code)]
+impl ProgramReflection{
+  pub fn find_uniform(&self, a_name: &str) -> Option<&UniformReflection>{
+    self.uniforms.iter().find(|uniform| uniform.name == a_name)
+  }
+
+  pub fn find_block(&self, a_name: &str) -> Option<&UniformBlockReflection>{
+    self.blocks.iter().find(|block| block.name == a_name)
+  }
+
+  // elements of sampler arrays are found by their index, as u_textures[1]
+  pub fn find_sampler(&self, a_name: &str) -> Option<&SamplerReflection>{
+    let name = match a_name.split_once('[') {
+      Some((res, _index)) => res,
+      None => a_name
+    };
+
+    self.samplers.iter().find(|sampler| sampler.name == name)
+  }
+
+  pub fn find_attribute(&self, a_name: &str) -> Option<&AttributeReflection>{
+    self.attributes.iter().find(|attribute| attribute.name == a_name)
+  }
+
+  // the uniform has to be active with the type of the value, arrays can be set with fewer elements
+  pub fn validate_uniform(&self, a_name: &str, a_info: &UniformType) -> Result<(), RendererError>{
+    match self.find_uniform(a_name) {
+      Some(res) if res.info.element_type == a_info.element_type && res.info.container_type == a_info.container_type && a_info.num_components <= res.info.num_components => Ok(()),
+      Some(res) => {
+        eprintln!("Error uniform {} is {:?} {:?}[{}], not {:?} {:?}[{}]", a_name,
+          res.info.element_type, res.info.container_type, res.info.num_components,
+          a_info.element_type, a_info.container_type, a_info.num_components);
+        Err(RendererError::InvalidUniform(String::from(a_name)))
+      },
+      None => Err(RendererError::InvalidUniform(String::from(a_name)))
+    }
+  }
 }
 
 pub trait UniformShader{
@@ -141,6 +451,14 @@ impl Uniform for UniformMaterial {
   fn get_f32(&self) -> f32{
     self.data.get::<f32>()
   }
+
+  fn get_data(&self) -> &UniformData{
+    &self.data
+  }
+
+  fn get_data_mut(&mut self) -> &mut UniformData{
+    &mut self.data
+  }
   
   fn get_name(&self) -> &str{
     &self.name.get_name()
@@ -158,13 +476,30 @@ pub trait Uniform{
 
   fn get_f32(&self) -> f32;
 
+  fn get_data(&self) -> &UniformData;
+  // marks the uniform as modified so it is uploaded on the next draw
+  fn get_data_mut(&mut self) -> &mut UniformData;
+
   fn get_name(&self) -> &str;
   fn set_name(&mut self, a_name: &str);
 
 }
 
+impl dyn Uniform{
+  // T has to be the type the uniform was created with
+  pub fn set<T: 'static + GetType>(&mut self, a: T){
+    self.get_data_mut().set::<T>(a);
+  }
+
+  pub fn get<T: 'static + GetType + Copy>(&self) -> T{
+    self.get_data().get::<T>()
+  }
+}
+
 pub trait Sampler{
   fn any(&self) -> &dyn std::any::Any;
 
+  // the name of the sampler uniform in the program the texture is bound to
   fn set_name(&mut self, a_name: &str);
+  fn get_name(&self) -> &str;
 }
@@ -1 +1,2 @@
 pub mod renderer_vulkan;
+pub mod shader_vulkan;
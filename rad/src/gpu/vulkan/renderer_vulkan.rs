@@ -1,5 +1,13 @@
+extern crate ash;
 
+use ash::vk;
+use ash::vk::Handle;
+use ash::extensions::khr;
 use glam::*;
+use std::cell::{Cell, RefCell};
+use std::ffi::{CStr, CString};
+use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::gpu::renderer::*;
 use crate::gpu::renderer_types::*;
@@ -7,203 +15,3534 @@ use crate::gpu::material::*;
 use crate::gpu::camera::*;
 use crate::gpu::uniforms::*;
 use crate::gpu::image::*;
-use std::rc::Rc;
+use crate::gpu::compressed::*;
+use crate::gpu::shader_log::*;
+use crate::gpu::vulkan::shader_vulkan::*;
+use crate::gui::window::Window;
 
-pub struct SamplerVulkan{
-  name: String,
+// Everything is drawn to an offscreen color image the same as the headless gl renderer, with a window
+// end_frame blits it to the swapchain. Images are stored bottom row first like gl so render targets are
+// sampled and read back the same way, the image is flipped when copied to the swapchain
+
+const COLOR_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+const DEPTH_FORMATS: [vk::Format; 3] = [vk::Format::D24_UNORM_S8_UINT, vk::Format::D32_SFLOAT_S8_UINT, vk::Format::D16_UNORM_S8_UINT];
+
+// sets per descriptor pool, another pool is added when a frame needs more
+const DESCRIPTOR_POOL_SETS: u32 = 256;
+
+// entry keeps the vulkan library loaded
+#[allow(dead_code)]
+pub struct InstanceVulkan {
+  entry: ash::Entry,
+  instance: ash::Instance,
+  surface_loader: khr::Surface,
+  // null when headless
+  surface: vk::SurfaceKHR,
+  version_major: i32,
+  version_minor: i32,
 }
 
-impl Sampler for SamplerVulkan {
-  fn any(&self) -> &dyn std::any::Any{
-    self
+impl InstanceVulkan {
+  pub fn new(a_window: Option<&Arc<Window>>, a_max_version: (i32, i32)) -> Result<InstanceVulkan, RendererError> {
+    let entry = match unsafe { ash::Entry::load() } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::UnsupportedAPI)
+    };
+
+    // loaders older than 1.1 do not report a version
+    let instance_version = match entry.try_enumerate_instance_version() {
+      Ok(Some(res)) => res,
+      Ok(None) => vk::API_VERSION_1_0,
+      Err(_res) => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let (version_major, version_minor) = std::cmp::min(
+      (vk::api_version_major(instance_version) as i32, vk::api_version_minor(instance_version) as i32),
+      a_max_version);
+
+    let extensions = match a_window {
+      Some(window) => match window.window.lock().unwrap().inner.vulkan_instance_extensions() {
+        Ok(res) => res,
+        Err(_res) => return Err(RendererError::UnsupportedAPI)
+      },
+      None => Vec::new()
+    };
+
+    let extension_names: Vec<CString> = extensions.iter().map(|name| CString::new(*name).unwrap()).collect();
+    let extension_pointers: Vec<*const std::os::raw::c_char> = extension_names.iter().map(|name| name.as_ptr()).collect();
+
+    let app_name = CString::new("rad").unwrap();
+
+    let app_info = vk::ApplicationInfo::builder()
+      .application_name(&app_name)
+      .engine_name(&app_name)
+      .api_version(vk::make_api_version(0, version_major as u32, version_minor as u32, 0));
+
+    let create_info = vk::InstanceCreateInfo::builder()
+      .application_info(&app_info)
+      .enabled_extension_names(&extension_pointers);
+
+    let instance = match unsafe { entry.create_instance(&create_info, None) } {
+      Ok(res) => res,
+      Err(vk::Result::ERROR_INCOMPATIBLE_DRIVER) => return Err(RendererError::InvalidVersion),
+      Err(_res) => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let surface_loader = khr::Surface::new(&entry, &instance);
+
+    let mut result = InstanceVulkan{
+      entry: entry,
+      instance: instance,
+      surface_loader: surface_loader,
+      surface: vk::SurfaceKHR::null(),
+      version_major: version_major,
+      version_minor: version_minor
+    };
+
+    if let Some(window) = a_window {
+      let surface = match window.window.lock().unwrap().inner.vulkan_create_surface(result.instance.handle().as_raw() as sdl2::video::VkInstance) {
+        Ok(res) => res,
+        Err(_res) => return Err(RendererError::UnsupportedAPI)
+      };
+
+      result.surface = vk::SurfaceKHR::from_raw(surface as u64);
+    }
+
+    Ok(result)
   }
+}
 
-  fn set_name(&mut self, a_name: &str){
-    self.name = String::from(a_name);
+impl Drop for InstanceVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      if self.surface != vk::SurfaceKHR::null() {
+        self.surface_loader.destroy_surface(self.surface, None);
+      }
+      self.instance.destroy_instance(None);
+    }
   }
 }
 
+// Shared by the renderer and every resource, so the device outlives anything created from it
 #[allow(dead_code)]
-pub struct VerticesVulkan {
-  id: i32
+pub struct DeviceVulkan {
+  instance: InstanceVulkan,
+  physical_device: vk::PhysicalDevice,
+  device: ash::Device,
+  queue: vk::Queue,
+  queue_family: u32,
+  command_pool: vk::CommandPool,
+  memory_properties: vk::PhysicalDeviceMemoryProperties,
+  // VK_EXT_depth_clip_control, keeps gl's -1 to 1 clip space depth
+  depth_clip_control: bool,
+  // 0 when the sampler_anisotropy feature is unavailable
+  max_anisotropy: f32,
+  // block compression features, each is enabled when available
+  compression_bc: bool,
+  compression_etc2: bool,
+  compression_astc: bool,
+  version_major: i32,
+  version_minor: i32,
 }
 
-impl Vertices for VerticesVulkan {
-  fn any(&self) -> &dyn std::any::Any{
-    self
+#[allow(dead_code)]
+impl DeviceVulkan {
+  pub fn new(a_instance: InstanceVulkan, a_min_version: (i32, i32)) -> Result<DeviceVulkan, RendererError> {
+    if (a_instance.version_major, a_instance.version_minor) < a_min_version {
+      return Err(RendererError::InvalidVersion)
+    }
+
+    let physical_devices = match unsafe { a_instance.instance.enumerate_physical_devices() } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::UnsupportedAPI)
+    };
+
+    // prefer a discrete gpu, then integrated, then anything else such as lavapipe
+    let mut selected: Option<(vk::PhysicalDevice, u32, vk::PhysicalDeviceProperties)> = None;
+    let mut selected_score = -1;
+
+    for physical_device in physical_devices {
+      let properties = unsafe { a_instance.instance.get_physical_device_properties(physical_device) };
+
+      let version = (vk::api_version_major(properties.api_version) as i32, vk::api_version_minor(properties.api_version) as i32);
+      if version < a_min_version {
+        continue;
+      }
+
+      let queue_families = unsafe { a_instance.instance.get_physical_device_queue_family_properties(physical_device) };
+
+      let queue_family = queue_families.iter().enumerate().position(|(i, family)| {
+        let present = a_instance.surface == vk::SurfaceKHR::null() || unsafe {
+          a_instance.surface_loader.get_physical_device_surface_support(physical_device, i as u32, a_instance.surface).unwrap_or(false)
+        };
+        family.queue_flags.contains(vk::QueueFlags::GRAPHICS) && present
+      });
+
+      let queue_family = match queue_family {
+        Some(res) => res as u32,
+        None => continue
+      };
+
+      let score = match properties.device_type {
+        vk::PhysicalDeviceType::DISCRETE_GPU => 3,
+        vk::PhysicalDeviceType::INTEGRATED_GPU => 2,
+        vk::PhysicalDeviceType::VIRTUAL_GPU => 1,
+        _ => 0
+      };
+
+      if score > selected_score {
+        selected_score = score;
+        selected = Some((physical_device, queue_family, properties));
+      }
+    }
+
+    let (physical_device, queue_family, properties) = match selected {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidVersion)
+    };
+
+    let (version_major, version_minor) = std::cmp::min(
+      (vk::api_version_major(properties.api_version) as i32, vk::api_version_minor(properties.api_version) as i32),
+      (a_instance.version_major, a_instance.version_minor));
+
+    let available_extensions = match unsafe { a_instance.instance.enumerate_device_extension_properties(physical_device) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let has_extension = |a_name: &CStr| available_extensions.iter().any(|extension| {
+      a_name == unsafe { CStr::from_ptr(extension.extension_name.as_ptr()) }
+    });
+
+    let mut extensions: Vec<*const std::os::raw::c_char> = Vec::new();
+
+    if a_instance.surface != vk::SurfaceKHR::null() {
+      extensions.push(khr::Swapchain::name().as_ptr());
+    }
+
+    // querying the feature needs vulkan 1.1
+    let mut depth_clip_control_features = vk::PhysicalDeviceDepthClipControlFeaturesEXT::default();
+    let mut depth_clip_control = false;
+
+    if (version_major, version_minor) >= (1, 1) && has_extension(vk::ExtDepthClipControlFn::name()) {
+      let mut features = vk::PhysicalDeviceFeatures2::builder().push_next(&mut depth_clip_control_features);
+      unsafe { a_instance.instance.get_physical_device_features2(physical_device, &mut features) };
+
+      depth_clip_control = depth_clip_control_features.depth_clip_control == vk::TRUE;
+      if depth_clip_control {
+        extensions.push(vk::ExtDepthClipControlFn::name().as_ptr());
+      }
+    }
+
+    let supported_features = unsafe { a_instance.instance.get_physical_device_features(physical_device) };
+
+    let max_anisotropy = match supported_features.sampler_anisotropy == vk::TRUE {
+      true => properties.limits.max_sampler_anisotropy,
+      false => 0.0
+    };
+
+    let compression_bc = supported_features.texture_compression_bc == vk::TRUE;
+    let compression_etc2 = supported_features.texture_compression_etc2 == vk::TRUE;
+    let compression_astc = supported_features.texture_compression_astc_ldr == vk::TRUE;
+
+    let enabled_features = vk::PhysicalDeviceFeatures::builder()
+      .sampler_anisotropy(max_anisotropy > 0.0)
+      .texture_compression_bc(compression_bc)
+      .texture_compression_etc2(compression_etc2)
+      .texture_compression_astc_ldr(compression_astc)
+      .build();
+
+    let queue_priorities = [1.0];
+    let queue_infos = [vk::DeviceQueueCreateInfo::builder()
+      .queue_family_index(queue_family)
+      .queue_priorities(&queue_priorities)
+      .build()];
+
+    let mut device_info = vk::DeviceCreateInfo::builder()
+      .queue_create_infos(&queue_infos)
+      .enabled_extension_names(&extensions)
+      .enabled_features(&enabled_features);
+
+    if depth_clip_control {
+      device_info = device_info.push_next(&mut depth_clip_control_features);
+    }
+
+    let device = match unsafe { a_instance.instance.create_device(physical_device, &device_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let queue = unsafe { device.get_device_queue(queue_family, 0) };
+
+    let pool_info = vk::CommandPoolCreateInfo::builder()
+      .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
+      .queue_family_index(queue_family);
+
+    let command_pool = match unsafe { device.create_command_pool(&pool_info, None) } {
+      Ok(res) => res,
+      Err(_res) => {
+        unsafe { device.destroy_device(None) };
+        return Err(RendererError::Error)
+      }
+    };
+
+    let memory_properties = unsafe { a_instance.instance.get_physical_device_memory_properties(physical_device) };
+
+    Ok(DeviceVulkan{
+      instance: a_instance,
+      physical_device: physical_device,
+      device: device,
+      queue: queue,
+      queue_family: queue_family,
+      command_pool: command_pool,
+      memory_properties: memory_properties,
+      depth_clip_control: depth_clip_control,
+      max_anisotropy: max_anisotropy,
+      compression_bc: compression_bc,
+      compression_etc2: compression_etc2,
+      compression_astc: compression_astc,
+      version_major: version_major,
+      version_minor: version_minor
+    })
   }
-}
 
-#[allow(dead_code)]
-pub struct GeometryVulkan {
-  id: i32
-}
+  pub fn find_memory_type(&self, a_type_bits: u32, a_flags: vk::MemoryPropertyFlags) -> Option<u32> {
+    (0..self.memory_properties.memory_type_count).find(|i| {
+      (a_type_bits & (1 << i)) != 0 &&
+        self.memory_properties.memory_types[*i as usize].property_flags.contains(a_flags)
+    })
+  }
 
-impl Geometry for GeometryVulkan {
-  fn any(&self) -> &dyn std::any::Any{
-    self
+  fn allocate_memory(&self, a_requirements: vk::MemoryRequirements, a_flags: vk::MemoryPropertyFlags) -> Result<vk::DeviceMemory, RendererError> {
+    let memory_type = match self.find_memory_type(a_requirements.memory_type_bits, a_flags) {
+      Some(res) => res,
+      None => return Err(RendererError::Error)
+    };
+
+    let allocate_info = vk::MemoryAllocateInfo::builder()
+      .allocation_size(a_requirements.size)
+      .memory_type_index(memory_type);
+
+    match unsafe { self.device.allocate_memory(&allocate_info, None) } {
+      Ok(res) => Ok(res),
+      Err(_res) => Err(RendererError::Error)
+    }
   }
-}
 
-#[allow(dead_code)]
-pub struct TextureVulkan {
-  id: i32
+  pub fn begin_single_time_commands(&self) -> Result<vk::CommandBuffer, RendererError> {
+    let allocate_info = vk::CommandBufferAllocateInfo::builder()
+      .command_pool(self.command_pool)
+      .level(vk::CommandBufferLevel::PRIMARY)
+      .command_buffer_count(1);
+
+    let command_buffer = match unsafe { self.device.allocate_command_buffers(&allocate_info) } {
+      Ok(res) => res[0],
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    let begin_info = vk::CommandBufferBeginInfo::builder()
+      .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+    if unsafe { self.device.begin_command_buffer(command_buffer, &begin_info) }.is_err() {
+      unsafe { self.device.free_command_buffers(self.command_pool, &[command_buffer]) };
+      return Err(RendererError::Error)
+    }
+
+    Ok(command_buffer)
+  }
+
+  // submits and waits until the commands have completed
+  pub fn end_single_time_commands(&self, a_command_buffer: vk::CommandBuffer) -> Result<(), RendererError> {
+    let command_buffers = [a_command_buffer];
+    let submit_info = vk::SubmitInfo::builder().command_buffers(&command_buffers);
+
+    let result = unsafe {
+      self.device.end_command_buffer(a_command_buffer)
+        .and_then(|_| self.device.queue_submit(self.queue, &[submit_info.build()], vk::Fence::null()))
+        .and_then(|_| self.device.queue_wait_idle(self.queue))
+    };
+
+    unsafe { self.device.free_command_buffers(self.command_pool, &command_buffers) };
+
+    match result {
+      Ok(_res) => Ok(()),
+      Err(_res) => Err(RendererError::Error)
+    }
+  }
+
+  pub fn cmd_image_barrier(&self,
+    a_command_buffer: vk::CommandBuffer,
+    a_image: vk::Image,
+    a_range: vk::ImageSubresourceRange,
+    a_layouts: (vk::ImageLayout, vk::ImageLayout),
+    a_access: (vk::AccessFlags, vk::AccessFlags),
+    a_stages: (vk::PipelineStageFlags, vk::PipelineStageFlags))
+  {
+    let barrier = vk::ImageMemoryBarrier::builder()
+      .old_layout(a_layouts.0)
+      .new_layout(a_layouts.1)
+      .src_access_mask(a_access.0)
+      .dst_access_mask(a_access.1)
+      .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+      .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+      .image(a_image)
+      .subresource_range(a_range);
+
+    unsafe {
+      self.device.cmd_pipeline_barrier(a_command_buffer, a_stages.0, a_stages.1,
+        vk::DependencyFlags::empty(), &[], &[], &[barrier.build()]);
+    }
+  }
 }
 
-impl Texture for TextureVulkan {
-  fn any(&self) -> &dyn std::any::Any{
-    self
+impl Drop for DeviceVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      let _ = self.device.device_wait_idle();
+      self.device.destroy_command_pool(self.command_pool, None);
+      self.device.destroy_device(None);
+    }
   }
 }
 
-#[allow(dead_code)]
-pub struct UniformVulkan {
-  name: UniformName,
-  data: UniformData,
+pub struct BufferVulkan {
+  device: Rc<DeviceVulkan>,
+  buffer: vk::Buffer,
+  memory: vk::DeviceMemory,
+  size: vk::DeviceSize,
 }
 
-impl Uniform for UniformVulkan {
-  fn any(&mut self) -> &mut dyn std::any::Any{
-    self
-  }
+impl BufferVulkan {
+  pub fn new(a_device: &Rc<DeviceVulkan>, a_size: vk::DeviceSize, a_usage: vk::BufferUsageFlags, a_flags: vk::MemoryPropertyFlags) -> Result<BufferVulkan, RendererError> {
+    let buffer_info = vk::BufferCreateInfo::builder()
+      .size(std::cmp::max(a_size, 1))
+      .usage(a_usage)
+      .sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+    let buffer = match unsafe { a_device.device.create_buffer(&buffer_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    let requirements = unsafe { a_device.device.get_buffer_memory_requirements(buffer) };
+
+    let memory = match a_device.allocate_memory(requirements, a_flags) {
+      Ok(res) => res,
+      Err(res) => {
+        unsafe { a_device.device.destroy_buffer(buffer, None) };
+        return Err(res)
+      }
+    };
 
-  fn set_f32(&mut self, _a: f32){
+    let result = BufferVulkan{device: a_device.clone(), buffer: buffer, memory: memory, size: a_size};
+
+    if unsafe { a_device.device.bind_buffer_memory(buffer, memory, 0) }.is_err() {
+      return Err(RendererError::Error)
+    }
+
+    Ok(result)
   }
 
-  fn get_f32(&self) -> f32{
-    0.0
+  // buffer must be host visible and coherent
+  pub fn write(&self, a_data: &[u8]) -> Result<(), RendererError> {
+    let size = std::cmp::min(a_data.len() as vk::DeviceSize, self.size);
+
+    unsafe {
+      let mapped = match self.device.device.map_memory(self.memory, 0, size, vk::MemoryMapFlags::empty()) {
+        Ok(res) => res,
+        Err(_res) => return Err(RendererError::Error)
+      };
+      std::ptr::copy_nonoverlapping(a_data.as_ptr(), mapped as *mut u8, size as usize);
+      self.device.device.unmap_memory(self.memory);
+    }
+
+    Ok(())
   }
- 
-  fn get_name(&self) -> &str{
-    &self.name.get_name()
+
+  pub fn read(&self, a_data: &mut [u8]) -> Result<(), RendererError> {
+    let size = std::cmp::min(a_data.len() as vk::DeviceSize, self.size);
+
+    unsafe {
+      let mapped = match self.device.device.map_memory(self.memory, 0, size, vk::MemoryMapFlags::empty()) {
+        Ok(res) => res,
+        Err(_res) => return Err(RendererError::Error)
+      };
+      std::ptr::copy_nonoverlapping(mapped as *const u8, a_data.as_mut_ptr(), size as usize);
+      self.device.device.unmap_memory(self.memory);
+    }
+
+    Ok(())
   }
-  
-  fn set_name(&mut self, a_name: &str){
-    self.name.set_name(a_name);
+}
+
+impl Drop for BufferVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      self.device.device.destroy_buffer(self.buffer, None);
+      self.device.device.free_memory(self.memory, None);
+    }
   }
 }
 
 #[allow(dead_code)]
-pub struct UniformShaderVulkan {
-  name: UniformName,
-  id: i32 //todo check type
+pub struct ImageVulkan {
+  device: Rc<DeviceVulkan>,
+  image: vk::Image,
+  memory: vk::DeviceMemory,
+  view: vk::ImageView,
+  format: vk::Format,
+  width: u32,
+  height: u32,
+  mip_levels: u32,
+  // 6 for cubemaps, 1 for 3d images which use the depth instead
+  array_layers: u32,
 }
 
-impl UniformShader for UniformShaderVulkan {
-  fn any(&mut self) -> &mut dyn std::any::Any{
-    self
+impl ImageVulkan {
+  pub fn new(
+    a_device: &Rc<DeviceVulkan>,
+    a_width: u32,
+    a_height: u32,
+    a_mip_levels: u32,
+    a_format: vk::Format,
+    a_usage: vk::ImageUsageFlags,
+    a_aspect: vk::ImageAspectFlags) -> Result<ImageVulkan, RendererError>
+  {
+    ImageVulkan::new_dimension(a_device, a_width, a_height, 1, TextureDimension::D2, a_mip_levels, a_format, a_usage, a_aspect)
   }
-}
 
-pub struct RendererVulkan {
-  pub version_major: i32,
+  // a_layers is the depth of 3d images, the number of layers of arrays and 6 for cubemaps
+  pub fn new_dimension(
+    a_device: &Rc<DeviceVulkan>,
+    a_width: u32,
+    a_height: u32,
+    a_layers: u32,
+    a_dimension: TextureDimension,
+    a_mip_levels: u32,
+    a_format: vk::Format,
+    a_usage: vk::ImageUsageFlags,
+    a_aspect: vk::ImageAspectFlags) -> Result<ImageVulkan, RendererError>
+  {
+    let (image_type, view_type, depth, array_layers, flags) = match a_dimension {
+      TextureDimension::D2 => (vk::ImageType::TYPE_2D, vk::ImageViewType::TYPE_2D, 1, 1, vk::ImageCreateFlags::empty()),
+      TextureDimension::D3 => (vk::ImageType::TYPE_3D, vk::ImageViewType::TYPE_3D, a_layers, 1, vk::ImageCreateFlags::empty()),
+      TextureDimension::Cube => (vk::ImageType::TYPE_2D, vk::ImageViewType::CUBE, 1, 6, vk::ImageCreateFlags::CUBE_COMPATIBLE),
+      TextureDimension::D2Array => (vk::ImageType::TYPE_2D, vk::ImageViewType::TYPE_2D_ARRAY, 1, a_layers, vk::ImageCreateFlags::empty())
+    };
 
-  clear_color: Vec4,
-  clear_depth: f32,
-  clear_stencil: i32
-}
+    let image_info = vk::ImageCreateInfo::builder()
+      .flags(flags)
+      .image_type(image_type)
+      .format(a_format)
+      .extent(vk::Extent3D{width: a_width, height: a_height, depth: depth})
+      .mip_levels(a_mip_levels)
+      .array_layers(array_layers)
+      .samples(vk::SampleCountFlags::TYPE_1)
+      .tiling(vk::ImageTiling::OPTIMAL)
+      .usage(a_usage)
+      .sharing_mode(vk::SharingMode::EXCLUSIVE)
+      .initial_layout(vk::ImageLayout::UNDEFINED);
 
-#[allow(dead_code)]
-impl Renderer for RendererVulkan {
-  fn name(&self) -> String{
-    String::from("Vulkan")
-  }
+    let image = match unsafe { a_device.device.create_image(&image_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
 
-  fn get_type(&self) -> RendererType{
-    RendererType::Vulkan
-  }
+    let requirements = unsafe { a_device.device.get_image_memory_requirements(image) };
 
-  fn begin_frame(&mut self, _clear: RendererClearType){}
-  fn end_frame(&mut self){}
+    let memory = match a_device.allocate_memory(requirements, vk::MemoryPropertyFlags::DEVICE_LOCAL) {
+      Ok(res) => res,
+      Err(res) => {
+        unsafe { a_device.device.destroy_image(image, None) };
+        return Err(res)
+      }
+    };
 
-  //clear immediatly
-  //= RendererClearColor | RendererClearDepth | RendererClearStencil
-  fn clear(&mut self, _clear: RendererClearType){}
+    let mut result = ImageVulkan{
+      device: a_device.clone(),
+      image: image,
+      memory: memory,
+      view: vk::ImageView::null(),
+      format: a_format,
+      width: a_width,
+      height: a_height,
+      mip_levels: a_mip_levels,
+      array_layers: array_layers
+    };
 
-  //Get and set clear values may be called before BeginFrame
-  fn set_clear_color(&mut self, _color: Vec4){}
-  fn set_clear_depth(&mut self, _depth: f32){}
-  fn set_clear_stencil(&mut self, _stencil: i32){}
-  fn get_clear_color(&self) -> Vec4{
-    self.clear_color
+    if unsafe { a_device.device.bind_image_memory(image, memory, 0) }.is_err() {
+      return Err(RendererError::Error)
+    }
+
+    let view_info = vk::ImageViewCreateInfo::builder()
+      .image(image)
+      .view_type(view_type)
+      .format(a_format)
+      .subresource_range(result.get_range(a_aspect));
+
+    result.view = match unsafe { a_device.device.create_image_view(&view_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    Ok(result)
   }
-  fn get_clear_depth(&self) -> f32{
-    self.clear_depth
+
+  // rgba8 pixels, the first row is at v = 0 the same as gl
+  pub fn new_rgba8(a_device: &Rc<DeviceVulkan>, a_pixels: &[u8], a_width: u32, a_height: u32, a_mipmaps: bool) -> Result<ImageVulkan, RendererError> {
+    ImageVulkan::new_pixels(a_device, a_pixels, a_width, a_height, 1, TextureDimension::D2, COLOR_FORMAT, vk::ImageAspectFlags::COLOR, a_mipmaps)
   }
 
-  fn get_clear_stencil(&self) -> i32{
-    self.clear_stencil
+  // pixels tightly packed in the format with every layer after the other. mipmaps are only generated for color images
+  // that aren't 3d
+  pub fn new_pixels(
+    a_device: &Rc<DeviceVulkan>,
+    a_pixels: &[u8],
+    a_width: u32,
+    a_height: u32,
+    a_layers: u32,
+    a_dimension: TextureDimension,
+    a_format: vk::Format,
+    a_aspect: vk::ImageAspectFlags,
+    a_mipmaps: bool) -> Result<ImageVulkan, RendererError>
+  {
+    let properties = unsafe { a_device.instance.instance.get_physical_device_format_properties(a_device.physical_device, a_format) };
+
+    // mipmaps are generated with linear blits
+    let mip_levels = match a_mipmaps && a_aspect == vk::ImageAspectFlags::COLOR && a_dimension != TextureDimension::D3 &&
+      properties.optimal_tiling_features.contains(vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR)
+    {
+      true => 32 - std::cmp::max(a_width, a_height).leading_zeros(),
+      false => 1
+    };
+
+    let image = match ImageVulkan::new_dimension(a_device, a_width, a_height, a_layers, a_dimension, mip_levels, a_format,
+      vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::TRANSFER_SRC, a_aspect)
+    {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let staging = match BufferVulkan::new(a_device, a_pixels.len() as vk::DeviceSize,
+      vk::BufferUsageFlags::TRANSFER_SRC,
+      vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT)
+    {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    match staging.write(a_pixels) {
+      Ok(_res) => {},
+      Err(res) => return Err(res)
+    };
+
+    let command_buffer = match a_device.begin_single_time_commands() {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let range = image.get_range(a_aspect);
+
+    a_device.cmd_image_barrier(command_buffer, image.image, range,
+      (vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL),
+      (vk::AccessFlags::empty(), vk::AccessFlags::TRANSFER_WRITE),
+      (vk::PipelineStageFlags::TOP_OF_PIPE, vk::PipelineStageFlags::TRANSFER));
+
+    let region = vk::BufferImageCopy{
+      buffer_offset: 0,
+      buffer_row_length: 0,
+      buffer_image_height: 0,
+      image_subresource: vk::ImageSubresourceLayers{aspect_mask: a_aspect, mip_level: 0, base_array_layer: 0, layer_count: image.array_layers},
+      image_offset: vk::Offset3D{x: 0, y: 0, z: 0},
+      image_extent: vk::Extent3D{width: a_width, height: a_height, depth: a_layers / image.array_layers}
+    };
+
+    unsafe {
+      a_device.device.cmd_copy_buffer_to_image(command_buffer, staging.buffer, image.image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &[region]);
+    }
+
+    let mut mip_width = a_width as i32;
+    let mut mip_height = a_height as i32;
+
+    for level in 1..mip_levels {
+      let src_range = vk::ImageSubresourceRange{base_mip_level: level - 1, level_count: 1, ..range};
+
+      a_device.cmd_image_barrier(command_buffer, image.image, src_range,
+        (vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::TRANSFER_SRC_OPTIMAL),
+        (vk::AccessFlags::TRANSFER_WRITE, vk::AccessFlags::TRANSFER_READ),
+        (vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::TRANSFER));
+
+      let next_width = std::cmp::max(mip_width / 2, 1);
+      let next_height = std::cmp::max(mip_height / 2, 1);
+
+      let blit = vk::ImageBlit{
+        src_subresource: vk::ImageSubresourceLayers{aspect_mask: vk::ImageAspectFlags::COLOR, mip_level: level - 1, base_array_layer: 0, layer_count: image.array_layers},
+        src_offsets: [vk::Offset3D{x: 0, y: 0, z: 0}, vk::Offset3D{x: mip_width, y: mip_height, z: 1}],
+        dst_subresource: vk::ImageSubresourceLayers{aspect_mask: vk::ImageAspectFlags::COLOR, mip_level: level, base_array_layer: 0, layer_count: image.array_layers},
+        dst_offsets: [vk::Offset3D{x: 0, y: 0, z: 0}, vk::Offset3D{x: next_width, y: next_height, z: 1}]
+      };
+
+      unsafe {
+        a_device.device.cmd_blit_image(command_buffer,
+          image.image, vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+          image.image, vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+          &[blit], vk::Filter::LINEAR);
+      }
+
+      a_device.cmd_image_barrier(command_buffer, image.image, src_range,
+        (vk::ImageLayout::TRANSFER_SRC_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL),
+        (vk::AccessFlags::TRANSFER_READ, vk::AccessFlags::SHADER_READ),
+        (vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::FRAGMENT_SHADER));
+
+      mip_width = next_width;
+      mip_height = next_height;
+    }
+
+    let last_range = vk::ImageSubresourceRange{base_mip_level: mip_levels - 1, level_count: 1, ..range};
+
+    a_device.cmd_image_barrier(command_buffer, image.image, last_range,
+      (vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL),
+      (vk::AccessFlags::TRANSFER_WRITE, vk::AccessFlags::SHADER_READ),
+      (vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::FRAGMENT_SHADER));
+
+    match a_device.end_single_time_commands(command_buffer) {
+      Ok(_res) => Ok(image),
+      Err(res) => Err(res)
+    }
   }
 
-  fn set_viewport(&mut self, _pos: IVec2, _size: IVec2){}
+  // every mip level is given, for compressed formats which can't be blitted
+  pub fn new_levels(a_device: &Rc<DeviceVulkan>, a_levels: &[Vec<u8>], a_width: u32, a_height: u32, a_format: vk::Format) -> Result<ImageVulkan, RendererError> {
+    let image = match ImageVulkan::new(a_device, a_width, a_height, a_levels.len() as u32, a_format,
+      vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST, vk::ImageAspectFlags::COLOR)
+    {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
 
-  fn get_viewport_pos(&self) -> IVec2{
-    IVec2::new(0,0)
+    let bytes: Vec<u8> = a_levels.concat();
+
+    let staging = match BufferVulkan::new(a_device, bytes.len() as vk::DeviceSize,
+      vk::BufferUsageFlags::TRANSFER_SRC,
+      vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT)
+    {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    match staging.write(&bytes) {
+      Ok(_res) => {},
+      Err(res) => return Err(res)
+    };
+
+    let command_buffer = match a_device.begin_single_time_commands() {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let range = image.get_range(vk::ImageAspectFlags::COLOR);
+
+    a_device.cmd_image_barrier(command_buffer, image.image, range,
+      (vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL),
+      (vk::AccessFlags::empty(), vk::AccessFlags::TRANSFER_WRITE),
+      (vk::PipelineStageFlags::TOP_OF_PIPE, vk::PipelineStageFlags::TRANSFER));
+
+    let mut offset = 0;
+    let regions: Vec<vk::BufferImageCopy> = a_levels.iter().enumerate().map(|(level, data)| {
+      let region = vk::BufferImageCopy{
+        buffer_offset: offset,
+        buffer_row_length: 0,
+        buffer_image_height: 0,
+        image_subresource: vk::ImageSubresourceLayers{aspect_mask: vk::ImageAspectFlags::COLOR, mip_level: level as u32, base_array_layer: 0, layer_count: 1},
+        image_offset: vk::Offset3D{x: 0, y: 0, z: 0},
+        image_extent: vk::Extent3D{width: std::cmp::max(a_width >> level, 1), height: std::cmp::max(a_height >> level, 1), depth: 1}
+      };
+      offset += data.len() as vk::DeviceSize;
+      region
+    }).collect();
+
+    unsafe {
+      a_device.device.cmd_copy_buffer_to_image(command_buffer, staging.buffer, image.image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &regions);
+    }
+
+    a_device.cmd_image_barrier(command_buffer, image.image, range,
+      (vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL),
+      (vk::AccessFlags::TRANSFER_WRITE, vk::AccessFlags::SHADER_READ),
+      (vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::FRAGMENT_SHADER));
+
+    match a_device.end_single_time_commands(command_buffer) {
+      Ok(_res) => Ok(image),
+      Err(res) => Err(res)
+    }
   }
-  fn get_viewport_size(&self) -> IVec2{
-    IVec2::new(0,0)
+
+  pub fn get_range(&self, a_aspect: vk::ImageAspectFlags) -> vk::ImageSubresourceRange {
+    vk::ImageSubresourceRange{
+      aspect_mask: a_aspect,
+      base_mip_level: 0,
+      level_count: self.mip_levels,
+      base_array_layer: 0,
+      layer_count: self.array_layers
+    }
   }
+}
 
-  fn load_shader(&mut self, _shader_type: ShaderType, _source: &str) -> Result<Box<dyn Shader>, RendererError>{
-    Err(RendererError::Unimplemented)
+impl Drop for ImageVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      if self.view != vk::ImageView::null() {
+        self.device.device.destroy_image_view(self.view, None);
+      }
+      self.device.device.destroy_image(self.image, None);
+      self.device.device.free_memory(self.memory, None);
+    }
   }
+}
 
-  fn load_program_vert_frag(&mut self, _shader_vert: Box<dyn Shader>, _shader_frag: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
-    Err(RendererError::Unimplemented)
+pub struct SamplerObjectVulkan {
+  device: Rc<DeviceVulkan>,
+  sampler: vk::Sampler,
+}
+
+impl SamplerObjectVulkan {
+  pub fn new(a_device: &Rc<DeviceVulkan>, a_desc: &SamplerDesc) -> Result<SamplerObjectVulkan, RendererError> {
+    // without mips only the first level is read, the same as a gl nearest or linear min filter
+    let (mipmap_mode, max_lod) = match a_desc.mip_filter {
+      Some(FilterMode::Nearest) => (vk::SamplerMipmapMode::NEAREST, a_desc.max_lod),
+      Some(FilterMode::Linear) => (vk::SamplerMipmapMode::LINEAR, a_desc.max_lod),
+      None => (vk::SamplerMipmapMode::NEAREST, 0.0)
+    };
+
+    let max_anisotropy = a_desc.max_anisotropy.min(a_device.max_anisotropy);
+
+    let sampler_info = vk::SamplerCreateInfo::builder()
+      .mag_filter(get_filter(a_desc.mag_filter))
+      .min_filter(get_filter(a_desc.min_filter))
+      .mipmap_mode(mipmap_mode)
+      .address_mode_u(get_address_mode(a_desc.wrap_u))
+      .address_mode_v(get_address_mode(a_desc.wrap_v))
+      .address_mode_w(get_address_mode(a_desc.wrap_w))
+      .mip_lod_bias(a_desc.lod_bias)
+      .anisotropy_enable(max_anisotropy > 1.0)
+      .max_anisotropy(max_anisotropy.max(1.0))
+      .compare_enable(a_desc.compare.is_some())
+      .compare_op(get_compare_op(a_desc.compare.unwrap_or(CompareFunction::Never)))
+      .min_lod(a_desc.min_lod)
+      .max_lod(max_lod.max(a_desc.min_lod))
+      .border_color(get_border_color(a_desc.border_color));
+
+    match unsafe { a_device.device.create_sampler(&sampler_info, None) } {
+      Ok(res) => Ok(SamplerObjectVulkan{device: a_device.clone(), sampler: res}),
+      Err(_res) => Err(RendererError::Error)
+    }
   }
+}
 
-  fn get_uniform(&mut self, _shader: &mut Box<dyn Program>, a_name: &str) -> Box<dyn UniformShader>{
-    Box::new(UniformShaderVulkan{name: UniformName::new(a_name), id: 0})
+impl Drop for SamplerObjectVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      self.device.device.destroy_sampler(self.sampler, None);
+    }
   }
+}
 
-  //fn set_uniform(&mut self, a_uniform: &Box<dyn Uniform>){}
+pub struct SamplerVulkan{
+  name: String,
+  texture: Rc<dyn Texture>,
+  sampler: Rc<SamplerObjectVulkan>,
+}
 
-  //fn set_texture(&mut self, a_texture: &Box<dyn Texture>){}
+impl Sampler for SamplerVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
 
-  fn gen_buffer_vertex(&mut self, _verts: &std::vec::Vec<f32>) -> Box<dyn Vertices>{
-    Box::new(VerticesVulkan{id: 0})
+  fn set_name(&mut self, a_name: &str){
+    self.name = String::from(a_name);
   }
 
-  fn gen_geometry(&mut self, _buffer: &Box<dyn Vertices>) -> Box<dyn Geometry>{
-    Box::new(GeometryVulkan{id: 0})
+  fn get_name(&self) -> &str{
+    &self.name
   }
+}
 
-  fn gen_mesh(&mut self, a_geometry: Box<dyn Geometry>, a_material: Box<dyn Material>) -> Box<Mesh>{
-    Box::new(Mesh{
-      geometry: a_geometry,
-      material: a_material
-      })
+// Images rendered to by a framebuffer. Targets that can be sampled rest in shader read layouts between render
+// passes, the default target stays in attachment layouts
+pub struct RenderTargetStateVulkan {
+  device: Rc<DeviceVulkan>,
+  width: u32,
+  height: u32,
+  colors: Vec<Rc<ImageVulkan>>,
+  depth: Option<Rc<ImageVulkan>>,
+  // framebuffer views. sampled depth stencil images use a depth only view which is unable to be an attachment
+  views: Vec<vk::ImageView>,
+  framebuffer: vk::Framebuffer,
+  sampled: bool,
+  // compatible render passes, one for each combination of clear flags
+  render_passes: RefCell<Vec<(RendererClearType, vk::RenderPass)>>,
+}
+
+#[allow(dead_code)]
+impl RenderTargetStateVulkan {
+  pub fn new(
+    a_device: &Rc<DeviceVulkan>,
+    a_width: u32,
+    a_height: u32,
+    a_color_formats: &[vk::Format],
+    a_depth_format: Option<vk::Format>,
+    a_sampled: bool) -> Result<RenderTargetStateVulkan, RendererError>
+  {
+    let sampled_usage = match a_sampled {
+      true => vk::ImageUsageFlags::SAMPLED,
+      false => vk::ImageUsageFlags::empty()
+    };
+
+    let mut target = RenderTargetStateVulkan{
+      device: a_device.clone(),
+      width: a_width,
+      height: a_height,
+      colors: Vec::new(),
+      depth: None,
+      views: Vec::new(),
+      framebuffer: vk::Framebuffer::null(),
+      sampled: a_sampled,
+      render_passes: RefCell::new(Vec::new())
+    };
+
+    for format in a_color_formats {
+      match ImageVulkan::new(a_device, a_width, a_height, 1, *format,
+        vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC | sampled_usage, vk::ImageAspectFlags::COLOR)
+      {
+        Ok(res) => target.colors.push(Rc::new(res)),
+        Err(res) => return Err(res)
+      };
+    }
+
+    if let Some(format) = a_depth_format {
+      let aspect = match a_sampled {
+        true => vk::ImageAspectFlags::DEPTH,
+        false => get_depth_aspect(format)
+      };
+
+      match ImageVulkan::new(a_device, a_width, a_height, 1, format,
+        vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | sampled_usage, aspect)
+      {
+        Ok(res) => target.depth = Some(Rc::new(res)),
+        Err(res) => return Err(res)
+      };
+    }
+
+    let attachments: Vec<(vk::Image, vk::Format, vk::ImageAspectFlags)> = target.colors.iter()
+      .map(|image| (image.image, image.format, vk::ImageAspectFlags::COLOR))
+      .chain(target.depth.iter().map(|image| (image.image, image.format, get_depth_aspect(image.format))))
+      .collect();
+
+    for (image, format, aspect) in &attachments {
+      let view_info = vk::ImageViewCreateInfo::builder()
+        .image(*image)
+        .view_type(vk::ImageViewType::TYPE_2D)
+        .format(*format)
+        .subresource_range(vk::ImageSubresourceRange{aspect_mask: *aspect, base_mip_level: 0, level_count: 1, base_array_layer: 0, layer_count: 1});
+
+      match unsafe { a_device.device.create_image_view(&view_info, None) } {
+        Ok(res) => target.views.push(res),
+        Err(_res) => return Err(RendererError::Error)
+      };
+    }
+
+    let render_pass = match target.get_render_pass(RendererClearType::NONE) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let framebuffer_info = vk::FramebufferCreateInfo::builder()
+      .render_pass(render_pass)
+      .attachments(&target.views)
+      .width(a_width)
+      .height(a_height)
+      .layers(1);
+
+    target.framebuffer = match unsafe { a_device.device.create_framebuffer(&framebuffer_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    // render passes load and store in the resting layouts so the images start there
+    let command_buffer = match a_device.begin_single_time_commands() {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    for image in &target.colors {
+      a_device.cmd_image_barrier(command_buffer, image.image, image.get_range(vk::ImageAspectFlags::COLOR),
+        (vk::ImageLayout::UNDEFINED, target.get_color_layout()),
+        (vk::AccessFlags::empty(), vk::AccessFlags::COLOR_ATTACHMENT_WRITE),
+        (vk::PipelineStageFlags::TOP_OF_PIPE, vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT));
+    }
+
+    if let Some(image) = &target.depth {
+      a_device.cmd_image_barrier(command_buffer, image.image, image.get_range(get_depth_aspect(image.format)),
+        (vk::ImageLayout::UNDEFINED, target.get_depth_layout()),
+        (vk::AccessFlags::empty(), vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE),
+        (vk::PipelineStageFlags::TOP_OF_PIPE, vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS));
+    }
+
+    match a_device.end_single_time_commands(command_buffer) {
+      Ok(_res) => Ok(target),
+      Err(res) => Err(res)
+    }
   }
 
+  pub fn get_color_layout(&self) -> vk::ImageLayout {
+    match self.sampled {
+      true => vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+      false => vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+    }
+  }
 
-  fn gen_buffer_texture(&mut self) -> Box<dyn Texture>{
-    Box::new(TextureVulkan{id: 0})
+  pub fn get_depth_layout(&self) -> vk::ImageLayout {
+    match self.sampled {
+      true => vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+      false => vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL
+    }
   }
 
-  fn gen_sampler(&mut self, _texture: Rc<dyn Texture>) -> Box<dyn Sampler>{
-    Box::new(SamplerVulkan{name: String::from("")})
+  // render passes and pipelines are compatible when the attachment formats are the same
+  pub fn get_formats(&self) -> Vec<vk::Format> {
+    self.colors.iter().chain(self.depth.iter()).map(|image| image.format).collect()
   }
 
-  fn load_texture(&mut self, _image: &image::DynamicImage, _texture: &mut Box<dyn Texture>){
+  pub fn get_render_pass(&self, a_clear: RendererClearType) -> Result<vk::RenderPass, RendererError> {
+    if let Some((_clear, render_pass)) = self.render_passes.borrow().iter().find(|(clear, _render_pass)| *clear == a_clear) {
+      return Ok(*render_pass)
+    }
+
+    let load_op = |a_flag: RendererClearType| match (a_clear & a_flag) == a_flag {
+      true => vk::AttachmentLoadOp::CLEAR,
+      false => vk::AttachmentLoadOp::LOAD
+    };
+
+    let mut attachments: Vec<vk::AttachmentDescription> = self.colors.iter().map(|image| {
+      vk::AttachmentDescription::builder()
+        .format(image.format)
+        .samples(vk::SampleCountFlags::TYPE_1)
+        .load_op(load_op(RendererClearType::COLOR))
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(self.get_color_layout())
+        .final_layout(self.get_color_layout())
+        .build()
+    }).collect();
+
+    let color_references: Vec<vk::AttachmentReference> = (0..self.colors.len()).map(|i| {
+      vk::AttachmentReference{attachment: i as u32, layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL}
+    }).collect();
+
+    let depth_reference = vk::AttachmentReference{attachment: self.colors.len() as u32, layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL};
+
+    if let Some(image) = &self.depth {
+      let (stencil_load_op, stencil_store_op) = match get_depth_aspect(image.format).contains(vk::ImageAspectFlags::STENCIL) {
+        true => (load_op(RendererClearType::STENCIL), vk::AttachmentStoreOp::STORE),
+        false => (vk::AttachmentLoadOp::DONT_CARE, vk::AttachmentStoreOp::DONT_CARE)
+      };
+
+      attachments.push(vk::AttachmentDescription::builder()
+        .format(image.format)
+        .samples(vk::SampleCountFlags::TYPE_1)
+        .load_op(load_op(RendererClearType::DEPTH))
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(stencil_load_op)
+        .stencil_store_op(stencil_store_op)
+        .initial_layout(self.get_depth_layout())
+        .final_layout(self.get_depth_layout())
+        .build());
+    }
+
+    let mut subpass = vk::SubpassDescription::builder()
+      .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+      .color_attachments(&color_references);
+
+    if self.depth.is_some() {
+      subpass = subpass.depth_stencil_attachment(&depth_reference);
+    }
+
+    let subpasses = [subpass.build()];
+
+    let attachment_stages = vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS;
+    let attachment_writes = vk::AccessFlags::COLOR_ATTACHMENT_WRITE | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE;
+
+    // previous passes, copies and reads of the images complete before this pass, and reads after it wait for it
+    let dependencies = [
+      vk::SubpassDependency{
+        src_subpass: vk::SUBPASS_EXTERNAL,
+        dst_subpass: 0,
+        src_stage_mask: attachment_stages | vk::PipelineStageFlags::TRANSFER | vk::PipelineStageFlags::FRAGMENT_SHADER,
+        dst_stage_mask: attachment_stages,
+        src_access_mask: attachment_writes,
+        dst_access_mask: attachment_writes | vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ,
+        dependency_flags: vk::DependencyFlags::empty()
+      },
+      vk::SubpassDependency{
+        src_subpass: 0,
+        dst_subpass: vk::SUBPASS_EXTERNAL,
+        src_stage_mask: attachment_stages,
+        dst_stage_mask: vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::TRANSFER,
+        src_access_mask: attachment_writes,
+        dst_access_mask: vk::AccessFlags::SHADER_READ | vk::AccessFlags::TRANSFER_READ,
+        dependency_flags: vk::DependencyFlags::empty()
+      }
+    ];
+
+    let render_pass_info = vk::RenderPassCreateInfo::builder()
+      .attachments(&attachments)
+      .subpasses(&subpasses)
+      .dependencies(&dependencies);
+
+    let render_pass = match unsafe { self.device.device.create_render_pass(&render_pass_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    self.render_passes.borrow_mut().push((a_clear, render_pass));
+
+    Ok(render_pass)
   }
+}
 
-  fn use_program(&mut self, _program: &Box<dyn Program>){}
+impl Drop for RenderTargetStateVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      if self.framebuffer != vk::Framebuffer::null() {
+        self.device.device.destroy_framebuffer(self.framebuffer, None);
+      }
 
-  fn draw_geometry(&mut self, _geometry: &Box<dyn Geometry>){}
-  fn draw_mesh(&mut self, _camera: &Camera, _geometry: &mut Box<Mesh>){}
+      for (_clear, render_pass) in self.render_passes.borrow().iter() {
+        self.device.device.destroy_render_pass(*render_pass, None);
+      }
 
-  fn read_render_buffer(&mut self) -> Image{
-    return Image{width: 0, height: 0, pitch: 0, pixels: std::vec::Vec::<u8>::new()}
+      for view in &self.views {
+        self.device.device.destroy_image_view(*view, None);
+      }
+    }
   }
 }
 
-impl RendererVulkan{
-  pub fn new() -> Result<Self, RendererError>{
-    Ok(Self {
-      version_major: 0,
-      clear_color: Vec4::new(0.0, 0.0, 0.0, 0.0),
-      clear_depth: 1.0,
-      clear_stencil: 0
-    })
+pub struct RenderTargetVulkan {
+  state: Rc<RenderTargetStateVulkan>,
+  colors: Vec<Rc<TextureVulkan>>,
+  depth: Option<Rc<TextureVulkan>>,
+}
+
+impl RenderTarget for RenderTargetVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
   }
+
+  fn get_width(&self) -> u32{
+    self.state.width
+  }
+
+  fn get_height(&self) -> u32{
+    self.state.height
+  }
+
+  fn num_color_attachments(&self) -> usize{
+    self.colors.len()
+  }
+
+  fn get_color_texture(&self, a_index: usize) -> Rc<dyn Texture>{
+    self.colors[a_index].clone()
+  }
+
+  fn get_depth_texture(&self) -> Option<Rc<dyn Texture>>{
+    match &self.depth {
+      Some(res) => Some(res.clone()),
+      None => None
+    }
+  }
+}
+
+// vertex input, topology, attachment formats and the render state are baked into pipelines. the scissor is dynamic
+#[derive(Clone, PartialEq)]
+pub struct PipelineKeyVulkan {
+  formats: Vec<vk::Format>,
+  layout: VertexLayout,
+  topology: PrimitiveTopology,
+  state: RenderState,
+}
+
+// uniforms are push constants. values are kept with the program the same as gl and pushed on draw
+#[allow(dead_code)]
+pub struct ProgramStateVulkan {
+  device: Rc<DeviceVulkan>,
+  set_layout: vk::DescriptorSetLayout,
+  layout: vk::PipelineLayout,
+  stages: Vec<(vk::ShaderStageFlags, Rc<ShaderModuleVulkan>)>,
+  // one pipeline for each combination of attachment formats, vertex layout, topology and render state the program has been drawn with
+  pipelines: RefCell<Vec<(PipelineKeyVulkan, vk::Pipeline)>>,
+  push_constant_stages: vk::ShaderStageFlags,
+  push_constants: RefCell<Vec<u8>>,
+  uniforms: Vec<UniformReflectionVulkan>,
+  textures: Vec<BindingReflectionVulkan>,
+  samplers: Vec<BindingReflectionVulkan>,
+  reflection: ProgramReflection,
+}
+
+impl ProgramStateVulkan {
+  pub fn get_uniform_offset(&self, a_name: &str) -> i32 {
+    match self.uniforms.iter().find(|uniform| uniform.name == a_name) {
+      Some(res) => res.offset as i32,
+      None => -1
+    }
+  }
+
+  // pipelines are only compatible with render passes using the same attachment formats
+  pub fn get_pipeline(&self, a_target: &RenderTargetStateVulkan, a_layout: &VertexLayout, a_topology: PrimitiveTopology, a_state: &RenderState) -> Result<vk::Pipeline, RendererError> {
+    let key = PipelineKeyVulkan{formats: a_target.get_formats(), layout: a_layout.clone(), topology: a_topology, state: RenderState{scissor: None, ..*a_state}};
+
+    if let Some((_key, pipeline)) = self.pipelines.borrow().iter().find(|(existing, _pipeline)| *existing == key) {
+      return Ok(*pipeline)
+    }
+
+    let render_pass = match a_target.get_render_pass(RendererClearType::NONE) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let pipeline = match self.create_pipeline(render_pass, a_target.colors.len(), a_layout, a_topology, a_state) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    self.pipelines.borrow_mut().push((key, pipeline));
+    Ok(pipeline)
+  }
+
+  fn create_pipeline(&self, a_render_pass: vk::RenderPass, a_num_colors: usize, a_layout: &VertexLayout, a_topology: PrimitiveTopology, a_state: &RenderState) -> Result<vk::Pipeline, RendererError> {
+    let entry_point = CString::new("main").unwrap();
+
+    let stages: Vec<vk::PipelineShaderStageCreateInfo> = self.stages.iter().map(|(stage, module)| {
+      vk::PipelineShaderStageCreateInfo::builder()
+        .stage(*stage)
+        .module(module.module)
+        .name(&entry_point)
+        .build()
+    }).collect();
+
+    let (vertex_bindings, vertex_attributes) = match get_vertex_input(a_layout) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let vertex_input = vk::PipelineVertexInputStateCreateInfo::builder()
+      .vertex_binding_descriptions(&vertex_bindings)
+      .vertex_attribute_descriptions(&vertex_attributes);
+
+    let topology = match get_primitive_topology(a_topology) {
+      Some(res) => res,
+      None => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::builder()
+      .topology(topology);
+
+    let mut depth_clip_control = vk::PipelineViewportDepthClipControlCreateInfoEXT::builder()
+      .negative_one_to_one(true);
+
+    let mut viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+      .viewport_count(1)
+      .scissor_count(1);
+
+    if self.device.depth_clip_control {
+      viewport_state = viewport_state.push_next(&mut depth_clip_control);
+    }
+
+    // y is not flipped so gl's counter clockwise winding is clockwise in vulkan's framebuffer coordinates
+    let rasterization = vk::PipelineRasterizationStateCreateInfo::builder()
+      .polygon_mode(vk::PolygonMode::FILL)
+      .cull_mode(match a_state.cull_mode {
+        CullMode::None => vk::CullModeFlags::NONE,
+        CullMode::Front => vk::CullModeFlags::FRONT,
+        CullMode::Back => vk::CullModeFlags::BACK
+      })
+      .front_face(vk::FrontFace::CLOCKWISE)
+      .line_width(1.0);
+
+    let multisample = vk::PipelineMultisampleStateCreateInfo::builder()
+      .rasterization_samples(vk::SampleCountFlags::TYPE_1);
+
+    // gl only writes depth when the test is enabled
+    let depth = a_state.depth.unwrap_or_default();
+    let stencil = a_state.stencil.unwrap_or_default();
+    let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::builder()
+      .depth_test_enable(a_state.depth.is_some())
+      .depth_write_enable(a_state.depth.is_some() && depth.write)
+      .depth_compare_op(get_compare_op(depth.compare))
+      .stencil_test_enable(a_state.stencil.is_some())
+      .front(get_stencil_op_state(&stencil.front, &stencil))
+      .back(get_stencil_op_state(&stencil.back, &stencil));
+
+    let blend = a_state.blend.unwrap_or_default();
+    let blend_attachments = vec![vk::PipelineColorBlendAttachmentState::builder()
+      .blend_enable(a_state.blend.is_some())
+      .src_color_blend_factor(get_blend_factor(blend.color_src))
+      .dst_color_blend_factor(get_blend_factor(blend.color_dst))
+      .color_blend_op(get_blend_op(blend.color_op))
+      .src_alpha_blend_factor(get_blend_factor(blend.alpha_src))
+      .dst_alpha_blend_factor(get_blend_factor(blend.alpha_dst))
+      .alpha_blend_op(get_blend_op(blend.alpha_op))
+      .color_write_mask(vk::ColorComponentFlags::from_raw(a_state.color_write.bits()))
+      .build(); a_num_colors];
+
+    let color_blend = vk::PipelineColorBlendStateCreateInfo::builder()
+      .attachments(&blend_attachments);
+
+    let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state = vk::PipelineDynamicStateCreateInfo::builder()
+      .dynamic_states(&dynamic_states);
+
+    let pipeline_info = vk::GraphicsPipelineCreateInfo::builder()
+      .stages(&stages)
+      .vertex_input_state(&vertex_input)
+      .input_assembly_state(&input_assembly)
+      .viewport_state(&viewport_state)
+      .rasterization_state(&rasterization)
+      .multisample_state(&multisample)
+      .depth_stencil_state(&depth_stencil)
+      .color_blend_state(&color_blend)
+      .dynamic_state(&dynamic_state)
+      .layout(self.layout)
+      .render_pass(a_render_pass)
+      .subpass(0);
+
+    match unsafe { self.device.device.create_graphics_pipelines(vk::PipelineCache::null(), &[pipeline_info.build()], None) } {
+      Ok(res) => Ok(res[0]),
+      Err((_pipelines, res)) => Err(RendererError::ShaderLink(ShaderLog::from_message(None, &res.to_string())))
+    }
+
+  }
+}
+
+impl Drop for ProgramStateVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      for (_key, pipeline) in self.pipelines.borrow().iter() {
+        self.device.device.destroy_pipeline(*pipeline, None);
+      }
+      self.device.device.destroy_pipeline_layout(self.layout, None);
+      self.device.device.destroy_descriptor_set_layout(self.set_layout, None);
+    }
+  }
+}
+
+pub struct ProgramVulkan {
+  state: Rc<ProgramStateVulkan>,
+}
+
+impl Program for ProgramVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_uniform(&self, a_name: &str, a_data: UniformData) -> Box<dyn Uniform>{
+    Box::new(UniformVulkan{
+      name: UniformName::new(a_name),
+      data: a_data,
+      id: self.state.get_uniform_offset(a_name),
+      modified: true
+    })
+  }
+
+  fn get_reflection(&self) -> &ProgramReflection{
+    &self.state.reflection
+  }
+}
+
+// kept by programs to create pipelines for other render targets
+pub struct ShaderModuleVulkan {
+  device: Rc<DeviceVulkan>,
+  module: vk::ShaderModule,
+}
+
+impl Drop for ShaderModuleVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      self.device.device.destroy_shader_module(self.module, None);
+    }
+  }
+}
+
+#[allow(dead_code)]
+pub struct ShaderVulkan {
+  shader_type: ShaderType,
+  module: Rc<ShaderModuleVulkan>,
+  reflection: ShaderReflectionVulkan,
+}
+
+impl Shader for ShaderVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_type(&self) -> ShaderType{
+    self.shader_type
+  }
+}
+
+pub struct VerticesVulkan {
+  buffer: Rc<BufferVulkan>,
+  size: usize
+}
+
+impl Vertices for VerticesVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+}
+
+// the buffer is replaced on update as recorded draws may still read the previous data
+pub struct InstancesVulkan {
+  buffer: RefCell<Rc<BufferVulkan>>,
+  layout: VertexBufferLayout,
+  num: Cell<u32>
+}
+
+impl Instances for InstancesVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_num_instances(&self) -> u32{
+    self.num.get()
+  }
+}
+
+pub struct IndicesVulkan {
+  buffer: Rc<BufferVulkan>,
+  index_type: vk::IndexType,
+  num: u32
+}
+
+impl Indices for IndicesVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+}
+
+// buffers are bound in the order of the layout's buffers
+pub struct GeometryVulkan {
+  buffers: Vec<Rc<BufferVulkan>>,
+  layout: VertexLayout,
+  indices: Option<(Rc<BufferVulkan>, vk::IndexType)>,
+  // number of indices when indexed, otherwise vertices
+  num: u32,
+  topology: PrimitiveTopology,
+}
+
+impl Geometry for GeometryVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+
+  fn get_num_elements(&self) -> u32{
+    self.num
+  }
+
+  fn get_topology(&self) -> PrimitiveTopology{
+    self.topology
+  }
+
+  fn set_topology(&mut self, a_topology: PrimitiveTopology){
+    self.topology = a_topology;
+  }
+}
+
+// image is created when data is loaded
+pub struct TextureVulkan {
+  image: RefCell<Option<Rc<ImageVulkan>>>
+}
+
+impl Texture for TextureVulkan {
+  fn any(&self) -> &dyn std::any::Any{
+    self
+  }
+}
+
+#[allow(dead_code)]
+pub struct UniformVulkan {
+  name: UniformName,
+  data: UniformData,
+  // offset in the push constant block, -1 if unused by the program
+  id: i32,
+  modified: bool,
+}
+
+impl Uniform for UniformVulkan {
+  fn any(&mut self) -> &mut dyn std::any::Any{
+    self
+  }
+
+  fn set_f32(&mut self, a: f32){
+    self.data.set::<f32>(a);
+    self.modified = true;
+  }
+
+  fn get_f32(&self) -> f32{
+    self.data.get::<f32>()
+  }
+
+  fn get_data(&self) -> &UniformData{
+    &self.data
+  }
+
+  fn get_data_mut(&mut self) -> &mut UniformData{
+    self.modified = true;
+    &mut self.data
+  }
+
+  fn get_name(&self) -> &str{
+    &self.name.get_name()
+  }
+
+  fn set_name(&mut self, a_name: &str){
+    self.name.set_name(a_name);
+  }
+}
+
+#[allow(dead_code)]
+pub struct UniformShaderVulkan {
+  name: UniformName,
+  id: i32
+}
+
+impl UniformShader for UniformShaderVulkan {
+  fn any(&mut self) -> &mut dyn std::any::Any{
+    self
+  }
+}
+
+pub struct SwapchainVulkan {
+  device: Rc<DeviceVulkan>,
+  loader: khr::Swapchain,
+  swapchain: vk::SwapchainKHR,
+  images: Vec<vk::Image>,
+  extent: vk::Extent2D,
+  image_available: vk::Semaphore,
+  render_finished: vk::Semaphore,
+}
+
+impl SwapchainVulkan {
+  pub fn new(a_device: &Rc<DeviceVulkan>, a_window: &Arc<Window>, a_old: Option<&SwapchainVulkan>) -> Result<SwapchainVulkan, RendererError> {
+    let instance = &a_device.instance;
+
+    let capabilities = match unsafe { instance.surface_loader.get_physical_device_surface_capabilities(a_device.physical_device, instance.surface) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    // offscreen image is copied to the swapchain
+    if !capabilities.supported_usage_flags.contains(vk::ImageUsageFlags::TRANSFER_DST) {
+      return Err(RendererError::UnsupportedAPI)
+    }
+
+    let formats = match unsafe { instance.surface_loader.get_physical_device_surface_formats(a_device.physical_device, instance.surface) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    // unorm to match the default gl framebuffer
+    let format = match formats.iter().find(|format| format.format == vk::Format::B8G8R8A8_UNORM || format.format == COLOR_FORMAT) {
+      Some(res) => *res,
+      None => match formats.first() {
+        Some(res) => *res,
+        None => return Err(RendererError::UnsupportedAPI)
+      }
+    };
+
+    let extent = match capabilities.current_extent.width {
+      u32::MAX => {
+        let (width, height) = a_window.window.lock().unwrap().inner.vulkan_drawable_size();
+        vk::Extent2D{
+          width: width.clamp(capabilities.min_image_extent.width, capabilities.max_image_extent.width),
+          height: height.clamp(capabilities.min_image_extent.height, capabilities.max_image_extent.height)
+        }
+      },
+      _ => capabilities.current_extent
+    };
+
+    // minimised
+    if extent.width == 0 || extent.height == 0 {
+      return Err(RendererError::Error)
+    }
+
+    let mut image_count = capabilities.min_image_count + 1;
+    if capabilities.max_image_count > 0 {
+      image_count = std::cmp::min(image_count, capabilities.max_image_count);
+    }
+
+    let composite_alpha = [
+      vk::CompositeAlphaFlagsKHR::OPAQUE,
+      vk::CompositeAlphaFlagsKHR::INHERIT,
+      vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+      vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED]
+      .into_iter()
+      .find(|flag| capabilities.supported_composite_alpha.contains(*flag))
+      .unwrap_or(vk::CompositeAlphaFlagsKHR::OPAQUE);
+
+    let loader = khr::Swapchain::new(&instance.instance, &a_device.device);
+
+    let swapchain_info = vk::SwapchainCreateInfoKHR::builder()
+      .surface(instance.surface)
+      .min_image_count(image_count)
+      .image_format(format.format)
+      .image_color_space(format.color_space)
+      .image_extent(extent)
+      .image_array_layers(1)
+      .image_usage(vk::ImageUsageFlags::TRANSFER_DST)
+      .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
+      .pre_transform(capabilities.current_transform)
+      .composite_alpha(composite_alpha)
+      .present_mode(vk::PresentModeKHR::FIFO)
+      .clipped(true)
+      .old_swapchain(match a_old {
+        Some(res) => res.swapchain,
+        None => vk::SwapchainKHR::null()
+      });
+
+    let swapchain = match unsafe { loader.create_swapchain(&swapchain_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    let mut result = SwapchainVulkan{
+      device: a_device.clone(),
+      loader: loader,
+      swapchain: swapchain,
+      images: Vec::new(),
+      extent: extent,
+      image_available: vk::Semaphore::null(),
+      render_finished: vk::Semaphore::null()
+    };
+
+    result.images = match unsafe { result.loader.get_swapchain_images(swapchain) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    let semaphore_info = vk::SemaphoreCreateInfo::default();
+
+    result.image_available = match unsafe { a_device.device.create_semaphore(&semaphore_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    result.render_finished = match unsafe { a_device.device.create_semaphore(&semaphore_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    Ok(result)
+  }
+}
+
+impl Drop for SwapchainVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      let _ = self.device.device.device_wait_idle();
+      self.device.device.destroy_semaphore(self.image_available, None);
+      self.device.device.destroy_semaphore(self.render_finished, None);
+      self.loader.destroy_swapchain(self.swapchain, None);
+    }
+  }
+}
+
+pub struct RendererVulkan {
+  pub version_major: i32,
+  pub version_minor: i32,
+
+  device: Rc<DeviceVulkan>,
+  window: Option<Arc<Window>>,
+  swapchain: Option<SwapchainVulkan>,
+  swapchain_outdated: bool,
+
+  width: u32,
+  height: u32,
+
+  // offscreen target copied to the swapchain, and the target currently drawn to
+  default_target: Rc<RenderTargetStateVulkan>,
+  target: Rc<RenderTargetStateVulkan>,
+
+  command_buffer: vk::CommandBuffer,
+  fence: vk::Fence,
+  descriptor_pools: Vec<vk::DescriptorPool>,
+  descriptor_pool_index: usize,
+  recording: bool,
+  in_render_pass: bool,
+  // resources used by recorded commands, released once the gpu has finished with them
+  frame_resources: Vec<Rc<dyn std::any::Any>>,
+
+  // bound to texture slots without a sampler, black the same as an unbound gl texture
+  default_image: Rc<ImageVulkan>,
+  default_sampler: Rc<SamplerObjectVulkan>,
+
+  clear_color: Vec4,
+  clear_depth: f32,
+  clear_stencil: i32,
+
+  viewport_pos: IVec2,
+  viewport_size: IVec2,
+
+  program: Option<Rc<ProgramStateVulkan>>,
+  // material samplers of the current draw by name
+  textures: Vec<(String, Rc<ImageVulkan>, Rc<SamplerObjectVulkan>)>,
+  render_state: RenderState,
+}
+
+#[allow(dead_code)]
+impl Renderer for RendererVulkan {
+  fn name(&self) -> String{
+    String::from("Vulkan")
+  }
+
+  fn get_type(&self) -> RendererType{
+    RendererType::Vulkan
+  }
+
+  // clearing is done by the render pass load operations
+  fn begin_frame(&mut self, a_clear: RendererClearType){
+    self.begin_render_pass(a_clear);
+  }
+
+  fn end_frame(&mut self){
+    if !self.begin_commands() {
+      return
+    }
+    self.end_render_pass();
+
+    let image_index = match self.window.is_some() {
+      true => self.acquire_swapchain_image(),
+      false => None
+    };
+
+    let (image_index, swapchain) = match (image_index, &self.swapchain) {
+      (Some(index), Some(swapchain)) => (index, swapchain),
+      _ => {
+        self.submit_commands(&[], &[]);
+        return
+      }
+    };
+
+    let swapchain_image = swapchain.images[image_index as usize];
+    let swapchain_extent = swapchain.extent;
+    let image_available = swapchain.image_available;
+    let render_finished = swapchain.render_finished;
+    let color = self.default_target.colors[0].clone();
+    let color_range = color.get_range(vk::ImageAspectFlags::COLOR);
+
+    self.device.cmd_image_barrier(self.command_buffer, swapchain_image, color_range,
+      (vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL),
+      (vk::AccessFlags::empty(), vk::AccessFlags::TRANSFER_WRITE),
+      (vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::TRANSFER));
+
+    self.device.cmd_image_barrier(self.command_buffer, color.image, color_range,
+      (vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL, vk::ImageLayout::TRANSFER_SRC_OPTIMAL),
+      (vk::AccessFlags::COLOR_ATTACHMENT_WRITE, vk::AccessFlags::TRANSFER_READ),
+      (vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT, vk::PipelineStageFlags::TRANSFER));
+
+    // the bottom row is first in the image and last in the swapchain
+    let subresource = vk::ImageSubresourceLayers{aspect_mask: vk::ImageAspectFlags::COLOR, mip_level: 0, base_array_layer: 0, layer_count: 1};
+    let blit = vk::ImageBlit{
+      src_subresource: subresource,
+      src_offsets: [vk::Offset3D{x: 0, y: 0, z: 0}, vk::Offset3D{x: self.width as i32, y: self.height as i32, z: 1}],
+      dst_subresource: subresource,
+      dst_offsets: [vk::Offset3D{x: 0, y: swapchain_extent.height as i32, z: 0}, vk::Offset3D{x: swapchain_extent.width as i32, y: 0, z: 1}]
+    };
+
+    unsafe {
+      self.device.device.cmd_blit_image(self.command_buffer,
+        color.image, vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        swapchain_image, vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        &[blit], vk::Filter::LINEAR);
+    }
+
+    self.device.cmd_image_barrier(self.command_buffer, swapchain_image, color_range,
+      (vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::PRESENT_SRC_KHR),
+      (vk::AccessFlags::TRANSFER_WRITE, vk::AccessFlags::empty()),
+      (vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::BOTTOM_OF_PIPE));
+
+    self.device.cmd_image_barrier(self.command_buffer, color.image, color_range,
+      (vk::ImageLayout::TRANSFER_SRC_OPTIMAL, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL),
+      (vk::AccessFlags::TRANSFER_READ, vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE),
+      (vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT));
+
+    if !self.submit_commands(&[image_available], &[render_finished]) {
+      return
+    }
+
+    let swapchain = match &self.swapchain {
+      Some(res) => res,
+      None => return
+    };
+
+    let wait_semaphores = [render_finished];
+    let swapchains = [swapchain.swapchain];
+    let image_indices = [image_index];
+
+    let present_info = vk::PresentInfoKHR::builder()
+      .wait_semaphores(&wait_semaphores)
+      .swapchains(&swapchains)
+      .image_indices(&image_indices);
+
+    match unsafe { swapchain.loader.queue_present(self.device.queue, &present_info) } {
+      Ok(false) => {},
+      Ok(true) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => self.swapchain_outdated = true,
+      Err(res) => eprintln!("Error {}", res)
+    };
+  }
+
+  //clear immediatly
+  fn clear(&mut self, a_clear: RendererClearType){
+    if !self.in_render_pass {
+      self.begin_render_pass(a_clear);
+      return
+    }
+
+    let mut attachments: Vec<vk::ClearAttachment> = Vec::new();
+
+    if (a_clear & RendererClearType::COLOR) == RendererClearType::COLOR {
+      for i in 0..self.target.colors.len() {
+        attachments.push(vk::ClearAttachment{
+          aspect_mask: vk::ImageAspectFlags::COLOR,
+          color_attachment: i as u32,
+          clear_value: self.get_clear_values()[0]
+        });
+      }
+    }
+
+    let mut aspect = vk::ImageAspectFlags::empty();
+
+    if (a_clear & RendererClearType::DEPTH) == RendererClearType::DEPTH {
+      aspect |= vk::ImageAspectFlags::DEPTH;
+    }
+
+    if (a_clear & RendererClearType::STENCIL) == RendererClearType::STENCIL {
+      aspect |= vk::ImageAspectFlags::STENCIL;
+    }
+
+    aspect &= match &self.target.depth {
+      Some(res) => get_depth_aspect(res.format),
+      None => vk::ImageAspectFlags::empty()
+    };
+
+    if !aspect.is_empty() {
+      attachments.push(vk::ClearAttachment{
+        aspect_mask: aspect,
+        color_attachment: 0,
+        clear_value: self.get_clear_values()[1]
+      });
+    }
+
+    if attachments.is_empty() {
+      return
+    }
+
+    let rect = vk::ClearRect{
+      rect: vk::Rect2D{offset: vk::Offset2D{x: 0, y: 0}, extent: vk::Extent2D{width: self.target.width, height: self.target.height}},
+      base_array_layer: 0,
+      layer_count: 1
+    };
+
+    unsafe {
+      self.device.device.cmd_clear_attachments(self.command_buffer, &attachments, &[rect]);
+    }
+  }
+
+  // Get and set clear values may be called before BeginFrame
+  fn set_clear_color(&mut self, a_color: Vec4){
+    self.clear_color = a_color;
+  }
+
+  fn set_clear_depth(&mut self, a_depth: f32){
+    self.clear_depth = a_depth;
+  }
+
+  fn set_clear_stencil(&mut self, a_stencil: i32){
+    self.clear_stencil = a_stencil;
+  }
+
+  fn get_clear_color(&self) -> Vec4{
+    self.clear_color
+  }
+
+  fn get_clear_depth(&self) -> f32{
+    self.clear_depth
+  }
+
+  fn get_clear_stencil(&self) -> i32{
+    self.clear_stencil
+  }
+
+  fn set_viewport(&mut self, a_pos: IVec2, a_size: IVec2){
+    self.viewport_pos = a_pos;
+    self.viewport_size = a_size;
+
+    if self.in_render_pass {
+      self.apply_viewport();
+    }
+  }
+
+  fn get_viewport_pos(&self) -> IVec2{
+    self.viewport_pos
+  }
+  fn get_viewport_size(&self) -> IVec2{
+    self.viewport_size
+  }
+
+  // pipelines for the state are created when drawing
+  fn set_render_state(&mut self, a_state: &RenderState){
+    let scissor = self.render_state.scissor;
+    self.render_state = *a_state;
+
+    if self.in_render_pass && scissor != a_state.scissor {
+      self.apply_viewport();
+    }
+  }
+
+  fn get_render_state(&self) -> &RenderState{
+    &self.render_state
+  }
+
+  fn load_shader(&mut self, a_shader_type: ShaderType, a_source: &str) -> Result<Box<dyn Shader>, RendererError>{
+    let (code, reflection) = match compile_glsl(a_shader_type, a_source) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let module_info = vk::ShaderModuleCreateInfo::builder().code(&code);
+
+    let module = match unsafe { self.device.device.create_shader_module(&module_info, None) } {
+      Ok(res) => res,
+      Err(res) => return Err(RendererError::ShaderCompile(ShaderLog::from_message(Some(a_shader_type), &res.to_string())))
+    };
+
+    Ok(Box::new(ShaderVulkan{
+      shader_type: a_shader_type,
+      module: Rc::new(ShaderModuleVulkan{device: self.device.clone(), module: module}),
+      reflection: reflection
+    }))
+  }
+
+  // the neutral source is the vulkan glsl load_shader compiles
+  fn load_shader_neutral(&mut self, a_shader_type: ShaderType, a_source: &str) -> Result<Box<dyn Shader>, RendererError>{
+    self.load_shader(a_shader_type, a_source)
+  }
+
+  fn load_program_vert_frag(&mut self, a_shader_vert: Box<dyn Shader>, a_shader_frag: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
+    let shader_vert = match a_shader_vert.any().downcast_ref::<ShaderVulkan>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    let shader_frag = match a_shader_frag.any().downcast_ref::<ShaderVulkan>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    let state = match self.create_program(&[(vk::ShaderStageFlags::VERTEX, shader_vert), (vk::ShaderStageFlags::FRAGMENT, shader_frag)]) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    // pipeline for the default target is created now so errors are reported on load
+    match state.get_pipeline(&self.default_target, &VertexLayout::position_uv(), PrimitiveTopology::Triangles, &RenderState::default()) {
+      Ok(_res) => Ok(Box::new(ProgramVulkan{state: Rc::new(state)})),
+      Err(res) => Err(res)
+    }
+  }
+
+  // naga only compiles vertex, fragment and compute shaders so other stages can not be loaded
+  fn load_program(&mut self, a_builder: ProgramBuilder) -> Result<Box<dyn Program>, RendererError>{
+    match a_builder.validate() {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let mut shaders = Vec::new();
+    for shader in a_builder.get_shaders() {
+      let shader = match shader.any().downcast_ref::<ShaderVulkan>() {
+        Some(res) => res,
+        None => return Err(RendererError::InvalidCast)
+      };
+
+      let stage = match shader.shader_type {
+        ShaderType::Vertex => vk::ShaderStageFlags::VERTEX,
+        ShaderType::Fragment => vk::ShaderStageFlags::FRAGMENT,
+        ShaderType::Compute => return Err(RendererError::Unimplemented),
+        _ => return Err(RendererError::UnsupportedAPI)
+      };
+
+      shaders.push((stage, shader));
+    }
+
+    let state = match self.create_program(&shaders) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    match state.get_pipeline(&self.default_target, &VertexLayout::position_uv(), PrimitiveTopology::Triangles, &RenderState::default()) {
+      Ok(_res) => Ok(Box::new(ProgramVulkan{state: Rc::new(state)})),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn set_patch_vertices(&mut self, _a_count: u32){
+
+  }
+
+  fn get_uniform(&mut self, a_shader: &mut Box<dyn Program>, a_name: &str) -> Box<dyn UniformShader>{
+    let shader = match a_shader.any().downcast_ref::<ProgramVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid shader cast")
+    };
+
+    Box::new(UniformShaderVulkan{
+      name: UniformName::new(a_name),
+      id: shader.state.get_uniform_offset(a_name)
+    })
+  }
+
+  // compute pipelines are not implemented yet, storage buffers and images can not be created
+  fn load_program_compute(&mut self, _a_shader: Box<dyn Shader>) -> Result<Box<dyn Program>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn dispatch(&mut self, _a_x: u32, _a_y: u32, _a_z: u32){
+
+  }
+
+  fn memory_barrier(&mut self, _a_barrier: BarrierType){
+
+  }
+
+  fn gen_buffer_storage(&mut self, _a_data: &[u8]) -> Result<Box<dyn StorageBuffer>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn update_buffer_storage(&mut self, _a_buffer: &mut Box<dyn StorageBuffer>, _a_data: &[u8]){
+
+  }
+
+  fn read_buffer_storage(&mut self, _a_buffer: &Box<dyn StorageBuffer>) -> Vec<u8>{
+    Vec::new()
+  }
+
+  fn bind_buffer_storage(&mut self, _a_binding: u32, _a_buffer: &Box<dyn StorageBuffer>){
+
+  }
+
+  fn gen_texture_storage(&mut self, _a_width: u32, _a_height: u32, _a_format: TextureFormat) -> Result<Box<dyn Texture>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn bind_image_storage(&mut self, _a_unit: u32, _a_texture: &dyn Texture, _a_access: StorageAccess, _a_format: TextureFormat){
+
+  }
+
+  // descriptor sets only have textures and samplers so far, uniforms use push constants
+  fn gen_buffer_uniform(&mut self, _a_layout: &UniformBlockLayout, _a_block: &dyn UniformBlock) -> Result<Box<dyn UniformBuffer>, RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn update_buffer_uniform(&mut self, _a_buffer: &mut Box<dyn UniformBuffer>, _a_block: &dyn UniformBlock) -> Result<(), RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn bind_buffer_uniform(&mut self, _a_binding: u32, _a_buffer: &Box<dyn UniformBuffer>){
+
+  }
+
+  fn bind_uniform_block(&mut self, _a_program: &Box<dyn Program>, _a_block_name: &str, _a_binding: u32, _a_layout: &UniformBlockLayout) -> Result<(), RendererError>{
+    Err(RendererError::Unimplemented)
+  }
+
+  fn gen_buffer_vertex(&mut self, a_verts: &std::vec::Vec<f32>) -> Box<dyn Vertices>{
+    let data: Vec<u8> = a_verts.iter().flat_map(|value| value.to_ne_bytes()).collect();
+    self.gen_buffer_vertex_bytes(&data)
+  }
+
+  fn gen_buffer_vertex_bytes(&mut self, a_data: &[u8]) -> Box<dyn Vertices>{
+    let buffer = match BufferVulkan::new(&self.device, a_data.len() as vk::DeviceSize,
+      vk::BufferUsageFlags::VERTEX_BUFFER,
+      vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT)
+    {
+      Ok(res) => res,
+      Err(_res) => panic!("Unable to create vertex buffer")
+    };
+
+    if buffer.write(a_data).is_err() {
+      panic!("Unable to write vertex buffer");
+    }
+
+    Box::new(VerticesVulkan{buffer: Rc::new(buffer), size: a_data.len()})
+  }
+
+  fn gen_geometry(&mut self, a_buffer: &Box<dyn Vertices>) -> Box<dyn Geometry>{
+    match self.gen_geometry_layout(&[a_buffer], &VertexLayout::position_uv()) {
+      Ok(res) => res,
+      Err(_res) => panic!("Invalid vertex")
+    }
+  }
+
+  fn gen_geometry_layout(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout) -> Result<Box<dyn Geometry>, RendererError>{
+    match gen_geometry(a_buffers, a_layout) {
+      Ok(res) => Ok(Box::new(res)),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn gen_buffer_index(&mut self, a_indices: IndexData) -> Box<dyn Indices>{
+    let index_type = match a_indices {
+      IndexData::Uint16(_res) => vk::IndexType::UINT16,
+      IndexData::Uint32(_res) => vk::IndexType::UINT32
+    };
+    let data = a_indices.to_bytes();
+
+    // zero sized buffers are invalid
+    let buffer = match BufferVulkan::new(&self.device, std::cmp::max(data.len(), 4) as vk::DeviceSize,
+      vk::BufferUsageFlags::INDEX_BUFFER,
+      vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT)
+    {
+      Ok(res) => res,
+      Err(_res) => panic!("Unable to create index buffer")
+    };
+
+    if buffer.write(&data).is_err() {
+      panic!("Unable to write index buffer");
+    }
+
+    Box::new(IndicesVulkan{buffer: Rc::new(buffer), index_type: index_type, num: a_indices.len() as u32})
+  }
+
+  fn gen_geometry_indexed(&mut self, a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout, a_indices: &Box<dyn Indices>) -> Result<Box<dyn Geometry>, RendererError>{
+    let indices = match a_indices.any().downcast_ref::<IndicesVulkan>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    let mut geometry = match gen_geometry(a_buffers, a_layout) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    geometry.indices = Some((indices.buffer.clone(), indices.index_type));
+    geometry.num = indices.num;
+
+    Ok(Box::new(geometry))
+  }
+
+  fn gen_instances(&mut self, a_layout: &VertexBufferLayout, a_data: &[u8]) -> Result<Box<dyn Instances>, RendererError>{
+    if a_layout.step != VertexStep::PerInstance || a_layout.stride == 0 {
+      return Err(RendererError::Error)
+    }
+
+    if let Err(res) = get_vertex_input(&VertexLayout{buffers: vec![a_layout.clone()]}) {
+      return Err(res)
+    }
+
+    let buffer = match self.gen_instance_buffer(a_data) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    Ok(Box::new(InstancesVulkan{
+      buffer: RefCell::new(Rc::new(buffer)),
+      layout: a_layout.clone(),
+      num: Cell::new((a_data.len() / a_layout.stride as usize) as u32)
+    }))
+  }
+
+  fn update_instances(&mut self, a_instances: &mut Box<dyn Instances>, a_data: &[u8]){
+    let instances = match a_instances.any().downcast_ref::<InstancesVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid instances")
+    };
+
+    let buffer = match self.gen_instance_buffer(a_data) {
+      Ok(res) => res,
+      Err(_res) => panic!("Unable to create instance buffer")
+    };
+
+    let previous = instances.buffer.replace(Rc::new(buffer));
+    self.frame_resources.push(previous);
+    instances.num.set((a_data.len() / instances.layout.stride as usize) as u32);
+  }
+
+  fn gen_mesh(&mut self, a_geometry: Box<dyn Geometry>, a_material: Box<dyn Material>) -> Box<Mesh>{
+    Box::new(Mesh{
+      geometry: a_geometry,
+      material: a_material
+      })
+  }
+
+  fn gen_buffer_texture(&mut self) -> Box<dyn Texture>{
+    Box::new(TextureVulkan{image: RefCell::new(None)})
+  }
+
+  fn gen_sampler(&mut self, a_texture: Rc<dyn Texture>, a_desc: &SamplerDesc) -> Box<dyn Sampler>{
+    let sampler = match SamplerObjectVulkan::new(&self.device, a_desc) {
+      Ok(res) => res,
+      Err(_res) => panic!("Unable to create sampler")
+    };
+
+    Box::new(SamplerVulkan{name: String::from(""), texture: a_texture, sampler: Rc::new(sampler)})
+  }
+
+  fn load_texture(&mut self, a_image: &image::DynamicImage, a_texture: &mut Box<dyn Texture>){
+    // sampling 16 bit unorm is optional
+    let format = match self.get_supported_format(TextureFormat::from_image(a_image), vk::FormatFeatureFlags::SAMPLED_IMAGE) {
+      Some(_res) => TextureFormat::from_image(a_image),
+      None => TextureFormat::from_image(a_image).get_fallback()
+    };
+
+    if let Err(res) = self.load_texture_format(a_image, format, a_texture) {
+      eprintln!("Error {}", res);
+    }
+  }
+
+  fn load_texture_format(&mut self, a_image: &image::DynamicImage, a_format: TextureFormat, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    self.load_texture_layers(std::slice::from_ref(a_image), TextureDimension::D2, a_format, a_texture)
+  }
+
+  fn load_texture_layers(&mut self, a_images: &[image::DynamicImage], a_dimension: TextureDimension, a_format: TextureFormat, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    let texture = match a_texture.any().downcast_ref::<TextureVulkan>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    match a_dimension.validate_images(a_images) {
+      Ok(_res) => {},
+      Err(res) => return Err(res)
+    };
+
+    // depth and stencil are copied separately which isn't supported yet
+    if a_format.has_stencil() {
+      return Err(RendererError::Unimplemented)
+    }
+
+    let format = match self.get_supported_format(a_format, vk::FormatFeatureFlags::SAMPLED_IMAGE) {
+      Some(res) => res,
+      None => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let aspect = match a_format.is_depth() {
+      true => vk::ImageAspectFlags::DEPTH,
+      false => vk::ImageAspectFlags::COLOR
+    };
+
+    let bytes: Vec<u8> = a_images.iter().flat_map(|image| a_format.get_bytes(image)).collect();
+
+    // depth 24 is in the low bits of x8_d24, or converted when only 32 bit float depth is available
+    let bytes = match (a_format, format) {
+      (TextureFormat::Depth24, vk::Format::X8_D24_UNORM_PACK32) => bytes.chunks_exact(4).flat_map(|texel| {
+        (u32::from_ne_bytes([texel[0], texel[1], texel[2], texel[3]]) >> 8).to_ne_bytes()
+      }).collect(),
+      (TextureFormat::Depth24, vk::Format::D32_SFLOAT) => bytes.chunks_exact(4).flat_map(|texel| {
+        ((u32::from_ne_bytes([texel[0], texel[1], texel[2], texel[3]]) >> 8) as f32 / 16777215.0).to_ne_bytes()
+      }).collect(),
+      _ => bytes
+    };
+
+    let image = match ImageVulkan::new_pixels(&self.device, &bytes, a_images[0].width(), a_images[0].height(), a_images.len() as u32,
+      a_dimension, format, aspect, true)
+    {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    // previous image may still be in use by recorded commands
+    if let Some(previous) = texture.image.replace(Some(Rc::new(image))) {
+      self.frame_resources.push(previous);
+    }
+
+    Ok(())
+  }
+
+  fn load_texture_compressed(&mut self, a_image: &CompressedImage, a_texture: &mut Box<dyn Texture>) -> Result<(), RendererError>{
+    if !self.is_compressed_format_supported(a_image.format, a_image.srgb) {
+      let image = match a_image.decompress(0) {
+        Ok(res) => res,
+        Err(res) => return Err(res)
+      };
+
+      return self.load_texture_format(&image, a_image.get_decompressed_format(), a_texture)
+    }
+
+    let texture = match a_texture.any().downcast_ref::<TextureVulkan>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    let image = match ImageVulkan::new_levels(&self.device, &a_image.levels, a_image.width, a_image.height,
+      get_compressed_format(a_image.format, a_image.srgb))
+    {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    if let Some(previous) = texture.image.replace(Some(Rc::new(image))) {
+      self.frame_resources.push(previous);
+    }
+
+    Ok(())
+  }
+
+  fn is_compressed_format_supported(&self, a_format: CompressedFormat, a_srgb: bool) -> bool{
+    let feature = match a_format {
+      CompressedFormat::ETC2RGB8 | CompressedFormat::ETC2RGBA8 => self.device.compression_etc2,
+      CompressedFormat::ASTC(_width, _height) => self.device.compression_astc && a_format.get_astc_index().is_some(),
+      _ => self.device.compression_bc
+    };
+
+    let properties = unsafe {
+      self.device.instance.instance.get_physical_device_format_properties(self.device.physical_device, get_compressed_format(a_format, a_srgb))
+    };
+    feature && properties.optimal_tiling_features.contains(vk::FormatFeatureFlags::SAMPLED_IMAGE)
+  }
+
+  fn use_program(&mut self, a_program: &Box<dyn Program>){
+    let program = match a_program.any().downcast_ref::<ProgramVulkan>() {
+      Some(res) => res,
+      None => return
+    };
+
+    self.program = Some(program.state.clone());
+  }
+
+  fn draw_geometry(&mut self, a_geometry: &Box<dyn Geometry>){
+    let geometry = match a_geometry.any().downcast_ref::<GeometryVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.draw_vertices(geometry, DrawRange{first: 0, count: geometry.num, base_vertex: 0}, None);
+  }
+
+  fn draw_geometry_range(&mut self, a_geometry: &Box<dyn Geometry>, a_range: DrawRange){
+    let geometry = match a_geometry.any().downcast_ref::<GeometryVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.draw_vertices(geometry, a_range, None);
+  }
+
+  fn draw_mesh(&mut self, _camera: &Camera, a_mesh: &mut Box<Mesh>){
+    let geometry = match a_mesh.geometry.any().downcast_ref::<GeometryVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    self.use_program(a_mesh.material.get_program());
+    self.set_render_state(a_mesh.material.get_render_state());
+
+    let num_uniforms = a_mesh.material.num_uniforms();
+    for i in 0..num_uniforms {
+      self.update_uniform(a_mesh.material.get_uniform(i));
+    }
+
+    self.textures.clear();
+
+    let num_samplers = a_mesh.material.num_samplers();
+    for i in 0..num_samplers {
+      self.update_sampler(a_mesh.material.get_sampler(i));
+    }
+
+    self.draw_vertices(geometry, DrawRange{first: 0, count: geometry.num, base_vertex: 0}, None);
+  }
+
+  fn draw_mesh_instanced(&mut self, _camera: &Camera, a_mesh: &mut Box<Mesh>, a_instances: &Box<dyn Instances>){
+    let geometry = match a_mesh.geometry.any().downcast_ref::<GeometryVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid vertex")
+    };
+
+    let instances = match a_instances.any().downcast_ref::<InstancesVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid instances")
+    };
+
+    self.use_program(a_mesh.material.get_program());
+    self.set_render_state(a_mesh.material.get_render_state());
+
+    let num_uniforms = a_mesh.material.num_uniforms();
+    for i in 0..num_uniforms {
+      self.update_uniform(a_mesh.material.get_uniform(i));
+    }
+
+    self.textures.clear();
+
+    let num_samplers = a_mesh.material.num_samplers();
+    for i in 0..num_samplers {
+      self.update_sampler(a_mesh.material.get_sampler(i));
+    }
+
+    self.draw_vertices(geometry, DrawRange{first: 0, count: geometry.num, base_vertex: 0}, Some(instances));
+  }
+
+  // images are stored bottom row first so rows are already in gl::ReadPixels order
+  fn read_render_buffer(&mut self) -> Image {
+    let target = self.target.clone();
+
+    let mut image = Image{
+      width: target.width,
+      height: target.height,
+      pitch: target.width * 4,
+      pixels: vec![0u8; (target.width * target.height * 4) as usize]};
+
+    let color = match target.colors.first() {
+      Some(res) => res.clone(),
+      None => return image
+    };
+
+    let texel_size = get_texel_size(color.format);
+    if texel_size == 0 || !self.begin_commands() {
+      return image
+    }
+    self.end_render_pass();
+
+    let buffer = match BufferVulkan::new(&self.device, (target.width * target.height * texel_size) as vk::DeviceSize,
+      vk::BufferUsageFlags::TRANSFER_DST,
+      vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT)
+    {
+      Ok(res) => res,
+      Err(_res) => return image
+    };
+
+    let color_range = color.get_range(vk::ImageAspectFlags::COLOR);
+
+    self.device.cmd_image_barrier(self.command_buffer, color.image, color_range,
+      (target.get_color_layout(), vk::ImageLayout::TRANSFER_SRC_OPTIMAL),
+      (vk::AccessFlags::COLOR_ATTACHMENT_WRITE, vk::AccessFlags::TRANSFER_READ),
+      (vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT, vk::PipelineStageFlags::TRANSFER));
+
+    let region = vk::BufferImageCopy{
+      buffer_offset: 0,
+      buffer_row_length: 0,
+      buffer_image_height: 0,
+      image_subresource: vk::ImageSubresourceLayers{aspect_mask: vk::ImageAspectFlags::COLOR, mip_level: 0, base_array_layer: 0, layer_count: 1},
+      image_offset: vk::Offset3D{x: 0, y: 0, z: 0},
+      image_extent: vk::Extent3D{width: target.width, height: target.height, depth: 1}
+    };
+
+    let buffer_barrier = vk::BufferMemoryBarrier::builder()
+      .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+      .dst_access_mask(vk::AccessFlags::HOST_READ)
+      .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+      .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+      .buffer(buffer.buffer)
+      .offset(0)
+      .size(vk::WHOLE_SIZE);
+
+    unsafe {
+      self.device.device.cmd_copy_image_to_buffer(self.command_buffer, color.image, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, buffer.buffer, &[region]);
+      self.device.device.cmd_pipeline_barrier(self.command_buffer, vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::HOST,
+        vk::DependencyFlags::empty(), &[], &[buffer_barrier.build()], &[]);
+    }
+
+    self.device.cmd_image_barrier(self.command_buffer, color.image, color_range,
+      (vk::ImageLayout::TRANSFER_SRC_OPTIMAL, target.get_color_layout()),
+      (vk::AccessFlags::TRANSFER_READ, vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE | vk::AccessFlags::SHADER_READ),
+      (vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::FRAGMENT_SHADER));
+
+    if !self.submit_commands(&[], &[]) {
+      return image
+    }
+
+    if unsafe { self.device.device.wait_for_fences(&[self.fence], true, u64::MAX) }.is_err() {
+      return image
+    }
+
+    let mut pixels = vec![0u8; (target.width * target.height * texel_size) as usize];
+    if buffer.read(&mut pixels).is_err() {
+      return image
+    }
+
+    image.pixels = to_rgba8(color.format, &pixels);
+
+    return image
+  }
+
+  fn gen_render_target(&mut self, a_desc: &RenderTargetDesc) -> Result<Box<dyn RenderTarget>, RendererError>{
+    if a_desc.width == 0 || a_desc.height == 0 {
+      return Err(RendererError::Error)
+    }
+
+    let mut color_formats: Vec<vk::Format> = Vec::new();
+    for format in &a_desc.color_formats {
+      if format.is_depth() {
+        return Err(RendererError::Error)
+      }
+
+      match self.get_supported_format(*format, vk::FormatFeatureFlags::COLOR_ATTACHMENT | vk::FormatFeatureFlags::SAMPLED_IMAGE) {
+        Some(res) => color_formats.push(res),
+        None => return Err(RendererError::UnsupportedAPI)
+      };
+    }
+
+    let depth_format = match a_desc.depth_stencil_format {
+      Some(format) if !format.is_depth() => return Err(RendererError::Error),
+      Some(format) => match self.get_supported_format(format, vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT | vk::FormatFeatureFlags::SAMPLED_IMAGE) {
+        Some(res) => Some(res),
+        None => return Err(RendererError::UnsupportedAPI)
+      },
+      None => None
+    };
+
+    let state = match RenderTargetStateVulkan::new(&self.device, a_desc.width, a_desc.height, &color_formats, depth_format, true) {
+      Ok(res) => Rc::new(res),
+      Err(res) => return Err(res)
+    };
+
+    let colors = state.colors.iter().map(|image| Rc::new(TextureVulkan{image: RefCell::new(Some(image.clone()))})).collect();
+    let depth = state.depth.as_ref().map(|image| Rc::new(TextureVulkan{image: RefCell::new(Some(image.clone()))}));
+
+    Ok(Box::new(RenderTargetVulkan{state: state, colors: colors, depth: depth}))
+  }
+
+  fn set_render_target(&mut self, a_target: Option<&Box<dyn RenderTarget>>){
+    let target = match a_target {
+      Some(target) => match target.any().downcast_ref::<RenderTargetVulkan>() {
+        Some(res) => res.state.clone(),
+        None => panic!("Invalid render target")
+      },
+      None => self.default_target.clone()
+    };
+
+    if !Rc::ptr_eq(&target, &self.target) {
+      self.end_render_pass();
+      self.target = target;
+    }
+  }
+
+  fn clear_render_target(&mut self, a_target: &Box<dyn RenderTarget>, a_clear: RendererClearType){
+    let target = match a_target.any().downcast_ref::<RenderTargetVulkan>() {
+      Some(res) => res.state.clone(),
+      None => panic!("Invalid render target")
+    };
+
+    if Rc::ptr_eq(&target, &self.target) {
+      self.clear(a_clear);
+      return
+    }
+
+    // an empty render pass that only clears, the current target continues with a new pass when next drawn to
+    self.begin_target_render_pass(&target, a_clear);
+    self.end_render_pass();
+  }
+}
+
+#[allow(dead_code)]
+impl RendererVulkan{
+  const VULKAN_MAX_VERSION_MINOR: i32 = 3;
+
+  pub fn new(a_min_version: Version, a_max_version: Version, a_window: Arc<Window>) -> Result<Self, RendererError>{
+    let instance = match InstanceVulkan::new(Some(&a_window), get_vulkan_version(a_max_version)) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let device = match DeviceVulkan::new(instance, get_vulkan_version(a_min_version)) {
+      Ok(res) => Rc::new(res),
+      Err(res) => return Err(res)
+    };
+
+    let swapchain = match SwapchainVulkan::new(&device, &a_window, None) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let width = a_window.width;
+    let height = a_window.height;
+
+    RendererVulkan::init(device, Some(a_window), Some(swapchain), width, height)
+  }
+
+  // Creates a renderer without a window. Output is rendered to an offscreen image of the given size
+  pub fn new_headless(
+    a_min_version: Version,
+    a_max_version: Version,
+    a_width: u32,
+    a_height: u32) -> Result<Self, RendererError>
+  {
+    let instance = match InstanceVulkan::new(None, get_vulkan_version(a_max_version)) {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    let device = match DeviceVulkan::new(instance, get_vulkan_version(a_min_version)) {
+      Ok(res) => Rc::new(res),
+      Err(res) => return Err(res)
+    };
+
+    RendererVulkan::init(device, None, None, a_width, a_height)
+  }
+
+  fn init(
+    a_device: Rc<DeviceVulkan>,
+    a_window: Option<Arc<Window>>,
+    a_swapchain: Option<SwapchainVulkan>,
+    a_width: u32,
+    a_height: u32) -> Result<Self, RendererError>
+  {
+    let depth_format = DEPTH_FORMATS.into_iter().find(|format| {
+      let properties = unsafe { a_device.instance.instance.get_physical_device_format_properties(a_device.physical_device, *format) };
+      properties.optimal_tiling_features.contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+    });
+
+    let depth_format = match depth_format {
+      Some(res) => res,
+      None => return Err(RendererError::UnsupportedAPI)
+    };
+
+    let default_target = match RenderTargetStateVulkan::new(&a_device, a_width, a_height, &[COLOR_FORMAT], Some(depth_format), false) {
+      Ok(res) => Rc::new(res),
+      Err(res) => return Err(res)
+    };
+
+    let default_image = match ImageVulkan::new_rgba8(&a_device, &[0, 0, 0, 255], 1, 1, false) {
+      Ok(res) => Rc::new(res),
+      Err(res) => return Err(res)
+    };
+
+    let default_sampler = match SamplerObjectVulkan::new(&a_device, &SamplerDesc::default()) {
+      Ok(res) => Rc::new(res),
+      Err(res) => return Err(res)
+    };
+
+    let allocate_info = vk::CommandBufferAllocateInfo::builder()
+      .command_pool(a_device.command_pool)
+      .level(vk::CommandBufferLevel::PRIMARY)
+      .command_buffer_count(1);
+
+    let command_buffer = match unsafe { a_device.device.allocate_command_buffers(&allocate_info) } {
+      Ok(res) => res[0],
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    let fence_info = vk::FenceCreateInfo::builder().flags(vk::FenceCreateFlags::SIGNALED);
+
+    let fence = match unsafe { a_device.device.create_fence(&fence_info, None) } {
+      Ok(res) => res,
+      Err(_res) => {
+        unsafe { a_device.device.free_command_buffers(a_device.command_pool, &[command_buffer]) };
+        return Err(RendererError::Error)
+      }
+    };
+
+    Ok(Self {
+      version_major: a_device.version_major,
+      version_minor: a_device.version_minor,
+      device: a_device.clone(),
+      window: a_window,
+      swapchain: a_swapchain,
+      swapchain_outdated: false,
+      width: a_width,
+      height: a_height,
+      default_target: default_target.clone(),
+      target: default_target,
+      command_buffer: command_buffer,
+      fence: fence,
+      descriptor_pools: Vec::new(),
+      descriptor_pool_index: 0,
+      recording: false,
+      in_render_pass: false,
+      frame_resources: Vec::new(),
+      default_image: default_image,
+      default_sampler: default_sampler,
+      clear_color: Vec4::new(0.0, 0.0, 0.0, 0.0),
+      clear_depth: 1.0,
+      clear_stencil: 0,
+      viewport_pos: IVec2::new(0, 0),
+      viewport_size: IVec2::new(a_width as i32, a_height as i32),
+      program: None,
+      textures: Vec::new(),
+      render_state: RenderState::default()
+    })
+  }
+
+  pub fn update_uniform(&self, a_uniform: &mut Box<dyn Uniform>){
+    let uniform = match a_uniform.any().downcast_mut::<UniformVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid uniform cast")
+    };
+
+    let program = match &self.program {
+      Some(res) => res,
+      None => return
+    };
+
+    if !uniform.modified || uniform.id < 0 {
+      return
+    }
+
+    // push constants use std430
+    let data = uniform.data.to_layout(BlockLayout::Std430);
+
+    let offset = uniform.id as usize;
+    let mut push_constants = program.push_constants.borrow_mut();
+    let end = std::cmp::min(offset + data.len(), push_constants.len());
+
+    if offset < end {
+      push_constants[offset..end].copy_from_slice(&data[..end - offset]);
+    }
+
+    uniform.modified = false;
+  }
+
+  pub fn update_sampler(&mut self, a_sampler: &Box<dyn Sampler>){
+    let sampler = match a_sampler.any().downcast_ref::<SamplerVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid sampler cast")
+    };
+
+    let texture = match sampler.texture.any().downcast_ref::<TextureVulkan>() {
+      Some(res) => res,
+      None => panic!("Invalid texture cast")
+    };
+
+    let image = match &*texture.image.borrow() {
+      Some(res) => res.clone(),
+      None => self.default_image.clone()
+    };
+
+    self.textures.push((sampler.name.clone(), image, sampler.sampler.clone()));
+  }
+
+  // texture formats map to the closest vulkan format with the required features
+  fn get_supported_format(&self, a_format: TextureFormat, a_features: vk::FormatFeatureFlags) -> Option<vk::Format> {
+    let formats: &[vk::Format] = match a_format {
+      TextureFormat::R8 => &[vk::Format::R8_UNORM],
+      TextureFormat::RG8 => &[vk::Format::R8G8_UNORM],
+      TextureFormat::RGBA8 => &[vk::Format::R8G8B8A8_UNORM],
+      TextureFormat::SRGB8A8 => &[vk::Format::R8G8B8A8_SRGB],
+      TextureFormat::R16 => &[vk::Format::R16_UNORM],
+      TextureFormat::RG16 => &[vk::Format::R16G16_UNORM],
+      TextureFormat::RGBA16 => &[vk::Format::R16G16B16A16_UNORM],
+      TextureFormat::R16F => &[vk::Format::R16_SFLOAT],
+      TextureFormat::RG16F => &[vk::Format::R16G16_SFLOAT],
+      TextureFormat::RGBA16F => &[vk::Format::R16G16B16A16_SFLOAT],
+      TextureFormat::RGBA32F => &[vk::Format::R32G32B32A32_SFLOAT],
+      TextureFormat::Depth16 => &[vk::Format::D16_UNORM],
+      TextureFormat::Depth24 => &[vk::Format::X8_D24_UNORM_PACK32, vk::Format::D32_SFLOAT],
+      TextureFormat::Depth24Stencil8 => &[vk::Format::D24_UNORM_S8_UINT, vk::Format::D32_SFLOAT_S8_UINT],
+      TextureFormat::Depth32F => &[vk::Format::D32_SFLOAT]
+    };
+
+    formats.iter().copied().find(|format| {
+      let properties = unsafe { self.device.instance.instance.get_physical_device_format_properties(self.device.physical_device, *format) };
+      properties.optimal_tiling_features.contains(a_features)
+    })
+  }
+
+  fn get_clear_values(&self) -> [vk::ClearValue; 2] {
+    [
+      vk::ClearValue{color: vk::ClearColorValue{float32: self.clear_color.to_array()}},
+      vk::ClearValue{depth_stencil: vk::ClearDepthStencilValue{depth: self.clear_depth, stencil: self.clear_stencil as u32}}
+    ]
+  }
+
+  // starts recording if the previous commands have been submitted
+  fn begin_commands(&mut self) -> bool {
+    if self.recording {
+      return true
+    }
+
+    unsafe {
+      if self.device.device.wait_for_fences(&[self.fence], true, u64::MAX).is_err() {
+        return false
+      }
+    }
+
+    self.frame_resources.clear();
+
+    let begin_info = vk::CommandBufferBeginInfo::builder()
+      .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+    let result = unsafe {
+      for pool in &self.descriptor_pools {
+        let _ = self.device.device.reset_descriptor_pool(*pool, vk::DescriptorPoolResetFlags::empty());
+      }
+
+      self.device.device.reset_fences(&[self.fence])
+        .and_then(|_| self.device.device.reset_command_buffer(self.command_buffer, vk::CommandBufferResetFlags::empty()))
+        .and_then(|_| self.device.device.begin_command_buffer(self.command_buffer, &begin_info))
+    };
+
+    self.descriptor_pool_index = 0;
+    self.recording = result.is_ok();
+    self.recording
+  }
+
+  fn submit_commands(&mut self, a_wait: &[vk::Semaphore], a_signal: &[vk::Semaphore]) -> bool {
+    self.end_render_pass();
+    self.recording = false;
+
+    let wait_stages = vec![vk::PipelineStageFlags::TRANSFER; a_wait.len()];
+    let command_buffers = [self.command_buffer];
+
+    let submit_info = vk::SubmitInfo::builder()
+      .wait_semaphores(a_wait)
+      .wait_dst_stage_mask(&wait_stages)
+      .command_buffers(&command_buffers)
+      .signal_semaphores(a_signal);
+
+    let result = unsafe {
+      self.device.device.end_command_buffer(self.command_buffer)
+        .and_then(|_| self.device.device.queue_submit(self.device.queue, &[submit_info.build()], self.fence))
+    };
+
+    match result {
+      Ok(_res) => true,
+      Err(res) => {
+        eprintln!("Error {}", res);
+        false
+      }
+    }
+  }
+
+  fn begin_render_pass(&mut self, a_clear: RendererClearType) {
+    let target = self.target.clone();
+    self.begin_target_render_pass(&target, a_clear);
+  }
+
+  fn begin_target_render_pass(&mut self, a_target: &Rc<RenderTargetStateVulkan>, a_clear: RendererClearType) {
+    if !self.begin_commands() {
+      return
+    }
+    self.end_render_pass();
+
+    let render_pass = match a_target.get_render_pass(a_clear) {
+      Ok(res) => res,
+      Err(_res) => return
+    };
+
+    // a clear value for each attachment, colors first
+    let [clear_color, clear_depth] = self.get_clear_values();
+    let mut clear_values = vec![clear_color; a_target.colors.len()];
+    if a_target.depth.is_some() {
+      clear_values.push(clear_depth);
+    }
+
+    let begin_info = vk::RenderPassBeginInfo::builder()
+      .render_pass(render_pass)
+      .framebuffer(a_target.framebuffer)
+      .render_area(vk::Rect2D{offset: vk::Offset2D{x: 0, y: 0}, extent: vk::Extent2D{width: a_target.width, height: a_target.height}})
+      .clear_values(&clear_values);
+
+    unsafe {
+      self.device.device.cmd_begin_render_pass(self.command_buffer, &begin_info, vk::SubpassContents::INLINE);
+    }
+
+    self.frame_resources.push(a_target.clone());
+    self.in_render_pass = true;
+    self.apply_viewport();
+  }
+
+  fn end_render_pass(&mut self) {
+    if self.in_render_pass {
+      unsafe {
+        self.device.device.cmd_end_render_pass(self.command_buffer);
+      }
+      self.in_render_pass = false;
+    }
+  }
+
+  // images are stored bottom row first so gl's bottom left viewport origin is the first row
+  fn apply_viewport(&self) {
+    let viewport = vk::Viewport{
+      x: self.viewport_pos.x as f32,
+      y: self.viewport_pos.y as f32,
+      width: std::cmp::max(self.viewport_size.x, 1) as f32,
+      height: std::cmp::max(self.viewport_size.y, 1) as f32,
+      min_depth: 0.0,
+      max_depth: 1.0
+    };
+
+    // the scissor is clamped to the target as vulkan does not allow negative offsets
+    let (min, max) = match self.render_state.scissor {
+      Some((pos, size)) => (pos.max(IVec2::ZERO), (pos + size.max(IVec2::ZERO)).max(IVec2::ZERO)),
+      None => (IVec2::ZERO, IVec2::new(self.target.width as i32, self.target.height as i32))
+    };
+    let max = max.min(IVec2::new(self.target.width as i32, self.target.height as i32));
+    let min = min.min(max);
+
+    let scissor = vk::Rect2D{
+      offset: vk::Offset2D{x: min.x, y: min.y},
+      extent: vk::Extent2D{width: (max.x - min.x) as u32, height: (max.y - min.y) as u32}
+    };
+
+    unsafe {
+      self.device.device.cmd_set_viewport(self.command_buffer, 0, &[viewport]);
+      self.device.device.cmd_set_scissor(self.command_buffer, 0, &[scissor]);
+    }
+  }
+
+  fn acquire_swapchain_image(&mut self) -> Option<u32> {
+    let window = match &self.window {
+      Some(res) => res.clone(),
+      None => return None
+    };
+
+    if self.swapchain_outdated || self.swapchain.is_none() {
+      let swapchain = SwapchainVulkan::new(&self.device, &window, self.swapchain.as_ref());
+      // old swapchain is dropped after the new one is created from it
+      self.swapchain = swapchain.ok();
+      self.swapchain_outdated = false;
+    }
+
+    let swapchain = match &self.swapchain {
+      Some(res) => res,
+      None => return None
+    };
+
+    match unsafe { swapchain.loader.acquire_next_image(swapchain.swapchain, u64::MAX, swapchain.image_available, vk::Fence::null()) } {
+      Ok((index, _suboptimal)) => Some(index),
+      Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+        self.swapchain_outdated = true;
+        None
+      },
+      Err(res) => {
+        eprintln!("Error {}", res);
+        None
+      }
+    }
+  }
+
+  fn allocate_descriptor_set(&mut self, a_layout: vk::DescriptorSetLayout) -> Option<vk::DescriptorSet> {
+    let layouts = [a_layout];
+
+    loop {
+      if self.descriptor_pool_index >= self.descriptor_pools.len() {
+        let pool_sizes = [
+          vk::DescriptorPoolSize{ty: vk::DescriptorType::SAMPLED_IMAGE, descriptor_count: DESCRIPTOR_POOL_SETS * 4},
+          vk::DescriptorPoolSize{ty: vk::DescriptorType::SAMPLER, descriptor_count: DESCRIPTOR_POOL_SETS * 4}
+        ];
+
+        let pool_info = vk::DescriptorPoolCreateInfo::builder()
+          .max_sets(DESCRIPTOR_POOL_SETS)
+          .pool_sizes(&pool_sizes);
+
+        match unsafe { self.device.device.create_descriptor_pool(&pool_info, None) } {
+          Ok(res) => self.descriptor_pools.push(res),
+          Err(_res) => return None
+        };
+      }
+
+      let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+        .descriptor_pool(self.descriptor_pools[self.descriptor_pool_index])
+        .set_layouts(&layouts);
+
+      match unsafe { self.device.device.allocate_descriptor_sets(&allocate_info) } {
+        Ok(res) => return Some(res[0]),
+        Err(vk::Result::ERROR_OUT_OF_POOL_MEMORY) | Err(vk::Result::ERROR_FRAGMENTED_POOL) => self.descriptor_pool_index += 1,
+        Err(_res) => return None
+      }
+    }
+  }
+
+  // zero sized buffers are invalid so empty data still gets a buffer
+  fn gen_instance_buffer(&self, a_data: &[u8]) -> Result<BufferVulkan, RendererError> {
+    let buffer = match BufferVulkan::new(&self.device, std::cmp::max(a_data.len(), 4) as vk::DeviceSize,
+      vk::BufferUsageFlags::VERTEX_BUFFER,
+      vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT)
+    {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    match buffer.write(a_data) {
+      Ok(_res) => Ok(buffer),
+      Err(res) => Err(res)
+    }
+  }
+
+  // the range is clamped to the geometry. the instance buffer is bound after the geometry's buffers
+  fn draw_vertices(&mut self, a_geometry: &GeometryVulkan, a_range: DrawRange, a_instances: Option<&InstancesVulkan>) {
+    let program = match &self.program {
+      Some(res) => res.clone(),
+      None => return
+    };
+
+    let mut layout = a_geometry.layout.clone();
+    let mut buffers = a_geometry.buffers.clone();
+    let mut num_instances = 1;
+
+    if let Some(instances) = a_instances {
+      layout.buffers.push(instances.layout.clone());
+      buffers.push(instances.buffer.borrow().clone());
+      num_instances = instances.num.get();
+    }
+
+    if !self.in_render_pass {
+      self.begin_render_pass(RendererClearType::NONE);
+      if !self.in_render_pass {
+        return
+      }
+    }
+
+    let pipeline = match program.get_pipeline(&self.target, &layout, a_geometry.topology, &self.render_state) {
+      Ok(res) => res,
+      Err(_res) => return
+    };
+
+    let first = std::cmp::min(a_range.first, a_geometry.num);
+    let count = std::cmp::min(a_range.count, a_geometry.num - first);
+
+    // vulkan has no line loops, a strip is drawn with indices that return to the first vertex
+    let loop_indices = match a_geometry.topology {
+      PrimitiveTopology::LineLoop if count > 1 => match self.gen_loop_indices(a_geometry, first, count) {
+        Ok(res) => Some(Rc::new(res)),
+        Err(_res) => return
+      },
+      _ => None
+    };
+
+    // textures of the program use the material sampler with the same name, a sampler named <texture>_sampler
+    // uses the same sampler as that texture. anything without a material sampler reads the default texture
+    let find_texture = |a_name: &str| -> (Rc<ImageVulkan>, Rc<SamplerObjectVulkan>) {
+      match self.textures.iter().find(|(name, _image, _sampler)| name == a_name) {
+        Some((_name, image, sampler)) => (image.clone(), sampler.clone()),
+        None => (self.default_image.clone(), self.default_sampler.clone())
+      }
+    };
+
+    let bound_textures: Vec<(Rc<ImageVulkan>, Rc<SamplerObjectVulkan>)> = program.textures.iter().map(|texture| {
+      find_texture(&texture.name)
+    }).collect();
+
+    let bound_samplers: Vec<(Rc<ImageVulkan>, Rc<SamplerObjectVulkan>)> = program.samplers.iter().map(|sampler| {
+      find_texture(sampler.name.strip_suffix("_sampler").unwrap_or(&sampler.name))
+    }).collect();
+
+    let image_infos: Vec<vk::DescriptorImageInfo> = bound_textures.iter().map(|(image, _sampler)| {
+      vk::DescriptorImageInfo{sampler: vk::Sampler::null(), image_view: image.view, image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL}
+    }).collect();
+
+    let sampler_infos: Vec<vk::DescriptorImageInfo> = bound_samplers.iter().map(|(_image, sampler)| {
+      vk::DescriptorImageInfo{sampler: sampler.sampler, image_view: vk::ImageView::null(), image_layout: vk::ImageLayout::UNDEFINED}
+    }).collect();
+
+    for (image, sampler) in bound_textures.into_iter().chain(bound_samplers.into_iter()) {
+      self.frame_resources.push(image);
+      self.frame_resources.push(sampler);
+    }
+
+    if !image_infos.is_empty() || !sampler_infos.is_empty() {
+      let descriptor_set = match self.allocate_descriptor_set(program.set_layout) {
+        Some(res) => res,
+        None => return
+      };
+
+      let mut writes: Vec<vk::WriteDescriptorSet> = Vec::new();
+
+      for (binding, info) in program.textures.iter().zip(image_infos.iter()) {
+        writes.push(vk::WriteDescriptorSet::builder()
+          .dst_set(descriptor_set)
+          .dst_binding(binding.binding)
+          .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+          .image_info(std::slice::from_ref(info))
+          .build());
+      }
+
+      for (binding, info) in program.samplers.iter().zip(sampler_infos.iter()) {
+        writes.push(vk::WriteDescriptorSet::builder()
+          .dst_set(descriptor_set)
+          .dst_binding(binding.binding)
+          .descriptor_type(vk::DescriptorType::SAMPLER)
+          .image_info(std::slice::from_ref(info))
+          .build());
+      }
+
+      unsafe {
+        self.device.device.update_descriptor_sets(&writes, &[]);
+        self.device.device.cmd_bind_descriptor_sets(self.command_buffer, vk::PipelineBindPoint::GRAPHICS,
+          program.layout, 0, &[descriptor_set], &[]);
+      }
+    }
+
+    unsafe {
+      self.device.device.cmd_bind_pipeline(self.command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+
+      let push_constants = program.push_constants.borrow();
+      if !push_constants.is_empty() {
+        self.device.device.cmd_push_constants(self.command_buffer, program.layout, program.push_constant_stages, 0, push_constants.as_slice());
+      }
+
+      let vertex_buffers: Vec<vk::Buffer> = buffers.iter().map(|buffer| buffer.buffer).collect();
+      let offsets = vec![0; vertex_buffers.len()];
+      if !vertex_buffers.is_empty() {
+        self.device.device.cmd_bind_vertex_buffers(self.command_buffer, 0, &vertex_buffers, &offsets);
+      }
+
+      match (&loop_indices, &a_geometry.indices) {
+        (Some(buffer), indices) => {
+          let base_vertex = match indices {
+            Some(_res) => a_range.base_vertex,
+            None => 0
+          };
+          self.device.device.cmd_bind_index_buffer(self.command_buffer, buffer.buffer, 0, vk::IndexType::UINT32);
+          self.device.device.cmd_draw_indexed(self.command_buffer, count + 1, num_instances, 0, base_vertex, 0);
+        },
+        (None, Some((buffer, index_type))) => {
+          self.device.device.cmd_bind_index_buffer(self.command_buffer, buffer.buffer, 0, *index_type);
+          self.device.device.cmd_draw_indexed(self.command_buffer, count, num_instances, first, a_range.base_vertex, 0);
+        },
+        (None, None) => self.device.device.cmd_draw(self.command_buffer, count, num_instances, first, 0)
+      }
+    }
+
+    if let Some(buffer) = loop_indices {
+      self.frame_resources.push(buffer);
+    }
+
+    self.frame_resources.push(program);
+    for buffer in buffers {
+      self.frame_resources.push(buffer);
+    }
+    if let Some((buffer, _index_type)) = &a_geometry.indices {
+      self.frame_resources.push(buffer.clone());
+    }
+  }
+
+  // 32 bit indices of the range followed by the first again, read back from the index buffer for indexed geometry
+  fn gen_loop_indices(&self, a_geometry: &GeometryVulkan, a_first: u32, a_count: u32) -> Result<BufferVulkan, RendererError> {
+    let mut indices: Vec<u32> = match &a_geometry.indices {
+      Some((buffer, index_type)) => {
+        let size = match *index_type {
+          vk::IndexType::UINT16 => 2,
+          _ => 4
+        };
+
+        let mut data = vec![0u8; ((a_first + a_count) * size) as usize];
+        if let Err(res) = buffer.read(&mut data) {
+          return Err(res)
+        }
+
+        data[(a_first * size) as usize..].chunks_exact(size as usize).map(|bytes| match size {
+          2 => u16::from_ne_bytes([bytes[0], bytes[1]]) as u32,
+          _ => u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        }).collect()
+      },
+      None => (a_first..a_first + a_count).collect()
+    };
+    indices.push(indices[0]);
+
+    let data: Vec<u8> = indices.iter().flat_map(|index| index.to_ne_bytes()).collect();
+    let buffer = match BufferVulkan::new(&self.device, data.len() as vk::DeviceSize,
+      vk::BufferUsageFlags::INDEX_BUFFER,
+      vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT)
+    {
+      Ok(res) => res,
+      Err(res) => return Err(res)
+    };
+
+    match buffer.write(&data) {
+      Ok(_res) => Ok(buffer),
+      Err(res) => Err(res)
+    }
+  }
+
+  fn create_program(&self, a_shaders: &[(vk::ShaderStageFlags, &ShaderVulkan)]) -> Result<ProgramStateVulkan, RendererError> {
+    let mut uniforms: Vec<UniformReflectionVulkan> = Vec::new();
+    let mut textures: Vec<BindingReflectionVulkan> = Vec::new();
+    let mut samplers: Vec<BindingReflectionVulkan> = Vec::new();
+    let mut push_constants_size = 0;
+    let mut push_constant_stages = vk::ShaderStageFlags::empty();
+
+    // stages share one push constant block and descriptor set
+    for (stage, shader) in a_shaders {
+      let reflection = &shader.reflection;
+
+      if reflection.push_constants_size > 0 {
+        push_constants_size = std::cmp::max(push_constants_size, reflection.push_constants_size);
+        push_constant_stages |= *stage;
+      }
+
+      for uniform in &reflection.push_constants {
+        if !uniforms.iter().any(|existing| existing.name == uniform.name) {
+          uniforms.push(uniform.clone());
+        }
+      }
+
+      for (src, dst) in [(&reflection.textures, &mut textures), (&reflection.samplers, &mut samplers)] {
+        for binding in src {
+          if binding.set != 0 {
+            eprintln!("Error only descriptor set 0 is supported, {} uses set {}", binding.name, binding.set);
+            return Err(RendererError::Unimplemented)
+          }
+
+          if !dst.iter().any(|existing| existing.binding == binding.binding) {
+            dst.push(binding.clone());
+          }
+        }
+      }
+    }
+
+    textures.sort_by_key(|binding| binding.binding);
+    samplers.sort_by_key(|binding| binding.binding);
+
+    let mut layout_bindings: Vec<vk::DescriptorSetLayoutBinding> = Vec::new();
+
+    for (bindings, descriptor_type) in [(&textures, vk::DescriptorType::SAMPLED_IMAGE), (&samplers, vk::DescriptorType::SAMPLER)] {
+      for binding in bindings {
+        layout_bindings.push(vk::DescriptorSetLayoutBinding::builder()
+          .binding(binding.binding)
+          .descriptor_type(descriptor_type)
+          .descriptor_count(1)
+          .stage_flags(vk::ShaderStageFlags::ALL_GRAPHICS)
+          .build());
+      }
+    }
+
+    let set_layout_info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(&layout_bindings);
+
+    let set_layout = match unsafe { self.device.device.create_descriptor_set_layout(&set_layout_info, None) } {
+      Ok(res) => res,
+      Err(_res) => return Err(RendererError::Error)
+    };
+
+    let set_layouts = [set_layout];
+    let push_constant_ranges = [vk::PushConstantRange{stage_flags: push_constant_stages, offset: 0, size: push_constants_size}];
+
+    let mut layout_info = vk::PipelineLayoutCreateInfo::builder().set_layouts(&set_layouts);
+    if push_constants_size > 0 {
+      layout_info = layout_info.push_constant_ranges(&push_constant_ranges);
+    }
+
+    let layout = match unsafe { self.device.device.create_pipeline_layout(&layout_info, None) } {
+      Ok(res) => res,
+      Err(_res) => {
+        unsafe { self.device.device.destroy_descriptor_set_layout(set_layout, None) };
+        return Err(RendererError::Error)
+      }
+    };
+
+    // uniform blocks aren't supported so none are reflected. samplers are the textures they are bound with
+    let mut reflection = ProgramReflection::default();
+    for uniform in &uniforms {
+      if let Some(info) = uniform.info {
+        reflection.uniforms.push(UniformReflection{name: uniform.name.clone(), info: info, location: uniform.offset as i32});
+      }
+    }
+    for texture in &textures {
+      reflection.samplers.push(SamplerReflection{name: texture.name.clone(), dimension: texture.dimension, count: 1, unit: texture.binding});
+    }
+    for (stage, shader) in a_shaders {
+      if *stage == vk::ShaderStageFlags::VERTEX {
+        reflection.attributes = shader.reflection.attributes.clone();
+      }
+    }
+
+    Ok(ProgramStateVulkan{
+      device: self.device.clone(),
+      set_layout: set_layout,
+      layout: layout,
+      stages: a_shaders.iter().map(|(stage, shader)| (*stage, shader.module.clone())).collect(),
+      pipelines: RefCell::new(Vec::new()),
+      push_constant_stages: push_constant_stages,
+      push_constants: RefCell::new(vec![0u8; push_constants_size as usize]),
+      uniforms: uniforms,
+      textures: textures,
+      samplers: samplers,
+      reflection: reflection
+    })
+  }
+}
+
+impl Drop for RendererVulkan {
+  fn drop(&mut self) {
+    unsafe {
+      let _ = self.device.device.device_wait_idle();
+    }
+
+    self.frame_resources.clear();
+
+    unsafe {
+      for pool in &self.descriptor_pools {
+        self.device.device.destroy_descriptor_pool(*pool, None);
+      }
+
+      self.device.device.destroy_fence(self.fence, None);
+      self.device.device.free_command_buffers(self.device.command_pool, &[self.command_buffer]);
+    }
+  }
+}
+
+fn get_depth_aspect(a_format: vk::Format) -> vk::ImageAspectFlags {
+  match a_format {
+    vk::Format::D16_UNORM_S8_UINT | vk::Format::D24_UNORM_S8_UINT | vk::Format::D32_SFLOAT_S8_UINT => vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL,
+    _ => vk::ImageAspectFlags::DEPTH
+  }
+}
+
+fn get_compressed_format(a_format: CompressedFormat, a_srgb: bool) -> vk::Format {
+  match (a_format, a_srgb) {
+    (CompressedFormat::BC1, false) => vk::Format::BC1_RGBA_UNORM_BLOCK,
+    (CompressedFormat::BC1, true) => vk::Format::BC1_RGBA_SRGB_BLOCK,
+    (CompressedFormat::BC2, false) => vk::Format::BC2_UNORM_BLOCK,
+    (CompressedFormat::BC2, true) => vk::Format::BC2_SRGB_BLOCK,
+    (CompressedFormat::BC3, false) => vk::Format::BC3_UNORM_BLOCK,
+    (CompressedFormat::BC3, true) => vk::Format::BC3_SRGB_BLOCK,
+    (CompressedFormat::BC4, _) => vk::Format::BC4_UNORM_BLOCK,
+    (CompressedFormat::BC5, _) => vk::Format::BC5_UNORM_BLOCK,
+    (CompressedFormat::BC7, false) => vk::Format::BC7_UNORM_BLOCK,
+    (CompressedFormat::BC7, true) => vk::Format::BC7_SRGB_BLOCK,
+    (CompressedFormat::ETC2RGB8, false) => vk::Format::ETC2_R8G8B8_UNORM_BLOCK,
+    (CompressedFormat::ETC2RGB8, true) => vk::Format::ETC2_R8G8B8_SRGB_BLOCK,
+    (CompressedFormat::ETC2RGBA8, false) => vk::Format::ETC2_R8G8B8A8_UNORM_BLOCK,
+    (CompressedFormat::ETC2RGBA8, true) => vk::Format::ETC2_R8G8B8A8_SRGB_BLOCK,
+    // unorm and srgb formats alternate in the order of the block sizes
+    (CompressedFormat::ASTC(_width, _height), srgb) => {
+      let index = a_format.get_astc_index().unwrap_or(0) as i32;
+      vk::Format::from_raw(vk::Format::ASTC_4X4_UNORM_BLOCK.as_raw() + 2 * index + srgb as i32)
+    }
+  }
+}
+
+fn get_filter(a_filter: FilterMode) -> vk::Filter {
+  match a_filter {
+    FilterMode::Nearest => vk::Filter::NEAREST,
+    FilterMode::Linear => vk::Filter::LINEAR
+  }
+}
+
+fn get_address_mode(a_wrap: WrapMode) -> vk::SamplerAddressMode {
+  match a_wrap {
+    WrapMode::Repeat => vk::SamplerAddressMode::REPEAT,
+    WrapMode::MirroredRepeat => vk::SamplerAddressMode::MIRRORED_REPEAT,
+    WrapMode::ClampToEdge => vk::SamplerAddressMode::CLAMP_TO_EDGE,
+    WrapMode::ClampToBorder => vk::SamplerAddressMode::CLAMP_TO_BORDER
+  }
+}
+
+fn get_compare_op(a_compare: CompareFunction) -> vk::CompareOp {
+  match a_compare {
+    CompareFunction::Never => vk::CompareOp::NEVER,
+    CompareFunction::Less => vk::CompareOp::LESS,
+    CompareFunction::Equal => vk::CompareOp::EQUAL,
+    CompareFunction::LessEqual => vk::CompareOp::LESS_OR_EQUAL,
+    CompareFunction::Greater => vk::CompareOp::GREATER,
+    CompareFunction::NotEqual => vk::CompareOp::NOT_EQUAL,
+    CompareFunction::GreaterEqual => vk::CompareOp::GREATER_OR_EQUAL,
+    CompareFunction::Always => vk::CompareOp::ALWAYS
+  }
+}
+
+// patches need tesselation programs which naga is unable to compile
+fn get_primitive_topology(a_topology: PrimitiveTopology) -> Option<vk::PrimitiveTopology> {
+  match a_topology {
+    PrimitiveTopology::Points => Some(vk::PrimitiveTopology::POINT_LIST),
+    PrimitiveTopology::Lines => Some(vk::PrimitiveTopology::LINE_LIST),
+    PrimitiveTopology::LineStrip | PrimitiveTopology::LineLoop => Some(vk::PrimitiveTopology::LINE_STRIP),
+    PrimitiveTopology::Triangles => Some(vk::PrimitiveTopology::TRIANGLE_LIST),
+    PrimitiveTopology::TriangleStrip => Some(vk::PrimitiveTopology::TRIANGLE_STRIP),
+    PrimitiveTopology::TriangleFan => Some(vk::PrimitiveTopology::TRIANGLE_FAN),
+    PrimitiveTopology::Patches => None
+  }
+}
+
+fn get_blend_factor(a_factor: BlendFactor) -> vk::BlendFactor {
+  match a_factor {
+    BlendFactor::Zero => vk::BlendFactor::ZERO,
+    BlendFactor::One => vk::BlendFactor::ONE,
+    BlendFactor::SrcColor => vk::BlendFactor::SRC_COLOR,
+    BlendFactor::OneMinusSrcColor => vk::BlendFactor::ONE_MINUS_SRC_COLOR,
+    BlendFactor::DstColor => vk::BlendFactor::DST_COLOR,
+    BlendFactor::OneMinusDstColor => vk::BlendFactor::ONE_MINUS_DST_COLOR,
+    BlendFactor::SrcAlpha => vk::BlendFactor::SRC_ALPHA,
+    BlendFactor::OneMinusSrcAlpha => vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+    BlendFactor::DstAlpha => vk::BlendFactor::DST_ALPHA,
+    BlendFactor::OneMinusDstAlpha => vk::BlendFactor::ONE_MINUS_DST_ALPHA
+  }
+}
+
+fn get_blend_op(a_op: BlendOp) -> vk::BlendOp {
+  match a_op {
+    BlendOp::Add => vk::BlendOp::ADD,
+    BlendOp::Subtract => vk::BlendOp::SUBTRACT,
+    BlendOp::ReverseSubtract => vk::BlendOp::REVERSE_SUBTRACT,
+    BlendOp::Min => vk::BlendOp::MIN,
+    BlendOp::Max => vk::BlendOp::MAX
+  }
+}
+
+fn get_stencil_op(a_op: StencilOp) -> vk::StencilOp {
+  match a_op {
+    StencilOp::Keep => vk::StencilOp::KEEP,
+    StencilOp::Zero => vk::StencilOp::ZERO,
+    StencilOp::Replace => vk::StencilOp::REPLACE,
+    StencilOp::IncrementClamp => vk::StencilOp::INCREMENT_AND_CLAMP,
+    StencilOp::DecrementClamp => vk::StencilOp::DECREMENT_AND_CLAMP,
+    StencilOp::Invert => vk::StencilOp::INVERT,
+    StencilOp::IncrementWrap => vk::StencilOp::INCREMENT_AND_WRAP,
+    StencilOp::DecrementWrap => vk::StencilOp::DECREMENT_AND_WRAP
+  }
+}
+
+fn get_stencil_op_state(a_face: &StencilFaceState, a_stencil: &StencilState) -> vk::StencilOpState {
+  vk::StencilOpState{
+    fail_op: get_stencil_op(a_face.fail),
+    pass_op: get_stencil_op(a_face.pass),
+    depth_fail_op: get_stencil_op(a_face.depth_fail),
+    compare_op: get_compare_op(a_face.compare),
+    compare_mask: a_stencil.read_mask,
+    write_mask: a_stencil.write_mask,
+    reference: a_stencil.reference
+  }
+}
+
+// vulkan only has fixed border colors without VK_EXT_custom_border_color, the closest one is used
+fn get_border_color(a_color: Vec4) -> vk::BorderColor {
+  if a_color.w < 0.5 {
+    vk::BorderColor::FLOAT_TRANSPARENT_BLACK
+  }
+  else if (a_color.x + a_color.y + a_color.z) / 3.0 < 0.5 {
+    vk::BorderColor::FLOAT_OPAQUE_BLACK
+  }
+  else {
+    vk::BorderColor::FLOAT_OPAQUE_WHITE
+  }
+}
+
+// geometry without indices, vertex buffers are checked against the layout
+fn gen_geometry(a_buffers: &[&Box<dyn Vertices>], a_layout: &VertexLayout) -> Result<GeometryVulkan, RendererError> {
+  if a_buffers.len() != a_layout.buffers.len() {
+    return Err(RendererError::Error)
+  }
+
+  // unsupported attributes are reported now instead of when the pipeline is created
+  if let Err(res) = get_vertex_input(a_layout) {
+    return Err(res)
+  }
+
+  let mut buffers = Vec::new();
+  let mut num: Option<usize> = None;
+  for (buffer, layout) in a_buffers.iter().zip(a_layout.buffers.iter()) {
+    let buffer = match buffer.any().downcast_ref::<VerticesVulkan>() {
+      Some(res) => res,
+      None => return Err(RendererError::InvalidCast)
+    };
+
+    if layout.step == VertexStep::PerVertex && layout.stride > 0 {
+      let count = buffer.size / layout.stride as usize;
+      num = Some(match num {
+        Some(res) => res.min(count),
+        None => count
+      });
+    }
+
+    buffers.push(buffer.buffer.clone());
+  }
+
+  Ok(GeometryVulkan{buffers: buffers, layout: a_layout.clone(), indices: None, num: num.unwrap_or(0) as u32, topology: PrimitiveTopology::Triangles})
+}
+
+// one binding for each buffer of the layout, matrices take a location for each column
+fn get_vertex_input(a_layout: &VertexLayout) -> Result<(Vec<vk::VertexInputBindingDescription>, Vec<vk::VertexInputAttributeDescription>), RendererError> {
+  let mut bindings = Vec::new();
+  let mut attributes = Vec::new();
+
+  for (binding, layout) in a_layout.buffers.iter().enumerate() {
+    let input_rate = match layout.step {
+      VertexStep::PerVertex => vk::VertexInputRate::VERTEX,
+      VertexStep::PerInstance => vk::VertexInputRate::INSTANCE
+    };
+    bindings.push(vk::VertexInputBindingDescription{binding: binding as u32, stride: layout.stride, input_rate: input_rate});
+
+    for attribute in &layout.attributes {
+      let rows = attribute.container_type.get_num_rows();
+      let format = match get_vertex_format(attribute.element_type, rows, attribute.normalized) {
+        Some(res) => res,
+        None => return Err(RendererError::UnsupportedAPI)
+      };
+
+      for column in 0..attribute.container_type.get_num_columns() {
+        attributes.push(vk::VertexInputAttributeDescription{
+          location: attribute.location + column,
+          binding: binding as u32,
+          format: format,
+          offset: attribute.offset + column * rows * attribute.element_type.get_size()
+        });
+      }
+    }
+  }
+
+  Ok((bindings, attributes))
+}
+
+// 64 bit integers and normalized 32 bit integers have no vertex format
+fn get_vertex_format(a_type: ElementType, a_rows: u32, a_normalized: bool) -> Option<vk::Format> {
+  let formats = match (a_type, a_normalized) {
+    (ElementType::Float16, _) => [vk::Format::R16_SFLOAT, vk::Format::R16G16_SFLOAT, vk::Format::R16G16B16_SFLOAT, vk::Format::R16G16B16A16_SFLOAT],
+    (ElementType::Float32, _) => [vk::Format::R32_SFLOAT, vk::Format::R32G32_SFLOAT, vk::Format::R32G32B32_SFLOAT, vk::Format::R32G32B32A32_SFLOAT],
+    (ElementType::Float64, _) => [vk::Format::R64_SFLOAT, vk::Format::R64G64_SFLOAT, vk::Format::R64G64B64_SFLOAT, vk::Format::R64G64B64A64_SFLOAT],
+    (ElementType::Int8, true) => [vk::Format::R8_SNORM, vk::Format::R8G8_SNORM, vk::Format::R8G8B8_SNORM, vk::Format::R8G8B8A8_SNORM],
+    (ElementType::Int8, false) => [vk::Format::R8_SINT, vk::Format::R8G8_SINT, vk::Format::R8G8B8_SINT, vk::Format::R8G8B8A8_SINT],
+    (ElementType::Uint8, true) => [vk::Format::R8_UNORM, vk::Format::R8G8_UNORM, vk::Format::R8G8B8_UNORM, vk::Format::R8G8B8A8_UNORM],
+    (ElementType::Uint8, false) => [vk::Format::R8_UINT, vk::Format::R8G8_UINT, vk::Format::R8G8B8_UINT, vk::Format::R8G8B8A8_UINT],
+    (ElementType::Int16, true) => [vk::Format::R16_SNORM, vk::Format::R16G16_SNORM, vk::Format::R16G16B16_SNORM, vk::Format::R16G16B16A16_SNORM],
+    (ElementType::Int16, false) => [vk::Format::R16_SINT, vk::Format::R16G16_SINT, vk::Format::R16G16B16_SINT, vk::Format::R16G16B16A16_SINT],
+    (ElementType::Uint16, true) => [vk::Format::R16_UNORM, vk::Format::R16G16_UNORM, vk::Format::R16G16B16_UNORM, vk::Format::R16G16B16A16_UNORM],
+    (ElementType::Uint16, false) => [vk::Format::R16_UINT, vk::Format::R16G16_UINT, vk::Format::R16G16B16_UINT, vk::Format::R16G16B16A16_UINT],
+    (ElementType::Int32, false) => [vk::Format::R32_SINT, vk::Format::R32G32_SINT, vk::Format::R32G32B32_SINT, vk::Format::R32G32B32A32_SINT],
+    (ElementType::Uint32, false) => [vk::Format::R32_UINT, vk::Format::R32G32_UINT, vk::Format::R32G32B32_UINT, vk::Format::R32G32B32A32_UINT],
+    _ => return None
+  };
+
+  formats.get((a_rows as usize).wrapping_sub(1)).copied()
+}
+
+// bytes per texel of the color formats able to be read back, 0 for others
+fn get_texel_size(a_format: vk::Format) -> u32 {
+  match a_format {
+    vk::Format::R8_UNORM => 1,
+    vk::Format::R8G8_UNORM => 2,
+    vk::Format::R8G8B8A8_UNORM => 4,
+    vk::Format::R16G16B16A16_SFLOAT => 8,
+    vk::Format::R32G32B32A32_SFLOAT => 16,
+    _ => 0
+  }
+}
+
+// converts the same as gl::ReadPixels with gl::RGBA and gl::UNSIGNED_BYTE, missing channels are 0 and alpha 1
+fn to_rgba8(a_format: vk::Format, a_data: &[u8]) -> Vec<u8> {
+  let to_unorm8 = |a_value: f32| (a_value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+
+  match a_format {
+    vk::Format::R8_UNORM => a_data.iter().flat_map(|r| [*r, 0, 0, 255]).collect(),
+    vk::Format::R8G8_UNORM => a_data.chunks_exact(2).flat_map(|rg| [rg[0], rg[1], 0, 255]).collect(),
+    vk::Format::R16G16B16A16_SFLOAT => a_data.chunks_exact(2)
+      .map(|half| to_unorm8(half_to_f32(u16::from_ne_bytes([half[0], half[1]]))))
+      .collect(),
+    vk::Format::R32G32B32A32_SFLOAT => a_data.chunks_exact(4)
+      .map(|float| to_unorm8(f32::from_ne_bytes([float[0], float[1], float[2], float[3]])))
+      .collect(),
+    _ => a_data.to_vec()
+  }
+}
+
+fn get_vulkan_version(a_version: Version) -> (i32, i32) {
+  let major = match a_version.major {
+    VersionNum::Value(res) => res,
+    _ => 1
+  };
+
+  let minor = match a_version.minor {
+    VersionNum::Value(res) => res,
+    VersionNum::Highest => RendererVulkan::VULKAN_MAX_VERSION_MINOR,
+    VersionNum::Lowest => 0
+  };
+
+  (major, minor)
 }
@@ -0,0 +1,160 @@
+extern crate naga;
+
+use crate::gpu::renderer_types::*;
+use crate::gpu::shader_compiler::*;
+use crate::gpu::uniforms::*;
+
+// Vulkan glsl is compiled to spirv with naga. Only what the renderer needs to build pipelines is reflected
+
+#[derive(Clone)]
+pub struct UniformReflectionVulkan {
+  pub name: String,
+  pub offset: u32,
+  // none for structs and non-square matrices
+  pub info: Option<UniformType>,
+}
+
+#[derive(Clone)]
+pub struct BindingReflectionVulkan {
+  pub name: String,
+  pub set: u32,
+  pub binding: u32,
+  // of textures, samplers are always D2
+  pub dimension: TextureDimension,
+}
+
+pub struct ShaderReflectionVulkan {
+  pub push_constants: Vec<UniformReflectionVulkan>,
+  pub push_constants_size: u32,
+  pub textures: Vec<BindingReflectionVulkan>,
+  pub samplers: Vec<BindingReflectionVulkan>,
+  // inputs of vertex shaders
+  pub attributes: Vec<AttributeReflection>,
+}
+
+pub fn compile_glsl(a_shader_type: ShaderType, a_source: &str) -> Result<(Vec<u32>, ShaderReflectionVulkan), RendererError> {
+  let shader = match ParsedShader::parse(a_shader_type, a_source) {
+    Ok(res) => res,
+    Err(res) => return Err(res)
+  };
+
+  match shader.to_spirv() {
+    Ok(res) => Ok((res, reflect(&shader.module))),
+    Err(res) => Err(res)
+  }
+}
+
+fn reflect(a_module: &naga::Module) -> ShaderReflectionVulkan {
+  let mut reflection = ShaderReflectionVulkan{
+    push_constants: Vec::new(),
+    push_constants_size: 0,
+    textures: Vec::new(),
+    samplers: Vec::new(),
+    attributes: Vec::new()
+  };
+
+  for (_handle, variable) in a_module.global_variables.iter() {
+    let name = match &variable.name {
+      Some(res) => res.clone(),
+      None => String::from("")
+    };
+
+    match variable.space {
+      naga::AddressSpace::PushConstant => {
+        if let naga::TypeInner::Struct{members, span} = &a_module.types[variable.ty].inner {
+          for member in members {
+            if let Some(member_name) = &member.name {
+              reflection.push_constants.push(UniformReflectionVulkan{name: member_name.clone(), offset: member.offset, info: get_uniform_type(a_module, member.ty)});
+            }
+          }
+          reflection.push_constants_size = *span;
+        }
+      },
+      naga::AddressSpace::Handle => {
+        let binding = match &variable.binding {
+          Some(res) => res,
+          None => continue
+        };
+
+        let mut resource = BindingReflectionVulkan{name: name, set: binding.group, binding: binding.binding, dimension: TextureDimension::D2};
+
+        match a_module.types[variable.ty].inner {
+          naga::TypeInner::Image{dim, arrayed, ..} => {
+            resource.dimension = match (dim, arrayed) {
+              (naga::ImageDimension::D3, _) => TextureDimension::D3,
+              (naga::ImageDimension::Cube, _) => TextureDimension::Cube,
+              (_, true) => TextureDimension::D2Array,
+              (_, false) => TextureDimension::D2
+            };
+            reflection.textures.push(resource);
+          },
+          naga::TypeInner::Sampler{..} => reflection.samplers.push(resource),
+          _ => {}
+        }
+      },
+      _ => {}
+    }
+  }
+
+  reflection.textures.sort_by_key(|t| (t.set, t.binding));
+  reflection.samplers.sort_by_key(|t| (t.set, t.binding));
+
+  // built ins such as gl_VertexIndex have no location
+  for entry_point in a_module.entry_points.iter().filter(|entry_point| entry_point.stage == naga::ShaderStage::Vertex) {
+    for argument in &entry_point.function.arguments {
+      if let (Some(name), Some(naga::Binding::Location{location, ..}), Some(info)) = (&argument.name, &argument.binding, get_uniform_type(a_module, argument.ty)) {
+        reflection.attributes.push(AttributeReflection{name: name.clone(), info: info, location: *location});
+      }
+    }
+  }
+
+  reflection.attributes.sort_by_key(|attribute| attribute.location);
+
+  reflection
+}
+
+fn get_uniform_type(a_module: &naga::Module, a_type: naga::Handle<naga::Type>) -> Option<UniformType> {
+  let (element_type, container_type, num_components) = match a_module.types[a_type].inner {
+    naga::TypeInner::Scalar{kind, width} => (get_element_type(kind, width), ContainerType::Single, 1),
+    naga::TypeInner::Vector{size, kind, width} => (get_element_type(kind, width), get_vector_container(size), 1),
+    naga::TypeInner::Matrix{columns, rows, width} if columns == rows => {
+      let container_type = match columns {
+        naga::VectorSize::Bi => ContainerType::Mat2x2,
+        naga::VectorSize::Tri => ContainerType::Mat3x3,
+        naga::VectorSize::Quad => ContainerType::Mat4x4
+      };
+      (get_element_type(naga::ScalarKind::Float, width), container_type, 1)
+    },
+    naga::TypeInner::Array{base, size: naga::ArraySize::Constant(size), ..} => {
+      return match get_uniform_type(a_module, base) {
+        Some(res) if res.num_components == 1 => Some(UniformType{num_components: size.get() as u16, ..res}),
+        _ => None
+      }
+    },
+    _ => return None
+  };
+
+  match element_type {
+    Some(res) => Some(UniformType{element_type: res, container_type: container_type, num_components: num_components}),
+    None => None
+  }
+}
+
+fn get_element_type(a_kind: naga::ScalarKind, a_width: naga::Bytes) -> Option<ElementType> {
+  match (a_kind, a_width) {
+    (naga::ScalarKind::Float, 4) => Some(ElementType::Float32),
+    (naga::ScalarKind::Float, 8) => Some(ElementType::Float64),
+    (naga::ScalarKind::Sint, 4) => Some(ElementType::Int32),
+    (naga::ScalarKind::Uint, 4) => Some(ElementType::Uint32),
+    (naga::ScalarKind::Bool, _) => Some(ElementType::Bool),
+    _ => None
+  }
+}
+
+fn get_vector_container(a_size: naga::VectorSize) -> ContainerType {
+  match a_size {
+    naga::VectorSize::Bi => ContainerType::Vec2,
+    naga::VectorSize::Tri => ContainerType::Vec3,
+    naga::VectorSize::Quad => ContainerType::Vec4
+  }
+}
@@ -4,8 +4,10 @@ use crate::core::unsafe_send::UnsafeSend;
 use crate::gpu::renderer;
 use crate::gpu::renderer_types;
 use crate::gpu::opengl::renderer_opengl;
+#[cfg(not(target_arch = "wasm32"))]
 use crate::gpu::vulkan::renderer_vulkan;
 use crate::gpu::directx::renderer_directx12;
+use crate::gpu::software::renderer_software;
 use std::fmt;
 use std::sync::Mutex;
 use std::sync::Arc;
@@ -133,9 +135,17 @@ impl Window {
       }
       ))
     },
+      #[cfg(not(target_arch = "wasm32"))]
       renderer_types::RendererType::Vulkan => 
       {
-      Ok(Box::new( match renderer_vulkan::RendererVulkan::new(){
+      Ok(Box::new( match renderer_vulkan::RendererVulkan::new(a_min_version, a_max_version, a_window){
+        Ok(res) => res,
+        Err(_res) => return Err(WindowError::SdlRendererError)
+      }))
+    },
+      renderer_types::RendererType::Software => 
+      {
+      Ok(Box::new( match renderer_software::RendererSoftware::new(a_window.width, a_window.height){
         Ok(res) => res,
         Err(_res) => return Err(WindowError::SdlRendererError)
       }))
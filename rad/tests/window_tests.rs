@@ -1,9 +1,16 @@
+use std::rc::Rc;
 use std::sync::Arc;
 use rad::gpu::renderer_types;
 use rad::gpu::renderer::*;
+use rad::gpu::material;
+use rad::gpu::software::renderer_software::RendererSoftware;
+use rad::gpu::vulkan::renderer_vulkan::RendererVulkan;
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten"))))]
+use rad::gpu::opengl::renderer_opengl::RendererOpenGL;
 use rad::gui::window::Window;
 use std::env;
 use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use glam::*;
 
@@ -13,12 +20,44 @@ fn main() {
   tests.run("create_window", create_window);
   tests.run("init_opengl", init_opengl);
   tests.run("init_opengles", init_opengles);
+  tests.run("init_backends", init_backends);
   tests.run("clear_screen", clear_screen);
+  tests.run("draw_textured_quad", draw_textured_quad);
+  tests.run("render_target", render_target);
+  tests.run("vertex_layout", vertex_layout);
+  tests.run("indexed_geometry", indexed_geometry);
+  tests.run("indexed_base_vertex", indexed_base_vertex);
+  tests.run("draw_instanced", draw_instanced);
+  tests.run("compute_buffer", compute_buffer);
+  tests.run("compute_image", compute_image);
+  tests.run("program_stages", program_stages);
+  tests.run("program_validation", program_validation);
+  tests.run("uniform_setters", uniform_setters);
+  tests.run("uniform_types", uniform_types);
+  tests.run("uniform_layout", uniform_layout);
+  tests.run("uniform_block_layout", uniform_block_layout);
+  tests.run("uniform_buffer", uniform_buffer);
+  tests.run("sampler_desc", sampler_desc);
+  tests.run("multi_texture", multi_texture);
+  tests.run("texture_formats", texture_formats);
+  tests.run("texture_dimensions", texture_dimensions);
+  tests.run("equirectangular_cube", equirectangular_cube);
+  tests.run("compressed_textures", compressed_textures);
+  tests.run("compressed_containers", compressed_containers);
+  tests.run("render_state", render_state);
+  tests.run("primitive_topology", primitive_topology);
+  tests.run("shader_diagnostics", shader_diagnostics);
+  tests.run("program_reflection", program_reflection);
+  tests.run("shader_preprocessor", shader_preprocessor);
+  tests.run("glsl_translation", glsl_translation);
+  tests.run("shader_compiler", shader_compiler);
+  tests.run("shader_reload", shader_reload);
 
   println!("\nTest Results:");
   println!("Total: {}", tests.passed + tests.failed);
   println!("Passed: {}", tests.passed);
   println!("Failed: {}", tests.failed);
+  println!("Skipped backends: {}", SKIPPED_BACKENDS.load(Ordering::Relaxed));
 
   if tests.failed > 0 {
     std::process::exit(1);
@@ -165,6 +204,174 @@ fn init_opengles() {
   test_opengles(do_nothing);
 }
 
+// the apis a test runs on and the lowest version of each it needs, None where it isn't run
+#[derive(Clone, Copy)]
+struct Requires{
+  opengl: Option<(i32, i32)>,
+  opengles: Option<(i32, i32)>,
+  vulkan: Option<(i32, i32)>,
+  software: bool
+}
+
+// everything drawn with the basic program
+const EVERY_BACKEND: Requires = Requires{opengl: Some((3, 3)), opengles: Some((3, 0)), vulkan: Some((1, 0)), software: true};
+const GL_BACKENDS: Requires = Requires{opengl: Some((3, 3)), opengles: Some((3, 0)), vulkan: None, software: false};
+const SOFTWARE_BACKEND: Requires = Requires{opengl: None, opengles: None, vulkan: None, software: true};
+
+// a renderer the tests run on. the highest version to test is read from the environment, e.g.
+// OPENGL_HEADLESS_MAJOR=4 for mesa llvmpipe or VULKAN_HEADLESS_MAJOR=1 VULKAN_HEADLESS_MINOR=3 for lavapipe,
+// and every version from the test's lowest up to it is run. the software renderer has no versions
+struct Backend{
+  renderer_type: renderer_types::RendererType,
+  headless: bool,
+  // the highest minor version of each major version
+  minor_versions: &'static [i32]
+}
+
+const BACKENDS: [Backend; 7] = [
+  Backend{renderer_type: renderer_types::RendererType::OpenGL, headless: false, minor_versions: &[5, 1, 3, 6]},
+  Backend{renderer_type: renderer_types::RendererType::OpenGLES, headless: false, minor_versions: &[1, 0, 2]},
+  Backend{renderer_type: renderer_types::RendererType::OpenGL, headless: true, minor_versions: &[5, 1, 3, 6]},
+  Backend{renderer_type: renderer_types::RendererType::OpenGLES, headless: true, minor_versions: &[1, 0, 2]},
+  Backend{renderer_type: renderer_types::RendererType::Vulkan, headless: false, minor_versions: &[3]},
+  Backend{renderer_type: renderer_types::RendererType::Vulkan, headless: true, minor_versions: &[3]},
+  Backend{renderer_type: renderer_types::RendererType::Software, headless: true, minor_versions: &[]}
+];
+
+// backends a test needed that weren't available, reported with the results
+static SKIPPED_BACKENDS: AtomicUsize = AtomicUsize::new(0);
+
+impl Backend{
+  fn get_name(&self) -> String{
+    match self.headless && self.renderer_type != renderer_types::RendererType::Software {
+      true => format!("{} headless", self.renderer_type),
+      false => self.renderer_type.to_string()
+    }
+  }
+
+  fn get_env_key(&self) -> String{
+    match self.headless {
+      true => self.renderer_type.to_string().to_uppercase() + "_HEADLESS",
+      false => self.renderer_type.to_string().to_uppercase()
+    }
+  }
+
+  fn get_min_version(&self, a_requires: &Requires) -> Option<(i32, i32)>{
+    match self.renderer_type {
+      renderer_types::RendererType::OpenGL => a_requires.opengl,
+      renderer_types::RendererType::OpenGLES => a_requires.opengles,
+      renderer_types::RendererType::Vulkan => a_requires.vulkan,
+      _ if a_requires.software => Some((0, 0)),
+      _ => None
+    }
+  }
+
+  // panics when the renderer can't be created for a version the environment says is supported
+  fn create_renderer(&self, a_version: (i32, i32)) -> Box<dyn Renderer>{
+    let (major, minor) = a_version;
+    let lowest = renderer_types::Version{major: renderer_types::VersionNum::Value(major), minor: renderer_types::VersionNum::Value(minor), patch: renderer_types::VersionNum::Lowest};
+    let highest = renderer_types::Version{major: renderer_types::VersionNum::Value(major), minor: renderer_types::VersionNum::Value(minor), patch: renderer_types::VersionNum::Highest};
+
+    let renderer_result: Result<Box<dyn Renderer>, String> = match (self.renderer_type, self.headless) {
+      (renderer_types::RendererType::Software, _) => match RendererSoftware::new(240, 160) {
+        Ok(res) => Ok(Box::new(res)),
+        Err(res) => Err(res.to_string())
+      },
+      (renderer_types::RendererType::Vulkan, true) => match RendererVulkan::new_headless(lowest, lowest, 240, 160) {
+        Ok(res) => Ok(Box::new(res)),
+        Err(res) => Err(res.to_string())
+      },
+      #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten"))))]
+      (_, true) => match RendererOpenGL::new_headless(lowest, lowest, 240, 160, self.renderer_type == renderer_types::RendererType::OpenGLES) {
+        Ok(res) => Ok(Box::new(res)),
+        Err(res) => Err(res.to_string())
+      },
+      #[cfg(not(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten")))))]
+      (_, true) => Err(String::from("headless gl needs egl")),
+      (_, false) => {
+        let window_result = Window::new(
+          self.renderer_type, "Test", 240, 160,
+          sdl2::sys::SDL_WINDOWPOS_CENTERED_MASK as i32, sdl2::sys::SDL_WINDOWPOS_CENTERED_MASK as i32,
+          sdl2::sys::SDL_WindowFlags::SDL_WINDOW_BORDERLESS as u32 | sdl2::sys::SDL_WindowFlags::SDL_WINDOW_ALLOW_HIGHDPI as u32);
+
+        assert!(window_result.is_ok(), "window creation failed");
+
+        let window = Arc::new(window_result.unwrap());
+        let renderer_result = Window::init_renderer(window.renderer_type, lowest, highest, &(window.video_subsystem.lock().unwrap()).inner, window.clone());
+
+        match renderer_result {
+          Ok(res) => Ok(res),
+          Err(_res) => Err(String::from("window renderer failed"))
+        }
+      }
+    };
+
+    match renderer_result {
+      Ok(res) => res,
+      Err(res) => panic!("Renderer creation failed with {} version {}.{}: {}", self.get_name(), major, minor, res)
+    }
+  }
+}
+
+fn skip_backend(a_backend: &Backend, a_reason: &str){
+  println!("Skipped {}: {}", a_backend.get_name(), a_reason);
+  SKIPPED_BACKENDS.fetch_add(1, Ordering::Relaxed);
+}
+
+// runs the test on every backend it requires, once for each version available. backends that aren't
+// available are reported as skipped rather than passing silently
+fn run_backends(a_requires: &Requires, function: fn(&mut Box<dyn Renderer>)){
+  for backend in BACKENDS.iter() {
+    let min_version = match backend.get_min_version(a_requires) {
+      Some(res) => res,
+      None => continue
+    };
+
+    if backend.renderer_type == renderer_types::RendererType::Software {
+      function(&mut backend.create_renderer(min_version));
+      continue;
+    }
+
+    if backend.headless && backend.renderer_type != renderer_types::RendererType::Vulkan && !cfg!(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "emscripten")))) {
+      skip_backend(backend, "headless gl needs egl");
+      continue;
+    }
+
+    let key = backend.get_env_key();
+    let max_version = match get_api_supported(&key) {
+      Some(res) => res,
+      None => {
+        skip_backend(backend, &format!("{}_MAJOR is not set", key));
+        continue;
+      }
+    };
+
+    if max_version < min_version {
+      skip_backend(backend, &format!("needs version {}.{}", min_version.0, min_version.1));
+      continue;
+    }
+
+    for major_version in 1..=max_version.0 {
+      let max_minor = match major_version == max_version.0 {
+        true => max_version.1,
+        false => backend.minor_versions.get((major_version - 1) as usize).copied().unwrap_or(0)
+      };
+
+      for minor_version in 0..=max_minor {
+        if (major_version, minor_version) >= min_version {
+          function(&mut backend.create_renderer((major_version, minor_version)));
+        }
+      }
+    }
+  }
+}
+
+fn create_only(_renderer: &mut Box<dyn Renderer>){}
+
+fn init_backends() {
+  run_backends(&Requires{opengl: Some((1, 0)), opengles: Some((1, 0)), vulkan: Some((1, 0)), software: true}, create_only);
+}
+
 fn squared(num: i32) -> i64{
   num as i64 * num as i64
 }
@@ -201,3 +408,2170 @@ fn clear_screen() {
   test_opengl(test_clear_screen);
   test_opengles(test_clear_screen);
 }
+
+fn pixel_square_error(image: &rad::gpu::image::Image, x: u32, y: u32, color: [u8; 4]) -> i64{
+  let offset = (y * image.width * 4 + x * 4) as usize;
+  let mut diff: i64 = 0;
+  for i in 0..4
+  {
+    diff += squared(color[i] as i32 - image.pixels[offset + i] as i32);
+  }
+  return diff;
+}
+
+// gl and gles both load the gl shaders, which are translated for gles contexts
+fn shader_path(renderer: &Box<dyn Renderer>) -> &'static str{
+  match renderer.get_type() {
+    renderer_types::RendererType::Vulkan => "../shaders/vulkan/",
+    _ => "../shaders/gl/"
+  }
+}
+
+fn load_basic_program(renderer: &mut Box<dyn Renderer>) -> Box<dyn Program>{
+  let shader_path = shader_path(renderer);
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.frag")).unwrap();
+
+  let shader_vert = renderer.load_shader(renderer_types::ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader(renderer_types::ShaderType::Fragment, &source_frag).unwrap();
+
+  return renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap();
+}
+
+fn load_instanced_program(renderer: &mut Box<dyn Renderer>) -> Box<dyn Program>{
+  let shader_path = shader_path(renderer);
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "instanced.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "instanced.frag")).unwrap();
+
+  let shader_vert = renderer.load_shader(renderer_types::ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader(renderer_types::ShaderType::Fragment, &source_frag).unwrap();
+
+  return renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap();
+}
+
+fn gen_quad_mesh(renderer: &mut Box<dyn Renderer>, texture: Rc<dyn Texture>) -> Box<Mesh>{
+  let program = load_basic_program(renderer);
+
+  let verts: std::vec::Vec<f32> = vec![
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    1.0, 1.0, 1.0, 1.0,
+    1.0, 1.0, 1.0, 1.0,
+    -1.0, 1.0, 0.0, 1.0,
+    -1.0, -1.0, 0.0, 0.0];
+
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let geometry = renderer.gen_geometry(&vert_buffer);
+  let sampler = renderer.gen_sampler(texture, &renderer_types::SamplerDesc::default());
+  let material = Box::new(material::MaterialBasic::new(program, sampler));
+
+  return renderer.gen_mesh(geometry, material);
+}
+
+fn test_draw_textured_quad(renderer: &mut Box<dyn Renderer>){
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+
+  // 2x2 texture, first row is at the bottom of the quad
+  let mut img = image::RgbaImage::new(2, 2);
+  img.put_pixel(0, 0, image::Rgba(colors[0]));
+  img.put_pixel(1, 0, image::Rgba(colors[1]));
+  img.put_pixel(0, 1, image::Rgba(colors[2]));
+  img.put_pixel(1, 1, image::Rgba(colors[3]));
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&image::DynamicImage::ImageRgba8(img), &mut texture);
+
+  let mut mesh = gen_quad_mesh(renderer, texture.into());
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(renderer_types::RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+
+  renderer.end_frame();
+
+  // sample near texel centers where filtering has little effect
+  let error = pixel_square_error(&image, 60, 40, colors[0]);
+  assert!(error <= 48, "Bottom left texel is not within tolerance. Error: {}", error);
+  let error = pixel_square_error(&image, 180, 40, colors[1]);
+  assert!(error <= 48, "Bottom right texel is not within tolerance. Error: {}", error);
+  let error = pixel_square_error(&image, 60, 120, colors[2]);
+  assert!(error <= 48, "Top left texel is not within tolerance. Error: {}", error);
+  let error = pixel_square_error(&image, 180, 120, colors[3]);
+  assert!(error <= 48, "Top right texel is not within tolerance. Error: {}", error);
+}
+
+fn draw_textured_quad() {
+  // basic shaders need glsl 330 and glsl es 300
+  run_backends(&EVERY_BACKEND, test_draw_textured_quad);
+}
+
+fn assert_quadrants(image: &rad::gpu::image::Image, colors: &[[u8; 4]; 4]){
+  let (x0, x1) = (image.width / 4, image.width * 3 / 4);
+  let (y0, y1) = (image.height / 4, image.height * 3 / 4);
+
+  let error = pixel_square_error(image, x0, y0, colors[0]);
+  assert!(error <= 48, "Bottom left texel is not within tolerance. Error: {}", error);
+  let error = pixel_square_error(image, x1, y0, colors[1]);
+  assert!(error <= 48, "Bottom right texel is not within tolerance. Error: {}", error);
+  let error = pixel_square_error(image, x0, y1, colors[2]);
+  assert!(error <= 48, "Top left texel is not within tolerance. Error: {}", error);
+  let error = pixel_square_error(image, x1, y1, colors[3]);
+  assert!(error <= 48, "Top right texel is not within tolerance. Error: {}", error);
+}
+
+fn test_render_target(renderer: &mut Box<dyn Renderer>){
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+
+  let mut img = image::RgbaImage::new(2, 2);
+  img.put_pixel(0, 0, image::Rgba(colors[0]));
+  img.put_pixel(1, 0, image::Rgba(colors[1]));
+  img.put_pixel(0, 1, image::Rgba(colors[2]));
+  img.put_pixel(1, 1, image::Rgba(colors[3]));
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&image::DynamicImage::ImageRgba8(img), &mut texture);
+
+  let desc = renderer_types::RenderTargetDesc{
+    width: 120,
+    height: 80,
+    color_formats: vec![renderer_types::TextureFormat::RGBA8],
+    depth_stencil_format: Some(renderer_types::TextureFormat::Depth24Stencil8)
+  };
+
+  let target = renderer.gen_render_target(&desc).unwrap();
+  assert!(target.num_color_attachments() == 1, "Render target has {} color attachments", target.num_color_attachments());
+
+  let mut mesh = gen_quad_mesh(renderer, texture.into());
+  let mut target_mesh = gen_quad_mesh(renderer, target.get_color_texture(0));
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(renderer_types::RendererClearType::COLOR);
+
+  // draw the texture into the target
+  renderer.set_render_target(Some(&target));
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(120, 80));
+  renderer.clear(renderer_types::RendererClearType::COLOR | renderer_types::RendererClearType::DEPTH | renderer_types::RendererClearType::STENCIL);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  assert!(image.width == 120 && image.height == 80, "Render target read back as {}x{}", image.width, image.height);
+  assert_quadrants(&image, &colors);
+
+  // then the target onto the screen, rows keep the same orientation
+  renderer.set_render_target(None);
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.draw_mesh(&camera, &mut target_mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert!(image.width == 240 && image.height == 160, "Screen read back as {}x{}", image.width, image.height);
+  assert_quadrants(&image, &colors);
+
+  // clearing a target that is not bound
+  let color = [191, 127, 63, 255];
+  renderer.set_clear_color(Vec4::new(color[0] as f32 / 255.0, color[1] as f32 / 255.0, color[2] as f32 / 255.0, color[3] as f32 / 255.0));
+  renderer.clear_render_target(&target, renderer_types::RendererClearType::COLOR);
+
+  renderer.set_render_target(Some(&target));
+  let image = renderer.read_render_buffer();
+  renderer.set_render_target(None);
+
+  let mse = mean_square_error(image.pixels.as_ref(), color);
+  assert!(mse <= 1.0, "Render target clear color is not within tolerance. MSE: {}", mse);
+
+  // float attachments read back as rgba8 too, where they can be rendered to
+  let float_desc = renderer_types::RenderTargetDesc{
+    width: 120,
+    height: 80,
+    color_formats: vec![renderer_types::TextureFormat::RGBA16F],
+    depth_stencil_format: None
+  };
+
+  let float_target = match renderer.gen_render_target(&float_desc) {
+    Ok(res) => res,
+    Err(renderer_types::RendererError::UnsupportedAPI) => return,
+    Err(res) => panic!("Float render target failed: {}", res)
+  };
+
+  renderer.clear_render_target(&float_target, renderer_types::RendererClearType::COLOR);
+
+  renderer.set_render_target(Some(&float_target));
+  let image = renderer.read_render_buffer();
+  renderer.set_render_target(None);
+
+  let mse = mean_square_error(image.pixels.as_ref(), color);
+  assert!(mse <= 1.0, "Float render target clear color is not within tolerance. MSE: {}", mse);
+}
+
+fn render_target() {
+  run_backends(&EVERY_BACKEND, test_render_target);
+}
+
+fn test_vertex_layout(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+
+  let mut img = image::RgbaImage::new(2, 2);
+  img.put_pixel(0, 0, image::Rgba(colors[0]));
+  img.put_pixel(1, 0, image::Rgba(colors[1]));
+  img.put_pixel(0, 1, image::Rgba(colors[2]));
+  img.put_pixel(1, 1, image::Rgba(colors[3]));
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&image::DynamicImage::ImageRgba8(img), &mut texture);
+
+  // normalized 16 bit positions and float uvs in separate buffers
+  let positions: Vec<i16> = vec![
+    -32767, -32767,
+    32767, -32767,
+    32767, 32767,
+    32767, 32767,
+    -32767, 32767,
+    -32767, -32767];
+  let uvs: Vec<f32> = vec![
+    0.0, 0.0,
+    1.0, 0.0,
+    1.0, 1.0,
+    1.0, 1.0,
+    0.0, 1.0,
+    0.0, 0.0];
+
+  let position_bytes: Vec<u8> = positions.iter().flat_map(|v| v.to_ne_bytes()).collect();
+  let position_buffer = renderer.gen_buffer_vertex_bytes(&position_bytes);
+  let uv_buffer = renderer.gen_buffer_vertex(&uvs);
+
+  let layout = VertexLayout{buffers: vec![
+    VertexBufferLayout{stride: 4, step: VertexStep::PerVertex, attributes: vec![
+      VertexAttribute{location: 0, semantic: VertexSemantic::Position, element_type: ElementType::Int16, container_type: ContainerType::Vec2, normalized: true, offset: 0}
+    ]},
+    VertexBufferLayout{stride: 8, step: VertexStep::PerVertex, attributes: vec![
+      VertexAttribute{location: 1, semantic: VertexSemantic::TexCoord, element_type: ElementType::Float32, container_type: ContainerType::Vec2, normalized: false, offset: 0}
+    ]}
+  ]};
+
+  assert!(renderer.gen_geometry_layout(&[&position_buffer], &layout).is_err(), "Geometry was created with a missing vertex buffer");
+
+  let geometry = renderer.gen_geometry_layout(&[&position_buffer, &uv_buffer], &layout).unwrap();
+  let program = load_basic_program(renderer);
+  let sampler = renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default());
+  let material = Box::new(material::MaterialBasic::new(program, sampler));
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &colors);
+}
+
+// a vertex at w = 0 that isn't clipped by near or far, the triangle reaches infinitely far up. the software
+// renderer reads all four position components, gl shaders set w themselves
+fn test_clip_w(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let verts: Vec<f32> = vec![
+    -1.0, -1.0, 0.0, 1.0, 0.0, 0.0,
+    1.0, -1.0, 0.0, 1.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+
+  let layout = VertexLayout{buffers: vec![
+    VertexBufferLayout{stride: 24, step: VertexStep::PerVertex, attributes: vec![
+      VertexAttribute{location: 0, semantic: VertexSemantic::Position, element_type: ElementType::Float32, container_type: ContainerType::Vec4, normalized: false, offset: 0},
+      VertexAttribute{location: 1, semantic: VertexSemantic::TexCoord, element_type: ElementType::Float32, container_type: ContainerType::Vec2, normalized: false, offset: 16}
+    ]}
+  ]};
+
+  let buffer = renderer.gen_buffer_vertex(&verts);
+  let geometry = renderer.gen_geometry_layout(&[&buffer], &layout).unwrap();
+  let program = load_basic_program(renderer);
+  let sampler = gen_color_sampler(renderer, [255, 0, 0, 255]);
+  let mut mesh = renderer.gen_mesh(geometry, Box::new(material::MaterialBasic::new(program, sampler)));
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  for (x, y) in [(120, 10), (120, 80), (120, 150)] {
+    let error = pixel_square_error(&image, x, y, [255, 0, 0, 255]);
+    assert!(error <= 3, "Triangle to w = 0 is not drawn at {}, {}. Error: {}", x, y, error);
+  }
+}
+
+fn vertex_layout() {
+  run_backends(&SOFTWARE_BACKEND, test_clip_w);
+
+  run_backends(&EVERY_BACKEND, test_vertex_layout);
+}
+
+fn gen_checker_texture(renderer: &mut Box<dyn Renderer>, colors: &[[u8; 4]; 4]) -> Box<dyn Texture>{
+  let mut img = image::RgbaImage::new(2, 2);
+  img.put_pixel(0, 0, image::Rgba(colors[0]));
+  img.put_pixel(1, 0, image::Rgba(colors[1]));
+  img.put_pixel(0, 1, image::Rgba(colors[2]));
+  img.put_pixel(1, 1, image::Rgba(colors[3]));
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&image::DynamicImage::ImageRgba8(img), &mut texture);
+  texture
+}
+
+fn test_indexed_geometry(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let texture = gen_checker_texture(renderer, &colors);
+
+  // each corner once
+  let verts: Vec<f32> = vec![
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    1.0, 1.0, 1.0, 1.0,
+    -1.0, 1.0, 0.0, 1.0];
+  let indices: Vec<u16> = vec![0, 1, 2, 2, 3, 0];
+
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let index_buffer = renderer.gen_buffer_index(IndexData::Uint16(&indices));
+  let geometry = renderer.gen_geometry_indexed(&[&vert_buffer], &VertexLayout::position_uv(), &index_buffer).unwrap();
+  assert!(geometry.get_num_elements() == 6, "Indexed geometry has {} elements", geometry.get_num_elements());
+
+  let program = load_basic_program(renderer);
+  let sampler = renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default());
+  let material = Box::new(material::MaterialBasic::new(program, sampler));
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  assert_quadrants(&image, &colors);
+
+  // only the first triangle, below the diagonal from the bottom left to the top right
+  renderer.clear(RendererClearType::COLOR);
+  renderer.draw_geometry_range(&mesh.geometry, DrawRange{first: 0, count: 3, base_vertex: 0});
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  let error = pixel_square_error(&image, 180, 40, colors[1]);
+  assert!(error <= 48, "Bottom right texel is not within tolerance. Error: {}", error);
+  let error = pixel_square_error(&image, 60, 120, [0, 0, 0, 255]);
+  assert!(error <= 48, "Second triangle was drawn. Error: {}", error);
+}
+
+fn indexed_geometry() {
+  run_backends(&EVERY_BACKEND, test_indexed_geometry);
+}
+
+fn test_indexed_base_vertex(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let texture = gen_checker_texture(renderer, &colors);
+
+  // a quad outside the viewport followed by the full screen quad
+  let verts: Vec<f32> = vec![
+    2.0, 2.0, 0.0, 0.0,
+    3.0, 2.0, 0.0, 0.0,
+    3.0, 3.0, 0.0, 0.0,
+    2.0, 3.0, 0.0, 0.0,
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    1.0, 1.0, 1.0, 1.0,
+    -1.0, 1.0, 0.0, 1.0];
+  let indices: Vec<u32> = vec![0, 1, 2, 2, 3, 0];
+
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let index_buffer = renderer.gen_buffer_index(IndexData::Uint32(&indices));
+  let geometry = renderer.gen_geometry_indexed(&[&vert_buffer], &VertexLayout::position_uv(), &index_buffer).unwrap();
+
+  let program = load_basic_program(renderer);
+  let sampler = renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default());
+  let material = Box::new(material::MaterialBasic::new(program, sampler));
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+
+  // draw_mesh binds the material, the range then draws the second quad
+  renderer.draw_mesh(&camera, &mut mesh);
+  let image = renderer.read_render_buffer();
+  let mse = mean_square_error(image.pixels.as_ref(), [0, 0, 0, 255]);
+  assert!(mse <= 1.0, "Quad outside the viewport was drawn. MSE: {}", mse);
+
+  renderer.draw_geometry_range(&mesh.geometry, DrawRange{first: 0, count: 6, base_vertex: 4});
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &colors);
+}
+
+fn indexed_base_vertex() {
+  // base vertex draws need gl 3.2 and gles 3.2
+  run_backends(&Requires{opengles: Some((3, 2)), ..EVERY_BACKEND}, test_indexed_base_vertex);
+}
+
+// a transform and color for each instance
+fn gen_instance_data(instances: &[(Mat4, [u8; 4])]) -> Vec<u8>{
+  let mut data = Vec::new();
+  for (transform, color) in instances {
+    let color = Vec4::new(color[0] as f32, color[1] as f32, color[2] as f32, color[3] as f32) / 255.0;
+    data.extend(transform.to_cols_array().iter().flat_map(|v| v.to_ne_bytes()));
+    data.extend(color.to_array().iter().flat_map(|v| v.to_ne_bytes()));
+  }
+  data
+}
+
+fn test_draw_instanced(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let white = [255, 255, 255, 255];
+  let texture = gen_checker_texture(renderer, &[white; 4]);
+
+  let verts: Vec<f32> = vec![
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    1.0, 1.0, 1.0, 1.0,
+    1.0, 1.0, 1.0, 1.0,
+    -1.0, 1.0, 0.0, 1.0,
+    -1.0, -1.0, 0.0, 0.0];
+
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let geometry = renderer.gen_geometry(&vert_buffer);
+
+  let program = load_instanced_program(renderer);
+  let sampler = renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default());
+  let material = Box::new(material::MaterialBasic::new(program, sampler));
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  // one quad in each quadrant
+  let quadrants = [Vec2::new(-0.5, -0.5), Vec2::new(0.5, -0.5), Vec2::new(-0.5, 0.5), Vec2::new(0.5, 0.5)];
+  let transforms: Vec<Mat4> = quadrants.iter().map(|pos| Mat4::from_translation(pos.extend(0.0)) * Mat4::from_scale(Vec3::new(0.5, 0.5, 1.0))).collect();
+
+  let layout = VertexBufferLayout{stride: 80, step: VertexStep::PerInstance, attributes: vec![
+    VertexAttribute{location: 2, semantic: VertexSemantic::Transform, element_type: ElementType::Float32, container_type: ContainerType::Mat4x4, normalized: false, offset: 0},
+    VertexAttribute{location: 6, semantic: VertexSemantic::Color, element_type: ElementType::Float32, container_type: ContainerType::Vec4, normalized: false, offset: 64}
+  ]};
+
+  let data = gen_instance_data(&(0..4).map(|i| (transforms[i], colors[i])).collect::<Vec<_>>());
+  let mut instances = renderer.gen_instances(&layout, &data).unwrap();
+  assert!(instances.get_num_instances() == 4, "Instances has {} instances", instances.get_num_instances());
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh_instanced(&camera, &mut mesh, &instances);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &colors);
+
+  // the next frame draws fewer instances with different colors
+  let data = gen_instance_data(&[(transforms[0], colors[3]), (transforms[3], colors[0])]);
+  renderer.update_instances(&mut instances, &data);
+  assert!(instances.get_num_instances() == 2, "Updated instances has {} instances", instances.get_num_instances());
+
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh_instanced(&camera, &mut mesh, &instances);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  let black = [0, 0, 0, 255];
+  assert_quadrants(&image, &[colors[3], black, black, colors[0]]);
+}
+
+fn draw_instanced() {
+  run_backends(&EVERY_BACKEND, test_draw_instanced);
+}
+
+fn load_compute_program(renderer: &mut Box<dyn Renderer>, name: &str) -> Box<dyn Program>{
+  let shader_path = shader_path(renderer);
+
+  let source = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + name)).unwrap();
+
+  let shader = renderer.load_shader(renderer_types::ShaderType::Compute, &source).unwrap();
+
+  return renderer.load_program_compute(shader).unwrap();
+}
+
+fn test_compute_buffer(renderer: &mut Box<dyn Renderer>){
+  let values: Vec<f32> = (0..100).map(|i| i as f32).collect();
+  let data: Vec<u8> = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+
+  let program = load_compute_program(renderer, "double.comp");
+  let buffer = renderer.gen_buffer_storage(&data).unwrap();
+  assert!(buffer.get_size() == data.len(), "Storage buffer has size {}", buffer.get_size());
+
+  renderer.use_program(&program);
+  renderer.bind_buffer_storage(0, &buffer);
+  renderer.dispatch((values.len() as u32).div_ceil(64), 1, 1);
+  renderer.memory_barrier(renderer_types::BarrierType::BUFFER_UPDATE);
+
+  let result = renderer.read_buffer_storage(&buffer);
+  assert!(result.len() == data.len(), "Storage buffer read back {} bytes", result.len());
+
+  for (i, bytes) in result.chunks_exact(4).enumerate() {
+    let value = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    assert!(value == values[i] * 2.0, "Value {} is {} instead of {}", i, value, values[i] * 2.0);
+  }
+}
+
+fn test_compute_unsupported(renderer: &mut Box<dyn Renderer>){
+  let result = renderer.gen_buffer_storage(&[0; 16]);
+  assert!(matches!(result, Err(renderer_types::RendererError::UnsupportedAPI)), "Storage buffers should be unsupported");
+
+  let result = renderer.gen_texture_storage(16, 16, renderer_types::TextureFormat::RGBA8);
+  assert!(matches!(result, Err(renderer_types::RendererError::UnsupportedAPI)), "Storage textures should be unsupported");
+}
+
+fn compute_buffer() {
+  // compute shaders need gl 4.3 and gles 3.1
+  run_backends(&Requires{opengl: Some((4, 3)), opengles: Some((3, 1)), ..GL_BACKENDS}, test_compute_buffer);
+  run_backends(&SOFTWARE_BACKEND, test_compute_unsupported);
+}
+
+fn test_compute_image(renderer: &mut Box<dyn Renderer>){
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+
+  let program = load_compute_program(renderer, "fill.comp");
+  let texture = renderer.gen_texture_storage(64, 64, renderer_types::TextureFormat::RGBA8).unwrap();
+
+  renderer.use_program(&program);
+  renderer.bind_image_storage(0, texture.as_ref(), renderer_types::StorageAccess::WriteOnly, renderer_types::TextureFormat::RGBA8);
+  renderer.dispatch(8, 8, 1);
+  renderer.memory_barrier(renderer_types::BarrierType::TEXTURE_FETCH);
+
+  // sample the written image with the regular pipeline
+  let mut mesh = gen_quad_mesh(renderer, texture.into());
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(renderer_types::RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &colors);
+}
+
+fn compute_image() {
+  run_backends(&Requires{opengl: Some((4, 3)), opengles: Some((3, 1)), ..GL_BACKENDS}, test_compute_image);
+}
+
+fn load_patch_shader(renderer: &mut Box<dyn Renderer>, shader_type: renderer_types::ShaderType, extension: &str) -> Box<dyn Shader>{
+  let shader_path = shader_path(renderer);
+
+  let source = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "patch." + extension)).unwrap();
+
+  return renderer.load_shader(shader_type, &source).unwrap();
+}
+
+fn test_program_stages(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let texture = gen_checker_texture(renderer, &colors);
+
+  let program = ProgramBuilder::new()
+    .stage(load_patch_shader(renderer, ShaderType::Vertex, "vert"))
+    .stage(load_patch_shader(renderer, ShaderType::TesselationControl, "tesc"))
+    .stage(load_patch_shader(renderer, ShaderType::TesselationEvaluation, "tese"))
+    .stage(load_patch_shader(renderer, ShaderType::Geometry, "geom"))
+    .stage(load_patch_shader(renderer, ShaderType::Fragment, "frag"))
+    .build(renderer.as_mut())
+    .unwrap();
+
+  let verts: Vec<f32> = vec![
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    1.0, 1.0, 1.0, 1.0,
+    1.0, 1.0, 1.0, 1.0,
+    -1.0, 1.0, 0.0, 1.0,
+    -1.0, -1.0, 0.0, 0.0];
+
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let geometry = renderer.gen_geometry(&vert_buffer);
+  let sampler = renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default());
+  let material = Box::new(material::MaterialBasic::new(program, sampler));
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  // each triangle is a patch
+  renderer.set_patch_vertices(3);
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &colors);
+}
+
+fn program_stages() {
+  // tesselation needs gl 4.0 and gles 3.2
+  run_backends(&Requires{opengl: Some((4, 0)), opengles: Some((3, 2)), ..GL_BACKENDS}, test_program_stages);
+}
+
+fn test_program_validation(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let shader_path = shader_path(renderer);
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.frag")).unwrap();
+
+  // a vertex stage is needed
+  let result = ProgramBuilder::new()
+    .stage(renderer.load_shader(ShaderType::Fragment, &source_frag).unwrap())
+    .build(renderer.as_mut());
+  assert!(result.is_err(), "Program without a vertex stage linked");
+
+  // stages can only be given once
+  let result = ProgramBuilder::new()
+    .stage(renderer.load_shader(ShaderType::Vertex, &source_vert).unwrap())
+    .stage(renderer.load_shader(ShaderType::Fragment, &source_frag).unwrap())
+    .stage(renderer.load_shader(ShaderType::Fragment, &source_frag).unwrap())
+    .build(renderer.as_mut());
+  assert!(result.is_err(), "Program with two fragment stages linked");
+
+  let result = ProgramBuilder::new()
+    .stage(renderer.load_shader(ShaderType::Vertex, &source_vert).unwrap())
+    .stage(renderer.load_shader(ShaderType::Fragment, &source_frag).unwrap())
+    .build(renderer.as_mut());
+  assert!(result.is_ok(), "Vertex and fragment program failed to link");
+}
+
+fn program_validation() {
+  run_backends(&EVERY_BACKEND, test_program_validation);
+}
+
+fn test_uniform_setters(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let texture = gen_checker_texture(renderer, &colors);
+
+  let mut mesh = gen_quad_mesh(renderer, texture.into());
+  let camera = rad::gpu::camera::Camera::new();
+
+  // shrink the quad to the middle of the screen
+  let scale = Mat4::from_scale(Vec3::new(0.5, 0.5, 1.0));
+  let uniform = mesh.material.find_uniform("u_mvp").unwrap();
+  uniform.set::<Mat4>(scale);
+  assert!(uniform.get::<Mat4>() == scale, "Uniform value was not set");
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  let black = [0, 0, 0, 255];
+  let points = [(90, 60, colors[0]), (150, 60, colors[1]), (90, 100, colors[2]), (150, 100, colors[3]), (20, 20, black), (220, 140, black)];
+  for (x, y, color) in points {
+    let error = pixel_square_error(&image, x, y, color);
+    assert!(error <= 48, "Pixel at {} {} is not within tolerance. Error: {}", x, y, error);
+  }
+
+  // adding a uniform with the same name replaces it
+  mesh.material.add_uniform("u_mvp", rad::gpu::uniforms::UniformData::new(Mat4::IDENTITY));
+  assert!(mesh.material.num_uniforms() == 1, "Material has {} uniforms", mesh.material.num_uniforms());
+
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &colors);
+}
+
+fn uniform_setters() {
+  run_backends(&EVERY_BACKEND, test_uniform_setters);
+}
+
+fn test_uniform_types(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+  use rad::gpu::uniforms::UniformData;
+  use rad::gpu::material::Material;
+
+  let shader_path = shader_path(renderer);
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "uniforms.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "uniforms.frag")).unwrap();
+
+  let shader_vert = renderer.load_shader(ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader(ShaderType::Fragment, &source_frag).unwrap();
+  let program = renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap();
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let texture = gen_checker_texture(renderer, &colors);
+
+  let verts: Vec<f32> = vec![
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    1.0, 1.0, 1.0, 1.0,
+    1.0, 1.0, 1.0, 1.0,
+    -1.0, 1.0, 0.0, 1.0,
+    -1.0, -1.0, 0.0, 0.0];
+
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let geometry = renderer.gen_geometry(&vert_buffer);
+  let sampler = renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default());
+  let mut material = Box::new(material::MaterialBasic::new(program, sampler));
+
+  let color_values = colors.map(|color| Vec4::new(color[0] as f32, color[1] as f32, color[2] as f32, color[3] as f32) / 255.0);
+  material.add_uniform("u_bones", UniformData::new([Mat4::ZERO, Mat4::IDENTITY]));
+  material.add_uniform("u_flags", UniformData::new(-3i32));
+  material.add_uniform("u_mask", UniformData::new(5u32));
+  material.add_uniform("u_enabled", UniformData::new(true));
+  material.add_uniform("u_select", UniformData::new(IVec2::new(2, 1)));
+  material.add_uniform("u_colors", UniformData::new(color_values));
+
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  let mse = mean_square_error(image.pixels.as_ref(), colors[2]);
+  assert!(mse <= 1.0, "Uniform color is not within tolerance. MSE: {}", mse);
+
+  // changing array elements and flags after the first upload
+  mesh.material.find_uniform("u_colors").unwrap().set::<[Vec4; 4]>([color_values[3]; 4]);
+  mesh.material.find_uniform("u_bones").unwrap().set::<[Mat4; 2]>([Mat4::IDENTITY, Mat4::from_scale(Vec3::new(0.5, 0.5, 1.0))]);
+
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  let error = pixel_square_error(&image, 120, 80, colors[3]);
+  assert!(error <= 48, "Updated uniform array is not within tolerance. Error: {}", error);
+  let error = pixel_square_error(&image, 20, 20, [0, 0, 0, 255]);
+  assert!(error <= 48, "Updated bone array is not within tolerance. Error: {}", error);
+
+  mesh.material.find_uniform("u_enabled").unwrap().set::<bool>(false);
+
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  let error = pixel_square_error(&image, 120, 80, [0, 0, 0, 255]);
+  assert!(error <= 48, "Disabled flag is not within tolerance. Error: {}", error);
+}
+
+fn uniform_types() {
+  run_backends(&GL_BACKENDS, test_uniform_types);
+}
+
+fn uniform_layout() {
+  use rad::gpu::uniforms::UniformData;
+
+  // columns of 3 are padded to 4, the padding after the last column is left out
+  let data = UniformData::new(Mat3::from_cols_array(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0])).to_layout(renderer_types::BlockLayout::Std430);
+  assert!(data.len() == 44, "Mat3 std430 size is {}", data.len());
+  assert!(data[16..20] == 4.0f32.to_ne_bytes(), "Mat3 second column is not aligned");
+
+  // bools take 4 bytes
+  let data = UniformData::new([true, false, true]).to_layout(renderer_types::BlockLayout::Std430);
+  assert!(data == [1u32, 0, 1].iter().flat_map(|v| v.to_ne_bytes()).collect::<Vec<u8>>(), "Bool array std430 layout is wrong");
+
+  // vec3 arrays have a stride of 16
+  let data = UniformData::new([Vec3::ONE, Vec3::splat(2.0)]).to_layout(renderer_types::BlockLayout::Std430);
+  assert!(data.len() == 28, "Vec3 array std430 size is {}", data.len());
+  assert!(data[16..20] == 2.0f32.to_ne_bytes(), "Vec3 array second element is not aligned");
+
+  let uniform = UniformData::new([Mat4::IDENTITY; 3]);
+  assert!(uniform.info.num_components == 3, "Mat4 array has {} components", uniform.info.num_components);
+}
+
+struct FrameBlock{
+  transform: Mat4,
+  tint: Vec4,
+  scale: f32,
+  offset: Vec3,
+  flags: [i32; 2]
+}
+
+// FrameBlock without the flags, and with the scale as an int
+struct WrongBlock{
+  frame: FrameBlock,
+  int_scale: bool
+}
+
+impl rad::gpu::uniforms::UniformBlock for WrongBlock{
+  fn write(&self, a_writer: &mut rad::gpu::uniforms::UniformBlockWriter){
+    a_writer.member("u_transform", &self.frame.transform);
+    a_writer.member("u_tint", &self.frame.tint);
+    match self.int_scale {
+      true => a_writer.member("u_scale", &(self.frame.scale as i32)),
+      false => a_writer.member("u_scale", &self.frame.scale)
+    };
+    a_writer.member("u_offset", &self.frame.offset);
+  }
+}
+
+struct FlagsBlock{
+  enabled: bool,
+  masks: [BVec3; 2]
+}
+
+impl rad::gpu::uniforms::UniformBlock for FlagsBlock{
+  fn write(&self, a_writer: &mut rad::gpu::uniforms::UniformBlockWriter){
+    a_writer.member("u_enabled", &self.enabled);
+    a_writer.member("u_masks", &self.masks);
+  }
+}
+
+impl rad::gpu::uniforms::UniformBlock for FrameBlock{
+  fn write(&self, a_writer: &mut rad::gpu::uniforms::UniformBlockWriter){
+    a_writer.member("u_transform", &self.transform);
+    a_writer.member("u_tint", &self.tint);
+    a_writer.member("u_scale", &self.scale);
+    a_writer.member("u_offset", &self.offset);
+    a_writer.member("u_flags", &self.flags);
+  }
+}
+
+fn uniform_block_layout() {
+  use rad::gpu::uniforms::UniformBlockLayout;
+  use renderer_types::BlockLayout;
+
+  let block = FrameBlock{transform: Mat4::IDENTITY, tint: Vec4::ONE, scale: 2.0, offset: Vec3::new(3.0, 4.0, 5.0), flags: [6, 7]};
+
+  // std140 aligns the vec3 to 16 and gives each array element 16 bytes
+  let layout = UniformBlockLayout::new(BlockLayout::Std140, &block);
+  let offsets: Vec<u32> = layout.members.iter().map(|member| member.offset).collect();
+  assert!(offsets == [0, 64, 80, 96, 112], "Std140 offsets are {:?}", offsets);
+  assert!(layout.size == 144, "Std140 size is {}", layout.size);
+
+  let data = layout.write(&block).unwrap();
+  assert!(data.len() == 144, "Std140 data is {} bytes", data.len());
+  assert!(data[80..84] == 2.0f32.to_ne_bytes(), "Std140 scale is not written");
+  assert!(data[104..108] == 5.0f32.to_ne_bytes(), "Std140 offset is not written");
+  assert!(data[128..132] == 7i32.to_ne_bytes(), "Std140 second flag is not written");
+
+  // writing into a larger buffer leaves the rest of it
+  let mut data = vec![0xffu8; 160];
+  layout.write_to(&block, &mut data).unwrap();
+  assert!(data[80..84] == 2.0f32.to_ne_bytes() && data[144..] == [0xffu8; 16], "Std140 block was not written in place");
+
+  // std430 packs the int array after the vec3
+  let layout = UniformBlockLayout::new(BlockLayout::Std430, &block);
+  let offsets: Vec<u32> = layout.members.iter().map(|member| member.offset).collect();
+  assert!(offsets == [0, 64, 80, 96, 108], "Std430 offsets are {:?}", offsets);
+  assert!(layout.size == 128, "Std430 size is {}", layout.size);
+
+  // the first member that doesn't match the layout is reported, including members left out
+  let mut wrong = WrongBlock{frame: block, int_scale: true};
+  match layout.write(&wrong) {
+    Err(renderer_types::RendererError::InvalidUniform(res)) => assert!(res == "u_scale", "Mismatch reported for {}", res),
+    _ => panic!("Block with an int scale was written")
+  }
+
+  wrong.int_scale = false;
+  match layout.write(&wrong) {
+    Err(renderer_types::RendererError::InvalidUniform(res)) => assert!(res == "u_flags", "Mismatch reported for {}", res),
+    _ => panic!("Block without flags was written")
+  }
+
+  // bools are 4 bytes and arrays of vectors are padded to 16 in std140
+  let flags = FlagsBlock{enabled: true, masks: [BVec3::new(true, false, false), BVec3::new(false, true, true)]};
+  let layout = UniformBlockLayout::new(BlockLayout::Std140, &flags);
+  let data = layout.write(&flags).unwrap();
+  let words: Vec<u32> = data.chunks(4).map(|bytes| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect();
+  assert!(words == [1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 1, 0], "Bool block is {:?}", words);
+}
+
+fn load_block_program(renderer: &mut Box<dyn Renderer>) -> Box<dyn Program>{
+  let shader_path = shader_path(renderer);
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "block.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "block.frag")).unwrap();
+
+  let shader_vert = renderer.load_shader(renderer_types::ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader(renderer_types::ShaderType::Fragment, &source_frag).unwrap();
+
+  return renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap();
+}
+
+fn test_uniform_buffer(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+  use rad::gpu::uniforms::UniformBlockLayout;
+
+  let white = [255, 255, 255, 255];
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+
+  // two programs reading the same block, each drawing a quad on one half of the screen
+  let mut meshes = Vec::new();
+  for x in [-0.5f32, 0.5] {
+    let program = load_block_program(renderer);
+
+    let verts: Vec<f32> = vec![
+      x - 0.5, -1.0, 0.0, 0.0,
+      x + 0.5, -1.0, 1.0, 0.0,
+      x + 0.5, 1.0, 1.0, 1.0,
+      x + 0.5, 1.0, 1.0, 1.0,
+      x - 0.5, 1.0, 0.0, 1.0,
+      x - 0.5, -1.0, 0.0, 0.0];
+
+    let texture = gen_checker_texture(renderer, &[white; 4]);
+    let vert_buffer = renderer.gen_buffer_vertex(&verts);
+    let geometry = renderer.gen_geometry(&vert_buffer);
+    let sampler = renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default());
+    let material = Box::new(material::MaterialBasic::new(program, sampler));
+    meshes.push(renderer.gen_mesh(geometry, material));
+  }
+
+  let mut block = FrameBlock{transform: Mat4::IDENTITY, tint: Vec4::new(1.0, 0.0, 0.0, 1.0), scale: 1.0, offset: Vec3::ZERO, flags: [0, 7]};
+  let layout = UniformBlockLayout::new(BlockLayout::Std140, &block);
+
+  for mesh in &meshes {
+    renderer.bind_uniform_block(mesh.material.get_program(), "Frame", 1, &layout).unwrap();
+  }
+
+  let mut buffer = renderer.gen_buffer_uniform(&layout, &block).unwrap();
+  renderer.bind_buffer_uniform(1, &buffer);
+
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  for mesh in &mut meshes {
+    renderer.draw_mesh(&camera, mesh);
+  }
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &[colors[0]; 4]);
+
+  // one update changes what both programs draw
+  block.tint = Vec4::new(0.0, 0.0, 1.0, 1.0);
+  block.offset = Vec3::new(0.0, 0.5, 0.0);
+  block.scale = 0.5;
+  renderer.update_buffer_uniform(&mut buffer, &block).unwrap();
+
+  renderer.begin_frame(RendererClearType::COLOR);
+  for mesh in &mut meshes {
+    renderer.draw_mesh(&camera, mesh);
+  }
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  // the quads are now half as wide and cover the top middle of the screen
+  let black = [0, 0, 0, 255];
+  let points = [(90, 120, colors[2]), (150, 120, colors[2]), (20, 120, black), (60, 40, black), (180, 40, black)];
+  for (x, y, color) in points {
+    let error = pixel_square_error(&image, x, y, color);
+    assert!(error <= 48, "Pixel at {} {} is not within tolerance. Error: {}", x, y, error);
+  }
+
+  // a layout that does not match the block is rejected
+  let wrong_layout = UniformBlockLayout::new(BlockLayout::Std430, &block);
+  let result = renderer.bind_uniform_block(meshes[0].material.get_program(), "Frame", 1, &wrong_layout);
+  assert!(result.is_err(), "Std430 layout matched a std140 block");
+}
+
+fn uniform_buffer() {
+  run_backends(&GL_BACKENDS, test_uniform_buffer);
+}
+
+// full screen quad with uvs from a_uv_min to a_uv_max over the texture
+fn draw_sampled_quad(renderer: &mut Box<dyn Renderer>, texture: Box<dyn Texture>, desc: &renderer_types::SamplerDesc, a_uv_min: f32, a_uv_max: f32) -> rad::gpu::image::Image{
+  let program = load_basic_program(renderer);
+  draw_program_quad(renderer, program, texture, desc, a_uv_min, a_uv_max)
+}
+
+fn draw_program_quad(renderer: &mut Box<dyn Renderer>, program: Box<dyn Program>, texture: Box<dyn Texture>, desc: &renderer_types::SamplerDesc, a_uv_min: f32, a_uv_max: f32) -> rad::gpu::image::Image{
+  use renderer_types::*;
+
+  let verts: Vec<f32> = vec![
+    -1.0, -1.0, a_uv_min, a_uv_min,
+    1.0, -1.0, a_uv_max, a_uv_min,
+    1.0, 1.0, a_uv_max, a_uv_max,
+    1.0, 1.0, a_uv_max, a_uv_max,
+    -1.0, 1.0, a_uv_min, a_uv_max,
+    -1.0, -1.0, a_uv_min, a_uv_min];
+
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let geometry = renderer.gen_geometry(&vert_buffer);
+  let sampler = renderer.gen_sampler(texture.into(), desc);
+  let material = Box::new(material::MaterialBasic::new(program, sampler));
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+  image
+}
+
+fn test_sampler_desc(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+
+  // next to the edge between the left and right texels linear filtering blends them
+  let texture = gen_checker_texture(renderer, &colors);
+  let image = draw_sampled_quad(renderer, texture, &SamplerDesc::default(), 0.0, 1.0);
+  let error = pixel_square_error(&image, 118, 40, [255, 0, 0, 255]);
+  assert!(error > 48, "Linear filter did not blend the texels. Error: {}", error);
+
+  let texture = gen_checker_texture(renderer, &colors);
+  let image = draw_sampled_quad(renderer, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+  let error = pixel_square_error(&image, 118, 40, [255, 0, 0, 255]);
+  assert!(error <= 3, "Nearest filter blended the texels. Error: {}", error);
+  let error = pixel_square_error(&image, 121, 40, [0, 255, 0, 255]);
+  assert!(error <= 3, "Nearest filter blended the texels. Error: {}", error);
+
+  // u is about -0.24 at x 60, repeat reads the right texel and clamp the left one
+  let repeat = SamplerDesc{wrap_u: WrapMode::Repeat, wrap_v: WrapMode::Repeat, ..SamplerDesc::nearest()};
+  let texture = gen_checker_texture(renderer, &colors);
+  let image = draw_sampled_quad(renderer, texture, &repeat, -1.0, 2.0);
+  let error = pixel_square_error(&image, 60, 20, [0, 255, 0, 255]);
+  assert!(error <= 3, "Repeat did not wrap the uv. Error: {}", error);
+
+  let texture = gen_checker_texture(renderer, &colors);
+  let image = draw_sampled_quad(renderer, texture, &SamplerDesc::nearest(), -1.0, 2.0);
+  let error = pixel_square_error(&image, 60, 20, [255, 0, 0, 255]);
+  assert!(error <= 3, "Clamp to edge did not clamp the uv. Error: {}", error);
+  let error = pixel_square_error(&image, 220, 140, [255, 255, 255, 255]);
+  assert!(error <= 3, "Clamp to edge did not clamp the uv. Error: {}", error);
+
+  let mirror = SamplerDesc{wrap_u: WrapMode::MirroredRepeat, wrap_v: WrapMode::MirroredRepeat, ..SamplerDesc::nearest()};
+  // v is about -0.62 at y 20 which mirrors to the top row
+  let texture = gen_checker_texture(renderer, &colors);
+  let image = draw_sampled_quad(renderer, texture, &mirror, -1.0, 2.0);
+  let error = pixel_square_error(&image, 60, 20, [0, 0, 255, 255]);
+  assert!(error <= 3, "Mirrored repeat did not mirror the uv. Error: {}", error);
+}
+
+fn sampler_desc() {
+  run_backends(&EVERY_BACKEND, test_sampler_desc);
+}
+
+fn gen_color_sampler(renderer: &mut Box<dyn Renderer>, color: [u8; 4]) -> Box<dyn rad::gpu::uniforms::Sampler>{
+  let mut img = image::RgbaImage::new(1, 1);
+  img.put_pixel(0, 0, image::Rgba(color));
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&image::DynamicImage::ImageRgba8(img), &mut texture);
+  renderer.gen_sampler(texture.into(), &renderer_types::SamplerDesc::default())
+}
+
+fn test_multi_texture(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+  use rad::gpu::material::Material;
+
+  let shader_path = shader_path(renderer);
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "multi.frag")).unwrap();
+
+  let shader_vert = renderer.load_shader(ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader(ShaderType::Fragment, &source_frag).unwrap();
+  let program = renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap();
+
+  let verts: Vec<f32> = vec![
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    1.0, 1.0, 1.0, 1.0,
+    1.0, 1.0, 1.0, 1.0,
+    -1.0, 1.0, 0.0, 1.0,
+    -1.0, -1.0, 0.0, 0.0];
+
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let geometry = renderer.gen_geometry(&vert_buffer);
+
+  // u_texture isn't used by the program so the green texture must not be bound to either sampler.
+  // samplers are added in the opposite order of the declarations
+  let unused = gen_color_sampler(renderer, [0, 255, 0, 255]);
+  let mut material = Box::new(material::MaterialBasic::new(program, unused));
+  let detail = gen_color_sampler(renderer, [0, 0, 255, 0]);
+  material.add_sampler("u_detail", detail);
+  let albedo = gen_color_sampler(renderer, [255, 0, 0, 255]);
+  material.add_sampler("u_albedo", albedo);
+  assert!(material.num_samplers() == 3, "Material has {} samplers", material.num_samplers());
+
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  let error = pixel_square_error(&image, 120, 80, [255, 0, 255, 255]);
+  assert!(error <= 3, "Textures were not bound to separate units. Error: {}", error);
+
+  // replaces the detail texture by name
+  let black = gen_color_sampler(renderer, [0, 0, 0, 0]);
+  mesh.material.add_sampler("u_detail", black);
+  assert!(mesh.material.num_samplers() == 3, "Sampler was not replaced");
+
+  renderer.clear(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  let error = pixel_square_error(&image, 120, 80, [255, 0, 0, 255]);
+  assert!(error <= 3, "Replaced texture was not bound. Error: {}", error);
+}
+
+fn test_texture_formats(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let mut img = image::Rgba32FImage::new(1, 1);
+  img.put_pixel(0, 0, image::Rgba([0.5, 0.25, 1.0, 0.75]));
+  let img = image::DynamicImage::ImageRgba32F(img);
+
+  // missing channels read as 0 with alpha 1, srgb is converted to linear and depth is in red
+  let formats = [
+    (TextureFormat::R8, [128, 0, 0, 255]),
+    (TextureFormat::RG8, [128, 64, 0, 255]),
+    (TextureFormat::RGBA8, [128, 64, 255, 191]),
+    (TextureFormat::SRGB8A8, [55, 13, 255, 191]),
+    (TextureFormat::R16F, [128, 0, 0, 255]),
+    (TextureFormat::RG16F, [128, 64, 0, 255]),
+    (TextureFormat::RGBA16F, [128, 64, 255, 191]),
+    (TextureFormat::RGBA32F, [128, 64, 255, 191]),
+    (TextureFormat::Depth16, [128, 0, 0, 255]),
+    (TextureFormat::Depth24, [128, 0, 0, 255]),
+    (TextureFormat::Depth32F, [128, 0, 0, 255])];
+
+  for (format, color) in formats {
+    let mut texture = renderer.gen_buffer_texture();
+    renderer.load_texture_format(&img, format, &mut texture).unwrap();
+
+    // float and depth textures aren't filterable in gles
+    let image = draw_sampled_quad(renderer, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+    let error = pixel_square_error(&image, 120, 80, color);
+    assert!(error <= 12, "{:?} texture is not within tolerance. Error: {}", format, error);
+  }
+
+  // grayscale is in red, 16 bit unorm falls back to half floats where it isn't supported
+  let luma = image::DynamicImage::ImageLuma16(image::ImageBuffer::from_pixel(1, 1, image::Luma([0x8080u16])));
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&luma, &mut texture);
+
+  let image = draw_sampled_quad(renderer, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+  let error = pixel_square_error(&image, 120, 80, [128, 0, 0, 255]);
+  assert!(error <= 12, "Luma16 texture is not within tolerance. Error: {}", error);
+}
+
+fn texture_formats() {
+  use renderer_types::*;
+
+  assert!(TextureFormat::from_image(&image::DynamicImage::new_rgb8(1, 1)) == TextureFormat::RGBA8, "8 bit image was not rgba8");
+  assert!(TextureFormat::from_image(&image::DynamicImage::new_luma8(1, 1)) == TextureFormat::R8, "8 bit grayscale image was not r8");
+  assert!(TextureFormat::from_image(&image::DynamicImage::new_luma_a8(1, 1)) == TextureFormat::RG8, "8 bit grayscale alpha image was not rg8");
+  assert!(TextureFormat::from_image(&image::DynamicImage::new_rgba16(1, 1)) == TextureFormat::RGBA16, "16 bit image was not rgba16");
+  assert!(TextureFormat::from_image(&image::DynamicImage::new_rgb32f(1, 1)) == TextureFormat::RGBA32F, "Float image was not rgba32f");
+
+  assert!(f32_to_f16(1.0) == 0x3c00, "1 is {:x}", f32_to_f16(1.0));
+  assert!(f32_to_f16(-2.0) == 0xc000, "-2 is {:x}", f32_to_f16(-2.0));
+  assert!(f32_to_f16(65504.0) == 0x7bff, "Largest half is {:x}", f32_to_f16(65504.0));
+  assert!(f32_to_f16(1.0e6) == 0x7c00, "Overflow is {:x}", f32_to_f16(1.0e6));
+  assert!(f32_to_f16(1.0e-7) == 0x0002, "Subnormal is {:x}", f32_to_f16(1.0e-7));
+
+  let bytes = TextureFormat::RGBA16F.get_bytes(&image::DynamicImage::new_rgba16(3, 1));
+  assert!(bytes.len() == 3 * 8, "Rgba16f has {} bytes", bytes.len());
+
+  // 16 bit unorm falls back to half floats with the same channels
+  for (format, fallback) in [(TextureFormat::R16, TextureFormat::R16F), (TextureFormat::RG16, TextureFormat::RG16F), (TextureFormat::RGBA16, TextureFormat::RGBA16F)] {
+    assert!(format.get_fallback() == fallback, "{:?} falls back to {:?}", format, format.get_fallback());
+  }
+
+  let bytes = TextureFormat::RG16F.get_bytes(&image::DynamicImage::ImageLumaA16(image::ImageBuffer::from_pixel(1, 1, image::LumaA([0xffffu16, 0x8000]))));
+  assert!(bytes == [0x3c00u16.to_ne_bytes(), f32_to_f16(0x8000 as f32 / 65535.0).to_ne_bytes()].concat(), "Luma alpha rg16f is {:?}", bytes);
+
+  // a 16 bit gradient is uploaded without losing precision
+  let gradient = image::DynamicImage::ImageLuma16(image::ImageBuffer::from_fn(256, 1, |x, _y| image::Luma([(x * 255 + x / 2) as u16])));
+  let format = TextureFormat::from_image(&gradient);
+  assert!(format == TextureFormat::R16, "16 bit grayscale image was {:?}", format);
+
+  let values: Vec<u16> = format.get_bytes(&gradient).chunks(2).map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]])).collect();
+  assert!(values[..] == gradient.as_luma16().unwrap().as_raw()[..], "Luma16 gradient changed when uploaded");
+
+  run_backends(&EVERY_BACKEND, test_texture_formats);
+}
+
+// basic.vert with a fragment shader from the renderer's shader folder
+fn load_frag_program(renderer: &mut Box<dyn Renderer>, frag: &str) -> Box<dyn Program>{
+  let shader_path = shader_path(renderer);
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + frag)).unwrap();
+
+  let shader_vert = renderer.load_shader(renderer_types::ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader(renderer_types::ShaderType::Fragment, &source_frag).unwrap();
+
+  renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap()
+}
+
+fn gen_color_image(color: [u8; 4]) -> image::DynamicImage{
+  let mut img = image::RgbaImage::new(1, 1);
+  img.put_pixel(0, 0, image::Rgba(color));
+  image::DynamicImage::ImageRgba8(img)
+}
+
+fn test_texture_dimensions(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 0, 255], [255, 0, 255, 255], [0, 255, 255, 255]];
+  let faces: Vec<image::DynamicImage> = colors.iter().map(|color| gen_color_image(*color)).collect();
+
+  let mut texture = renderer.gen_buffer_texture();
+  assert!(renderer.load_texture_layers(&faces[0..5], TextureDimension::Cube, TextureFormat::RGBA8, &mut texture).is_err(), "Cubemap was loaded with 5 faces");
+  renderer.load_texture_layers(&faces, TextureDimension::Cube, TextureFormat::RGBA8, &mut texture).unwrap();
+
+  // +x, -x, +y and -z
+  let program = load_frag_program(renderer, "cube.frag");
+  let image = draw_program_quad(renderer, program, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+  assert_quadrants(&image, &[colors[0], colors[2], colors[1], colors[5]]);
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture_layers(&faces[0..2], TextureDimension::D3, TextureFormat::RGBA8, &mut texture).unwrap();
+
+  let program = load_frag_program(renderer, "volume.frag");
+  let image = draw_program_quad(renderer, program, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+  assert_quadrants(&image, &[colors[0], colors[1], colors[0], colors[1]]);
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture_layers(&faces[3..6], TextureDimension::D2Array, TextureFormat::RGBA8, &mut texture).unwrap();
+
+  let program = load_frag_program(renderer, "array.frag");
+  let image = draw_program_quad(renderer, program, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+  assert_quadrants(&image, &[colors[3], colors[5], colors[3], colors[5]]);
+}
+
+fn test_texture_dimensions_unsupported(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let faces: Vec<image::DynamicImage> = (0..6).map(|_i| gen_color_image([255, 255, 255, 255])).collect();
+  let mut texture = renderer.gen_buffer_texture();
+  let result = renderer.load_texture_layers(&faces, TextureDimension::Cube, TextureFormat::RGBA8, &mut texture);
+  assert!(matches!(result, Err(RendererError::UnsupportedAPI)), "Software renderer loaded a cubemap");
+}
+
+fn texture_dimensions() {
+  run_backends(&Requires{software: false, ..EVERY_BACKEND}, test_texture_dimensions);
+  run_backends(&SOFTWARE_BACKEND, test_texture_dimensions_unsupported);
+}
+
+fn equirectangular_cube() {
+  // red is u and green is v so each face texel shows where it was read from
+  let mut panorama = image::Rgba32FImage::new(16, 8);
+  for (x, y, pixel) in panorama.enumerate_pixels_mut() {
+    *pixel = image::Rgba([(x as f32 + 0.5) / 16.0, (y as f32 + 0.5) / 8.0, 0.0, 1.0]);
+  }
+
+  let faces = rad::gpu::image::equirectangular_to_cube(&image::DynamicImage::ImageRgba32F(panorama), 3);
+  assert!(faces.len() == 6, "Cubemap has {} faces", faces.len());
+
+  // centers of the faces point along the axes, -z is the center of the panorama and +y the top row
+  let center = |face: usize| faces[face].to_rgba32f().get_pixel(1, 1).0;
+  let expected = [(0, Some(0.75), 0.5), (1, Some(0.25), 0.5), (2, None, 0.0625), (3, None, 0.9375), (5, Some(0.5), 0.5)];
+
+  for (face, u, v) in expected {
+    let [r, g, _b, _a] = center(face);
+    if let Some(u) = u {
+      assert!((r - u).abs() < 0.01, "Face {} read u {} instead of {}", face, r, u);
+    }
+    assert!((g - v).abs() < 0.01, "Face {} read v {} instead of {}", face, g, v);
+  }
+}
+
+// a ktx2 file with the level index and no data format descriptor
+fn gen_ktx2(vk_format: u32, width: u32, height: u32, levels: &[Vec<u8>]) -> Vec<u8>{
+  let mut data = vec![0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+  for value in [vk_format, 1, width, height, 0, 0, 1, levels.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0, 0] {
+    data.extend_from_slice(&value.to_le_bytes());
+  }
+
+  let mut offset = (80 + 24 * levels.len()) as u64;
+  for level in levels {
+    for value in [offset, level.len() as u64, level.len() as u64] {
+      data.extend_from_slice(&value.to_le_bytes());
+    }
+    offset += level.len() as u64;
+  }
+
+  for level in levels {
+    data.extend_from_slice(level);
+  }
+  data
+}
+
+fn gen_dds(four_cc: &[u8; 4], width: u32, height: u32, levels: &[Vec<u8>]) -> Vec<u8>{
+  let mut header = [0u32; 31];
+  header[0] = 124;
+  header[1] = 0x1 | 0x2 | 0x4 | 0x1000 | 0x20000;
+  header[2] = height;
+  header[3] = width;
+  header[6] = levels.len() as u32;
+  header[18] = 32;
+  header[19] = 0x4;
+  header[20] = u32::from_le_bytes(*four_cc);
+
+  let mut data = b"DDS ".to_vec();
+  for value in header {
+    data.extend_from_slice(&value.to_le_bytes());
+  }
+  for level in levels {
+    data.extend_from_slice(level);
+  }
+  data
+}
+
+fn gen_bc1_block(color: u16) -> Vec<u8>{
+  let [low, high] = color.to_le_bytes();
+  vec![low, high, low, high, 0, 0, 0, 0]
+}
+
+// individual mode with the same 4 bit color in both halves, every texel is offset by 2
+fn gen_etc2_block(color: [u8; 3]) -> Vec<u8>{
+  vec![color[0] * 0x11, color[1] * 0x11, color[2] * 0x11, 0, 0, 0, 0, 0]
+}
+
+// void extent block of 16 bit channels
+fn gen_astc_block(color: [u16; 4]) -> Vec<u8>{
+  let mut block = 0xFFFFFFFFFFFFFDFCu64.to_le_bytes().to_vec();
+  for channel in color {
+    block.extend_from_slice(&channel.to_le_bytes());
+  }
+  block
+}
+
+fn test_compressed_textures(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+
+  // 2x2 blocks of the colors with a second level of a single block
+  let bc1 = [0xF800, 0x07E0, 0x001F, 0xFFFF].map(gen_bc1_block);
+  let etc2 = [[0xF, 0, 0], [0, 0xF, 0], [0, 0, 0xF], [0xF, 0xF, 0xF]].map(gen_etc2_block);
+  let astc = [[0xFFFF, 0, 0, 0xFFFF], [0, 0xFFFF, 0, 0xFFFF], [0, 0, 0xFFFF, 0xFFFF], [0xFFFF; 4]].map(gen_astc_block);
+
+  let files = [
+    gen_ktx2(133, 8, 8, &[bc1.concat(), bc1[0].clone()]),
+    gen_dds(b"DXT1", 8, 8, &[bc1.concat(), bc1[0].clone()]),
+    gen_ktx2(147, 8, 8, &[etc2.concat(), etc2[0].clone()]),
+    gen_ktx2(157, 8, 8, &[astc.concat(), astc[0].clone()])];
+
+  for file in files {
+    let image = rad::gpu::compressed::CompressedImage::from_bytes(&file).unwrap();
+
+    let mut texture = renderer.gen_buffer_texture();
+    renderer.load_texture_compressed(&image, &mut texture).unwrap();
+
+    let result = draw_sampled_quad(renderer, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+    assert_quadrants(&result, &colors);
+  }
+}
+
+fn compressed_textures() {
+  run_backends(&EVERY_BACKEND, test_compressed_textures);
+}
+
+fn compressed_containers() {
+  use rad::gpu::compressed::CompressedImage;
+  use renderer_types::*;
+
+  // 6x6 is 2x2 blocks with a 3x3 level of 1 block
+  let bc1 = gen_bc1_block(0xF800);
+  let image = CompressedImage::from_bytes(&gen_ktx2(134, 6, 6, &[bc1.repeat(4), bc1.clone()])).unwrap();
+  assert!(image.format == CompressedFormat::BC1 && image.srgb, "Ktx2 format is {:?} srgb {}", image.format, image.srgb);
+  assert!(image.levels.len() == 2 && image.get_level_size(1) == (3, 3), "Ktx2 has {} levels", image.levels.len());
+
+  let decoded = image.decompress(1).unwrap().to_rgba8();
+  assert!(decoded.dimensions() == (3, 3) && decoded.get_pixel(2, 2).0 == [255, 0, 0, 255], "Bc1 decoded as {:?}", decoded.get_pixel(2, 2));
+
+  // a level with the wrong size and a file cut short
+  assert!(CompressedImage::from_bytes(&gen_ktx2(133, 8, 8, &[bc1.clone()])).is_err(), "Ktx2 level of the wrong size was loaded");
+  let dds = gen_dds(b"DXT5", 4, 4, &[vec![0; 16]]);
+  assert!(CompressedImage::from_bytes(&dds[..dds.len() - 1]).is_err(), "Truncated dds was loaded");
+  assert!(CompressedImage::from_bytes(&dds).unwrap().format == CompressedFormat::BC3, "Dxt5 isn't bc3");
+
+  // more levels than a 1x1 image has
+  assert!(CompressedImage::from_bytes(&gen_dds(b"DXT1", 1, 1, &vec![bc1.clone(); 40])).is_err(), "Dds with 40 levels was loaded");
+  assert!(CompressedImage::from_bytes(&gen_ktx2(131, 1, 1, &vec![bc1.clone(); 33])).is_err(), "Ktx2 with 33 levels was loaded");
+  assert!(CompressedImage::from_bytes(&gen_ktx2(131, 1, 1, &vec![bc1.clone(); 2])).is_err(), "Ktx2 with 2 levels of 1x1 was loaded");
+
+  // supported by the gpu only
+  let bc7 = CompressedImage::from_bytes(&gen_ktx2(145, 4, 4, &[vec![0; 16]])).unwrap();
+  assert!(matches!(bc7.decompress(0), Err(RendererError::UnsupportedAPI)), "Bc7 was decompressed");
+
+  // etc2 t mode from the red overflowing, 4 bit colors of red and blue with the first distance of 3 added to blue
+  let etc2 = CompressedImage{format: CompressedFormat::ETC2RGB8, srgb: false, width: 4, height: 4,
+    levels: vec![vec![0xFB, 0x00, 0x00, 0xF2, 0x00, 0x00, 0xFF, 0xFF]]};
+  let decoded = etc2.decompress(0).unwrap().to_rgba8();
+  assert!(decoded.get_pixel(0, 0).0 == [3, 3, 255, 255], "Etc2 t mode decoded as {:?}", decoded.get_pixel(0, 0));
+
+  let rgba = CompressedImage{format: CompressedFormat::ETC2RGBA8, srgb: false, width: 4, height: 4,
+    levels: vec![[vec![128, 0x10, 0, 0, 0, 0, 0, 0], gen_etc2_block([0, 0xF, 0])].concat()]};
+  let decoded = rgba.decompress(0).unwrap().to_rgba8();
+  assert!(decoded.get_pixel(3, 3).0 == [2, 255, 2, 125], "Etc2 with alpha decoded as {:?}", decoded.get_pixel(3, 3));
+
+  // astc 4x4 weights of 2 bits, rgb endpoints from red to blue with the first row at red
+  let mut bits: u128 = 0x42 | (8 << 13);
+  for (i, value) in [255u128, 0, 0, 0, 0, 255].iter().enumerate() {
+    bits |= value << (17 + 8 * i);
+  }
+  for i in 4..16 {
+    bits |= 3 << (126 - 2 * i);
+  }
+
+  let astc = CompressedImage{format: CompressedFormat::ASTC(4, 4), srgb: false, width: 4, height: 4, levels: vec![bits.to_le_bytes().to_vec()]};
+  let decoded = astc.decompress(0).unwrap().to_rgba8();
+  assert!(decoded.get_pixel(1, 0).0 == [255, 0, 0, 255], "Astc first row decoded as {:?}", decoded.get_pixel(1, 0));
+  assert!(decoded.get_pixel(2, 3).0 == [0, 0, 255, 255], "Astc last row decoded as {:?}", decoded.get_pixel(2, 3));
+}
+
+fn gen_state_quad(renderer: &mut Box<dyn Renderer>, color: [u8; 4], transform: Mat4, state: renderer_types::RenderState) -> Box<Mesh>{
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&gen_color_image(color), &mut texture);
+
+  let mut mesh = gen_quad_mesh(renderer, texture.into());
+  mesh.material.set_render_state(state);
+  mesh.material.find_uniform("u_mvp").unwrap().set::<Mat4>(transform);
+  mesh
+}
+
+fn test_render_state(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let camera = rad::gpu::camera::Camera::new();
+  let left = Mat4::from_translation(Vec3::new(-0.5, 0.0, 0.0)) * Mat4::from_scale(Vec3::new(0.5, 1.0, 1.0));
+  let right = Mat4::from_translation(Vec3::new(0.5, 0.0, 0.0)) * Mat4::from_scale(Vec3::new(0.5, 1.0, 1.0));
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 1.0, 1.0));
+  renderer.set_clear_depth(1.0);
+  renderer.set_clear_stencil(0);
+
+  // half transparent red blended into the left half, then only green written to the bottom half
+  let blend_state = RenderState{scissor: Some((IVec2::new(0, 0), IVec2::new(120, 160))), ..RenderState::transparent()};
+  let mask_state = RenderState{color_write: ColorWriteMask::GREEN, scissor: Some((IVec2::new(0, 0), IVec2::new(240, 80))), ..RenderState::default()};
+  let mut blend = gen_state_quad(renderer, [255, 0, 0, 128], Mat4::IDENTITY, blend_state);
+  let mut mask = gen_state_quad(renderer, [0, 255, 0, 255], Mat4::IDENTITY, mask_state);
+
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut blend);
+  assert!(*renderer.get_render_state() == blend_state, "Material render state was not applied");
+  renderer.draw_mesh(&camera, &mut mask);
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &[[128, 255, 127, 255], [0, 255, 255, 255], [128, 0, 127, 255], [0, 0, 255, 255]]);
+
+  // the farther green quad fails the depth test, blue on the right is nearer. the mirrored quad is back facing
+  let depth_state = RenderState{depth: Some(DepthState::default()), ..RenderState::default()};
+  let cull_state = RenderState{cull_mode: CullMode::Back, ..RenderState::default()};
+  let mut near = gen_state_quad(renderer, [255, 0, 0, 255], Mat4::IDENTITY, depth_state);
+  let mut far = gen_state_quad(renderer, [0, 255, 0, 255], Mat4::from_translation(Vec3::new(0.0, 0.0, 0.5)), depth_state);
+  let mut nearer = gen_state_quad(renderer, [0, 0, 255, 255], Mat4::from_translation(Vec3::new(0.0, 0.0, -0.5)) * right, depth_state);
+  let mut back = gen_state_quad(renderer, [255, 255, 255, 255], Mat4::from_scale(Vec3::new(-1.0, 1.0, 1.0)), cull_state);
+
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR | RendererClearType::DEPTH);
+  renderer.draw_mesh(&camera, &mut near);
+  renderer.draw_mesh(&camera, &mut far);
+  renderer.draw_mesh(&camera, &mut nearer);
+  renderer.draw_mesh(&camera, &mut back);
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &[[255, 0, 0, 255], [0, 0, 255, 255], [255, 0, 0, 255], [0, 0, 255, 255]]);
+
+  // the left half is marked in the stencil buffer without writing color, green is only drawn where it is marked
+  let mark = StencilFaceState{pass: StencilOp::Replace, ..StencilFaceState::default()};
+  let equal = StencilFaceState{compare: CompareFunction::Equal, ..StencilFaceState::default()};
+  let mut marker = gen_state_quad(renderer, [255, 255, 255, 255], left, RenderState{stencil: Some(StencilState::both(mark, 1)), color_write: ColorWriteMask::NONE, ..RenderState::default()});
+  let mut marked = gen_state_quad(renderer, [0, 255, 0, 255], Mat4::IDENTITY, RenderState{stencil: Some(StencilState::both(equal, 1)), ..RenderState::default()});
+
+  renderer.begin_frame(RendererClearType::COLOR | RendererClearType::STENCIL);
+  renderer.draw_mesh(&camera, &mut marker);
+  renderer.draw_mesh(&camera, &mut marked);
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+
+  assert_quadrants(&image, &[[0, 255, 0, 255], [0, 0, 0, 255], [0, 255, 0, 255], [0, 0, 0, 255]]);
+
+  renderer.set_render_state(&RenderState::default());
+}
+
+fn render_state() {
+  run_backends(&EVERY_BACKEND, test_render_state);
+}
+
+fn draw_topology(renderer: &mut Box<dyn Renderer>, texture: Box<dyn Texture>, verts: Vec<f32>, topology: renderer_types::PrimitiveTopology) -> rad::gpu::image::Image{
+  use renderer_types::*;
+  use rad::gpu::material::Material;
+
+  let program = load_basic_program(renderer);
+  let vert_buffer = renderer.gen_buffer_vertex(&verts);
+  let mut geometry = renderer.gen_geometry(&vert_buffer);
+  geometry.set_topology(topology);
+  assert!(geometry.get_topology() == topology, "Topology was not set");
+
+  let sampler = renderer.gen_sampler(texture.into(), &SamplerDesc::nearest());
+  let mut material = Box::new(material::MaterialBasic::new(program, sampler));
+  material.set_render_state(RenderState{cull_mode: CullMode::Back, ..RenderState::default()});
+  let mut mesh = renderer.gen_mesh(geometry, material);
+  let camera = rad::gpu::camera::Camera::new();
+
+  renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+  renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+  renderer.begin_frame(RendererClearType::COLOR);
+  renderer.draw_mesh(&camera, &mut mesh);
+
+  let image = renderer.read_render_buffer();
+  renderer.end_frame();
+  renderer.set_render_state(&RenderState::default());
+  image
+}
+
+// vertices at pixel centers of a 240x160 viewport
+fn gen_pixel_verts(pixels: &[(u32, u32)]) -> Vec<f32>{
+  pixels.iter().flat_map(|(x, y)| [(*x as f32 + 0.5) / 120.0 - 1.0, (*y as f32 + 0.5) / 80.0 - 1.0, 0.0, 0.0]).collect()
+}
+
+fn test_primitive_topology(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let white = [255, 255, 255, 255];
+  let black = [0, 0, 0, 255];
+  let gen_white = |renderer: &mut Box<dyn Renderer>| {
+    let mut texture = renderer.gen_buffer_texture();
+    renderer.load_texture(&gen_color_image(white), &mut texture);
+    texture
+  };
+
+  let texture = gen_white(renderer);
+  let image = draw_topology(renderer, texture, gen_pixel_verts(&[(60, 40), (180, 40), (60, 120)]), PrimitiveTopology::Points);
+  for (x, y, color) in [(60, 40, white), (180, 40, white), (60, 120, white), (61, 40, black), (180, 120, black)] {
+    let error = pixel_square_error(&image, x, y, color);
+    assert!(error <= 48, "Point pixel {} {} is not within tolerance. Error: {}", x, y, error);
+  }
+
+  let texture = gen_white(renderer);
+  let image = draw_topology(renderer, texture, gen_pixel_verts(&[(10, 40), (230, 40), (120, 10), (120, 150)]), PrimitiveTopology::Lines);
+  for (x, y, color) in [(60, 40, white), (200, 40, white), (120, 100, white), (60, 41, black), (121, 100, black)] {
+    let error = pixel_square_error(&image, x, y, color);
+    assert!(error <= 48, "Line pixel {} {} is not within tolerance. Error: {}", x, y, error);
+  }
+
+  // a loop closes the rectangle with its left edge
+  let rectangle = gen_pixel_verts(&[(20, 20), (219, 20), (219, 139), (20, 139)]);
+  for (topology, left) in [(PrimitiveTopology::LineStrip, black), (PrimitiveTopology::LineLoop, white)] {
+    let texture = gen_white(renderer);
+    let image = draw_topology(renderer, texture, rectangle.clone(), topology);
+    for (x, y, color) in [(120, 20, white), (219, 80, white), (120, 139, white), (20, 80, left), (120, 80, black)] {
+      let error = pixel_square_error(&image, x, y, color);
+      assert!(error <= 48, "{:?} pixel {} {} is not within tolerance. Error: {}", topology, x, y, error);
+    }
+  }
+
+  // every triangle of the strip and fan is front facing so none are culled
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let strip = vec![
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    -1.0, 1.0, 0.0, 1.0,
+    1.0, 1.0, 1.0, 1.0];
+  let fan = vec![
+    -1.0, -1.0, 0.0, 0.0,
+    1.0, -1.0, 1.0, 0.0,
+    1.0, 1.0, 1.0, 1.0,
+    -1.0, 1.0, 0.0, 1.0];
+
+  for (verts, topology) in [(strip, PrimitiveTopology::TriangleStrip), (fan, PrimitiveTopology::TriangleFan)] {
+    let texture = gen_checker_texture(renderer, &colors);
+    let image = draw_topology(renderer, texture, verts, topology);
+    assert_quadrants(&image, &colors);
+  }
+}
+
+fn primitive_topology() {
+  run_backends(&EVERY_BACKEND, test_primitive_topology);
+}
+
+fn test_shader_diagnostics(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let shader_path = shader_path(renderer);
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.frag")).unwrap();
+
+  let broken_frag = source_frag.replace("texture(u_texture, v_uv);", "texture(u_texture, v_uv) * u_missing;");
+  let broken_line = broken_frag.lines().position(|line| line.contains("u_missing")).unwrap() as u32 + 1;
+
+  match renderer.load_shader(ShaderType::Fragment, &broken_frag) {
+    Err(RendererError::ShaderCompile(res)) => {
+      assert!(res.shader_type == Some(ShaderType::Fragment), "Compile error has the wrong stage");
+      assert!(!res.log.is_empty(), "Compile error has no log");
+      assert!(res.get_errors().any(|diagnostic| diagnostic.line == Some(broken_line)), "No error on line {} in {:?}", broken_line, res.diagnostics);
+    },
+    Err(res) => panic!("Expected a compile error, got {}", res),
+    Ok(_res) => panic!("Broken shader compiled")
+  }
+
+  // vulkan doesn't link stages until a pipeline is made
+  if renderer.get_type() == RendererType::Vulkan {
+    return
+  }
+
+  // compiles alone but a stage without main can't be linked
+  let mainless_frag = source_frag.replace("void main()", "void not_main()");
+  let shader_vert = renderer.load_shader(ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader(ShaderType::Fragment, &mainless_frag).unwrap();
+
+  match renderer.load_program_vert_frag(shader_vert, shader_frag) {
+    Err(RendererError::ShaderLink(res)) => {
+      assert!(res.shader_type.is_none(), "Link error has a stage");
+      assert!(res.get_errors().count() > 0, "Link error has no diagnostics in {}", res.log);
+    },
+    Err(res) => panic!("Expected a link error, got {}", res),
+    Ok(_res) => panic!("Program without a fragment main linked")
+  }
+}
+
+fn shader_diagnostics() {
+  use rad::gpu::shader_log::*;
+
+  let mesa = ShaderLog::from_info_log(Some(renderer_types::ShaderType::Fragment), "0:12(37): error: `u_missing' undeclared\n0:14(2): warning: unused\n\0", 0);
+  assert!(mesa.diagnostics.len() == 2, "Mesa log has {} diagnostics", mesa.diagnostics.len());
+  assert!(mesa.diagnostics[0] == ShaderDiagnostic{severity: ShaderDiagnosticSeverity::Error, file: None, line: Some(12), column: Some(37), message: String::from("`u_missing' undeclared")}, "Mesa error was misparsed");
+  assert!(mesa.diagnostics[1].severity == ShaderDiagnosticSeverity::Warning && mesa.diagnostics[1].line == Some(14), "Mesa warning was misparsed");
+  assert!(mesa.get_errors().count() == 1, "Warnings counted as errors");
+
+  let nvidia = ShaderLog::from_info_log(None, "0(7) : error C1008: undefined variable \"u_missing\"", 0);
+  assert!(nvidia.diagnostics[0].line == Some(7) && nvidia.diagnostics[0].message == "undefined variable \"u_missing\"", "Nvidia log was misparsed");
+
+  // lines before the prepended ones are moved up and the prepended ones have no line
+  let angle = ShaderLog::from_info_log(None, "ERROR: 0:5: 'u_missing' : undeclared identifier\nERROR: 0:1: 'x' : syntax error\nERROR: 2 compilation errors.  No code generated.", 2);
+  assert!(angle.diagnostics.len() == 2, "Angle log has {} diagnostics", angle.diagnostics.len());
+  assert!(angle.diagnostics[0].line == Some(3) && angle.diagnostics[0].message == "'u_missing' : undeclared identifier", "Angle log was misparsed");
+  assert!(angle.diagnostics[1].line.is_none(), "Prepended line was not hidden");
+
+  let link = ShaderLog::from_info_log(None, "error: fragment shader input `v_missing' has no matching output in the previous stage\n", 0);
+  assert!(link.diagnostics[0].line.is_none() && link.diagnostics[0].severity == ShaderDiagnosticSeverity::Error, "Link log was misparsed");
+
+  run_backends(&Requires{software: false, ..EVERY_BACKEND}, test_shader_diagnostics);
+}
+
+fn test_program_reflection(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+  use rad::gpu::uniforms::*;
+  use rad::gpu::material::Material;
+
+  let program = load_basic_program(renderer);
+  let reflection = program.get_reflection();
+
+  let mvp = reflection.find_uniform("u_mvp").expect("u_mvp was not reflected");
+  assert!(mvp.info == UniformType{element_type: ElementType::Float32, container_type: ContainerType::Mat4x4, num_components: 1}, "u_mvp is {:?}", mvp.info);
+  assert!(mvp.location >= 0, "u_mvp has no location");
+
+  let texture = reflection.find_sampler("u_texture").expect("u_texture was not reflected");
+  assert!(texture.dimension == TextureDimension::D2 && texture.count == 1, "u_texture is {:?}", texture);
+
+  let attributes: Vec<(&str, u32)> = reflection.attributes.iter().map(|attribute| (attribute.name.as_str(), attribute.location)).collect();
+  assert!(attributes == [("i_position", 0), ("i_uv", 1)], "Attributes are {:?}", attributes);
+  assert!(reflection.attributes.iter().all(|attribute| attribute.info.container_type == ContainerType::Vec2), "Attributes aren't vec2");
+
+  // misspelled and mistyped uniforms are reported
+  assert!(reflection.find_uniform("u_mpv").is_none(), "Misspelled uniform was found");
+  assert!(matches!(reflection.validate_uniform("u_mpv", &mvp.info), Err(RendererError::InvalidUniform(_))), "Misspelled uniform was valid");
+  assert!(matches!(reflection.validate_uniform("u_mvp", &UniformData::new(Vec4::ZERO).info), Err(RendererError::InvalidUniform(_))), "Mistyped uniform was valid");
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&gen_color_image([255, 255, 255, 255]), &mut texture);
+  let sampler = renderer.gen_sampler(texture.into(), &SamplerDesc::default());
+
+  let mut material = material::MaterialBasic::from_program(program);
+  assert!(material.find_uniform("u_mvp").is_some(), "Material has no u_mvp");
+  material.add_sampler("u_textur", sampler);
+  assert!(matches!(material.validate(), Err(RendererError::InvalidUniform(res)) if res == "u_textur"), "Misspelled sampler was valid");
+
+  let sampler = material.find_sampler("u_textur").unwrap();
+  sampler.set_name("u_texture");
+  assert!(material.validate().is_ok(), "Material is invalid");
+
+  if !matches!(renderer.get_type(), RendererType::OpenGL | RendererType::OpenGLES) {
+    return
+  }
+
+  let program = load_frag_program(renderer, "uniforms.frag");
+  let reflection = program.get_reflection();
+
+  for (name, element_type, container_type, num_components) in [
+    ("u_flags", ElementType::Int32, ContainerType::Single, 1),
+    ("u_mask", ElementType::Uint32, ContainerType::Single, 1),
+    ("u_enabled", ElementType::Bool, ContainerType::Single, 1),
+    ("u_select", ElementType::Int32, ContainerType::Vec2, 1),
+    ("u_colors", ElementType::Float32, ContainerType::Vec4, 4)]
+  {
+    let uniform = reflection.find_uniform(name).expect(name);
+    assert!(uniform.info == UniformType{element_type: element_type, container_type: container_type, num_components: num_components}, "{} is {:?}", name, uniform.info);
+  }
+
+  // arrays are left out of materials made from the program
+  let material = material::MaterialBasic::from_program(program);
+  assert!(material.num_uniforms() == 5, "Material has {} uniforms", material.num_uniforms());
+  assert!(material.validate().is_ok(), "Material is invalid");
+
+  let program = load_block_program(renderer);
+  let block = program.get_reflection().find_block("Frame").expect("Frame was not reflected");
+  let members: Vec<(&str, u32, u16)> = block.members.iter().map(|member| (member.name.as_str(), member.offset, member.info.num_components)).collect();
+  assert!(block.size == 144, "Frame is {} bytes", block.size);
+  assert!(members == [("u_transform", 0, 1), ("u_tint", 64, 1), ("u_scale", 80, 1), ("u_offset", 96, 1), ("u_flags", 112, 2)], "Members are {:?}", members);
+  assert!(program.get_reflection().find_uniform("u_tint").is_none(), "Block member was reflected as a uniform");
+}
+
+fn program_reflection() {
+  run_backends(&EVERY_BACKEND, test_program_reflection);
+}
+
+// shader files written to a temporary root, named by the test so tests don't share them
+fn write_shader_root(name: &str, files: &[(&str, &str)]) -> String{
+  let root = std::env::temp_dir().join(name);
+  std::fs::create_dir_all(&root).unwrap();
+
+  for (path, source) in files {
+    std::fs::write(root.join(path), source).unwrap();
+  }
+
+  root.to_string_lossy().into_owned() + "/"
+}
+
+fn test_shader_preprocessor(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+  use rad::gpu::shader_preprocessor::*;
+
+  let shader_path = shader_path(renderer);
+
+  // basic.frag with its color passed through an included function
+  let basic_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.frag")).unwrap();
+  let tinted_frag: String = basic_frag.lines().map(|line| match line {
+    res if res.contains("out vec4 Color") => res.to_owned() + "\n#include \"tint.glsl\"\n",
+    res if res.trim_start().starts_with("Color = ") => res.replace("Color = ", "Color = tint(").replace(";", ");") + "\n",
+    res => res.to_owned() + "\n"
+  }).collect();
+
+  let root = write_shader_root(&format!("rad_shader_preprocessor_{}", renderer.get_type()), &[
+    ("tinted.frag", &tinted_frag),
+    ("tint.glsl", "#include \"colors.glsl\"\nvec4 tint(vec4 a_color)\n{\n#ifdef TINT_RED\n    return a_color * RED;\n#else\n    return a_color;\n#endif\n}\n"),
+    // included by tint.glsl already so it isn't included again
+    ("colors.glsl", "#include \"tint.glsl\"\nconst vec4 RED = vec4(1.0, 0.0, 0.0, 1.0);\n"),
+    ("broken.frag", &tinted_frag.replace("#include \"tint.glsl\"", "#include \"broken.glsl\"")),
+    ("broken.glsl", "vec4 tint(vec4 a_color)\n{\n    return a_color * u_missing;\n}\n")]);
+
+  let mut preprocessor = ShaderPreprocessor::new(&root);
+  let shader_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.vert")).unwrap();
+
+  for (permutation, color) in [
+    (ShaderPermutation::new("tinted.frag"), [255, 255, 255, 255]),
+    (ShaderPermutation::new("tinted.frag").define("TINT_RED", ""), [255, 0, 0, 255])]
+  {
+    let shader_vert = renderer.load_shader(ShaderType::Vertex, &shader_vert).unwrap();
+    let shader_frag = preprocessor.load_shader(renderer.as_mut(), ShaderType::Fragment, &permutation).unwrap();
+    let program = renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap();
+
+    let mut texture = renderer.gen_buffer_texture();
+    renderer.load_texture(&gen_color_image([255, 255, 255, 255]), &mut texture);
+    let image = draw_program_quad(renderer, program, texture, &SamplerDesc::default(), 0.0, 1.0);
+
+    let error = pixel_square_error(&image, 120, 80, color);
+    assert!(error <= 48, "{:?} is not within tolerance. Error: {}", permutation, error);
+  }
+
+  // the error is in the include, not the expanded source
+  match preprocessor.load_shader(renderer.as_mut(), ShaderType::Fragment, &ShaderPermutation::new("broken.frag")) {
+    Err(RendererError::ShaderCompile(res)) => {
+      assert!(res.get_errors().any(|diagnostic| diagnostic.file.as_deref() == Some("broken.glsl") && diagnostic.line == Some(3)), "No error in broken.glsl in {:?}", res.diagnostics);
+    },
+    Err(res) => panic!("Expected a compile error, got {}", res),
+    Ok(_res) => panic!("Broken include compiled")
+  }
+}
+
+fn shader_preprocessor() {
+  use rad::gpu::shader_preprocessor::*;
+  use rad::gpu::shader_log::*;
+
+  let root = write_shader_root("rad_shader_preprocessor", &[
+    ("main.frag", "#version 330 core\n#include \"common.glsl\"\nvoid main()\n{\n}\n"),
+    ("common.glsl", "// common\n#include <inner.glsl>\nfloat value;\n"),
+    ("inner.glsl", "float inner;\n"),
+    ("missing.frag", "#version 330 core\n\n#include \"missing.glsl\"\n")]);
+
+  let mut preprocessor = ShaderPreprocessor::new(&root);
+  preprocessor.add_define("QUALITY", "2");
+
+  let normal = ShaderPermutation::new("main.frag").define("NORMAL_MAP", "").define("ALPHA", "1");
+  let source = preprocessor.get_source(&normal).unwrap();
+  let lines: Vec<&str> = source.source.lines().collect();
+  assert!(lines == ["#version 330 core", "#define QUALITY 2", "#define ALPHA 1", "#define NORMAL_MAP", "// common", "float inner;", "float value;", "void main()", "{", "}"], "Expanded to {:?}", lines);
+
+  assert!(source.get_location(1) == Some(("main.frag", 1)), "Version is at {:?}", source.get_location(1));
+  assert!(source.get_location(2).is_none(), "Define has a location");
+  assert!(source.get_location(6) == Some(("inner.glsl", 1)), "Inner is at {:?}", source.get_location(6));
+  assert!(source.get_location(8) == Some(("main.frag", 3)), "Main is at {:?}", source.get_location(8));
+  assert!(source.get_location(11).is_none(), "Line past the end has a location");
+
+  // defines in any order are the same permutation and share the source
+  let same = preprocessor.get_source(&ShaderPermutation::new("main.frag").define("ALPHA", "1").define("NORMAL_MAP", "")).unwrap();
+  assert!(Rc::ptr_eq(&source, &same), "Permutation was not cached");
+  let other = preprocessor.get_source(&ShaderPermutation::new("main.frag").define("ALPHA", "0")).unwrap();
+  assert!(!Rc::ptr_eq(&source, &other) && other.source.contains("#define ALPHA 0"), "Permutations share a source");
+
+  let mut log = ShaderLog::from_info_log(None, "0:7(1): error: value redefined\n0:3(1): warning: define\n", 0);
+  source.map_log(&mut log);
+  assert!(log.diagnostics[0].file.as_deref() == Some("common.glsl") && log.diagnostics[0].line == Some(3), "Error mapped to {:?}", log.diagnostics[0]);
+  assert!(log.diagnostics[1].line.is_none(), "Define line was mapped");
+
+  match preprocessor.get_source(&ShaderPermutation::new("missing.frag")) {
+    Err(renderer_types::RendererError::ShaderCompile(res)) => {
+      assert!(res.diagnostics[0].file.as_deref() == Some("missing.frag") && res.diagnostics[0].line == Some(3), "Missing include at {:?}", res.diagnostics[0]);
+    },
+    _ => panic!("Missing include was expanded")
+  }
+
+  run_backends(&Requires{software: false, ..EVERY_BACKEND}, test_shader_preprocessor);
+}
+
+fn test_glsl_translation(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  // every shader is loaded from the other dialect's folder
+  let shader_path = match renderer.get_type() {
+    RendererType::OpenGLES => "../shaders/gl/",
+    _ => "../shaders/gles/"
+  };
+
+  let mut names: Vec<String> = std::fs::read_dir(shader_path).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+  names.sort();
+
+  for name in names.iter().map(|name| name.as_str()) {
+    let shader_type = match name.rsplit('.').next() {
+      Some("vert") => ShaderType::Vertex,
+      Some("tesc") => ShaderType::TesselationControl,
+      Some("tese") => ShaderType::TesselationEvaluation,
+      Some("geom") => ShaderType::Geometry,
+      Some("comp") => ShaderType::Compute,
+      _ => ShaderType::Fragment
+    };
+
+    let source = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + name)).unwrap();
+    match renderer.load_shader(shader_type, &source) {
+      Ok(_) | Err(RendererError::UnsupportedAPI) => {},
+      Err(res) => panic!("{} did not translate: {}", name, res)
+    }
+  }
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.vert")).unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.frag")).unwrap();
+
+  let shader_vert = renderer.load_shader(ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader(ShaderType::Fragment, &source_frag).unwrap();
+  let program = renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap();
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let texture = gen_checker_texture(renderer, &colors);
+  let image = draw_program_quad(renderer, program, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+  assert_quadrants(&image, &colors);
+
+  // lines added for the other dialect don't move diagnostics
+  let broken_frag = source_frag.replace("texture(u_texture, v_uv);", "texture(u_texture, v_uv) * u_missing;");
+  let broken_line = broken_frag.lines().position(|line| line.contains("u_missing")).unwrap() as u32 + 1;
+
+  match renderer.load_shader(ShaderType::Fragment, &broken_frag) {
+    Err(RendererError::ShaderCompile(res)) => {
+      assert!(res.get_errors().any(|diagnostic| diagnostic.line == Some(broken_line)), "No error on line {} in {:?}", broken_line, res.diagnostics);
+    },
+    Err(res) => panic!("Expected a compile error, got {}", res),
+    Ok(_res) => panic!("Broken shader compiled")
+  }
+}
+
+fn glsl_translation() {
+  use rad::gpu::opengl::glsl_translate::translate_glsl;
+
+  let gl_frag = "#version 330 core\n\nnoperspective in vec2 v_uv;\nuniform sampler2DArray u_layers;\nout vec4 Color;\n";
+  let gles_frag = translate_glsl(gl_frag, true, (3, 1));
+  let lines: Vec<&str> = gles_frag.lines().collect();
+  assert!(lines == ["#version 310 es", "precision highp float;", "precision highp int;", "precision highp sampler2DArray;", "#line 2", "", " in vec2 v_uv;", "uniform sampler2DArray u_layers;", "out vec4 Color;"], "Translated to {:?}", lines);
+
+  // declared precisions aren't repeated and desktop gl keeps them as they are allowed
+  let es_frag = "// comment\n#version 300 es\n#extension GL_OES_texture_3D : enable\nprecision mediump float;\nin vec2 v_uv;\n";
+  let lines: Vec<String> = translate_glsl(es_frag, true, (3, 2)).lines().map(String::from).collect();
+  assert!(lines == es_frag.lines().map(String::from).collect::<Vec<String>>(), "Source in the context's dialect was changed");
+
+  let gl_lines: Vec<String> = translate_glsl(es_frag, false, (4, 5)).lines().map(String::from).collect();
+  assert!(gl_lines == ["// comment", "#version 450 core", "", "precision mediump float;", "in vec2 v_uv;"], "Translated to {:?}", gl_lines);
+  assert!(translate_glsl(es_frag, false, (3, 2)).contains("#version 150 core"), "Gl 3.2 is not glsl 150");
+
+  let es_translated = translate_glsl("#version 330 core\nprecision mediump float;\n", true, (3, 0));
+  assert!(es_translated == "#version 300 es\nprecision highp int;\n#line 2\nprecision mediump float;\n", "Translated to {:?}", es_translated);
+
+  // #line in glsl es 1.00 numbers the line after it
+  let es2_translated = translate_glsl("#version 330 core\nprecision mediump float;\n", true, (2, 0));
+  assert!(es2_translated == "#version 100\nprecision highp int;\n#line 1\nprecision mediump float;\n", "Translated to {:?}", es2_translated);
+
+  // contexts before 3.0 have no version integers and are read from GL_VERSION
+  use rad::gpu::opengl::renderer_opengl::parse_version_string;
+  assert!(parse_version_string("2.1 Mesa 20.0.8") == Some((2, 1)));
+  assert!(parse_version_string("4.6.0 NVIDIA 535.54") == Some((4, 6)));
+  assert!(parse_version_string("OpenGL ES 2.0 Mesa 20.0.8") == Some((2, 0)));
+  assert!(parse_version_string("OpenGL ES-CM 1.1") == Some((1, 1)));
+  assert!(parse_version_string("unknown").is_none());
+
+  run_backends(&GL_BACKENDS, test_glsl_translation);
+}
+
+const CONSTANTS_VERT: &str = "#version 450\n\
+layout (location = 0) in vec2 i_position;\n\
+layout (location = 0) out vec2 v_uv;\n\
+layout (push_constant) uniform Constants { mat4 u_transform; vec4 u_offset; float u_scale1; } constants;\n\
+vec4 offset() { return constants.u_offset * constants.u_scale1; }\n\
+void main() { gl_Position = constants.u_transform * vec4(i_position * constants.u_scale1, 0.0, 1.0) + offset(); v_uv = i_position; }\n";
+
+fn test_shader_compiler(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate("../shaders/vulkan/basic.vert").unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate("../shaders/vulkan/basic.frag").unwrap();
+
+  let shader_vert = renderer.load_shader_neutral(ShaderType::Vertex, &source_vert).unwrap();
+  let shader_frag = renderer.load_shader_neutral(ShaderType::Fragment, &source_frag).unwrap();
+  let program = renderer.load_program_vert_frag(shader_vert, shader_frag).unwrap();
+
+  // the push constant and texture are set by the names in the source
+  assert!(program.get_reflection().find_uniform("u_mvp").is_some(), "No u_mvp in {:?}", program.get_reflection().uniforms);
+
+  let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+  let texture = gen_checker_texture(renderer, &colors);
+  let image = draw_program_quad(renderer, program, texture, &SamplerDesc::nearest(), 0.0, 1.0);
+  assert_quadrants(&image, &colors);
+
+  // parse errors point at the neutral source
+  let broken_frag = source_frag.replace("v_uv);", "v_uv) * u_missing;");
+  let broken_line = broken_frag.lines().position(|line| line.contains("u_missing")).unwrap() as u32 + 1;
+
+  match renderer.load_shader_neutral(ShaderType::Fragment, &broken_frag) {
+    Err(RendererError::ShaderCompile(res)) => {
+      assert!(res.get_errors().any(|diagnostic| diagnostic.line == Some(broken_line)), "No error on line {} in {:?}", broken_line, res.diagnostics);
+    },
+    Err(res) => panic!("Expected a compile error, got {}", res),
+    Ok(_res) => panic!("Broken shader compiled")
+  }
+
+  // a block with more members links and each is set by its name, the software renderer has one fixed program
+  if renderer.get_type() == RendererType::Software {
+    return;
+  }
+
+  let constants_vert = renderer.load_shader_neutral(ShaderType::Vertex, CONSTANTS_VERT).unwrap();
+  let constants_frag = renderer.load_shader_neutral(ShaderType::Fragment, &source_frag).unwrap();
+  let constants_program = renderer.load_program_vert_frag(constants_vert, constants_frag).unwrap();
+
+  for name in ["u_transform", "u_offset", "u_scale1"] {
+    assert!(constants_program.get_reflection().find_uniform(name).is_some(), "No {} in {:?}", name, constants_program.get_reflection().uniforms);
+  }
+}
+
+fn shader_compiler() {
+  use renderer_types::*;
+  use rad::gpu::shader_compiler::*;
+
+  let source_vert = rad::core::filesystem::filesystem::read_text_file_immediate("../shaders/vulkan/basic.vert").unwrap();
+  let source_frag = rad::core::filesystem::filesystem::read_text_file_immediate("../shaders/vulkan/basic.frag").unwrap();
+
+  let gles_vert = ParsedShader::parse(ShaderType::Vertex, &source_vert).unwrap().to_glsl(true, (3, 0)).unwrap();
+  assert!(gles_vert.starts_with("#version 300 es") && gles_vert.contains("uniform mat4x4 u_mvp;"), "Compiled to {}", gles_vert);
+
+  let gl_frag = ParsedShader::parse(ShaderType::Fragment, &source_frag).unwrap().to_glsl(false, (4, 6)).unwrap();
+  assert!(gl_frag.starts_with("#version 450 core") && gl_frag.contains("sampler2D u_texture;"), "Compiled to {}", gl_frag);
+
+  // every member of a block becomes a uniform, naga writes one push constant a stage
+  let constants_vert = ParsedShader::parse(ShaderType::Vertex, CONSTANTS_VERT).unwrap().to_glsl(false, (3, 3)).unwrap();
+  assert!(constants_vert.contains("uniform mat4x4 u_transform;") && constants_vert.contains("uniform vec4 u_offset;") && constants_vert.contains("uniform float u_scale1;"), "Compiled to {}", constants_vert);
+  assert!(!constants_vert.contains("constants.") && !constants_vert.contains("uniform Constants"), "Compiled to {}", constants_vert);
+
+  let array_vert = CONSTANTS_VERT.replace("float u_scale1;", "float u_scale1; float u_weights[3];").replace("* constants.u_scale1, 0.0", "* constants.u_weights[1], 0.0");
+  let array_vert = ParsedShader::parse(ShaderType::Vertex, &array_vert).unwrap().to_glsl(false, (3, 3)).unwrap();
+  assert!(array_vert.contains("uniform float u_weights[3];") && array_vert.contains("u_weights[1]"), "Compiled to {}", array_vert);
+
+  // a member that can't be a uniform is an error rather than glsl that won't link
+  let struct_vert = CONSTANTS_VERT.replace("layout (push_constant)", "struct Offset { vec4 value; };\nlayout (push_constant)").replace("vec4 u_offset;", "Offset u_offset;").replace("constants.u_offset", "constants.u_offset.value");
+  match ParsedShader::parse(ShaderType::Vertex, &struct_vert).unwrap().to_glsl(false, (3, 3)) {
+    Err(RendererError::ShaderCompile(res)) => assert!(res.log.contains("u_offset"), "Log is {}", res.log),
+    _ => panic!("Struct push constant member compiled")
+  }
+
+  match ParsedShader::parse(ShaderType::Fragment, &source_frag).unwrap().to_glsl(true, (2, 0)) {
+    Err(RendererError::UnsupportedAPI) => {},
+    _ => panic!("Gles 2 is not unsupported")
+  }
+
+  let hlsl_frag = ParsedShader::parse(ShaderType::Fragment, &source_frag).unwrap().to_hlsl().unwrap();
+  assert!(hlsl_frag.contains("Texture2D<float4> u_texture : register(t0);") && hlsl_frag.contains("SamplerState u_texture_sampler : register(s1);"), "Compiled to {}", hlsl_frag);
+
+  // hlsl has no point size
+  let hlsl_vert = ParsedShader::parse(ShaderType::Vertex, &source_vert.replace("gl_PointSize = 1.0;", "")).unwrap().to_hlsl().unwrap();
+  assert!(hlsl_vert.contains(&format!("register(b0, space{})", HLSL_PUSH_CONSTANT_SPACE)), "Compiled to {}", hlsl_vert);
+
+  match ParsedShader::parse(ShaderType::Vertex, &source_vert).unwrap().to_hlsl() {
+    Err(RendererError::ShaderCompile(res)) => assert!(res.shader_type == Some(ShaderType::Vertex)),
+    _ => panic!("Point size compiled to hlsl")
+  }
+
+  match ParsedShader::parse(ShaderType::Geometry, &source_vert) {
+    Err(RendererError::UnsupportedAPI) => {},
+    _ => panic!("Geometry shaders are not unsupported")
+  }
+
+  run_backends(&EVERY_BACKEND, test_shader_compiler);
+}
+
+fn test_shader_reload(renderer: &mut Box<dyn Renderer>){
+  use renderer_types::*;
+  use rad::gpu::shader_preprocessor::*;
+  use rad::gpu::shader_reload::*;
+
+  fn draw_center(renderer: &mut Box<dyn Renderer>, mesh: &mut Box<Mesh>, color: [u8; 4]) -> i64{
+    let camera = rad::gpu::camera::Camera::new();
+
+    renderer.set_viewport(IVec2::new(0, 0), IVec2::new(240, 160));
+    renderer.set_clear_color(Vec4::new(0.0, 0.0, 0.0, 1.0));
+    renderer.begin_frame(RendererClearType::COLOR);
+    renderer.draw_mesh(&camera, mesh);
+
+    let image = renderer.read_render_buffer();
+    renderer.end_frame();
+
+    pixel_square_error(&image, 120, 80, color)
+  }
+
+  let shader_path = shader_path(renderer);
+
+  // basic.frag with its color passed through an included function
+  let basic_frag = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.frag")).unwrap();
+  let tinted_frag: String = basic_frag.lines().map(|line| match line {
+    res if res.contains("out vec4 Color") => res.to_owned() + "\n#include \"tint.glsl\"\n",
+    res if res.trim_start().starts_with("Color = ") => res.replace("Color = ", "Color = tint(").replace(";", ");") + "\n",
+    res => res.to_owned() + "\n"
+  }).collect();
+
+  let basic_vert = rad::core::filesystem::filesystem::read_text_file_immediate(&(shader_path.to_owned() + "basic.vert")).unwrap();
+  let tint = |color: &str| format!("vec4 tint(vec4 a_color)\n{{\n    return a_color * {};\n}}\n", color);
+
+  let name = format!("rad_shader_reload_{}", renderer.get_type());
+  let root = write_shader_root(&name, &[
+    ("basic.vert", &basic_vert),
+    ("tinted.frag", &tinted_frag),
+    ("tint.glsl", &tint("vec4(1.0, 0.0, 0.0, 1.0)"))]);
+
+  let mut reloader = ShaderReloader::new(ShaderPreprocessor::new(&root));
+  let stages = [(ShaderType::Vertex, ShaderPermutation::new("basic.vert")), (ShaderType::Fragment, ShaderPermutation::new("tinted.frag"))];
+  let (id, program) = reloader.load_program(renderer.as_mut(), &stages).unwrap();
+
+  let mut texture = renderer.gen_buffer_texture();
+  renderer.load_texture(&gen_color_image([255, 255, 255, 255]), &mut texture);
+  let texture: Rc<dyn Texture> = texture.into();
+  let mut mesh = gen_quad_mesh(renderer, texture.clone());
+  mesh.material.set_program(program);
+
+  // shares the program, until the first reload it draws with the basic program
+  let mut other = gen_quad_mesh(renderer, texture);
+
+  let error = draw_center(renderer, &mut mesh, [255, 0, 0, 255]);
+  assert!(error <= 48, "Loaded program is not within tolerance. Error: {}", error);
+  assert!(reloader.update(renderer.as_mut(), &mut [(id, mesh.material.as_mut())]).is_empty(), "Unchanged program failed to reload");
+
+  // the include changing swaps the program in every material with it
+  write_shader_root(&name, &[("tint.glsl", &tint("vec4(0.0, 1.0, 0.0, 1.0)"))]);
+  let errors = reloader.update(renderer.as_mut(), &mut [(id, mesh.material.as_mut()), (id, other.material.as_mut())]);
+  assert!(errors.is_empty(), "Reload failed: {:?}", errors.iter().map(|res| res.error.to_string()).collect::<Vec<String>>());
+
+  for mesh in [&mut mesh, &mut other] {
+    let error = draw_center(renderer, mesh, [0, 255, 0, 255]);
+    assert!(error <= 48, "Reloaded program is not within tolerance. Error: {}", error);
+  }
+
+  // a broken change keeps the programs and is reported once for the program until the files change again
+  write_shader_root(&name, &[("tint.glsl", &tint("u_missing"))]);
+  match reloader.update(renderer.as_mut(), &mut [(id, mesh.material.as_mut()), (id, other.material.as_mut())]).as_slice() {
+    [res] => assert!(res.id == id && matches!(res.error, RendererError::ShaderCompile(_)), "Expected a compile error, got {}", res.error),
+    _ => panic!("Broken program was not reported once")
+  }
+
+  for mesh in [&mut mesh, &mut other] {
+    let error = draw_center(renderer, mesh, [0, 255, 0, 255]);
+    assert!(error <= 48, "Program kept after a failed reload is not within tolerance. Error: {}", error);
+  }
+
+  assert!(reloader.update(renderer.as_mut(), &mut [(id, mesh.material.as_mut()), (id, other.material.as_mut())]).is_empty(), "Failed reload was reported again");
+}
+
+fn shader_reload() {
+  run_backends(&Requires{software: false, ..EVERY_BACKEND}, test_shader_reload);
+}
+
+fn multi_texture() {
+  run_backends(&Requires{software: false, ..EVERY_BACKEND}, test_multi_texture);
+}
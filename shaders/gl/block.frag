@@ -0,0 +1,27 @@
+#version 330 core
+
+in vec2 v_uv;
+out vec4 Color;
+
+layout (std140) uniform Frame
+{
+    mat4 u_transform;
+    vec4 u_tint;
+    float u_scale;
+    vec3 u_offset;
+    int u_flags[2];
+};
+
+uniform sampler2D u_texture;
+
+void main()
+{
+    if (u_flags[1] == 7)
+    {
+        Color = texture(u_texture, v_uv) * u_tint;
+    }
+    else
+    {
+        Color = vec4(0.0, 0.0, 0.0, 1.0);
+    }
+}
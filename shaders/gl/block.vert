@@ -0,0 +1,21 @@
+#version 330 core
+
+layout (location = 0) in vec2 i_position;
+layout (location = 1) in vec2 i_uv;
+
+out vec2 v_uv;
+
+layout (std140) uniform Frame
+{
+    mat4 u_transform;
+    vec4 u_tint;
+    float u_scale;
+    vec3 u_offset;
+    int u_flags[2];
+};
+
+void main()
+{
+    gl_Position = u_transform * vec4(i_position * u_scale + u_offset.xy, 0.0, 1.0);
+    v_uv = i_uv;
+}
@@ -0,0 +1,15 @@
+#version 330 core
+
+in vec2 v_uv;
+out vec4 Color;
+
+uniform samplerCube u_texture;
+
+void main()
+{
+    // +x, -x, +y and -z in the quadrants of the screen
+    vec3 direction = v_uv.x < 0.5 ?
+      (v_uv.y < 0.5 ? vec3(1.0, 0.0, 0.0) : vec3(-1.0, 0.0, 0.0)) :
+      (v_uv.y < 0.5 ? vec3(0.0, 1.0, 0.0) : vec3(0.0, 0.0, -1.0));
+    Color = texture(u_texture, direction);
+}
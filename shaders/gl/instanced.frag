@@ -0,0 +1,12 @@
+#version 330 core
+
+in vec2 v_uv;
+in vec4 v_color;
+out vec4 Color;
+
+uniform sampler2D u_texture;
+
+void main()
+{
+    Color = texture(u_texture, v_uv) * v_color;
+}
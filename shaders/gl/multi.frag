@@ -0,0 +1,13 @@
+#version 330 core
+
+in vec2 v_uv;
+out vec4 Color;
+
+// each sampler has its own texture unit
+uniform sampler2D u_albedo;
+uniform sampler2D u_detail;
+
+void main()
+{
+    Color = texture(u_albedo, v_uv) + texture(u_detail, v_uv);
+}
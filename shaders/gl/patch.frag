@@ -0,0 +1,11 @@
+#version 400 core
+
+in vec2 v_uv;
+out vec4 Color;
+
+uniform sampler2D u_texture;
+
+void main()
+{
+    Color = texture(u_texture, v_uv);
+}
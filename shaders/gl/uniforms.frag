@@ -0,0 +1,22 @@
+#version 330 core
+
+in vec2 v_uv;
+out vec4 Color;
+
+uniform int u_flags;
+uniform uint u_mask;
+uniform bool u_enabled;
+uniform ivec2 u_select;
+uniform vec4 u_colors[4];
+
+void main()
+{
+    if (u_enabled && u_flags == -3 && u_mask == 5u && u_select == ivec2(2, 1))
+    {
+        Color = u_colors[u_select.x];
+    }
+    else
+    {
+        Color = vec4(0.0, 0.0, 0.0, 1.0);
+    }
+}
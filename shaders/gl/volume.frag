@@ -0,0 +1,12 @@
+#version 330 core
+
+in vec2 v_uv;
+out vec4 Color;
+
+uniform sampler3D u_texture;
+
+void main()
+{
+    // the first slice on the left and the last on the right
+    Color = texture(u_texture, vec3(v_uv, v_uv.x < 0.5 ? 0.25 : 0.75));
+}
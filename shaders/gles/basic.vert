@@ -13,4 +13,6 @@ void main()
 {
     gl_Position = u_mvp * vec4(i_position, 0.0, 1.0);
     v_uv = i_uv;
+    // the point size is undefined unless written
+    gl_PointSize = 1.0;
 }
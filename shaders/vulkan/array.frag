@@ -0,0 +1,13 @@
+#version 450
+
+layout (location = 0) in vec2 v_uv;
+layout (location = 0) out vec4 Color;
+
+layout (set = 0, binding = 0) uniform texture2DArray u_texture;
+layout (set = 0, binding = 1) uniform sampler u_texture_sampler;
+
+void main()
+{
+    // the first layer on the left and the third on the right
+    Color = texture(sampler2DArray(u_texture, u_texture_sampler), vec3(v_uv, v_uv.x < 0.5 ? 0.0 : 2.0));
+}
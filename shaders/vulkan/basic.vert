@@ -0,0 +1,18 @@
+#version 450
+
+layout (location = 0) in vec2 i_position;
+layout (location = 1) in vec2 i_uv;
+
+layout (location = 0) out vec2 v_uv;
+
+layout (push_constant) uniform Uniforms {
+    mat4 u_mvp;
+};
+
+void main()
+{
+    gl_Position = u_mvp * vec4(i_position, 0.0, 1.0);
+    v_uv = i_uv;
+    // the point size is undefined unless written
+    gl_PointSize = 1.0;
+}
@@ -0,0 +1,16 @@
+#version 450
+
+layout (location = 0) in vec2 v_uv;
+layout (location = 0) out vec4 Color;
+
+layout (set = 0, binding = 0) uniform textureCube u_texture;
+layout (set = 0, binding = 1) uniform sampler u_texture_sampler;
+
+void main()
+{
+    // +x, -x, +y and -z in the quadrants of the screen
+    vec3 direction = v_uv.x < 0.5 ?
+      (v_uv.y < 0.5 ? vec3(1.0, 0.0, 0.0) : vec3(-1.0, 0.0, 0.0)) :
+      (v_uv.y < 0.5 ? vec3(0.0, 1.0, 0.0) : vec3(0.0, 0.0, -1.0));
+    Color = texture(samplerCube(u_texture, u_texture_sampler), direction);
+}
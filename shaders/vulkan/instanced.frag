@@ -0,0 +1,14 @@
+#version 450
+
+layout (location = 0) in vec2 v_uv;
+layout (location = 1) in vec4 v_color;
+layout (location = 0) out vec4 Color;
+
+// textures and samplers are separate, a sampler is bound as <texture name>_sampler
+layout (set = 0, binding = 0) uniform texture2D u_texture;
+layout (set = 0, binding = 1) uniform sampler u_texture_sampler;
+
+void main()
+{
+    Color = texture(sampler2D(u_texture, u_texture_sampler), v_uv) * v_color;
+}
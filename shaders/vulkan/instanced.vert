@@ -0,0 +1,20 @@
+#version 450
+
+layout (location = 0) in vec2 i_position;
+layout (location = 1) in vec2 i_uv;
+layout (location = 2) in mat4 i_transform;
+layout (location = 6) in vec4 i_color;
+
+layout (location = 0) out vec2 v_uv;
+layout (location = 1) out vec4 v_color;
+
+layout (push_constant) uniform Uniforms {
+    mat4 u_mvp;
+};
+
+void main()
+{
+    gl_Position = u_mvp * i_transform * vec4(i_position, 0.0, 1.0);
+    v_uv = i_uv;
+    v_color = i_color;
+}
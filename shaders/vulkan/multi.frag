@@ -0,0 +1,15 @@
+#version 450
+
+layout (location = 0) in vec2 v_uv;
+layout (location = 0) out vec4 Color;
+
+// textures are bound by name, a sampler is bound as <texture name>_sampler
+layout (set = 0, binding = 0) uniform texture2D u_albedo;
+layout (set = 0, binding = 1) uniform sampler u_albedo_sampler;
+layout (set = 0, binding = 2) uniform texture2D u_detail;
+layout (set = 0, binding = 3) uniform sampler u_detail_sampler;
+
+void main()
+{
+    Color = texture(sampler2D(u_albedo, u_albedo_sampler), v_uv) + texture(sampler2D(u_detail, u_detail_sampler), v_uv);
+}